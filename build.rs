@@ -0,0 +1,40 @@
+fn main() {
+    #[cfg(feature = "wayland")]
+    wayland::generate_protocols();
+}
+
+/// Protocols that aren't part of `wayland-protocols`, generated the same way it does.
+#[cfg(feature = "wayland")]
+mod wayland {
+    use std::env;
+    use std::path::Path;
+
+    use wayland_scanner::Side;
+
+    const PROTOCOLS: &[(&str, &str)] = &[
+        (
+            "src/platform_impl/linux/wayland/protocols/blur.xml",
+            "blur_client_api.rs",
+        ),
+        (
+            "src/platform_impl/linux/wayland/protocols/xdg-shell.xml",
+            "xdg_shell_client_api.rs",
+        ),
+        (
+            "src/platform_impl/linux/wayland/protocols/xdg-decoration-unstable-v1.xml",
+            "xdg_decoration_client_api.rs",
+        ),
+    ];
+
+    pub fn generate_protocols() {
+        let out_dir = env::var("OUT_DIR").unwrap();
+        for &(protocol_file, generated_file) in PROTOCOLS {
+            println!("cargo:rerun-if-changed={}", protocol_file);
+            wayland_scanner::generate_code(
+                protocol_file,
+                Path::new(&out_dir).join(generated_file),
+                Side::Client,
+            );
+        }
+    }
+}
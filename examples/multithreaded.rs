@@ -7,7 +7,7 @@ fn main() {
         dpi::{PhysicalPosition, PhysicalSize, Position, Size},
         event::{ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent},
         event_loop::{ControlFlow, EventLoop},
-        window::{CursorIcon, Fullscreen, WindowBuilder},
+        window::{CursorGrabMode, CursorIcon, Fullscreen, WindowBuilder, WindowLevel},
     };
 
     const WINDOW_COUNT: usize = 3;
@@ -64,7 +64,10 @@ fn main() {
                         let state = !modifiers.shift();
                         use VirtualKeyCode::*;
                         match key {
-                            A => window.set_always_on_top(state),
+                            A => window.set_window_level(match state {
+                                true => WindowLevel::AlwaysOnTop,
+                                false => WindowLevel::Normal,
+                            }),
                             C => window.set_cursor_icon(match state {
                                 true => CursorIcon::Progress,
                                 false => CursorIcon::Default,
@@ -89,7 +92,16 @@ fn main() {
                                 )),
                                 (false, _) => None,
                             }),
-                            G => window.set_cursor_grab(state).unwrap(),
+                            G => {
+                                let mode = if state {
+                                    CursorGrabMode::Confined
+                                } else {
+                                    CursorGrabMode::None
+                                };
+                                if let Err(err) = window.set_cursor_grab(mode) {
+                                    println!("error: {}", err);
+                                }
+                            }
                             H => window.set_cursor_visible(!state),
                             I => {
                                 println!("Info:");
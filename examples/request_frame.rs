@@ -0,0 +1,54 @@
+use std::time::Instant;
+
+use simple_logger::SimpleLogger;
+use winit::{
+    event::{Event, WindowEvent},
+    event_loop::{ControlFlow, EventLoop},
+    window::WindowBuilder,
+};
+
+fn main() {
+    SimpleLogger::new().init().unwrap();
+    let event_loop = EventLoop::new();
+
+    let window = WindowBuilder::new()
+        .with_title("A fantastic window!")
+        .build(&event_loop)
+        .unwrap();
+
+    // The next frame is only drawn once the compositor is ready for it.
+    window.request_frame();
+    let mut frames = 0;
+    let mut second_start = Instant::now();
+
+    event_loop.run(move |event, _, control_flow| {
+        *control_flow = ControlFlow::Wait;
+
+        match event {
+            Event::WindowEvent { event, .. } => match event {
+                WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+                WindowEvent::FrameReady { target_time } => {
+                    // Request the next frame before presenting this one, so that the commit of
+                    // the frame schedules the callback.
+                    window.request_frame();
+                    println!(
+                        "frame ready, to be presented in {:?}",
+                        target_time.saturating_duration_since(Instant::now())
+                    );
+
+                    frames += 1;
+                    if second_start.elapsed().as_secs() >= 1 {
+                        println!("\n{} frames in the last second\n", frames);
+                        frames = 0;
+                        second_start = Instant::now();
+                    }
+                }
+                WindowEvent::Occluded(occluded) => {
+                    println!("\noccluded: {}\n", occluded);
+                }
+                _ => (),
+            },
+            _ => (),
+        }
+    });
+}
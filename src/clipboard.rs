@@ -0,0 +1,83 @@
+//! The clipboard and the primary selection.
+//!
+//! Contents are exchanged as bytes, labeled with their MIME type, like `text/plain;charset=utf-8`
+//! or `image/png`. An application offers the same contents in as many types as it can convert
+//! them to, and the application which reads them picks the type it understands best.
+//!
+//! The clipboard is retrieved with [`EventLoopWindowTarget::clipboard`], and uses the connection
+//! of the event loop, so an application doesn't need a second one to copy and paste.
+//!
+//! [`EventLoopWindowTarget::clipboard`]: crate::event_loop::EventLoopWindowTarget::clipboard
+use std::{collections::HashMap, fmt};
+
+use crate::{error::ExternalError, event_loop::EventLoopWindowTarget};
+
+/// A selection which holds contents to paste.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ClipboardKind {
+    /// The clipboard, which is set by an explicit copy, and read by an explicit paste.
+    Clipboard,
+    /// The primary selection, which is set by selecting text, and typically read with the middle
+    /// mouse button.
+    Primary,
+}
+
+/// The clipboard and the primary selection of an event loop, which is returned by
+/// [`EventLoopWindowTarget::clipboard`].
+///
+/// [`EventLoopWindowTarget::clipboard`]: crate::event_loop::EventLoopWindowTarget::clipboard
+pub struct Clipboard<'a, T: 'static> {
+    pub(crate) target: &'a EventLoopWindowTarget<T>,
+}
+
+impl<T> fmt::Debug for Clipboard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Clipboard { .. }")
+    }
+}
+
+impl<T> Clipboard<'_, T> {
+    /// Offers `contents` in the selection, which maps MIME types to the contents converted to
+    /// them. Setting empty contents clears the selection.
+    ///
+    /// The contents are kept by the event loop, which sends them to the applications that paste
+    /// them, until another application takes over the selection.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** `text/plain;charset=utf-8` is also offered as `UTF8_STRING`, which older
+    ///   applications ask for.
+    /// - **Wayland:** Compositors only allow an application to set the selection in response to
+    ///   input, so an error is returned unless one of its windows has the keyboard focus. A
+    ///   selection which the compositor still refuses is read from the compositor again once it
+    ///   announces the selection. The primary selection requires
+    ///   `zwp_primary_selection_device_manager_v1`.
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported.
+    #[inline]
+    pub fn set_contents(
+        &self,
+        kind: ClipboardKind,
+        contents: HashMap<String, Vec<u8>>,
+    ) -> Result<(), ExternalError> {
+        self.target.p.set_clipboard_contents(kind, contents)
+    }
+
+    /// Reads the contents of the selection converted to `mime_type`, which is `None` if the
+    /// selection is empty, or isn't available in that type.
+    ///
+    /// This blocks until the owner of the selection sent the contents, or gave up.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Reading `text/plain;charset=utf-8` falls back to `UTF8_STRING`.
+    /// - **Wayland:** The primary selection requires `zwp_primary_selection_device_manager_v1`.
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported.
+    #[inline]
+    pub fn get_contents(
+        &self,
+        kind: ClipboardKind,
+        mime_type: &str,
+    ) -> Result<Option<Vec<u8>>, ExternalError> {
+        self.target.p.clipboard_contents(kind, mime_type)
+    }
+}
@@ -0,0 +1,237 @@
+use crate::dpi::PhysicalPosition;
+use crate::icon::{BadIcon, RgbaIcon, PIXEL_SIZE};
+use crate::window::CursorIcon;
+use std::{error::Error, fmt, sync::Arc, time::Duration};
+
+#[derive(Debug)]
+/// An error produced when creating a `CustomCursor` with invalid arguments.
+pub enum BadCursor {
+    /// Produced when the length of the `rgba` argument isn't divisible by 4, thus `rgba` can't be
+    /// safely interpreted as 32bpp RGBA pixels.
+    ByteCountNotDivisibleBy4 { byte_count: usize },
+    /// Produced when the number of pixels (`rgba.len() / 4`) isn't equal to `width * height`.
+    /// At least one of your arguments is incorrect.
+    DimensionsVsPixelCount {
+        width: u32,
+        height: u32,
+        width_x_height: usize,
+        pixel_count: usize,
+    },
+    /// Produced when the hotspot isn't inside of the image.
+    HotspotOutOfBounds {
+        width: u32,
+        height: u32,
+        hotspot: PhysicalPosition<u32>,
+    },
+    /// Produced when `CustomCursor::from_animation` is given no frames.
+    EmptyAnimation,
+    /// Produced when a frame given to `CustomCursor::from_animation` is itself animated.
+    NestedAnimation,
+}
+
+impl fmt::Display for BadCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BadCursor::ByteCountNotDivisibleBy4 { byte_count } => write!(f,
+                "The length of the `rgba` argument ({:?}) isn't divisible by 4, making it impossible to interpret as 32bpp RGBA pixels.",
+                byte_count,
+            ),
+            BadCursor::DimensionsVsPixelCount {
+                width,
+                height,
+                width_x_height,
+                pixel_count,
+            } => write!(f,
+                "The specified dimensions ({:?}x{:?}) don't match the number of pixels supplied by the `rgba` argument ({:?}). For those dimensions, the expected pixel count is {:?}.",
+                width, height, pixel_count, width_x_height,
+            ),
+            BadCursor::HotspotOutOfBounds {
+                width,
+                height,
+                hotspot,
+            } => write!(f,
+                "The specified hotspot ({:?}, {:?}) is outside of the image ({:?}x{:?}).",
+                hotspot.x, hotspot.y, width, height,
+            ),
+            BadCursor::EmptyAnimation => write!(f, "An animated cursor needs at least one frame."),
+            BadCursor::NestedAnimation => {
+                write!(f, "The frames of an animated cursor can't be animated themselves.")
+            }
+        }
+    }
+}
+
+impl Error for BadCursor {}
+
+/// One image of a `CustomCursor`.
+#[allow(dead_code)] // Custom cursors are not supported on every platform
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CursorImage {
+    pub(crate) rgba: Vec<u8>,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) hotspot_x: u32,
+    pub(crate) hotspot_y: u32,
+    /// How long the image is shown, which is zero unless it's a frame of an animation.
+    pub(crate) delay: Duration,
+}
+
+#[allow(dead_code)] // Custom cursors are not supported on every platform
+impl CursorImage {
+    /// Returns the pixels as premultiplied ARGB, which is what Xcursor and `wl_shm` expect.
+    pub(crate) fn argb_pixels(&self) -> impl Iterator<Item = u32> + '_ {
+        self.rgba.chunks_exact(PIXEL_SIZE).map(|pixel| {
+            let alpha = pixel[3] as u32;
+            let premultiply = |channel: u8| (channel as u32 * alpha + 127) / 255;
+            alpha << 24
+                | premultiply(pixel[0]) << 16
+                | premultiply(pixel[1]) << 8
+                | premultiply(pixel[2])
+        })
+    }
+}
+
+/// A cursor made of RGBA images, which replaces the `CursorIcon` of a window with
+/// [`Window::set_cursor`].
+///
+/// Cloning a cursor is cheap, since the images are shared between the clones. Two cursors are
+/// only equal if one is a clone of the other.
+///
+/// [`Window::set_cursor`]: crate::window::Window::set_cursor
+#[derive(Clone)]
+pub struct CustomCursor {
+    pub(crate) frames: Arc<[CursorImage]>,
+}
+
+impl fmt::Debug for CustomCursor {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        let first = &self.frames[0];
+        formatter
+            .debug_struct("CustomCursor")
+            .field("width", &first.width)
+            .field("height", &first.height)
+            .field("frames", &self.frames.len())
+            .finish()
+    }
+}
+
+impl PartialEq for CustomCursor {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.frames, &other.frames)
+    }
+}
+
+impl Eq for CustomCursor {}
+
+impl CustomCursor {
+    /// Creates a `CustomCursor` from 32bpp RGBA data, with the given hotspot, which is the pixel
+    /// of the image at the position of the pointer.
+    ///
+    /// The length of `rgba` must be divisible by 4, `width * height` must equal
+    /// `rgba.len() / 4`, and the hotspot must be inside of the image. Otherwise, this will return
+    /// a `BadCursor` error.
+    pub fn from_rgba(
+        rgba: Vec<u8>,
+        width: u32,
+        height: u32,
+        hotspot: PhysicalPosition<u32>,
+    ) -> Result<Self, BadCursor> {
+        // The same checks as for icons, except that an OS error can't happen.
+        let RgbaIcon {
+            rgba,
+            width,
+            height,
+        } = RgbaIcon::from_rgba(rgba, width, height).map_err(|err| match err {
+            BadIcon::ByteCountNotDivisibleBy4 { byte_count } => {
+                BadCursor::ByteCountNotDivisibleBy4 { byte_count }
+            }
+            BadIcon::DimensionsVsPixelCount {
+                width,
+                height,
+                width_x_height,
+                pixel_count,
+            } => BadCursor::DimensionsVsPixelCount {
+                width,
+                height,
+                width_x_height,
+                pixel_count,
+            },
+            BadIcon::OsError(_) => unreachable!(),
+        })?;
+        if hotspot.x >= width || hotspot.y >= height {
+            return Err(BadCursor::HotspotOutOfBounds {
+                width,
+                height,
+                hotspot,
+            });
+        }
+
+        let image = CursorImage {
+            rgba,
+            width,
+            height,
+            hotspot_x: hotspot.x,
+            hotspot_y: hotspot.y,
+            delay: Duration::from_secs(0),
+        };
+        Ok(CustomCursor {
+            frames: vec![image].into(),
+        })
+    }
+
+    /// Creates an animated `CustomCursor`, which shows each of the `frames` for its duration, and
+    /// starts over after the last one.
+    ///
+    /// The frames must be made with [`CustomCursor::from_rgba`], but don't need to have the same
+    /// size.
+    pub fn from_animation(frames: Vec<(CustomCursor, Duration)>) -> Result<Self, BadCursor> {
+        if frames.is_empty() {
+            return Err(BadCursor::EmptyAnimation);
+        }
+
+        let mut images = Vec::with_capacity(frames.len());
+        for (cursor, delay) in frames {
+            if cursor.is_animated() {
+                return Err(BadCursor::NestedAnimation);
+            }
+            images.push(CursorImage {
+                delay,
+                ..cursor.frames[0].clone()
+            });
+        }
+        Ok(CustomCursor {
+            frames: images.into(),
+        })
+    }
+
+    /// Returns whether the cursor has more than one frame.
+    pub fn is_animated(&self) -> bool {
+        self.frames.len() > 1
+    }
+}
+
+/// The cursor of a window, which is either from the cursor theme of the system, or made by the
+/// application.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cursor {
+    Icon(CursorIcon),
+    Custom(CustomCursor),
+}
+
+impl Default for Cursor {
+    fn default() -> Self {
+        Cursor::Icon(CursorIcon::default())
+    }
+}
+
+impl From<CursorIcon> for Cursor {
+    fn from(icon: CursorIcon) -> Self {
+        Cursor::Icon(icon)
+    }
+}
+
+impl From<CustomCursor> for Cursor {
+    fn from(cursor: CustomCursor) -> Self {
+        Cursor::Custom(cursor)
+    }
+}
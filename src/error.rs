@@ -25,6 +25,12 @@ pub struct OsError {
     error: platform_impl::OsError,
 }
 
+/// The error type for when the `EventLoop` could not be created.
+#[derive(Debug)]
+pub struct EventLoopError {
+    pub(crate) error: platform_impl::EventLoopError,
+}
+
 impl NotSupportedError {
     #[inline]
     #[allow(dead_code)]
@@ -33,6 +39,13 @@ impl NotSupportedError {
     }
 }
 
+impl From<platform_impl::EventLoopError> for EventLoopError {
+    #[inline]
+    fn from(error: platform_impl::EventLoopError) -> Self {
+        EventLoopError { error }
+    }
+}
+
 impl OsError {
     #[allow(dead_code)]
     pub(crate) fn new(line: u32, file: &'static str, error: platform_impl::OsError) -> OsError {
@@ -56,6 +69,12 @@ impl fmt::Display for OsError {
     }
 }
 
+impl fmt::Display for EventLoopError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        self.error.fmt(f)
+    }
+}
+
 impl fmt::Display for ExternalError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         match self {
@@ -78,5 +97,6 @@ impl fmt::Display for NotSupportedError {
 }
 
 impl error::Error for OsError {}
+impl error::Error for EventLoopError {}
 impl error::Error for ExternalError {}
 impl error::Error for NotSupportedError {}
@@ -38,6 +38,7 @@ use std::path::PathBuf;
 
 use crate::{
     dpi::{PhysicalPosition, PhysicalSize},
+    event_loop::{FdToken, TimerId},
     platform_impl,
     window::{Theme, WindowId},
 };
@@ -181,7 +182,7 @@ impl<'a, T> Event<'a, T> {
 }
 
 /// Describes the reason the event loop is resuming.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum StartCause {
     /// Sent if the time specified by `ControlFlow::WaitUntil` has been reached. Contains the
     /// moment the timeout was requested and the requested resume time. The actual resume time is
@@ -204,6 +205,21 @@ pub enum StartCause {
 
     /// Sent once, immediately after `run` is called. Indicates that the loop was just initialized.
     Init,
+
+    /// Sent if a file descriptor registered with the given token became ready. If several file
+    /// descriptors are ready at once, one iteration is dispatched for each of them.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Unix:** See `EventLoopWindowTargetExtUnix::register_fd`.
+    /// - **Other platforms:** Never sent.
+    FdReady(FdToken),
+
+    /// Sent if timers set with [`EventLoopWindowTarget::set_timer`] have expired. Contains the
+    /// identifiers of the expired timers, ordered by their deadline.
+    ///
+    /// [`EventLoopWindowTarget::set_timer`]: crate::event_loop::EventLoopWindowTarget::set_timer
+    TimerFired(Vec<TimerId>),
 }
 
 /// Describes an event from a `Window`.
@@ -350,8 +366,51 @@ pub enum WindowEvent<'a> {
     /// Applications might wish to react to this to change the theme of the content of the window
     /// when the system changes the window theme.
     ///
-    /// At the moment this is only supported on Windows.
+    /// It's only sent to the windows which follow the system theme, see
+    /// [`Window::set_theme`](crate::window::Window::set_theme).
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **macOS / iOS / Android:** Unsupported.
     ThemeChanged(Theme),
+
+    /// The window has been occluded (completely hidden from view).
+    ///
+    /// This is different to window visibility as it depends on whether the window is closed,
+    /// minimised, set invisible, or fully occluded by another window. Applications may stop
+    /// drawing while the window is occluded.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Sent when the window is unmapped, e.g. when it's minimized, or fully covered by
+    ///   other windows.
+    /// - **Wayland:** Sent when the compositor suspends the window, with the `suspended` state of
+    ///   xdg-shell version 6. Compositors which don't support it stop sending frame callbacks to
+    ///   hidden surfaces instead, so it's sent when a frame requested with
+    ///   [`Window::request_frame`](crate::window::Window::request_frame) isn't ready a second
+    ///   after the window was drawn, and `Occluded(false)` follows once the frame is ready. The
+    ///   window is assumed to be drawn while handling `RedrawRequested` or `FrameReady`.
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported.
+    Occluded(bool),
+
+    /// The popup was dismissed by the system, usually because the user clicked outside of it.
+    ///
+    /// The popup is hidden, and should be dropped.
+    ///
+    /// ## Platform-specific
+    ///
+    /// Only sent to popups built with `WindowBuilderExtUnix::with_popup`, which is only supported
+    /// on X11 and Wayland.
+    PopupDismissed,
+
+    /// The compositor is ready for a new frame, requested with
+    /// [`Window::request_frame`](crate::window::Window::request_frame).
+    ///
+    /// `target_time` is an estimate of the time at which a frame drawn in response to this event
+    /// will be presented, i.e. the next refresh of the monitor showing the window. It's derived
+    /// from the refresh rate of the monitor, not from presentation feedback of the compositor, so
+    /// it can be off by a refresh when frames are missed.
+    FrameReady { target_time: Instant },
 }
 
 impl Clone for WindowEvent<'static> {
@@ -438,6 +497,11 @@ impl Clone for WindowEvent<'static> {
             },
             Touch(touch) => Touch(*touch),
             ThemeChanged(theme) => ThemeChanged(theme.clone()),
+            Occluded(occluded) => Occluded(*occluded),
+            PopupDismissed => PopupDismissed,
+            FrameReady { target_time } => FrameReady {
+                target_time: *target_time,
+            },
             ScaleFactorChanged { .. } => {
                 unreachable!("Static event can't be about scale factor changing")
             }
@@ -524,6 +588,9 @@ impl<'a> WindowEvent<'a> {
             }),
             Touch(touch) => Some(Touch(touch)),
             ThemeChanged(theme) => Some(ThemeChanged(theme)),
+            Occluded(occluded) => Some(Occluded(occluded)),
+            PopupDismissed => Some(PopupDismissed),
+            FrameReady { target_time } => Some(FrameReady { target_time }),
             ScaleFactorChanged { .. } => None,
         }
     }
@@ -557,6 +624,7 @@ impl DeviceId {
 ///
 /// Note that these events are delivered regardless of input focus.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum DeviceEvent {
     Added,
     Removed,
@@ -667,6 +735,7 @@ pub struct Touch {
 
 /// Describes the force of a touch event
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Force {
     /// On iOS, the force is calibrated so that the same number corresponds to
     /// roughly the same amount of pressure on the screen regardless of the
@@ -13,7 +13,13 @@ use instant::Instant;
 use std::ops::Deref;
 use std::{error, fmt};
 
-use crate::{event::Event, monitor::MonitorHandle, platform_impl};
+use crate::{
+    clipboard::Clipboard,
+    error::{EventLoopError, NotSupportedError},
+    event::Event,
+    monitor::MonitorHandle,
+    platform_impl,
+};
 
 /// Provides a way to retrieve events from the system and from the windows that were registered to
 /// the events loop.
@@ -64,9 +70,9 @@ impl<T> fmt::Debug for EventLoopWindowTarget<T> {
 ///
 /// ## Persistency
 /// Almost every change is persistent between multiple calls to the event loop closure within a
-/// given run loop. The only exception to this is `Exit` which, once set, cannot be unset. Changes
-/// are **not** persistent between multiple calls to `run_return` - issuing a new call will reset
-/// the control flow to `Poll`.
+/// given run loop. The only exception to this is `ExitWithCode` which, once set, cannot be unset.
+/// Changes are **not** persistent between multiple calls to `run_return` - issuing a new call will
+/// reset the control flow to `Poll`.
 ///
 /// [events_cleared]: crate::event::Event::RedrawEventsCleared
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -86,9 +92,62 @@ pub enum ControlFlow {
     /// arrives or the given time is reached.
     WaitUntil(Instant),
     /// Send a `LoopDestroyed` event and stop the event loop. This variant is *sticky* - once set,
-    /// `control_flow` cannot be changed from `Exit`, and any future attempts to do so will result
-    /// in the `control_flow` parameter being reset to `Exit`.
-    Exit,
+    /// `control_flow` cannot be changed from `ExitWithCode`, and any future attempts to do so will
+    /// result in the `control_flow` parameter being reset to `ExitWithCode`.
+    ///
+    /// The contained number is the exit code of the process when exiting from `EventLoop::run`,
+    /// and the value returned by `run_return`. The [`Exit`] constant is a shortcut for this with
+    /// exit code 0.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **iOS / Web:** The supplied exit code is unused.
+    /// - **Unix:** On most Unix-like platforms, only the 8 least significant bits are used, see
+    ///   [`std::process::exit`].
+    ///
+    /// [`Exit`]: ControlFlow::Exit
+    ExitWithCode(i32),
+}
+
+impl ControlFlow {
+    /// Alias for [`ExitWithCode`]`(0)`.
+    ///
+    /// [`ExitWithCode`]: ControlFlow::ExitWithCode
+    #[allow(non_upper_case_globals)]
+    pub const Exit: Self = Self::ExitWithCode(0);
+}
+
+/// Identifies a file descriptor registered with the event loop.
+///
+/// The token is chosen by the user when registering the file descriptor, and is handed back in
+/// [`StartCause::FdReady`] when it becomes ready.
+///
+/// [`StartCause::FdReady`]: crate::event::StartCause::FdReady
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FdToken(pub usize);
+
+/// Identifies a timer set with [`EventLoopWindowTarget::set_timer`].
+///
+/// The identifier is chosen by the user, and is handed back in [`StartCause::TimerFired`] when
+/// the timer expires.
+///
+/// [`StartCause::TimerFired`]: crate::event::StartCause::TimerFired
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TimerId(pub usize);
+
+/// Filters the [`DeviceEvent`]s received by the event loop, set with
+/// [`EventLoopWindowTarget::set_device_event_filter`].
+///
+/// [`DeviceEvent`]: crate::event::DeviceEvent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DeviceEventFilter {
+    /// Always filter out device events.
+    Always,
+    /// Filter out device events while no window of the application has focus.
+    #[default]
+    Unfocused,
+    /// Never filter out device events.
+    Never,
 }
 
 impl Default for ControlFlow {
@@ -113,12 +172,31 @@ impl EventLoop<()> {
     /// If it is not set, winit will try to connect to a wayland connection, and if it fails will
     /// fallback on x11. If this variable is set with any other value, winit will panic.
     ///
+    /// Use [`EventLoop::try_new`] to handle a failing display backend initialisation instead of
+    /// panicking.
+    ///
     /// ## Platform-specific
     ///
     /// - **iOS:** Can only be called on the main thread.
     pub fn new() -> EventLoop<()> {
         EventLoop::<()>::with_user_event()
     }
+
+    /// Builds a new event loop with a `()` as the user event type, returning an error if the
+    /// display backend could not be initialized.
+    ///
+    /// All caveats documented in [`EventLoop::new`] apply to this function, except that an
+    /// unusable display backend or an unknown `WINIT_UNIX_BACKEND` value are reported through
+    /// [`EventLoopError`] instead of panicking.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **iOS:** Can only be called on the main thread.
+    /// - **Unix:** The reasons why each backend failed are available through
+    ///   `EventLoopErrorExtUnix`. Every other platform always returns `Ok`.
+    pub fn try_new() -> Result<EventLoop<()>, EventLoopError> {
+        EventLoop::<()>::try_with_user_event()
+    }
 }
 
 impl<T> EventLoop<T> {
@@ -136,6 +214,21 @@ impl<T> EventLoop<T> {
         }
     }
 
+    /// Builds a new event loop, returning an error if the display backend could not be
+    /// initialized.
+    ///
+    /// All caveats documented in [`EventLoop::try_new`] apply to this function.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **iOS:** Can only be called on the main thread.
+    pub fn try_with_user_event() -> Result<EventLoop<T>, EventLoopError> {
+        Ok(EventLoop {
+            event_loop: platform_impl::EventLoop::try_new()?,
+            _marker: ::std::marker::PhantomData,
+        })
+    }
+
     /// Hijacks the calling thread and initializes the winit event loop with the provided
     /// closure. Since the closure is `'static`, it must be a `move` closure if it needs to
     /// access any data from the calling context.
@@ -143,7 +236,8 @@ impl<T> EventLoop<T> {
     /// See the [`ControlFlow`] docs for information on how changes to `&mut ControlFlow` impact the
     /// event loop's behavior.
     ///
-    /// Any values not passed to this function will *not* be dropped.
+    /// Any values not passed to this function will *not* be dropped. Once the loop exits, the
+    /// process exits with the code given in [`ControlFlow::ExitWithCode`].
     ///
     /// [`ControlFlow`]: crate::event_loop::ControlFlow
     #[inline]
@@ -190,11 +284,68 @@ impl<T> EventLoopWindowTarget<T> {
     pub fn primary_monitor(&self) -> Option<MonitorHandle> {
         self.p.primary_monitor()
     }
+
+    /// Sets a one-shot timer which wakes up the event loop once `deadline` is reached, and starts
+    /// an iteration with [`StartCause::TimerFired`] containing `id`.
+    ///
+    /// Setting a timer with the `id` of a pending timer replaces its deadline. Timers are
+    /// independent of the [`ControlFlow`]: the event loop waits until either the control flow's
+    /// deadline or the earliest timer's deadline, whichever comes first.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported.
+    ///
+    /// [`StartCause::TimerFired`]: crate::event::StartCause::TimerFired
+    #[inline]
+    pub fn set_timer(&self, deadline: Instant, id: TimerId) -> Result<(), NotSupportedError> {
+        self.p.set_timer(deadline, id)
+    }
+
+    /// Changes when the event loop receives [`DeviceEvent`]s. The default is
+    /// [`DeviceEventFilter::Unfocused`], so an application doesn't observe the input meant for
+    /// other applications.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Selects or deselects the raw XInput2 events as the focus changes, so filtered
+    ///   events aren't even sent to the application.
+    /// - **Wayland:** Only relative pointer motion is reported as device events.
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported.
+    ///
+    /// [`DeviceEvent`]: crate::event::DeviceEvent
+    #[inline]
+    pub fn set_device_event_filter(&self, filter: DeviceEventFilter) {
+        self.p.set_device_event_filter(filter)
+    }
+
+    /// Cancels a timer set with [`EventLoopWindowTarget::set_timer`]. Does nothing if the timer
+    /// has already fired or was never set.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported.
+    #[inline]
+    pub fn cancel_timer(&self, id: TimerId) {
+        self.p.cancel_timer(id)
+    }
+
+    /// Returns the clipboard and the primary selection, which use the connection of the event
+    /// loop.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported, setting and reading the
+    ///   contents return an error.
+    #[inline]
+    pub fn clipboard(&self) -> Clipboard<'_, T> {
+        Clipboard { target: self }
+    }
 }
 
 /// Used to send custom events to `EventLoop`.
 pub struct EventLoopProxy<T: 'static> {
-    event_loop_proxy: platform_impl::EventLoopProxy<T>,
+    pub(crate) event_loop_proxy: platform_impl::EventLoopProxy<T>,
 }
 
 impl<T: 'static> Clone for EventLoopProxy<T> {
@@ -234,3 +385,25 @@ impl<T> fmt::Display for EventLoopClosed<T> {
 }
 
 impl<T: fmt::Debug> error::Error for EventLoopClosed<T> {}
+
+/// The error that is returned when an `EventLoopProxy` fails to send an event without blocking.
+/// Contains the original event.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TrySendError<T> {
+    /// The proxy is bounded, and its capacity is taken up by events the event loop hasn't received
+    /// yet.
+    Full(T),
+    /// The event loop no longer exists.
+    Closed(T),
+}
+
+impl<T> fmt::Display for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(_) => f.write_str("Tried to send to a full `EventLoopProxy`"),
+            TrySendError::Closed(_) => f.write_str("Tried to wake up a closed `EventLoop`"),
+        }
+    }
+}
+
+impl<T: fmt::Debug> error::Error for TrySendError<T> {}
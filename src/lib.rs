@@ -149,6 +149,8 @@ extern crate objc;
 #[cfg(all(target_arch = "wasm32", feature = "std_web"))]
 extern crate std_web as stdweb;
 
+pub mod clipboard;
+mod cursor;
 pub mod dpi;
 #[macro_use]
 pub mod error;
@@ -0,0 +1,248 @@
+#![cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+
+//! A backend without a display server, for testing applications.
+//!
+//! An event loop created with [`EventLoopExtHeadless::new_headless`], or with the
+//! `WINIT_UNIX_BACKEND` environment variable set to `headless`, creates virtual windows on
+//! virtual monitors. Virtual windows honor the same `Window` API as on a display server: resizing,
+//! moving or making a window fullscreen updates its geometry and queues the matching events.
+//!
+//! Nothing happens on its own: tests inject input with
+//! [`EventLoopWindowTargetExtHeadless`], and step the event loop with
+//! [`pump_events`](crate::platform::pump_events::EventLoopExtPumpEvents::pump_events):
+//!
+//! ```rust,ignore
+//! let mut event_loop = EventLoop::new_headless();
+//! let window = Window::new(&event_loop).unwrap();
+//!
+//! event_loop
+//!     .inject_window_event(window.id(), WindowEvent::CloseRequested)
+//!     .unwrap();
+//! event_loop.pump_events(Some(Duration::from_millis(0)), |event, _, control_flow| {
+//!     handle_event(event, control_flow);
+//! });
+//! ```
+//!
+//! The event loop starts with a single 1920x1080 primary monitor at `(0, 0)`, with a scale factor
+//! of 1.
+
+use crate::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    error::NotSupportedError,
+    event::{DeviceEvent, DeviceId, WindowEvent},
+    event_loop::{EventLoop, EventLoopWindowTarget},
+    monitor::MonitorHandle,
+    platform_impl::{
+        headless, DeviceId as LinuxDeviceId, EventLoop as LinuxEventLoop,
+        EventLoopWindowTarget as LinuxEventLoopWindowTarget, MonitorHandle as LinuxMonitorHandle,
+        Window as LinuxWindow,
+    },
+    window::{
+        CursorGrabMode, CursorIcon, CustomCursor, UserAttentionType, Window, WindowButtons,
+        WindowId, WindowLevel,
+    },
+};
+
+/// Additional methods on `EventLoop` to create a headless event loop.
+pub trait EventLoopExtHeadless {
+    /// Creates an event loop whose windows and monitors are virtual, without connecting to a
+    /// display server.
+    ///
+    /// Unlike the other constructors, this can be called on any thread.
+    fn new_headless() -> Self;
+}
+
+impl<T> EventLoopExtHeadless for EventLoop<T> {
+    #[inline]
+    fn new_headless() -> Self {
+        EventLoop {
+            event_loop: LinuxEventLoop::Headless(headless::EventLoop::new()),
+            _marker: ::std::marker::PhantomData,
+        }
+    }
+}
+
+/// Additional methods on `EventLoopWindowTarget` to drive a headless event loop.
+///
+/// Every method returns `NotSupportedError` if the event loop isn't headless.
+pub trait EventLoopWindowTargetExtHeadless {
+    /// True if the `EventLoopWindowTarget` is headless.
+    fn is_headless(&self) -> bool;
+
+    /// Adds a virtual monitor, which becomes part of `available_monitors`.
+    ///
+    /// Windows are on the monitor containing their outer position, or on the primary monitor if
+    /// no monitor contains it.
+    fn add_virtual_monitor(
+        &self,
+        position: PhysicalPosition<i32>,
+        size: PhysicalSize<u32>,
+        scale_factor: f64,
+    ) -> Result<MonitorHandle, NotSupportedError>;
+
+    /// Changes the scale factor of a virtual monitor.
+    ///
+    /// Every window on the monitor receives `WindowEvent::ScaleFactorChanged` during the next
+    /// iteration of the event loop, followed by `Resized` if its new size differs from its
+    /// current one.
+    fn set_virtual_monitor_scale_factor(
+        &self,
+        monitor: &MonitorHandle,
+        scale_factor: f64,
+    ) -> Result<(), NotSupportedError>;
+
+    /// Queues an event for a window, which is dispatched during the next iteration of the event
+    /// loop.
+    ///
+    /// `Resized` and `Moved` also change the geometry of the virtual window when they're
+    /// dispatched, as if the user resized or moved it.
+    fn inject_window_event(
+        &self,
+        window_id: WindowId,
+        event: WindowEvent<'_>,
+    ) -> Result<(), NotSupportedError>;
+
+    /// Queues a device event, which is dispatched during the next iteration of the event loop.
+    fn inject_device_event(
+        &self,
+        device_id: DeviceId,
+        event: DeviceEvent,
+    ) -> Result<(), NotSupportedError>;
+
+    /// Returns the id of a virtual device, to attribute injected events to.
+    fn virtual_device_id(&self) -> DeviceId;
+}
+
+impl<T> EventLoopWindowTargetExtHeadless for EventLoopWindowTarget<T> {
+    #[inline]
+    fn is_headless(&self) -> bool {
+        matches!(self.p, LinuxEventLoopWindowTarget::Headless(_))
+    }
+
+    #[inline]
+    fn add_virtual_monitor(
+        &self,
+        position: PhysicalPosition<i32>,
+        size: PhysicalSize<u32>,
+        scale_factor: f64,
+    ) -> Result<MonitorHandle, NotSupportedError> {
+        match self.p {
+            LinuxEventLoopWindowTarget::Headless(ref target) => Ok(MonitorHandle {
+                inner: LinuxMonitorHandle::Headless(target.add_virtual_monitor(
+                    position,
+                    size,
+                    scale_factor,
+                )),
+            }),
+            _ => Err(NotSupportedError::new()),
+        }
+    }
+
+    #[inline]
+    fn set_virtual_monitor_scale_factor(
+        &self,
+        monitor: &MonitorHandle,
+        scale_factor: f64,
+    ) -> Result<(), NotSupportedError> {
+        match (&self.p, &monitor.inner) {
+            (
+                LinuxEventLoopWindowTarget::Headless(ref target),
+                LinuxMonitorHandle::Headless(ref monitor),
+            ) => {
+                target.set_virtual_monitor_scale_factor(monitor, scale_factor);
+                Ok(())
+            }
+            _ => Err(NotSupportedError::new()),
+        }
+    }
+
+    #[inline]
+    fn inject_window_event(
+        &self,
+        window_id: WindowId,
+        event: WindowEvent<'_>,
+    ) -> Result<(), NotSupportedError> {
+        match self.p {
+            LinuxEventLoopWindowTarget::Headless(ref target) => {
+                target.inject_window_event(window_id.0, event);
+                Ok(())
+            }
+            _ => Err(NotSupportedError::new()),
+        }
+    }
+
+    #[inline]
+    fn inject_device_event(
+        &self,
+        device_id: DeviceId,
+        event: DeviceEvent,
+    ) -> Result<(), NotSupportedError> {
+        match self.p {
+            LinuxEventLoopWindowTarget::Headless(ref target) => {
+                target.inject_device_event(device_id.0, event);
+                Ok(())
+            }
+            _ => Err(NotSupportedError::new()),
+        }
+    }
+
+    #[inline]
+    fn virtual_device_id(&self) -> DeviceId {
+        DeviceId(LinuxDeviceId::Headless(headless::VIRTUAL_DEVICE_ID))
+    }
+}
+
+/// The state of a virtual window which can't be queried through the `Window` API.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VirtualWindowState {
+    pub title: String,
+    pub visible: bool,
+    pub resizable: bool,
+    pub decorations: bool,
+    pub enabled_buttons: WindowButtons,
+    pub maximized: bool,
+    pub minimized: bool,
+    pub window_level: WindowLevel,
+    pub blur: bool,
+    /// The last region set with `set_blur_region`, or `None` if the whole window is blurred.
+    pub blur_region: Option<(PhysicalPosition<i32>, PhysicalSize<u32>)>,
+    pub min_inner_size: Option<PhysicalSize<u32>>,
+    pub max_inner_size: Option<PhysicalSize<u32>>,
+    pub resize_increments: Option<PhysicalSize<u32>>,
+    pub cursor_icon: CursorIcon,
+    /// The custom cursor, which replaces `cursor_icon` until another icon is set.
+    pub custom_cursor: Option<CustomCursor>,
+    /// The name and size of the theme set with `set_cursor_theme`, or `None` if the window follows
+    /// the theme of the desktop.
+    pub cursor_theme: Option<(String, u32)>,
+    pub cursor_grab: CursorGrabMode,
+    pub cursor_visible: bool,
+    pub cursor_hittest: bool,
+    /// The last position set with `set_cursor_position`, relative to the window.
+    pub cursor_position: Option<PhysicalPosition<i32>>,
+    /// The last position set with `set_ime_position`, relative to the window.
+    pub ime_position: Option<PhysicalPosition<i32>>,
+    pub user_attention: Option<UserAttentionType>,
+}
+
+/// Additional methods on `Window` to inspect virtual windows.
+pub trait WindowExtHeadless {
+    /// Returns the state of the window, or `None` if it isn't a virtual window.
+    fn virtual_state(&self) -> Option<VirtualWindowState>;
+}
+
+impl WindowExtHeadless for Window {
+    #[inline]
+    fn virtual_state(&self) -> Option<VirtualWindowState> {
+        match self.window {
+            LinuxWindow::Headless(ref window) => Some(window.virtual_state()),
+            _ => None,
+        }
+    }
+}
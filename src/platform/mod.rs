@@ -9,9 +9,12 @@
 //!  - `windows`
 //!  - `web`
 //!
-//! And the following platform-specific module:
+//! And the following platform-specific modules:
 //!
 //! - `run_return` (available on `windows`, `unix`, `macos`, and `android`)
+//! - `pump_events` (available on `unix`)
+//! - `replay` (available on `unix`)
+//! - `headless` (available on `unix`)
 //!
 //! However only the module corresponding to the platform you're compiling to will be available.
 
@@ -21,5 +24,8 @@ pub mod macos;
 pub mod unix;
 pub mod windows;
 
+pub mod headless;
+pub mod pump_events;
+pub mod replay;
 pub mod run_return;
 pub mod web;
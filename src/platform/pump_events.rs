@@ -0,0 +1,69 @@
+#![cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+
+use std::time::Duration;
+
+use crate::{
+    event::Event,
+    event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget},
+};
+
+/// The status returned by [`EventLoopExtPumpEvents::pump_events`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PumpStatus {
+    /// The event loop is still running and `pump_events` should be called again.
+    Continue,
+    /// `ControlFlow::ExitWithCode` was set and `Event::LoopDestroyed` has been dispatched.
+    /// Contains the exit code.
+    Exit(i32),
+}
+
+/// Additional methods on `EventLoop` for pumping events within an external main loop.
+pub trait EventLoopExtPumpEvents {
+    /// A type provided by the user that can be passed through `Event::UserEvent`.
+    type UserEvent;
+
+    /// Dispatches all pending events and returns control flow to the caller.
+    ///
+    /// The first call dispatches `NewEvents(StartCause::Init)`. Every call then waits for new
+    /// events as requested by `control_flow`, but never longer than `timeout`, and dispatches a
+    /// single iteration of the event loop, from `NewEvents` up to `RedrawEventsCleared`. Passing
+    /// `Some(Duration::from_millis(0))` never blocks, while `None` waits exactly as `run` would.
+    ///
+    /// Once `control_flow` is set to `ControlFlow::ExitWithCode`, `LoopDestroyed` is dispatched
+    /// and `PumpStatus::Exit` is returned. Calling `pump_events` again afterwards starts a new run
+    /// with `StartCause::Init`, and resets the control flow to `Poll`.
+    ///
+    /// # Caveats
+    /// The same caveats as for [`run_return`] apply: it is not possible to hide every kind of
+    /// blocking OS operation from the caller.
+    ///
+    /// [`run_return`]: crate::platform::run_return::EventLoopExtRunReturn::run_return
+    fn pump_events<F>(&mut self, timeout: Option<Duration>, event_handler: F) -> PumpStatus
+    where
+        F: FnMut(
+            Event<'_, Self::UserEvent>,
+            &EventLoopWindowTarget<Self::UserEvent>,
+            &mut ControlFlow,
+        );
+}
+
+impl<T> EventLoopExtPumpEvents for EventLoop<T> {
+    type UserEvent = T;
+
+    fn pump_events<F>(&mut self, timeout: Option<Duration>, event_handler: F) -> PumpStatus
+    where
+        F: FnMut(
+            Event<'_, Self::UserEvent>,
+            &EventLoopWindowTarget<Self::UserEvent>,
+            &mut ControlFlow,
+        ),
+    {
+        self.event_loop.pump_events(timeout, event_handler)
+    }
+}
@@ -0,0 +1,536 @@
+#![cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+
+//! Recording of the events dispatched by an event loop, and their deterministic replay without a
+//! display server.
+//!
+//! [`EventRecorder`] turns events into [`EventRecord`]s which, with the `serde` feature, can be
+//! written to a trace with [`EventRecorder::record_to`] and read back with [`read_records`]. The
+//! trace holds one JSON object per line, so it can be inspected and edited by hand. An event loop
+//! created with [`EventLoopExtReplay::new_replay`] then dispatches these records to the same event
+//! handler as a live session:
+//!
+//! ```rust,ignore
+//! let recorder = EventRecorder::new();
+//! let mut trace = BufWriter::new(File::create("events.jsonl")?);
+//! event_loop.run(move |event, _, control_flow| {
+//!     recorder.record_to(&event, &mut trace).unwrap();
+//!     handle_event(event, control_flow);
+//! });
+//!
+//! // Later, in a test:
+//! let records = read_records(BufReader::new(File::open("events.jsonl")?))?;
+//! let mut event_loop = EventLoop::new_replay(records);
+//! event_loop.run_return(|event, _, control_flow| handle_event(event, control_flow));
+//! ```
+
+#[cfg(feature = "serde")]
+use std::io::{self, BufRead, Write};
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{
+        self, AxisId, DeviceEvent, DeviceId, ElementState, Event, Force, ModifiersState,
+        MouseButton, MouseScrollDelta, TouchPhase, WindowEvent,
+    },
+    event_loop::EventLoop,
+    platform_impl::{
+        headless, DeviceId as LinuxDeviceId, EventLoop as LinuxEventLoop, WindowId as LinuxWindowId,
+    },
+    window::{Theme, WindowId},
+};
+
+/// Records the events dispatched by an event loop, with the time elapsed since the recorder was
+/// created.
+#[derive(Debug, Clone)]
+pub struct EventRecorder {
+    start: Instant,
+}
+
+impl Default for EventRecorder {
+    fn default() -> Self {
+        EventRecorder::new()
+    }
+}
+
+impl EventRecorder {
+    /// Creates a recorder, whose records are timed relative to now.
+    pub fn new() -> Self {
+        EventRecorder {
+            start: Instant::now(),
+        }
+    }
+
+    /// Returns the record of `event`, or `None` if it isn't replayed.
+    ///
+    /// `UserEvent`s aren't recorded, since they aren't serializable in general, and neither are
+    /// the events that the replay generates itself: `MainEventsCleared`, `RedrawEventsCleared` and
+    /// `LoopDestroyed`. Only the start of `NewEvents` iterations is recorded, not their cause.
+    pub fn record<T>(&self, event: &Event<'_, T>) -> Option<EventRecord> {
+        let event = match *event {
+            Event::NewEvents(_) => RecordedEvent::NewEvents,
+            Event::WindowEvent {
+                window_id,
+                ref event,
+            } => RecordedEvent::WindowEvent {
+                window_id: window_id.0.raw(),
+                event: RecordedWindowEvent::new(event, self.start),
+            },
+            Event::DeviceEvent {
+                device_id,
+                ref event,
+            } => RecordedEvent::DeviceEvent {
+                device_id: device_id.0.raw(),
+                event: event.clone(),
+            },
+            Event::Suspended => RecordedEvent::Suspended,
+            Event::Resumed => RecordedEvent::Resumed,
+            Event::RedrawRequested(window_id) => RecordedEvent::RedrawRequested(window_id.0.raw()),
+            Event::UserEvent(_)
+            | Event::MainEventsCleared
+            | Event::RedrawEventsCleared
+            | Event::LoopDestroyed => return None,
+        };
+
+        Some(EventRecord {
+            time: self.start.elapsed(),
+            event,
+        })
+    }
+
+    /// Writes the record of `event` to `writer` as a line of JSON, unless it isn't replayed.
+    ///
+    /// The lines are read back with [`read_records`].
+    #[cfg(feature = "serde")]
+    pub fn record_to<T, W: Write>(&self, event: &Event<'_, T>, mut writer: W) -> io::Result<()> {
+        let record = match self.record(event) {
+            Some(record) => record,
+            None => return Ok(()),
+        };
+        serde_json::to_writer(&mut writer, &record)?;
+        writer.write_all(b"\n")
+    }
+}
+
+/// Reads the records written with [`EventRecorder::record_to`], for
+/// [`EventLoopExtReplay::new_replay`].
+///
+/// Blank lines are skipped. An error is returned for the first line which isn't a record.
+#[cfg(feature = "serde")]
+pub fn read_records<R: BufRead>(reader: R) -> io::Result<Vec<EventRecord>> {
+    let mut records = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        records.push(serde_json::from_str(&line)?);
+    }
+    Ok(records)
+}
+
+/// A recorded event.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EventRecord {
+    /// The time elapsed between the creation of the `EventRecorder` and the event.
+    pub time: Duration,
+    pub event: RecordedEvent,
+}
+
+/// The serializable counterpart of an [`Event`].
+///
+/// Window and device ids are recorded as the raw ids of the backend they were recorded on.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RecordedEvent {
+    /// The start of an event loop iteration.
+    NewEvents,
+    WindowEvent {
+        window_id: u64,
+        event: RecordedWindowEvent,
+    },
+    DeviceEvent {
+        device_id: u64,
+        event: DeviceEvent,
+    },
+    Suspended,
+    Resumed,
+    RedrawRequested(u64),
+}
+
+/// The serializable counterpart of a [`WindowEvent`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RecordedWindowEvent {
+    Resized(PhysicalSize<u32>),
+    Moved(PhysicalPosition<i32>),
+    CloseRequested,
+    Destroyed,
+    DroppedFile(PathBuf),
+    HoveredFile(PathBuf),
+    HoveredFileCancelled,
+    ReceivedCharacter(char),
+    Focused(bool),
+    KeyboardInput {
+        device_id: u64,
+        input: event::KeyboardInput,
+        is_synthetic: bool,
+    },
+    ModifiersChanged(ModifiersState),
+    CursorMoved {
+        device_id: u64,
+        position: PhysicalPosition<f64>,
+        modifiers: ModifiersState,
+    },
+    CursorEntered {
+        device_id: u64,
+    },
+    CursorLeft {
+        device_id: u64,
+    },
+    MouseWheel {
+        device_id: u64,
+        delta: MouseScrollDelta,
+        phase: TouchPhase,
+        modifiers: ModifiersState,
+    },
+    MouseInput {
+        device_id: u64,
+        state: ElementState,
+        button: MouseButton,
+        modifiers: ModifiersState,
+    },
+    TouchpadPressure {
+        device_id: u64,
+        pressure: f32,
+        stage: i64,
+    },
+    AxisMotion {
+        device_id: u64,
+        axis: AxisId,
+        value: f64,
+    },
+    Touch {
+        device_id: u64,
+        phase: TouchPhase,
+        location: PhysicalPosition<f64>,
+        force: Option<Force>,
+        id: u64,
+    },
+    /// The size suggested by the OS, before the event handler could change it.
+    ScaleFactorChanged {
+        scale_factor: f64,
+        new_inner_size: PhysicalSize<u32>,
+    },
+    ThemeChanged(Theme),
+    Occluded(bool),
+    PopupDismissed,
+    /// `target_time` is relative to the creation of the `EventRecorder`.
+    FrameReady {
+        target_time: Duration,
+    },
+}
+
+impl RecordedWindowEvent {
+    #[allow(deprecated)]
+    fn new(event: &WindowEvent<'_>, start: Instant) -> Self {
+        use self::RecordedWindowEvent::*;
+
+        match *event {
+            WindowEvent::Resized(size) => Resized(size),
+            WindowEvent::Moved(position) => Moved(position),
+            WindowEvent::CloseRequested => CloseRequested,
+            WindowEvent::Destroyed => Destroyed,
+            WindowEvent::DroppedFile(ref path) => DroppedFile(path.clone()),
+            WindowEvent::HoveredFile(ref path) => HoveredFile(path.clone()),
+            WindowEvent::HoveredFileCancelled => HoveredFileCancelled,
+            WindowEvent::ReceivedCharacter(c) => ReceivedCharacter(c),
+            WindowEvent::Focused(focused) => Focused(focused),
+            WindowEvent::KeyboardInput {
+                device_id,
+                input,
+                is_synthetic,
+            } => KeyboardInput {
+                device_id: device_id.0.raw(),
+                input,
+                is_synthetic,
+            },
+            WindowEvent::ModifiersChanged(modifiers) => ModifiersChanged(modifiers),
+            WindowEvent::CursorMoved {
+                device_id,
+                position,
+                modifiers,
+            } => CursorMoved {
+                device_id: device_id.0.raw(),
+                position,
+                modifiers,
+            },
+            WindowEvent::CursorEntered { device_id } => CursorEntered {
+                device_id: device_id.0.raw(),
+            },
+            WindowEvent::CursorLeft { device_id } => CursorLeft {
+                device_id: device_id.0.raw(),
+            },
+            WindowEvent::MouseWheel {
+                device_id,
+                delta,
+                phase,
+                modifiers,
+            } => MouseWheel {
+                device_id: device_id.0.raw(),
+                delta,
+                phase,
+                modifiers,
+            },
+            WindowEvent::MouseInput {
+                device_id,
+                state,
+                button,
+                modifiers,
+            } => MouseInput {
+                device_id: device_id.0.raw(),
+                state,
+                button,
+                modifiers,
+            },
+            WindowEvent::TouchpadPressure {
+                device_id,
+                pressure,
+                stage,
+            } => TouchpadPressure {
+                device_id: device_id.0.raw(),
+                pressure,
+                stage,
+            },
+            WindowEvent::AxisMotion {
+                device_id,
+                axis,
+                value,
+            } => AxisMotion {
+                device_id: device_id.0.raw(),
+                axis,
+                value,
+            },
+            WindowEvent::Touch(touch) => Touch {
+                device_id: touch.device_id.0.raw(),
+                phase: touch.phase,
+                location: touch.location,
+                force: touch.force,
+                id: touch.id,
+            },
+            WindowEvent::ScaleFactorChanged {
+                scale_factor,
+                ref new_inner_size,
+            } => ScaleFactorChanged {
+                scale_factor,
+                new_inner_size: **new_inner_size,
+            },
+            WindowEvent::ThemeChanged(theme) => ThemeChanged(theme),
+            WindowEvent::Occluded(occluded) => Occluded(occluded),
+            WindowEvent::PopupDismissed => PopupDismissed,
+            WindowEvent::FrameReady { target_time } => FrameReady {
+                target_time: target_time.saturating_duration_since(start),
+            },
+        }
+    }
+
+    /// Returns the window event replayed at `replay_start + self.time`. `ScaleFactorChanged` points
+    /// to `new_inner_size`, which is set to the recorded size.
+    #[allow(deprecated)]
+    fn into_window_event(
+        self,
+        replay_start: Instant,
+        new_inner_size: &mut PhysicalSize<u32>,
+    ) -> WindowEvent<'_> {
+        use self::RecordedWindowEvent::*;
+
+        match self {
+            Resized(size) => WindowEvent::Resized(size),
+            Moved(position) => WindowEvent::Moved(position),
+            CloseRequested => WindowEvent::CloseRequested,
+            Destroyed => WindowEvent::Destroyed,
+            DroppedFile(path) => WindowEvent::DroppedFile(path),
+            HoveredFile(path) => WindowEvent::HoveredFile(path),
+            HoveredFileCancelled => WindowEvent::HoveredFileCancelled,
+            ReceivedCharacter(c) => WindowEvent::ReceivedCharacter(c),
+            Focused(focused) => WindowEvent::Focused(focused),
+            KeyboardInput {
+                device_id,
+                input,
+                is_synthetic,
+            } => WindowEvent::KeyboardInput {
+                device_id: replayed_device_id(device_id),
+                input,
+                is_synthetic,
+            },
+            ModifiersChanged(modifiers) => WindowEvent::ModifiersChanged(modifiers),
+            CursorMoved {
+                device_id,
+                position,
+                modifiers,
+            } => WindowEvent::CursorMoved {
+                device_id: replayed_device_id(device_id),
+                position,
+                modifiers,
+            },
+            CursorEntered { device_id } => WindowEvent::CursorEntered {
+                device_id: replayed_device_id(device_id),
+            },
+            CursorLeft { device_id } => WindowEvent::CursorLeft {
+                device_id: replayed_device_id(device_id),
+            },
+            MouseWheel {
+                device_id,
+                delta,
+                phase,
+                modifiers,
+            } => WindowEvent::MouseWheel {
+                device_id: replayed_device_id(device_id),
+                delta,
+                phase,
+                modifiers,
+            },
+            MouseInput {
+                device_id,
+                state,
+                button,
+                modifiers,
+            } => WindowEvent::MouseInput {
+                device_id: replayed_device_id(device_id),
+                state,
+                button,
+                modifiers,
+            },
+            TouchpadPressure {
+                device_id,
+                pressure,
+                stage,
+            } => WindowEvent::TouchpadPressure {
+                device_id: replayed_device_id(device_id),
+                pressure,
+                stage,
+            },
+            AxisMotion {
+                device_id,
+                axis,
+                value,
+            } => WindowEvent::AxisMotion {
+                device_id: replayed_device_id(device_id),
+                axis,
+                value,
+            },
+            Touch {
+                device_id,
+                phase,
+                location,
+                force,
+                id,
+            } => WindowEvent::Touch(event::Touch {
+                device_id: replayed_device_id(device_id),
+                phase,
+                location,
+                force,
+                id,
+            }),
+            ScaleFactorChanged {
+                scale_factor,
+                new_inner_size: recorded_size,
+            } => {
+                *new_inner_size = recorded_size;
+                WindowEvent::ScaleFactorChanged {
+                    scale_factor,
+                    new_inner_size,
+                }
+            }
+            ThemeChanged(theme) => WindowEvent::ThemeChanged(theme),
+            Occluded(occluded) => WindowEvent::Occluded(occluded),
+            PopupDismissed => WindowEvent::PopupDismissed,
+            FrameReady { target_time } => WindowEvent::FrameReady {
+                target_time: replay_start + target_time,
+            },
+        }
+    }
+}
+
+impl RecordedEvent {
+    /// Returns the event to dispatch for this record, or `None` for `NewEvents`, which the replay
+    /// dispatches itself.
+    pub(crate) fn into_event<T>(
+        self,
+        replay_start: Instant,
+        new_inner_size: &mut PhysicalSize<u32>,
+    ) -> Option<Event<'_, T>> {
+        match self {
+            RecordedEvent::NewEvents => None,
+            RecordedEvent::WindowEvent { window_id, event } => Some(Event::WindowEvent {
+                window_id: replayed_window_id(window_id),
+                event: event.into_window_event(replay_start, new_inner_size),
+            }),
+            RecordedEvent::DeviceEvent { device_id, event } => Some(Event::DeviceEvent {
+                device_id: replayed_device_id(device_id),
+                event,
+            }),
+            RecordedEvent::Suspended => Some(Event::Suspended),
+            RecordedEvent::Resumed => Some(Event::Resumed),
+            RecordedEvent::RedrawRequested(window_id) => {
+                Some(Event::RedrawRequested(replayed_window_id(window_id)))
+            }
+        }
+    }
+}
+
+fn replayed_window_id(raw: u64) -> WindowId {
+    WindowId(LinuxWindowId::Headless(headless::WindowId(raw)))
+}
+
+fn replayed_device_id(raw: u64) -> DeviceId {
+    DeviceId(LinuxDeviceId::Headless(headless::DeviceId(raw)))
+}
+
+/// Additional methods on `EventLoop` to replay recorded events.
+pub trait EventLoopExtReplay {
+    /// Creates an event loop which dispatches `records` in order, without connecting to a display
+    /// server.
+    ///
+    /// Every recorded `NewEvents` starts a new iteration, so events are dispatched in the same
+    /// iterations as they were recorded in, followed by the user events sent to the event loop's
+    /// proxies and by `RedrawRequested`. The event loop never waits: the first iteration starts
+    /// with `StartCause::Init`, and the following ones with `StartCause::Poll`, regardless of the
+    /// control flow. Once every record has been dispatched, the event loop exits as if
+    /// `ControlFlow::Exit` was set.
+    ///
+    /// The event loop is [headless](crate::platform::headless), so the application can create
+    /// virtual windows while replaying. Each recorded window id stands for the oldest virtual
+    /// window which doesn't stand for another recorded id yet, from the first event it appears in,
+    /// and replayed `Resized` and `Moved` events change the geometry of that window. Recorded ids
+    /// without a virtual window to stand for, and device ids, are replayed with their recorded raw
+    /// values.
+    fn new_replay<I>(records: I) -> Self
+    where
+        I: IntoIterator<Item = EventRecord>;
+}
+
+impl<T> EventLoopExtReplay for EventLoop<T> {
+    #[inline]
+    fn new_replay<I>(records: I) -> Self
+    where
+        I: IntoIterator<Item = EventRecord>,
+    {
+        EventLoop {
+            event_loop: LinuxEventLoop::Headless(headless::EventLoop::new_replay(
+                records.into_iter().collect(),
+            )),
+            _marker: ::std::marker::PhantomData,
+        }
+    }
+}
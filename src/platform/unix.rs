@@ -6,23 +6,29 @@
     target_os = "openbsd"
 ))]
 
-use std::os::raw;
+use std::{
+    io,
+    os::{
+        raw,
+        unix::io::{AsRawFd, RawFd},
+    },
+};
 #[cfg(feature = "x11")]
 use std::{ptr, sync::Arc};
 
 use crate::{
-    event_loop::{EventLoop, EventLoopWindowTarget},
+    error::EventLoopError,
+    event_loop::{EventLoop, EventLoopProxy, EventLoopWindowTarget, FdToken, TrySendError},
     monitor::MonitorHandle,
     window::{Window, WindowBuilder},
 };
 
-#[cfg(feature = "x11")]
-use crate::dpi::Size;
+use crate::dpi::{Position, Size};
 #[cfg(feature = "x11")]
 use crate::platform_impl::x11::{ffi::XVisualInfo, XConnection};
 use crate::platform_impl::{
     EventLoop as LinuxEventLoop, EventLoopWindowTarget as LinuxEventLoopWindowTarget,
-    Window as LinuxWindow,
+    PopupAttributes, Window as LinuxWindow,
 };
 
 // TODO: stupid hack so that glutin can do its work
@@ -32,6 +38,8 @@ pub use crate::platform_impl::x11;
 #[cfg(feature = "x11")]
 pub use crate::platform_impl::{x11::util::WindowType as XWindowType, XNotSupported};
 
+pub use crate::platform_impl::EventLoopError as UnixEventLoopError;
+
 /// Additional methods on `EventLoopWindowTarget` that are specific to Unix.
 pub trait EventLoopWindowTargetExtUnix {
     /// True if the `EventLoopWindowTarget` uses Wayland.
@@ -54,6 +62,23 @@ pub trait EventLoopWindowTargetExtUnix {
     /// The pointer will become invalid when the winit `EventLoop` is destroyed.
     #[cfg(feature = "wayland")]
     fn wayland_display(&self) -> Option<*mut raw::c_void>;
+
+    /// Registers a file descriptor with the event loop.
+    ///
+    /// Whenever `fd` is readable, the event loop wakes up and starts an iteration with
+    /// [`StartCause::FdReady(token)`][fd_ready]. The file descriptor is level-triggered, so it
+    /// keeps waking the event loop until it has been read from or unregistered.
+    ///
+    /// Returns an error if `fd` is negative or already registered.
+    ///
+    /// [fd_ready]: crate::event::StartCause::FdReady
+    fn register_fd(&self, fd: RawFd, token: FdToken) -> io::Result<()>;
+
+    /// Unregisters a file descriptor previously registered with
+    /// [`register_fd`](#tymethod.register_fd).
+    ///
+    /// This must be called before `fd` is closed. Returns an error if `fd` isn't registered.
+    fn unregister_fd(&self, fd: RawFd) -> io::Result<()>;
 }
 
 impl<T> EventLoopWindowTargetExtUnix for EventLoopWindowTarget<T> {
@@ -66,7 +91,7 @@ impl<T> EventLoopWindowTargetExtUnix for EventLoopWindowTarget<T> {
     #[inline]
     #[cfg(feature = "x11")]
     fn is_x11(&self) -> bool {
-        !self.p.is_wayland()
+        self.p.is_x11()
     }
 
     #[inline]
@@ -75,7 +100,6 @@ impl<T> EventLoopWindowTargetExtUnix for EventLoopWindowTarget<T> {
     fn xlib_xconnection(&self) -> Option<Arc<XConnection>> {
         match self.p {
             LinuxEventLoopWindowTarget::X(ref e) => Some(e.x_connection().clone()),
-            #[cfg(feature = "wayland")]
             _ => None,
         }
     }
@@ -87,10 +111,19 @@ impl<T> EventLoopWindowTargetExtUnix for EventLoopWindowTarget<T> {
             LinuxEventLoopWindowTarget::Wayland(ref p) => {
                 Some(p.display().get_display_ptr() as *mut _)
             }
-            #[cfg(feature = "x11")]
             _ => None,
         }
     }
+
+    #[inline]
+    fn register_fd(&self, fd: RawFd, token: FdToken) -> io::Result<()> {
+        self.p.register_fd(fd, token)
+    }
+
+    #[inline]
+    fn unregister_fd(&self, fd: RawFd) -> io::Result<()> {
+        self.p.unregister_fd(fd)
+    }
 }
 
 /// Additional methods on `EventLoop` that are specific to Unix.
@@ -117,6 +150,18 @@ pub trait EventLoopExtUnix {
     where
         Self: Sized;
 
+    /// Builds a new `EventLoop` that is forced to use Wayland, returning an error if the
+    /// connection to the compositor failed.
+    ///
+    /// # Panics
+    ///
+    /// If called outside the main thread. To initialize a Wayland event loop outside
+    /// the main thread, use [`try_new_wayland_any_thread`](#tymethod.try_new_wayland_any_thread).
+    #[cfg(feature = "wayland")]
+    fn try_new_wayland() -> Result<Self, EventLoopError>
+    where
+        Self: Sized;
+
     /// Builds a new `EventLoop` on any thread.
     ///
     /// This method bypasses the cross-platform compatibility requirement
@@ -125,6 +170,15 @@ pub trait EventLoopExtUnix {
     where
         Self: Sized;
 
+    /// Builds a new `EventLoop` on any thread, returning an error if no backend could be
+    /// initialized.
+    ///
+    /// This method bypasses the cross-platform compatibility requirement
+    /// that `EventLoop` be created on the main thread.
+    fn try_new_any_thread() -> Result<Self, EventLoopError>
+    where
+        Self: Sized;
+
     /// Builds a new X11 `EventLoop` on any thread.
     ///
     /// This method bypasses the cross-platform compatibility requirement
@@ -142,6 +196,16 @@ pub trait EventLoopExtUnix {
     fn new_wayland_any_thread() -> Self
     where
         Self: Sized;
+
+    /// Builds a new Wayland `EventLoop` on any thread, returning an error if the connection to
+    /// the compositor failed.
+    ///
+    /// This method bypasses the cross-platform compatibility requirement
+    /// that `EventLoop` be created on the main thread.
+    #[cfg(feature = "wayland")]
+    fn try_new_wayland_any_thread() -> Result<Self, EventLoopError>
+    where
+        Self: Sized;
 }
 
 fn wrap_ev<T>(event_loop: LinuxEventLoop<T>) -> EventLoop<T> {
@@ -157,6 +221,11 @@ impl<T> EventLoopExtUnix for EventLoop<T> {
         wrap_ev(LinuxEventLoop::new_any_thread())
     }
 
+    #[inline]
+    fn try_new_any_thread() -> Result<Self, EventLoopError> {
+        Ok(wrap_ev(LinuxEventLoop::try_new_any_thread()?))
+    }
+
     #[inline]
     #[cfg(feature = "x11")]
     fn new_x11_any_thread() -> Result<Self, XNotSupported> {
@@ -166,11 +235,15 @@ impl<T> EventLoopExtUnix for EventLoop<T> {
     #[inline]
     #[cfg(feature = "wayland")]
     fn new_wayland_any_thread() -> Self {
-        wrap_ev(
-            LinuxEventLoop::new_wayland_any_thread()
-                // TODO: propagate
-                .expect("failed to open Wayland connection"),
-        )
+        Self::try_new_wayland_any_thread().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    #[inline]
+    #[cfg(feature = "wayland")]
+    fn try_new_wayland_any_thread() -> Result<Self, EventLoopError> {
+        LinuxEventLoop::new_wayland_any_thread()
+            .map(wrap_ev)
+            .map_err(|err| UnixEventLoopError::Wayland(err).into())
     }
 
     #[inline]
@@ -182,11 +255,110 @@ impl<T> EventLoopExtUnix for EventLoop<T> {
     #[inline]
     #[cfg(feature = "wayland")]
     fn new_wayland() -> Self {
-        wrap_ev(
-            LinuxEventLoop::new_wayland()
-                // TODO: propagate
-                .expect("failed to open Wayland connection"),
-        )
+        Self::try_new_wayland().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    #[inline]
+    #[cfg(feature = "wayland")]
+    fn try_new_wayland() -> Result<Self, EventLoopError> {
+        LinuxEventLoop::new_wayland()
+            .map(wrap_ev)
+            .map_err(|err| UnixEventLoopError::Wayland(err).into())
+    }
+}
+
+/// Additional methods on `EventLoop` to create proxies with a bounded capacity.
+pub trait EventLoopExtBoundedProxy {
+    /// A type provided by the user that can be passed through `Event::UserEvent`.
+    type UserEvent;
+
+    /// Creates an `EventLoopProxy` which can't have more than `capacity` events waiting to be
+    /// received by the event loop. The capacity is shared by all clones of the returned proxy.
+    ///
+    /// [`EventLoopProxyExtUnix::try_send_event`] fails with [`TrySendError::Full`] once the
+    /// capacity is used up, while `send_event` blocks until the event loop catches up. Don't call
+    /// `send_event` on a bounded proxy from the event loop's thread, since it would never catch up.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is 0.
+    fn create_proxy_bounded(&self, capacity: usize) -> EventLoopProxy<Self::UserEvent>;
+}
+
+impl<T> EventLoopExtBoundedProxy for EventLoop<T> {
+    type UserEvent = T;
+
+    #[inline]
+    fn create_proxy_bounded(&self, capacity: usize) -> EventLoopProxy<T> {
+        EventLoopProxy {
+            event_loop_proxy: self.event_loop.create_proxy_bounded(capacity),
+        }
+    }
+}
+
+/// Additional methods on `EventLoopProxy` that are specific to Unix.
+pub trait EventLoopProxyExtUnix {
+    /// A type provided by the user that can be passed through `Event::UserEvent`.
+    type UserEvent;
+
+    /// Sends an event to the `EventLoop` like `send_event`, but fails with
+    /// [`TrySendError::Full`] instead of blocking when the proxy was created with
+    /// [`EventLoopExtBoundedProxy::create_proxy_bounded`] and its capacity is used up.
+    ///
+    /// Proxies created with `create_proxy` are never full.
+    fn try_send_event(&self, event: Self::UserEvent) -> Result<(), TrySendError<Self::UserEvent>>;
+
+    /// Wakes up the `EventLoop` without sending an event, which starts a new iteration with
+    /// `StartCause::WaitCancelled`. Does nothing if the event loop no longer exists.
+    ///
+    /// Wakeups requested before the event loop gets to handle them are coalesced into one.
+    fn wake_up(&self);
+}
+
+impl<T: 'static> EventLoopProxyExtUnix for EventLoopProxy<T> {
+    type UserEvent = T;
+
+    #[inline]
+    fn try_send_event(&self, event: T) -> Result<(), TrySendError<T>> {
+        self.event_loop_proxy.try_send_event(event)
+    }
+
+    #[inline]
+    fn wake_up(&self) {
+        self.event_loop_proxy.wake_up()
+    }
+}
+
+/// The file descriptor is readable whenever the event loop has work to do, which allows waiting
+/// for it from another reactor and then calling
+/// [`pump_events`](crate::platform::pump_events::EventLoopExtPumpEvents::pump_events) with a
+/// zero timeout.
+///
+/// Call `pump_events` once before waiting, since events may already be queued in-process without
+/// the file descriptor being readable.
+///
+/// ## Platform-specific
+///
+/// - **Wayland:** Timers internal to the backend, such as the keyboard repeat, don't make the file
+///   descriptor readable.
+impl<T> AsRawFd for EventLoop<T> {
+    #[inline]
+    fn as_raw_fd(&self) -> RawFd {
+        self.event_loop.as_raw_fd()
+    }
+}
+
+/// Additional methods on `EventLoopError` that are specific to Unix.
+pub trait EventLoopErrorExtUnix {
+    /// Returns the reason why the event loop could not be created, including the failure of
+    /// each backend that was tried.
+    fn unix_error(&self) -> &UnixEventLoopError;
+}
+
+impl EventLoopErrorExtUnix for EventLoopError {
+    #[inline]
+    fn unix_error(&self) -> &UnixEventLoopError {
+        &self.error
     }
 }
 
@@ -238,6 +410,9 @@ pub trait WindowExtUnix {
     fn wayland_display(&self) -> Option<*mut raw::c_void>;
 
     /// Sets the color theme of the client side window decorations on wayland
+    ///
+    /// The decorations then keep these colors, instead of following `Window::set_theme` and the
+    /// desktop theme.
     #[cfg(feature = "wayland")]
     fn set_wayland_theme<T: Theme>(&self, theme: T);
 
@@ -257,7 +432,6 @@ impl WindowExtUnix for Window {
     fn xlib_window(&self) -> Option<raw::c_ulong> {
         match self.window {
             LinuxWindow::X(ref w) => Some(w.xlib_window()),
-            #[cfg(feature = "wayland")]
             _ => None,
         }
     }
@@ -267,7 +441,6 @@ impl WindowExtUnix for Window {
     fn xlib_display(&self) -> Option<*mut raw::c_void> {
         match self.window {
             LinuxWindow::X(ref w) => Some(w.xlib_display()),
-            #[cfg(feature = "wayland")]
             _ => None,
         }
     }
@@ -277,7 +450,6 @@ impl WindowExtUnix for Window {
     fn xlib_screen_id(&self) -> Option<raw::c_int> {
         match self.window {
             LinuxWindow::X(ref w) => Some(w.xlib_screen_id()),
-            #[cfg(feature = "wayland")]
             _ => None,
         }
     }
@@ -288,7 +460,6 @@ impl WindowExtUnix for Window {
     fn xlib_xconnection(&self) -> Option<Arc<XConnection>> {
         match self.window {
             LinuxWindow::X(ref w) => Some(w.xlib_xconnection()),
-            #[cfg(feature = "wayland")]
             _ => None,
         }
     }
@@ -298,7 +469,6 @@ impl WindowExtUnix for Window {
     fn xcb_connection(&self) -> Option<*mut raw::c_void> {
         match self.window {
             LinuxWindow::X(ref w) => Some(w.xcb_connection()),
-            #[cfg(feature = "wayland")]
             _ => None,
         }
     }
@@ -308,7 +478,6 @@ impl WindowExtUnix for Window {
     fn wayland_surface(&self) -> Option<*mut raw::c_void> {
         match self.window {
             LinuxWindow::Wayland(ref w) => Some(w.surface().as_ref().c_ptr() as *mut _),
-            #[cfg(feature = "x11")]
             _ => None,
         }
     }
@@ -318,7 +487,6 @@ impl WindowExtUnix for Window {
     fn wayland_display(&self) -> Option<*mut raw::c_void> {
         match self.window {
             LinuxWindow::Wayland(ref w) => Some(w.display().get_display_ptr() as *mut _),
-            #[cfg(feature = "x11")]
             _ => None,
         }
     }
@@ -326,10 +494,8 @@ impl WindowExtUnix for Window {
     #[inline]
     #[cfg(feature = "wayland")]
     fn set_wayland_theme<T: Theme>(&self, theme: T) {
-        match self.window {
-            LinuxWindow::Wayland(ref w) => w.set_theme(theme),
-            #[cfg(feature = "x11")]
-            _ => {}
+        if let LinuxWindow::Wayland(ref w) = self.window {
+            w.set_csd_theme(theme)
         }
     }
 
@@ -358,11 +524,9 @@ pub trait WindowBuilderExtUnix {
     /// Build window with `_GTK_THEME_VARIANT` hint set to the specified value. Currently only relevant on X11.
     #[cfg(feature = "x11")]
     fn with_gtk_theme_variant(self, variant: String) -> Self;
-    /// Build window with resize increment hint. Only implemented on X11.
-    #[cfg(feature = "x11")]
+    /// Build window with resize increment hint. See `Window::set_resize_increments`.
     fn with_resize_increments<S: Into<Size>>(self, increments: S) -> Self;
-    /// Build window with base size hint. Only implemented on X11.
-    #[cfg(feature = "x11")]
+    /// Build window with base size hint, which the resize increments are counted from.
     fn with_base_size<S: Into<Size>>(self, base_size: S) -> Self;
 
     /// Build window with a given application ID. It should match the `.desktop` file distributed with
@@ -372,6 +536,45 @@ pub trait WindowBuilderExtUnix {
     /// [Desktop Entry Spec](https://specifications.freedesktop.org/desktop-entry-spec/desktop-entry-spec-latest.html#desktop-file-id)
     #[cfg(feature = "wayland")]
     fn with_app_id(self, app_id: String) -> Self;
+
+    /// Build a popup window, like a menu or a tooltip, placed relative to `anchor_rect` in the
+    /// client area of `parent`.
+    ///
+    /// The popup is undecorated, extends from `anchor_rect` in the direction of `gravity`, and is
+    /// moved or resized as allowed by `constraint_adjustment` to stay on the monitor. It is hidden
+    /// when the user clicks outside of it, which sends [`WindowEvent::PopupDismissed`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** The popup is an override-redirect window, which grabs the pointer while it's
+    ///   visible.
+    /// - **Wayland:** The popup is an `xdg_popup`, which grabs the seat with the latest key or
+    ///   button press. Building the window fails if `parent` was closed, and dropping a popup
+    ///   closes the popups built on top of it as well.
+    ///
+    /// [`WindowEvent::PopupDismissed`]: crate::event::WindowEvent::PopupDismissed
+    fn with_popup(
+        self,
+        parent: &Window,
+        anchor_rect: PopupAnchorRect,
+        gravity: PopupGravity,
+        constraint_adjustment: PopupConstraintAdjustment,
+    ) -> Self;
+
+    /// Build a window owned by `owner`, like a dialog, which the system keeps above its owner.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Sets `WM_TRANSIENT_FOR`, and the `Dialog` window type unless another type was
+    ///   set with `with_x11_window_type`.
+    /// - **Wayland:** Sets the parent of the `xdg_toplevel`.
+    fn with_owner(self, owner: &Window) -> Self;
+
+    /// Build a modal window, which swallows the input to its owner while it's visible; defaults to
+    /// false. Only relevant along with `with_owner`.
+    ///
+    /// On X11, the window manager is also told with `_NET_WM_STATE_MODAL`.
+    fn with_modal(self, modal: bool) -> Self;
 }
 
 impl WindowBuilderExtUnix for WindowBuilder {
@@ -421,14 +624,12 @@ impl WindowBuilderExtUnix for WindowBuilder {
     }
 
     #[inline]
-    #[cfg(feature = "x11")]
     fn with_resize_increments<S: Into<Size>>(mut self, increments: S) -> Self {
         self.platform_specific.resize_increments = Some(increments.into());
         self
     }
 
     #[inline]
-    #[cfg(feature = "x11")]
     fn with_base_size<S: Into<Size>>(mut self, base_size: S) -> Self {
         self.platform_specific.base_size = Some(base_size.into());
         self
@@ -440,6 +641,85 @@ impl WindowBuilderExtUnix for WindowBuilder {
         self.platform_specific.app_id = Some(app_id);
         self
     }
+
+    #[inline]
+    fn with_popup(
+        mut self,
+        parent: &Window,
+        anchor_rect: PopupAnchorRect,
+        gravity: PopupGravity,
+        constraint_adjustment: PopupConstraintAdjustment,
+    ) -> Self {
+        self.window.decorations = false;
+        self.platform_specific.popup = Some(PopupAttributes {
+            parent: parent.id().0,
+            anchor_rect,
+            gravity,
+            constraint_adjustment,
+            scale_factor: parent.scale_factor(),
+        });
+        self
+    }
+
+    #[inline]
+    fn with_owner(mut self, owner: &Window) -> Self {
+        self.platform_specific.owner = Some(owner.id().0);
+        self
+    }
+
+    #[inline]
+    fn with_modal(mut self, modal: bool) -> Self {
+        self.platform_specific.modal = modal;
+        self
+    }
+}
+
+/// A rectangle of the client area of a window, which a popup is placed relative to.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PopupAnchorRect {
+    /// The position of the rectangle, relative to the top-left corner of the client area.
+    pub position: Position,
+    pub size: Size,
+}
+
+/// The direction in which a popup extends from its anchor rectangle.
+///
+/// The popup is attached to the matching edge or corner of the rectangle, e.g. `BottomRight`
+/// places its top-left corner on the bottom-right corner of the rectangle. `None` centers it on the
+/// rectangle.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum PopupGravity {
+    None,
+    Top,
+    Bottom,
+    Left,
+    Right,
+    TopLeft,
+    BottomLeft,
+    TopRight,
+    BottomRight,
+}
+
+bitflags! {
+    /// How a popup may be adjusted when it would go past the edges of its monitor.
+    ///
+    /// Flipping is tried first, then sliding, then resizing. Without any adjustment, the popup
+    /// is placed as requested, even partially offscreen.
+    #[derive(Default)]
+    pub struct PopupConstraintAdjustment: u32 {
+        /// Move the popup horizontally until it fits.
+        const SLIDE_X = 1 << 0;
+        /// Move the popup vertically until it fits.
+        const SLIDE_Y = 1 << 1;
+        /// Extend the popup in the opposite horizontal direction, if it fits there.
+        const FLIP_X = 1 << 2;
+        /// Extend the popup in the opposite vertical direction, if it fits there.
+        const FLIP_Y = 1 << 3;
+        /// Shrink the popup horizontally to the part that fits.
+        const RESIZE_X = 1 << 4;
+        /// Shrink the popup vertically to the part that fits.
+        const RESIZE_Y = 1 << 5;
+    }
 }
 
 /// Additional methods on `MonitorHandle` that are specific to Linux.
@@ -103,7 +103,7 @@ impl WindowExtWindows for Window {
 
     #[inline]
     fn theme(&self) -> Theme {
-        self.window.theme()
+        self.window.theme().unwrap_or(Theme::Light)
     }
 }
 
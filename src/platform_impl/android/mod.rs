@@ -1,6 +1,7 @@
 #![cfg(target_os = "android")]
 
 use crate::{
+    clipboard,
     dpi::{PhysicalPosition, PhysicalSize, Position, Size},
     error, event,
     event_loop::{self, ControlFlow},
@@ -13,7 +14,7 @@ use ndk::{
 };
 use ndk_glue::{Event, Rect};
 use std::{
-    collections::VecDeque,
+    collections::{HashMap, VecDeque},
     sync::{Arc, Mutex, RwLock},
     time::{Duration, Instant},
 };
@@ -52,10 +53,10 @@ pub struct EventLoop<T: 'static> {
 
 macro_rules! call_event_handler {
     ( $event_handler:expr, $window_target:expr, $cf:expr, $event:expr ) => {{
-        if $cf != ControlFlow::Exit {
-            $event_handler($event, $window_target, &mut $cf);
+        if let ControlFlow::ExitWithCode(code) = $cf {
+            $event_handler($event, $window_target, &mut ControlFlow::ExitWithCode(code));
         } else {
-            $event_handler($event, $window_target, &mut ControlFlow::Exit);
+            $event_handler($event, $window_target, &mut $cf);
         }
     }};
 }
@@ -77,16 +78,20 @@ impl<T: 'static> EventLoop<T> {
         }
     }
 
+    pub fn try_new() -> Result<Self, EventLoopError> {
+        Ok(Self::new())
+    }
+
     pub fn run<F>(mut self, event_handler: F) -> !
     where
         F: 'static
             + FnMut(event::Event<'_, T>, &event_loop::EventLoopWindowTarget<T>, &mut ControlFlow),
     {
-        self.run_return(event_handler);
-        ::std::process::exit(0);
+        let exit_code = self.run_return(event_handler);
+        ::std::process::exit(exit_code);
     }
 
-    pub fn run_return<F>(&mut self, mut event_handler: F)
+    pub fn run_return<F>(&mut self, mut event_handler: F) -> i32
     where
         F: FnMut(event::Event<'_, T>, &event_loop::EventLoopWindowTarget<T>, &mut ControlFlow),
     {
@@ -97,7 +102,7 @@ impl<T: 'static> EventLoop<T> {
                 event_handler,
                 self.window_target(),
                 control_flow,
-                event::Event::NewEvents(self.start_cause)
+                event::Event::NewEvents(self.start_cause.clone())
             );
 
             let mut redraw = false;
@@ -285,7 +290,7 @@ impl<T: 'static> EventLoop<T> {
             );
 
             match control_flow {
-                ControlFlow::Exit => {
+                ControlFlow::ExitWithCode(code) => {
                     self.first_event = poll(
                         self.looper
                             .poll_once_timeout(Duration::from_millis(0))
@@ -295,7 +300,7 @@ impl<T: 'static> EventLoop<T> {
                         start: Instant::now(),
                         requested_resume: None,
                     };
-                    break 'event_loop;
+                    break 'event_loop code;
                 }
                 ControlFlow::Poll => {
                     self.first_event = poll(
@@ -386,6 +391,38 @@ impl<T: 'static> EventLoopWindowTarget<T> {
         v.push_back(MonitorHandle);
         v
     }
+
+    pub fn set_timer(
+        &self,
+        _deadline: Instant,
+        _id: event_loop::TimerId,
+    ) -> Result<(), error::NotSupportedError> {
+        Err(error::NotSupportedError::new())
+    }
+
+    pub fn cancel_timer(&self, _id: event_loop::TimerId) {}
+
+    pub fn set_device_event_filter(&self, _filter: event_loop::DeviceEventFilter) {}
+
+    pub fn set_clipboard_contents(
+        &self,
+        _kind: clipboard::ClipboardKind,
+        _contents: HashMap<String, Vec<u8>>,
+    ) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn clipboard_contents(
+        &self,
+        _kind: clipboard::ClipboardKind,
+        _mime_type: &str,
+    ) -> Result<Option<Vec<u8>>, error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
 }
 
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
@@ -451,6 +488,8 @@ impl Window {
         // TODO
     }
 
+    pub fn request_frame(&self) {}
+
     pub fn inner_position(&self) -> Result<PhysicalPosition<i32>, error::NotSupportedError> {
         Err(error::NotSupportedError::new())
     }
@@ -479,16 +518,40 @@ impl Window {
 
     pub fn set_max_inner_size(&self, _: Option<Size>) {}
 
+    pub fn set_resize_increments(&self, _: Option<Size>) {}
+
     pub fn set_title(&self, _title: &str) {}
 
     pub fn set_visible(&self, _visibility: bool) {}
 
+    pub fn is_visible(&self) -> Option<bool> {
+        None
+    }
+
     pub fn set_resizable(&self, _resizeable: bool) {}
 
+    pub fn is_resizable(&self) -> bool {
+        false
+    }
+
+    pub fn set_enabled_buttons(&self, _buttons: window::WindowButtons) {}
+
+    pub fn enabled_buttons(&self) -> window::WindowButtons {
+        window::WindowButtons::all()
+    }
+
     pub fn set_minimized(&self, _minimized: bool) {}
 
+    pub fn is_minimized(&self) -> Option<bool> {
+        None
+    }
+
     pub fn set_maximized(&self, _maximized: bool) {}
 
+    pub fn is_maximized(&self) -> bool {
+        false
+    }
+
     pub fn set_fullscreen(&self, _monitor: Option<window::Fullscreen>) {
         warn!("Cannot set fullscreen on Android");
     }
@@ -499,7 +562,15 @@ impl Window {
 
     pub fn set_decorations(&self, _decorations: bool) {}
 
-    pub fn set_always_on_top(&self, _always_on_top: bool) {}
+    pub fn is_decorated(&self) -> bool {
+        true
+    }
+
+    pub fn set_window_level(&self, _level: window::WindowLevel) {}
+
+    pub fn set_blur(&self, _blur: bool) {}
+
+    pub fn set_blur_region(&self, _position: Position, _size: Size) {}
 
     pub fn set_window_icon(&self, _window_icon: Option<crate::icon::Icon>) {}
 
@@ -507,15 +578,46 @@ impl Window {
 
     pub fn request_user_attention(&self, _request_type: Option<window::UserAttentionType>) {}
 
+    pub fn set_theme(&self, _theme: Option<window::Theme>) {}
+
+    pub fn theme(&self) -> Option<window::Theme> {
+        None
+    }
+
     pub fn set_cursor_icon(&self, _: window::CursorIcon) {}
 
+    pub fn set_custom_cursor(&self, _: window::CustomCursor) {}
+
+    pub fn set_cursor_theme(&self, _: &str, _: u32) {}
+
     pub fn set_cursor_position(&self, _: Position) -> Result<(), error::ExternalError> {
         Err(error::ExternalError::NotSupported(
             error::NotSupportedError::new(),
         ))
     }
 
-    pub fn set_cursor_grab(&self, _: bool) -> Result<(), error::ExternalError> {
+    pub fn set_cursor_grab(&self, _: window::CursorGrabMode) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn set_cursor_hittest(&self, _: bool) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn drag_window(&self) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn drag_resize_window(
+        &self,
+        _: window::ResizeDirection,
+    ) -> Result<(), error::ExternalError> {
         Err(error::ExternalError::NotSupported(
             error::NotSupportedError::new(),
         ))
@@ -553,6 +655,8 @@ impl Display for OsError {
     }
 }
 
+pub type EventLoopError = std::convert::Infallible;
+
 pub(crate) use crate::icon::NoIcon as PlatformIcon;
 
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
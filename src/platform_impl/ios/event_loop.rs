@@ -1,17 +1,21 @@
 use std::{
-    collections::VecDeque,
+    collections::{HashMap, VecDeque},
     ffi::c_void,
     fmt::{self, Debug},
     marker::PhantomData,
     mem, ptr,
     sync::mpsc::{self, Receiver, Sender},
+    time::Instant,
 };
 
 use crate::{
+    clipboard::ClipboardKind,
     dpi::LogicalSize,
+    error::{ExternalError, NotSupportedError},
     event::Event,
     event_loop::{
-        ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootEventLoopWindowTarget,
+        ControlFlow, DeviceEventFilter, EventLoopClosed,
+        EventLoopWindowTarget as RootEventLoopWindowTarget, TimerId,
     },
     monitor::MonitorHandle as RootMonitorHandle,
     platform::ios::Idiom,
@@ -63,6 +67,30 @@ impl<T: 'static> EventLoopWindowTarget<T> {
 
         Some(RootMonitorHandle { inner: monitor })
     }
+
+    pub fn set_timer(&self, _deadline: Instant, _id: TimerId) -> Result<(), NotSupportedError> {
+        Err(NotSupportedError::new())
+    }
+
+    pub fn cancel_timer(&self, _id: TimerId) {}
+
+    pub fn set_device_event_filter(&self, _filter: DeviceEventFilter) {}
+
+    pub fn set_clipboard_contents(
+        &self,
+        _kind: ClipboardKind,
+        _contents: HashMap<String, Vec<u8>>,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn clipboard_contents(
+        &self,
+        _kind: ClipboardKind,
+        _mime_type: &str,
+    ) -> Result<Option<Vec<u8>>, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
 }
 
 pub struct EventLoop<T: 'static> {
@@ -99,6 +127,10 @@ impl<T: 'static> EventLoop<T> {
         }
     }
 
+    pub fn try_new() -> Result<EventLoop<T>, super::EventLoopError> {
+        Ok(Self::new())
+    }
+
     pub fn run<F>(self, event_handler: F) -> !
     where
         F: 'static + FnMut(Event<'_, T>, &RootEventLoopWindowTarget<T>, &mut ControlFlow),
@@ -101,6 +101,8 @@ impl DeviceId {
 unsafe impl Send for DeviceId {}
 unsafe impl Sync for DeviceId {}
 
+pub type EventLoopError = std::convert::Infallible;
+
 #[derive(Debug)]
 pub enum OsError {}
 
@@ -0,0 +1,38 @@
+//! The cursor theme of the X11 and Wayland backends.
+
+use std::env;
+
+/// A cursor theme, with the size of its cursors on a window whose scale factor is 1.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CursorTheme {
+    pub name: String,
+    pub size: u32,
+}
+
+impl CursorTheme {
+    /// The theme used when nothing configures one, which libXcursor resolves to the theme
+    /// inherited by `~/.icons/default` or `/usr/share/icons/default`.
+    pub const DEFAULT_NAME: &'static str = "default";
+
+    /// The size used when nothing configures one, like libXcursor and most desktops do.
+    pub const DEFAULT_SIZE: u32 = 24;
+
+    /// Reads `XCURSOR_THEME` and `XCURSOR_SIZE`, which take precedence over the settings of the
+    /// desktop.
+    pub fn from_env() -> (Option<String>, Option<u32>) {
+        let name = env::var("XCURSOR_THEME")
+            .ok()
+            .filter(|name| !name.is_empty());
+        let size = env::var("XCURSOR_SIZE")
+            .ok()
+            .and_then(|size| size.parse().ok())
+            .filter(|&size| size > 0);
+        (name, size)
+    }
+
+    /// Returns the size of the cursors in physical pixels, on a window with the given scale
+    /// factor.
+    pub fn scaled_size(&self, scale_factor: f64) -> u32 {
+        ((self.size as f64 * scale_factor).round() as u32).max(1)
+    }
+}
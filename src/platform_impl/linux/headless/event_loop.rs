@@ -0,0 +1,708 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io,
+    marker::PhantomData,
+    mem,
+    os::unix::io::{AsRawFd, RawFd},
+    rc::Rc,
+    sync::{
+        mpsc::{self, SendError},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use mio::Poll;
+
+use crate::{
+    clipboard::ClipboardKind,
+    dpi::{PhysicalPosition, PhysicalSize},
+    error::ExternalError,
+    event::{DeviceEvent, Event, StartCause, WindowEvent},
+    event_loop::{
+        ControlFlow, DeviceEventFilter, EventLoopClosed, EventLoopWindowTarget as RootELW, FdToken,
+        TrySendError,
+    },
+    platform::{
+        pump_events::PumpStatus,
+        replay::{EventRecord, RecordedEvent},
+    },
+    platform_impl::platform::{
+        frame_interval, is_input_event, min_timeout, sticky_exit_callback,
+        DeviceId as PlatformDeviceId, ProxyBound, ProxyPermit, WindowId as PlatformWindowId,
+    },
+};
+
+use super::{clamp_size, DeviceId, MonitorHandle, PendingEvent, SharedState, State, WindowId};
+
+/// A message sent by an `EventLoopProxy`, `None` being a wakeup without a payload.
+type UserMessage<T> = Option<(T, Option<ProxyPermit>)>;
+
+pub struct EventLoopWindowTarget<T> {
+    pub(crate) state: SharedState,
+    _marker: PhantomData<T>,
+}
+
+impl<T> EventLoopWindowTarget<T> {
+    pub fn register_fd(&self, _fd: RawFd, _token: FdToken) -> io::Result<()> {
+        // Only the proxies wake up a headless event loop, so there is nothing to poll file
+        // descriptors with.
+        Err(io::Error::from(io::ErrorKind::Other))
+    }
+
+    pub fn unregister_fd(&self, _fd: RawFd) -> io::Result<()> {
+        Err(io::Error::from(io::ErrorKind::NotFound))
+    }
+
+    /// Device events are filtered when they're dispatched, so injected events are subject to
+    /// the filter which is set at that time.
+    pub fn set_device_event_filter(&self, filter: DeviceEventFilter) {
+        self.state.lock().unwrap().device_event_filter = filter;
+    }
+
+    /// The selections are only shared with the windows of this event loop, as there are no
+    /// other applications.
+    pub fn set_clipboard_contents(
+        &self,
+        kind: ClipboardKind,
+        contents: HashMap<String, Vec<u8>>,
+    ) -> Result<(), ExternalError> {
+        let mut state = self.state.lock().unwrap();
+        if contents.is_empty() {
+            state.clipboard.remove(&kind);
+        } else {
+            state.clipboard.insert(kind, contents);
+        }
+        Ok(())
+    }
+
+    pub fn clipboard_contents(
+        &self,
+        kind: ClipboardKind,
+        mime_type: &str,
+    ) -> Result<Option<Vec<u8>>, ExternalError> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .clipboard
+            .get(&kind)
+            .and_then(|contents| contents.get(mime_type))
+            .cloned())
+    }
+
+    #[inline]
+    pub fn available_monitors(&self) -> Vec<MonitorHandle> {
+        self.state.lock().unwrap().monitors.clone()
+    }
+
+    #[inline]
+    pub fn primary_monitor(&self) -> MonitorHandle {
+        self.state.lock().unwrap().monitors[0].clone()
+    }
+
+    pub fn add_virtual_monitor(
+        &self,
+        position: PhysicalPosition<i32>,
+        size: PhysicalSize<u32>,
+        scale_factor: f64,
+    ) -> MonitorHandle {
+        self.state
+            .lock()
+            .unwrap()
+            .add_monitor(position, size, scale_factor)
+    }
+
+    pub fn set_virtual_monitor_scale_factor(&self, monitor: &MonitorHandle, scale_factor: f64) {
+        self.state
+            .lock()
+            .unwrap()
+            .set_monitor_scale_factor(monitor.id, scale_factor);
+    }
+
+    pub fn inject_window_event(&self, window_id: PlatformWindowId, event: WindowEvent<'_>) {
+        let window_id = WindowId(window_id.raw());
+        let event = match event {
+            WindowEvent::ScaleFactorChanged {
+                scale_factor,
+                new_inner_size,
+            } => PendingEvent::ScaleFactorChanged {
+                window_id,
+                scale_factor,
+                new_inner_size: *new_inner_size,
+            },
+            event => PendingEvent::Window(window_id, event.to_static().unwrap()),
+        };
+        self.state.lock().unwrap().events.push_back(event);
+    }
+
+    pub fn inject_device_event(&self, device_id: PlatformDeviceId, event: DeviceEvent) {
+        let device_id = DeviceId(device_id.raw());
+        self.state
+            .lock()
+            .unwrap()
+            .events
+            .push_back(PendingEvent::Device(device_id, event));
+    }
+}
+
+/// The records replayed by an event loop created with `new_replay`.
+struct Replay {
+    records: VecDeque<EventRecord>,
+    /// When the current replay started, which recorded times are relative to.
+    start: Instant,
+    /// The virtual windows standing in for the recorded window ids.
+    windows: HashMap<u64, WindowId>,
+}
+
+impl Replay {
+    /// Returns the virtual window standing in for the recorded window `raw`, which is the oldest
+    /// window not standing in for another recorded window yet. Without such a window, the
+    /// recorded id is replayed as is.
+    fn window_id(&mut self, raw: u64, state: &State) -> u64 {
+        if let Some(window_id) = self.windows.get(&raw) {
+            return window_id.0;
+        }
+        let windows = &self.windows;
+        match state
+            .windows
+            .keys()
+            .find(|window_id| !windows.values().any(|mapped| mapped == *window_id))
+        {
+            Some(&window_id) => {
+                self.windows.insert(raw, window_id);
+                window_id.0
+            }
+            None => raw,
+        }
+    }
+}
+
+pub struct EventLoop<T: 'static> {
+    replay: Option<Replay>,
+    user_channel: mpsc::Receiver<UserMessage<T>>,
+    user_sender: mpsc::Sender<UserMessage<T>>,
+    /// User events received while waiting, which are dispatched by the next iteration.
+    pending_user_events: VecDeque<(T, Option<ProxyPermit>)>,
+    // Never becomes readable, it only backs `AsRawFd`.
+    poll: Poll,
+    target: Rc<RootELW<T>>,
+    control_flow: ControlFlow,
+    loop_running: bool,
+}
+
+pub struct EventLoopProxy<T: 'static> {
+    user_sender: mpsc::Sender<UserMessage<T>>,
+    bound: Option<ProxyBound>,
+}
+
+impl<T: 'static> Clone for EventLoopProxy<T> {
+    fn clone(&self) -> Self {
+        EventLoopProxy {
+            user_sender: self.user_sender.clone(),
+            bound: self.bound.clone(),
+        }
+    }
+}
+
+impl<T: 'static> EventLoop<T> {
+    pub fn new() -> EventLoop<T> {
+        let (user_sender, user_channel) = mpsc::channel();
+
+        let target = Rc::new(RootELW {
+            p: super::super::EventLoopWindowTarget::Headless(EventLoopWindowTarget {
+                state: Arc::new(Mutex::new(State::new())),
+                _marker: PhantomData,
+            }),
+            _marker: PhantomData,
+        });
+
+        EventLoop {
+            replay: None,
+            user_channel,
+            user_sender,
+            pending_user_events: VecDeque::new(),
+            poll: Poll::new().unwrap(),
+            target,
+            control_flow: ControlFlow::default(),
+            loop_running: false,
+        }
+    }
+
+    pub fn new_replay(records: Vec<EventRecord>) -> EventLoop<T> {
+        EventLoop {
+            replay: Some(Replay {
+                records: records.into(),
+                start: Instant::now(),
+                windows: HashMap::new(),
+            }),
+            ..EventLoop::new()
+        }
+    }
+
+    pub fn create_proxy(&self) -> EventLoopProxy<T> {
+        EventLoopProxy {
+            user_sender: self.user_sender.clone(),
+            bound: None,
+        }
+    }
+
+    pub fn create_proxy_bounded(&self, capacity: usize) -> EventLoopProxy<T> {
+        EventLoopProxy {
+            bound: Some(ProxyBound::new(capacity)),
+            ..self.create_proxy()
+        }
+    }
+
+    pub(crate) fn window_target(&self) -> &RootELW<T> {
+        &self.target
+    }
+
+    fn state(&self) -> &SharedState {
+        match self.target.p {
+            super::super::EventLoopWindowTarget::Headless(ref target) => &target.state,
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn run_return<F>(&mut self, mut callback: F) -> i32
+    where
+        F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        loop {
+            if let PumpStatus::Exit(code) = self.pump_events(None, &mut callback) {
+                return code;
+            }
+        }
+    }
+
+    pub fn run<F>(mut self, callback: F) -> !
+    where
+        F: 'static + FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        let exit_code = self.run_return(callback);
+        ::std::process::exit(exit_code);
+    }
+
+    /// Dispatches a single iteration of the event loop, after waiting for user events as
+    /// requested by the control flow, but no longer than `timeout`.
+    ///
+    /// A replay never waits, so `timeout` and the `Wait`/`WaitUntil` control flows are ignored
+    /// while replaying, and the first call only dispatches the `Init` iteration.
+    pub fn pump_events<F>(&mut self, timeout: Option<Duration>, mut callback: F) -> PumpStatus
+    where
+        F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        if !self.loop_running {
+            self.loop_running = true;
+            if let Some(ref mut replay) = self.replay {
+                replay.start = Instant::now();
+            }
+
+            // Changes to the control flow don't persist between runs.
+            self.control_flow = ControlFlow::default();
+            self.single_iteration(&mut callback, StartCause::Init);
+        } else if self.replay.is_some() {
+            self.single_iteration(&mut callback, StartCause::Poll);
+        }
+
+        match self.replay {
+            // The replay ends once every recorded event has been dispatched.
+            Some(ref replay) => {
+                if replay.records.is_empty()
+                    && !matches!(self.control_flow, ControlFlow::ExitWithCode(_))
+                {
+                    self.control_flow = ControlFlow::Exit;
+                }
+            }
+            // The `Init` iteration may already have requested an exit.
+            None => {
+                if !matches!(self.control_flow, ControlFlow::ExitWithCode(_)) {
+                    self.poll_events_with_timeout(timeout, &mut callback);
+                }
+            }
+        }
+
+        if let ControlFlow::ExitWithCode(code) = self.control_flow {
+            self.loop_running = false;
+
+            sticky_exit_callback(
+                Event::LoopDestroyed,
+                &self.target,
+                &mut self.control_flow,
+                &mut callback,
+            );
+
+            PumpStatus::Exit(code)
+        } else {
+            PumpStatus::Continue
+        }
+    }
+
+    /// Waits for user events according to the control flow, but no longer than `timeout`, and
+    /// dispatches a single iteration of the event loop.
+    fn poll_events_with_timeout<F>(&mut self, timeout: Option<Duration>, callback: &mut F)
+    where
+        F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        let start = Instant::now();
+        let (deadline, control_flow_timeout, mut cause);
+
+        match self.control_flow {
+            ControlFlow::ExitWithCode(_) => return,
+            ControlFlow::Poll => {
+                cause = StartCause::Poll;
+                deadline = None;
+                control_flow_timeout = Some(Duration::from_millis(0));
+            }
+            ControlFlow::Wait => {
+                cause = StartCause::WaitCancelled {
+                    start,
+                    requested_resume: None,
+                };
+                deadline = None;
+                control_flow_timeout = None;
+            }
+            ControlFlow::WaitUntil(wait_deadline) => {
+                cause = StartCause::ResumeTimeReached {
+                    start,
+                    requested_resume: wait_deadline,
+                };
+                control_flow_timeout = Some(wait_deadline.saturating_duration_since(start));
+                deadline = Some(wait_deadline);
+            }
+        }
+
+        // Don't block while there are events left to dispatch.
+        let timeout = if self.pending_user_events.is_empty()
+            && !self.state().lock().unwrap().has_pending_events()
+        {
+            min_timeout(control_flow_timeout, timeout)
+        } else {
+            Some(Duration::from_millis(0))
+        };
+        self.receive_user_events(timeout);
+
+        if matches!(deadline, Some(deadline) if Instant::now() < deadline) {
+            cause = StartCause::WaitCancelled {
+                start,
+                requested_resume: deadline,
+            };
+        }
+
+        self.single_iteration(callback, cause);
+    }
+
+    /// Waits up to `timeout` for a message from the proxies, then moves every received user event
+    /// to `pending_user_events`.
+    fn receive_user_events(&mut self, timeout: Option<Duration>) {
+        let message = match timeout {
+            Some(timeout) if timeout == Duration::from_millis(0) => None,
+            Some(timeout) => self.user_channel.recv_timeout(timeout).ok(),
+            // The event loop holds a sender, so the channel never disconnects.
+            None => self.user_channel.recv().ok(),
+        };
+
+        let messages: Vec<UserMessage<T>> = message
+            .into_iter()
+            .chain(std::iter::from_fn(|| self.user_channel.try_recv().ok()))
+            .collect();
+        self.pending_user_events
+            .extend(messages.into_iter().flatten());
+    }
+
+    /// Dispatches `NewEvents(cause)`, the events queued before the iteration started, the
+    /// recorded events up to the start of the next recorded iteration, and the pending user
+    /// events, up to and including `RedrawEventsCleared`.
+    fn single_iteration<F>(&mut self, callback: &mut F, cause: StartCause)
+    where
+        F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        let mut control_flow = self.control_flow;
+
+        sticky_exit_callback(
+            Event::NewEvents(cause),
+            &self.target,
+            &mut control_flow,
+            callback,
+        );
+
+        // Events queued while dispatching are left for the next iteration.
+        let (events, frame_requests) = {
+            let mut state = self.state().lock().unwrap();
+            let events = mem::take(&mut state.events);
+            let frame_requests: Vec<WindowId> = state
+                .windows
+                .iter_mut()
+                .filter(|(_, window)| window.frame_requested)
+                .map(|(&window_id, window)| {
+                    window.frame_requested = false;
+                    window_id
+                })
+                .collect();
+            (events, frame_requests)
+        };
+
+        for event in events {
+            match event {
+                PendingEvent::Window(window_id, event) => {
+                    let mut state = self.state().lock().unwrap();
+                    state.apply_window_event(window_id, &event);
+                    // Input to a window is swallowed while it has a modal child.
+                    let swallowed = is_input_event(&event) && state.has_modal_child(window_id);
+                    drop(state);
+                    if swallowed {
+                        continue;
+                    }
+                    sticky_exit_callback(
+                        Event::WindowEvent {
+                            window_id: root_window_id(window_id),
+                            event,
+                        },
+                        &self.target,
+                        &mut control_flow,
+                        callback,
+                    );
+                }
+                PendingEvent::ScaleFactorChanged {
+                    window_id,
+                    scale_factor,
+                    mut new_inner_size,
+                } => {
+                    sticky_exit_callback(
+                        Event::WindowEvent {
+                            window_id: root_window_id(window_id),
+                            event: WindowEvent::ScaleFactorChanged {
+                                scale_factor,
+                                new_inner_size: &mut new_inner_size,
+                            },
+                        },
+                        &self.target,
+                        &mut control_flow,
+                        callback,
+                    );
+                    self.resize_after_scale_change(
+                        window_id,
+                        new_inner_size,
+                        &mut control_flow,
+                        callback,
+                    );
+                }
+                PendingEvent::Device(device_id, event) => {
+                    if !self.state().lock().unwrap().device_events_enabled() {
+                        continue;
+                    }
+                    sticky_exit_callback(
+                        Event::DeviceEvent {
+                            device_id: crate::event::DeviceId(PlatformDeviceId::Headless(
+                                device_id,
+                            )),
+                            event,
+                        },
+                        &self.target,
+                        &mut control_flow,
+                        callback,
+                    );
+                }
+            }
+        }
+
+        for window_id in frame_requests {
+            let target_time = Instant::now() + frame_interval(None);
+            sticky_exit_callback(
+                Event::WindowEvent {
+                    window_id: root_window_id(window_id),
+                    event: WindowEvent::FrameReady { target_time },
+                },
+                &self.target,
+                &mut control_flow,
+                callback,
+            );
+        }
+
+        if self.replay.is_some() {
+            self.replay_iteration(&mut control_flow, callback);
+        }
+
+        let user_events = mem::take(&mut self.pending_user_events).into_iter().chain(
+            std::iter::from_fn(|| self.user_channel.try_recv().ok())
+                .flatten()
+                .collect::<Vec<_>>(),
+        );
+        for (event, _permit) in user_events {
+            sticky_exit_callback(
+                Event::UserEvent(event),
+                &self.target,
+                &mut control_flow,
+                callback,
+            );
+        }
+
+        sticky_exit_callback(
+            Event::MainEventsCleared,
+            &self.target,
+            &mut control_flow,
+            callback,
+        );
+
+        let redraw_requests = mem::take(&mut self.state().lock().unwrap().redraw_requests);
+        for window_id in redraw_requests {
+            sticky_exit_callback(
+                Event::RedrawRequested(root_window_id(window_id)),
+                &self.target,
+                &mut control_flow,
+                callback,
+            );
+        }
+
+        sticky_exit_callback(
+            Event::RedrawEventsCleared,
+            &self.target,
+            &mut control_flow,
+            callback,
+        );
+
+        self.control_flow = control_flow;
+    }
+
+    /// Dispatches the recorded events up to the start of the next recorded iteration.
+    fn replay_iteration<F>(&mut self, control_flow: &mut ControlFlow, callback: &mut F)
+    where
+        F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        // Skip the start of the recorded iteration being replayed
+        if let Some(EventRecord {
+            event: RecordedEvent::NewEvents,
+            ..
+        }) = self.replay.as_ref().unwrap().records.front()
+        {
+            self.replay.as_mut().unwrap().records.pop_front();
+        }
+
+        loop {
+            let mut record = {
+                let replay = self.replay.as_mut().unwrap();
+                match replay.records.front() {
+                    None
+                    | Some(EventRecord {
+                        event: RecordedEvent::NewEvents,
+                        ..
+                    }) => break,
+                    Some(_) => replay.records.pop_front().unwrap(),
+                }
+            };
+
+            let shared_state = self.state().clone();
+            let mut state = shared_state.lock().unwrap();
+            let replay = self.replay.as_mut().unwrap();
+            match record.event {
+                RecordedEvent::RedrawRequested(ref mut window_id) => {
+                    let window_id = WindowId(replay.window_id(*window_id, &state));
+                    state.request_redraw(window_id);
+                    continue;
+                }
+                RecordedEvent::WindowEvent {
+                    ref mut window_id, ..
+                } => *window_id = replay.window_id(*window_id, &state),
+                _ => (),
+            }
+            let replay_start = replay.start;
+
+            let mut new_inner_size = PhysicalSize::new(0, 0);
+            let event = match record.event.into_event(replay_start, &mut new_inner_size) {
+                Some(event) => event,
+                None => continue,
+            };
+            let mut scale_factor_changed = None;
+            if let Event::WindowEvent {
+                window_id,
+                ref event,
+            } = event
+            {
+                let window_id = WindowId(window_id.0.raw());
+                state.apply_window_event(window_id, event);
+                if let WindowEvent::ScaleFactorChanged { .. } = event {
+                    scale_factor_changed = Some(window_id);
+                }
+            }
+            drop(state);
+
+            sticky_exit_callback(event, &self.target, control_flow, callback);
+
+            if let Some(window_id) = scale_factor_changed {
+                self.resize_after_scale_change(window_id, new_inner_size, control_flow, callback);
+            }
+        }
+    }
+
+    /// Resizes a window to the size requested by the handler of `ScaleFactorChanged`, and
+    /// dispatches `Resized` right away if its size changed.
+    fn resize_after_scale_change<F>(
+        &self,
+        window_id: WindowId,
+        new_inner_size: PhysicalSize<u32>,
+        control_flow: &mut ControlFlow,
+        callback: &mut F,
+    ) where
+        F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        let size = {
+            let mut state = self.state().lock().unwrap();
+            let window = match state.windows.get_mut(&window_id) {
+                Some(window) => window,
+                None => return,
+            };
+            let size = clamp_size(&window.attributes, new_inner_size);
+            if window.size == size {
+                return;
+            }
+            window.size = size;
+            state.request_redraw(window_id);
+            size
+        };
+
+        sticky_exit_callback(
+            Event::WindowEvent {
+                window_id: root_window_id(window_id),
+                event: WindowEvent::Resized(size),
+            },
+            &self.target,
+            control_flow,
+            callback,
+        );
+    }
+}
+
+fn root_window_id(window_id: WindowId) -> crate::window::WindowId {
+    crate::window::WindowId(PlatformWindowId::Headless(window_id))
+}
+
+impl<T: 'static> AsRawFd for EventLoop<T> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.poll.as_raw_fd()
+    }
+}
+
+impl<T: 'static> EventLoopProxy<T> {
+    pub fn send_event(&self, event: T) -> Result<(), EventLoopClosed<T>> {
+        let permit = self.bound.as_ref().map(ProxyBound::acquire);
+        self.user_sender
+            .send(Some((event, permit)))
+            .map_err(|SendError(message)| EventLoopClosed(message.unwrap().0))
+    }
+
+    pub fn try_send_event(&self, event: T) -> Result<(), TrySendError<T>> {
+        let permit = match self.bound {
+            Some(ref bound) => match bound.try_acquire() {
+                Some(permit) => Some(permit),
+                None => return Err(TrySendError::Full(event)),
+            },
+            None => None,
+        };
+        self.user_sender
+            .send(Some((event, permit)))
+            .map_err(|SendError(message)| TrySendError::Closed(message.unwrap().0))
+    }
+
+    pub fn wake_up(&self) {
+        let _ = self.user_sender.send(None);
+    }
+}
@@ -0,0 +1,268 @@
+//! A backend without a display server, whose windows and monitors are virtual.
+//!
+//! Window events are either injected by the application with
+//! [`EventLoopWindowTargetExtHeadless`](crate::platform::headless::EventLoopWindowTargetExtHeadless),
+//! generated by the virtual windows themselves, or replayed from records of
+//! [`EventRecorder`](crate::platform::replay::EventRecorder).
+
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    clipboard::ClipboardKind,
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{DeviceEvent, WindowEvent},
+    event_loop::DeviceEventFilter,
+    platform::headless::VirtualWindowState,
+    window::{Fullscreen, Theme},
+};
+
+pub use self::{
+    event_loop::{EventLoop, EventLoopProxy, EventLoopWindowTarget},
+    monitor::{MonitorHandle, VideoMode},
+    window::Window,
+};
+
+mod event_loop;
+mod monitor;
+mod window;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WindowId(pub(crate) u64);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DeviceId(pub(crate) u64);
+
+/// The id of the virtual device which injected events are usually attributed to.
+pub const VIRTUAL_DEVICE_ID: DeviceId = DeviceId(0);
+
+/// An event waiting to be dispatched by the event loop.
+#[derive(Debug)]
+pub enum PendingEvent {
+    Window(WindowId, WindowEvent<'static>),
+    /// `WindowEvent::ScaleFactorChanged`, which can't be stored as a `WindowEvent<'static>`.
+    ScaleFactorChanged {
+        window_id: WindowId,
+        scale_factor: f64,
+        new_inner_size: PhysicalSize<u32>,
+    },
+    Device(DeviceId, DeviceEvent),
+}
+
+#[derive(Debug)]
+pub struct WindowState {
+    pub(crate) position: PhysicalPosition<i32>,
+    pub(crate) size: PhysicalSize<u32>,
+    pub(crate) fullscreen: Option<Fullscreen>,
+    /// The geometry to restore when leaving fullscreen or unmaximizing.
+    pub(crate) restore: Option<(PhysicalPosition<i32>, PhysicalSize<u32>)>,
+    pub(crate) frame_requested: bool,
+    pub(crate) focused: bool,
+    /// The theme set with `set_theme`, as a virtual desktop has no theme of its own.
+    pub(crate) theme: Option<Theme>,
+    /// The owner of a modal window, whose input is swallowed while the window is visible.
+    pub(crate) modal_owner: Option<WindowId>,
+    pub(crate) attributes: VirtualWindowState,
+}
+
+/// The virtual monitors and windows of a headless event loop, shared with its windows.
+#[derive(Debug)]
+pub struct State {
+    /// Never empty, the first monitor is the primary one.
+    pub(crate) monitors: Vec<MonitorHandle>,
+    pub(crate) windows: BTreeMap<WindowId, WindowState>,
+    next_window_id: u64,
+    pub(crate) events: VecDeque<PendingEvent>,
+    pub(crate) redraw_requests: Vec<WindowId>,
+    pub(crate) device_event_filter: DeviceEventFilter,
+    /// The contents of the virtual clipboard and primary selection, by MIME type.
+    pub(crate) clipboard: HashMap<ClipboardKind, HashMap<String, Vec<u8>>>,
+}
+
+pub type SharedState = Arc<Mutex<State>>;
+
+impl State {
+    fn new() -> Self {
+        State {
+            monitors: vec![MonitorHandle::new(
+                1,
+                PhysicalPosition::new(0, 0),
+                PhysicalSize::new(1920, 1080),
+                1.0,
+            )],
+            windows: BTreeMap::new(),
+            next_window_id: 1,
+            events: VecDeque::new(),
+            redraw_requests: Vec::new(),
+            device_event_filter: Default::default(),
+            clipboard: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn next_window_id(&mut self) -> WindowId {
+        let id = WindowId(self.next_window_id);
+        self.next_window_id += 1;
+        id
+    }
+
+    pub(crate) fn add_monitor(
+        &mut self,
+        position: PhysicalPosition<i32>,
+        size: PhysicalSize<u32>,
+        scale_factor: f64,
+    ) -> MonitorHandle {
+        let id = self.monitors.len() as u32 + 1;
+        let monitor = MonitorHandle::new(id, position, size, scale_factor);
+        self.monitors.push(monitor.clone());
+        monitor
+    }
+
+    /// Changes the scale factor of a monitor, and queues `ScaleFactorChanged` for the windows on
+    /// it.
+    pub(crate) fn set_monitor_scale_factor(&mut self, monitor_id: u32, scale_factor: f64) {
+        let old_scale_factor = match self.monitors.iter().find(|m| m.id == monitor_id) {
+            Some(monitor) => monitor.scale_factor,
+            None => return,
+        };
+        if old_scale_factor == scale_factor {
+            return;
+        }
+
+        let window_ids: Vec<WindowId> = self
+            .windows
+            .iter()
+            .filter(|&(_, window)| self.monitor_at(window.position).id == monitor_id)
+            .map(|(&window_id, _)| window_id)
+            .collect();
+
+        for monitor in self.monitors.iter_mut().filter(|m| m.id == monitor_id) {
+            monitor.scale_factor = scale_factor;
+        }
+
+        for window_id in window_ids {
+            let size = self.windows[&window_id].size;
+            self.events.push_back(PendingEvent::ScaleFactorChanged {
+                window_id,
+                scale_factor,
+                new_inner_size: size
+                    .to_logical::<f64>(old_scale_factor)
+                    .to_physical(scale_factor),
+            });
+        }
+    }
+
+    /// Returns the monitor containing `position`, or the primary monitor if there is none.
+    pub(crate) fn monitor_at(&self, position: PhysicalPosition<i32>) -> &MonitorHandle {
+        self.monitors
+            .iter()
+            .find(|monitor| monitor.contains(position))
+            .unwrap_or(&self.monitors[0])
+    }
+
+    /// Whether the window has a visible modal child, which swallows its input.
+    pub(crate) fn has_modal_child(&self, window_id: WindowId) -> bool {
+        self.windows
+            .values()
+            .any(|window| window.modal_owner == Some(window_id) && window.attributes.visible)
+    }
+
+    pub(crate) fn current_monitor(&self, window_id: WindowId) -> &MonitorHandle {
+        self.monitor_at(self.windows[&window_id].position)
+    }
+
+    pub(crate) fn scale_factor(&self, window_id: WindowId) -> f64 {
+        self.current_monitor(window_id).scale_factor
+    }
+
+    /// Resizes a window, queuing `Resized` and a redraw if its size changed.
+    pub(crate) fn resize(&mut self, window_id: WindowId, size: PhysicalSize<u32>) {
+        let window = self.windows.get_mut(&window_id).unwrap();
+        let size = clamp_size(&window.attributes, size);
+        if window.size != size {
+            window.size = size;
+            self.events
+                .push_back(PendingEvent::Window(window_id, WindowEvent::Resized(size)));
+            self.request_redraw(window_id);
+        }
+    }
+
+    /// Moves a window, queuing `Moved` if its position changed, and `ScaleFactorChanged` if it
+    /// moved to a monitor with another scale factor.
+    pub(crate) fn move_to(&mut self, window_id: WindowId, position: PhysicalPosition<i32>) {
+        let old_scale_factor = self.scale_factor(window_id);
+        let window = self.windows.get_mut(&window_id).unwrap();
+        if window.position == position {
+            return;
+        }
+        window.position = position;
+        let size = window.size;
+        self.events.push_back(PendingEvent::Window(
+            window_id,
+            WindowEvent::Moved(position),
+        ));
+
+        let scale_factor = self.scale_factor(window_id);
+        if scale_factor != old_scale_factor {
+            self.events.push_back(PendingEvent::ScaleFactorChanged {
+                window_id,
+                scale_factor,
+                new_inner_size: size
+                    .to_logical::<f64>(old_scale_factor)
+                    .to_physical(scale_factor),
+            });
+        }
+    }
+
+    pub(crate) fn request_redraw(&mut self, window_id: WindowId) {
+        if !self.redraw_requests.contains(&window_id) {
+            self.redraw_requests.push(window_id);
+        }
+    }
+
+    /// Applies the geometry changes reported by an injected or replayed event to its window.
+    pub(crate) fn apply_window_event(&mut self, window_id: WindowId, event: &WindowEvent<'_>) {
+        if let Some(window) = self.windows.get_mut(&window_id) {
+            match *event {
+                WindowEvent::Resized(size) => window.size = size,
+                WindowEvent::Moved(position) => window.position = position,
+                WindowEvent::Focused(focused) => window.focused = focused,
+                _ => (),
+            }
+        }
+    }
+
+    /// Returns whether device events are currently delivered, according to the device event
+    /// filter.
+    pub(crate) fn device_events_enabled(&self) -> bool {
+        match self.device_event_filter {
+            DeviceEventFilter::Always => false,
+            DeviceEventFilter::Unfocused => self.windows.values().any(|window| window.focused),
+            DeviceEventFilter::Never => true,
+        }
+    }
+
+    /// Returns whether the event loop has events to dispatch without waiting.
+    pub(crate) fn has_pending_events(&self) -> bool {
+        !self.events.is_empty()
+            || !self.redraw_requests.is_empty()
+            || self.windows.values().any(|window| window.frame_requested)
+    }
+}
+
+/// Clamps `size` to the minimum and maximum sizes of a window.
+pub(crate) fn clamp_size(
+    attributes: &VirtualWindowState,
+    mut size: PhysicalSize<u32>,
+) -> PhysicalSize<u32> {
+    if let Some(min_size) = attributes.min_inner_size {
+        size.width = size.width.max(min_size.width);
+        size.height = size.height.max(min_size.height);
+    }
+    if let Some(max_size) = attributes.max_inner_size {
+        size.width = size.width.min(max_size.width);
+        size.height = size.height.min(max_size.height);
+    }
+    size
+}
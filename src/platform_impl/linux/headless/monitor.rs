@@ -0,0 +1,135 @@
+use std::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+};
+
+use crate::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
+    platform_impl::platform::{
+        MonitorHandle as PlatformMonitorHandle, VideoMode as PlatformVideoMode,
+    },
+};
+
+/// A virtual monitor. Handles are snapshots, which don't reflect later changes to the monitor.
+#[derive(Debug, Clone)]
+pub struct MonitorHandle {
+    pub(crate) id: u32,
+    pub(crate) position: PhysicalPosition<i32>,
+    pub(crate) size: PhysicalSize<u32>,
+    pub(crate) scale_factor: f64,
+}
+
+impl PartialEq for MonitorHandle {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for MonitorHandle {}
+
+impl PartialOrd for MonitorHandle {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for MonitorHandle {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.id.cmp(&other.id)
+    }
+}
+
+impl Hash for MonitorHandle {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl MonitorHandle {
+    pub(crate) fn new(
+        id: u32,
+        position: PhysicalPosition<i32>,
+        size: PhysicalSize<u32>,
+        scale_factor: f64,
+    ) -> Self {
+        MonitorHandle {
+            id,
+            position,
+            size,
+            scale_factor,
+        }
+    }
+
+    pub(crate) fn contains(&self, position: PhysicalPosition<i32>) -> bool {
+        let x = i64::from(position.x) - i64::from(self.position.x);
+        let y = i64::from(position.y) - i64::from(self.position.y);
+        x >= 0 && y >= 0 && x < i64::from(self.size.width) && y < i64::from(self.size.height)
+    }
+
+    #[inline]
+    pub fn name(&self) -> Option<String> {
+        Some(format!("Virtual-{}", self.id))
+    }
+
+    #[inline]
+    pub fn native_identifier(&self) -> u32 {
+        self.id
+    }
+
+    #[inline]
+    pub fn size(&self) -> PhysicalSize<u32> {
+        self.size
+    }
+
+    #[inline]
+    pub fn position(&self) -> PhysicalPosition<i32> {
+        self.position
+    }
+
+    #[inline]
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    /// A virtual monitor has a single video mode, which it is always using.
+    #[inline]
+    pub fn video_modes(&self) -> impl Iterator<Item = RootVideoMode> {
+        std::iter::once(RootVideoMode {
+            video_mode: PlatformVideoMode::Headless(VideoMode {
+                size: self.size,
+                monitor: self.clone(),
+            }),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VideoMode {
+    pub(crate) size: PhysicalSize<u32>,
+    pub(crate) monitor: MonitorHandle,
+}
+
+impl VideoMode {
+    #[inline]
+    pub fn size(&self) -> PhysicalSize<u32> {
+        self.size
+    }
+
+    #[inline]
+    pub fn bit_depth(&self) -> u16 {
+        32
+    }
+
+    #[inline]
+    pub fn refresh_rate(&self) -> u16 {
+        60
+    }
+
+    #[inline]
+    pub fn monitor(&self) -> RootMonitorHandle {
+        RootMonitorHandle {
+            inner: PlatformMonitorHandle::Headless(self.monitor.clone()),
+        }
+    }
+}
@@ -0,0 +1,527 @@
+use std::sync::MutexGuard;
+
+use raw_window_handle::unix::XlibHandle;
+
+use crate::{
+    dpi::{LogicalSize, PhysicalPosition, PhysicalSize, Position, Size},
+    error::{ExternalError, NotSupportedError, OsError as RootOsError},
+    event::WindowEvent,
+    icon::Icon,
+    monitor::MonitorHandle as RootMonitorHandle,
+    platform::headless::VirtualWindowState,
+    platform_impl::platform::{
+        MonitorHandle as PlatformMonitorHandle, PlatformSpecificWindowBuilderAttributes,
+        VideoMode as PlatformVideoMode, WindowId as PlatformWindowId,
+    },
+    window::{
+        CursorGrabMode, CursorIcon, CustomCursor, Fullscreen, ResizeDirection, Theme,
+        UserAttentionType, WindowAttributes, WindowButtons, WindowLevel,
+    },
+};
+
+use super::{
+    clamp_size, EventLoopWindowTarget, MonitorHandle, PendingEvent, SharedState, State, WindowId,
+    WindowState,
+};
+
+pub struct Window {
+    id: WindowId,
+    state: SharedState,
+}
+
+impl Window {
+    pub fn new<T>(
+        event_loop: &EventLoopWindowTarget<T>,
+        attribs: WindowAttributes,
+        pl_attribs: PlatformSpecificWindowBuilderAttributes,
+    ) -> Result<Self, RootOsError> {
+        let mut state = event_loop.state.lock().unwrap();
+        let id = state.next_window_id();
+        let monitor = state.monitors[0].clone();
+        let scale_factor = monitor.scale_factor;
+
+        let min_inner_size = attribs
+            .min_inner_size
+            .map(|size| size.to_physical::<u32>(scale_factor));
+        let max_inner_size = attribs
+            .max_inner_size
+            .map(|size| size.to_physical::<u32>(scale_factor));
+        let size = attribs
+            .inner_size
+            .unwrap_or_else(|| LogicalSize::new(800.0, 600.0).into())
+            .to_physical::<u32>(scale_factor);
+
+        let mut position = monitor.position;
+        let popup_parent = pl_attribs
+            .popup
+            .as_ref()
+            .and_then(|popup| match popup.parent {
+                PlatformWindowId::Headless(parent) => state
+                    .windows
+                    .get(&parent)
+                    .map(|parent| (popup, parent.position)),
+                #[allow(unreachable_patterns)]
+                _ => None,
+            });
+        let size = match popup_parent {
+            Some((popup, parent_position)) => {
+                let anchor = popup
+                    .anchor_rect
+                    .position
+                    .to_physical::<i32>(popup.scale_factor);
+                let bounds = state.monitor_at(PhysicalPosition::new(
+                    parent_position.x + anchor.x,
+                    parent_position.y + anchor.y,
+                ));
+                let (popup_position, popup_size) =
+                    popup.place(parent_position, size, bounds.position, bounds.size);
+                position = popup_position;
+                popup_size
+            }
+            None => {
+                if pl_attribs.popup.is_some() {
+                    warn!("The parent of a popup isn't a window of this event loop");
+                }
+                size
+            }
+        };
+
+        let modal_owner = match pl_attribs.owner {
+            Some(PlatformWindowId::Headless(owner)) if pl_attribs.modal => Some(owner),
+            _ => None,
+        };
+
+        let attributes = VirtualWindowState {
+            title: attribs.title,
+            visible: attribs.visible,
+            resizable: attribs.resizable,
+            decorations: attribs.decorations,
+            enabled_buttons: attribs.enabled_buttons,
+            maximized: false,
+            minimized: false,
+            window_level: attribs.window_level,
+            blur: attribs.blur,
+            blur_region: None,
+            min_inner_size,
+            max_inner_size,
+            resize_increments: pl_attribs
+                .resize_increments
+                .map(|size| size.to_physical::<u32>(scale_factor)),
+            cursor_icon: CursorIcon::default(),
+            custom_cursor: None,
+            cursor_theme: None,
+            cursor_grab: CursorGrabMode::None,
+            cursor_visible: true,
+            cursor_hittest: true,
+            cursor_position: None,
+            ime_position: None,
+            user_attention: None,
+        };
+        state.windows.insert(
+            id,
+            WindowState {
+                position,
+                size: clamp_size(&attributes, size),
+                fullscreen: None,
+                restore: None,
+                frame_requested: false,
+                focused: false,
+                theme: None,
+                modal_owner,
+                attributes,
+            },
+        );
+        if attribs.visible {
+            state.request_redraw(id);
+        }
+        if attribs.maximized {
+            set_maximized(&mut state, id, true);
+        }
+        if attribs.fullscreen.is_some() {
+            set_fullscreen(&mut state, id, attribs.fullscreen);
+        }
+        drop(state);
+
+        Ok(Window {
+            id,
+            state: event_loop.state.clone(),
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+
+    /// Runs `f` on the state of this window.
+    fn with_window<R>(&self, f: impl FnOnce(&mut WindowState) -> R) -> R {
+        f(self.lock().windows.get_mut(&self.id).unwrap())
+    }
+
+    pub(crate) fn virtual_state(&self) -> VirtualWindowState {
+        self.with_window(|window| window.attributes.clone())
+    }
+
+    #[inline]
+    pub fn id(&self) -> WindowId {
+        self.id
+    }
+
+    #[inline]
+    pub fn set_title(&self, title: &str) {
+        self.with_window(|window| window.attributes.title = title.to_owned());
+    }
+
+    #[inline]
+    pub fn is_visible(&self) -> Option<bool> {
+        Some(self.with_window(|window| window.attributes.visible))
+    }
+
+    #[inline]
+    pub fn set_visible(&self, visible: bool) {
+        let mut state = self.lock();
+        let window = state.windows.get_mut(&self.id).unwrap();
+        let was_visible = window.attributes.visible;
+        window.attributes.visible = visible;
+        if visible && !was_visible {
+            state.request_redraw(self.id);
+        }
+    }
+
+    #[inline]
+    pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
+        Ok(self.with_window(|window| window.position))
+    }
+
+    #[inline]
+    pub fn inner_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
+        // Virtual windows have no decorations taking up space.
+        self.outer_position()
+    }
+
+    #[inline]
+    pub fn set_outer_position(&self, position: Position) {
+        let mut state = self.lock();
+        let position = position.to_physical::<i32>(state.scale_factor(self.id));
+        state.move_to(self.id, position);
+    }
+
+    #[inline]
+    pub fn inner_size(&self) -> PhysicalSize<u32> {
+        self.with_window(|window| window.size)
+    }
+
+    #[inline]
+    pub fn outer_size(&self) -> PhysicalSize<u32> {
+        self.inner_size()
+    }
+
+    #[inline]
+    pub fn set_inner_size(&self, size: Size) {
+        let mut state = self.lock();
+        let size = size.to_physical::<u32>(state.scale_factor(self.id));
+        state.resize(self.id, size);
+    }
+
+    #[inline]
+    pub fn set_min_inner_size(&self, dimensions: Option<Size>) {
+        let mut state = self.lock();
+        let scale_factor = state.scale_factor(self.id);
+        let window = state.windows.get_mut(&self.id).unwrap();
+        window.attributes.min_inner_size = dimensions.map(|size| size.to_physical(scale_factor));
+        let size = window.size;
+        state.resize(self.id, size);
+    }
+
+    #[inline]
+    pub fn set_max_inner_size(&self, dimensions: Option<Size>) {
+        let mut state = self.lock();
+        let scale_factor = state.scale_factor(self.id);
+        let window = state.windows.get_mut(&self.id).unwrap();
+        window.attributes.max_inner_size = dimensions.map(|size| size.to_physical(scale_factor));
+        let size = window.size;
+        state.resize(self.id, size);
+    }
+
+    #[inline]
+    pub fn set_enabled_buttons(&self, buttons: WindowButtons) {
+        self.with_window(|window| window.attributes.enabled_buttons = buttons);
+    }
+
+    #[inline]
+    pub fn enabled_buttons(&self) -> WindowButtons {
+        self.with_window(|window| window.attributes.enabled_buttons)
+    }
+
+    #[inline]
+    pub fn set_resize_increments(&self, increments: Option<Size>) {
+        let mut state = self.lock();
+        let scale_factor = state.scale_factor(self.id);
+        let window = state.windows.get_mut(&self.id).unwrap();
+        window.attributes.resize_increments = increments.map(|size| size.to_physical(scale_factor));
+    }
+
+    #[inline]
+    pub fn is_resizable(&self) -> bool {
+        self.with_window(|window| window.attributes.resizable)
+    }
+
+    #[inline]
+    pub fn set_resizable(&self, resizable: bool) {
+        self.with_window(|window| window.attributes.resizable = resizable);
+    }
+
+    #[inline]
+    pub fn set_cursor_icon(&self, cursor: CursorIcon) {
+        self.with_window(|window| {
+            window.attributes.cursor_icon = cursor;
+            window.attributes.custom_cursor = None;
+        });
+    }
+
+    #[inline]
+    pub fn set_custom_cursor(&self, cursor: CustomCursor) {
+        self.with_window(|window| window.attributes.custom_cursor = Some(cursor));
+    }
+
+    #[inline]
+    pub fn set_cursor_theme(&self, name: &str, size: u32) {
+        self.with_window(|window| window.attributes.cursor_theme = Some((name.to_owned(), size)));
+    }
+
+    #[inline]
+    pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
+        self.with_window(|window| window.attributes.cursor_grab = mode);
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_cursor_visible(&self, visible: bool) {
+        self.with_window(|window| window.attributes.cursor_visible = visible);
+    }
+
+    #[inline]
+    pub fn set_cursor_hittest(&self, hittest: bool) -> Result<(), ExternalError> {
+        self.with_window(|window| window.attributes.cursor_hittest = hittest);
+        Ok(())
+    }
+
+    #[inline]
+    pub fn scale_factor(&self) -> f64 {
+        self.lock().scale_factor(self.id)
+    }
+
+    #[inline]
+    pub fn set_cursor_position(&self, position: Position) -> Result<(), ExternalError> {
+        let position = position.to_physical(self.scale_factor());
+        self.with_window(|window| window.attributes.cursor_position = Some(position));
+        Ok(())
+    }
+
+    #[inline]
+    pub fn drag_window(&self) -> Result<(), ExternalError> {
+        // There's no window manager to hand the pointer over to.
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn drag_resize_window(&self, _direction: ResizeDirection) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn is_maximized(&self) -> bool {
+        self.with_window(|window| window.attributes.maximized)
+    }
+
+    #[inline]
+    pub fn set_maximized(&self, maximized: bool) {
+        set_maximized(&mut self.lock(), self.id, maximized);
+    }
+
+    #[inline]
+    pub fn set_minimized(&self, minimized: bool) {
+        self.with_window(|window| window.attributes.minimized = minimized);
+    }
+
+    #[inline]
+    pub fn is_minimized(&self) -> Option<bool> {
+        Some(self.with_window(|window| window.attributes.minimized))
+    }
+
+    #[inline]
+    pub fn fullscreen(&self) -> Option<Fullscreen> {
+        self.with_window(|window| window.fullscreen.clone())
+    }
+
+    #[inline]
+    pub fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) {
+        set_fullscreen(&mut self.lock(), self.id, fullscreen);
+    }
+
+    #[inline]
+    pub fn is_decorated(&self) -> bool {
+        self.with_window(|window| window.attributes.decorations)
+    }
+
+    #[inline]
+    pub fn set_decorations(&self, decorations: bool) {
+        self.with_window(|window| window.attributes.decorations = decorations);
+    }
+
+    #[inline]
+    pub fn set_window_level(&self, level: WindowLevel) {
+        self.with_window(|window| window.attributes.window_level = level);
+    }
+
+    #[inline]
+    pub fn set_blur(&self, blur: bool) {
+        self.with_window(|window| {
+            window.attributes.blur = blur;
+            window.attributes.blur_region = None;
+        });
+    }
+
+    #[inline]
+    pub fn set_blur_region(&self, position: Position, size: Size) {
+        let scale_factor = self.scale_factor();
+        let region = (
+            position.to_physical(scale_factor),
+            size.to_physical(scale_factor),
+        );
+        self.with_window(|window| {
+            window.attributes.blur = true;
+            window.attributes.blur_region = Some(region);
+        });
+    }
+
+    #[inline]
+    pub fn set_window_icon(&self, _icon: Option<Icon>) {
+        // Virtual windows aren't displayed anywhere.
+    }
+
+    #[inline]
+    pub fn set_ime_position(&self, position: Position) {
+        let position = position.to_physical(self.scale_factor());
+        self.with_window(|window| window.attributes.ime_position = Some(position));
+    }
+
+    #[inline]
+    pub fn request_user_attention(&self, request_type: Option<UserAttentionType>) {
+        self.with_window(|window| window.attributes.user_attention = request_type);
+    }
+
+    #[inline]
+    pub fn set_theme(&self, theme: Option<Theme>) {
+        self.with_window(|window| window.theme = theme);
+    }
+
+    #[inline]
+    pub fn theme(&self) -> Option<Theme> {
+        self.with_window(|window| window.theme)
+    }
+
+    #[inline]
+    pub fn request_redraw(&self) {
+        self.lock().request_redraw(self.id);
+    }
+
+    #[inline]
+    pub fn request_frame(&self) {
+        self.with_window(|window| window.frame_requested = true);
+    }
+
+    #[inline]
+    pub fn current_monitor(&self) -> MonitorHandle {
+        self.lock().current_monitor(self.id).clone()
+    }
+
+    #[inline]
+    pub fn available_monitors(&self) -> Vec<MonitorHandle> {
+        self.lock().monitors.clone()
+    }
+
+    #[inline]
+    pub fn primary_monitor(&self) -> MonitorHandle {
+        self.lock().monitors[0].clone()
+    }
+
+    /// Virtual windows have no native handle, so this returns an empty handle.
+    pub fn raw_window_handle(&self) -> XlibHandle {
+        XlibHandle::empty()
+    }
+}
+
+impl Drop for Window {
+    fn drop(&mut self) {
+        let mut state = self.lock();
+        state.windows.remove(&self.id);
+        state
+            .redraw_requests
+            .retain(|&window_id| window_id != self.id);
+        state
+            .events
+            .push_back(PendingEvent::Window(self.id, WindowEvent::Destroyed));
+    }
+}
+
+fn set_maximized(state: &mut State, id: WindowId, maximized: bool) {
+    let window = state.windows.get_mut(&id).unwrap();
+    if window.attributes.maximized == maximized {
+        return;
+    }
+    window.attributes.maximized = maximized;
+    if window.fullscreen.is_some() {
+        // The geometry is restored once the window leaves fullscreen.
+        return;
+    }
+
+    if maximized {
+        window.restore = Some((window.position, window.size));
+        let monitor = state.current_monitor(id).clone();
+        state.move_to(id, monitor.position);
+        state.resize(id, monitor.size);
+    } else if let Some((position, size)) = window.restore.take() {
+        state.move_to(id, position);
+        state.resize(id, size);
+    }
+}
+
+fn set_fullscreen(state: &mut State, id: WindowId, fullscreen: Option<Fullscreen>) {
+    let window = state.windows.get_mut(&id).unwrap();
+    let was_fullscreen = window.fullscreen.is_some();
+    let maximized = window.attributes.maximized;
+    window.fullscreen = fullscreen.clone();
+
+    let (monitor, size) = match fullscreen {
+        None => {
+            if was_fullscreen && !maximized {
+                if let Some((position, size)) = window.restore.take() {
+                    state.move_to(id, position);
+                    state.resize(id, size);
+                }
+            }
+            return;
+        }
+        Some(Fullscreen::Exclusive(ref video_mode)) => match video_mode.video_mode {
+            PlatformVideoMode::Headless(ref video_mode) => {
+                (video_mode.monitor.clone(), video_mode.size)
+            }
+            _ => return,
+        },
+        Some(Fullscreen::Borderless(Some(RootMonitorHandle {
+            inner: PlatformMonitorHandle::Headless(ref monitor),
+        }))) => (monitor.clone(), monitor.size),
+        Some(Fullscreen::Borderless(_)) => {
+            let monitor = state.current_monitor(id).clone();
+            let size = monitor.size;
+            (monitor, size)
+        }
+    };
+
+    let window = state.windows.get_mut(&id).unwrap();
+    if window.restore.is_none() {
+        window.restore = Some((window.position, window.size));
+    }
+    state.move_to(id, monitor.position);
+    state.resize(id, size);
+}
@@ -11,9 +11,15 @@ compile_error!("Please select a feature to build for unix: `x11`, `wayland`");
 
 #[cfg(feature = "wayland")]
 use std::error::Error;
-use std::{collections::VecDeque, env, fmt};
+use std::{
+    collections::{HashMap, VecDeque},
+    env, fmt, io,
+    os::unix::io::{AsRawFd, RawFd},
+    sync::{Arc, Condvar},
+    time::{Duration, Instant},
+};
 #[cfg(feature = "x11")]
-use std::{ffi::CStr, mem::MaybeUninit, os::raw::*, sync::Arc};
+use std::{ffi::CStr, mem::MaybeUninit, os::raw::*};
 
 #[cfg(feature = "x11")]
 use parking_lot::Mutex;
@@ -24,17 +30,29 @@ pub use self::x11::XNotSupported;
 #[cfg(feature = "x11")]
 use self::x11::{ffi::XVisualInfo, util::WindowType as XWindowType, XConnection, XError};
 use crate::{
+    clipboard::ClipboardKind,
     dpi::{PhysicalPosition, PhysicalSize, Position, Size},
     error::{ExternalError, NotSupportedError, OsError as RootOsError},
-    event::Event,
-    event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootELW},
+    event::{Event, WindowEvent},
+    event_loop::{
+        ControlFlow, DeviceEventFilter, EventLoopClosed, EventLoopWindowTarget as RootELW, FdToken,
+        TimerId, TrySendError,
+    },
     icon::Icon,
     monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
-    window::{CursorIcon, Fullscreen, UserAttentionType, WindowAttributes},
+    platform::pump_events::PumpStatus,
+    window::{
+        CursorGrabMode, CursorIcon, CustomCursor, Fullscreen, ResizeDirection, Theme,
+        UserAttentionType, WindowAttributes, WindowButtons, WindowLevel,
+    },
 };
 
+pub use self::popup::PopupAttributes;
 pub(crate) use crate::icon::RgbaIcon as PlatformIcon;
 
+mod cursor_theme;
+pub mod headless;
+mod popup;
 #[cfg(feature = "wayland")]
 pub mod wayland;
 #[cfg(feature = "x11")]
@@ -42,11 +60,12 @@ pub mod x11;
 
 /// Environment variable specifying which backend should be used on unix platform.
 ///
-/// Legal values are x11 and wayland. If this variable is set only the named backend
+/// Legal values are x11, wayland and headless. If this variable is set only the named backend
 /// will be tried by winit. If it is not set, winit will try to connect to a wayland connection,
 /// and if it fails will fallback on x11.
 ///
-/// If this variable is set with any other value, winit will panic.
+/// If this variable is set with any other value, creating the event loop fails with
+/// `EventLoopError::UnknownBackend`.
 const BACKEND_PREFERENCE_ENV_VAR: &str = "WINIT_UNIX_BACKEND";
 
 #[derive(Clone)]
@@ -55,9 +74,7 @@ pub struct PlatformSpecificWindowBuilderAttributes {
     pub visual_infos: Option<XVisualInfo>,
     #[cfg(feature = "x11")]
     pub screen_id: Option<i32>,
-    #[cfg(feature = "x11")]
     pub resize_increments: Option<Size>,
-    #[cfg(feature = "x11")]
     pub base_size: Option<Size>,
     #[cfg(feature = "x11")]
     pub class: Option<(String, String)>,
@@ -69,6 +86,9 @@ pub struct PlatformSpecificWindowBuilderAttributes {
     pub gtk_theme_variant: Option<String>,
     #[cfg(feature = "wayland")]
     pub app_id: Option<String>,
+    pub popup: Option<PopupAttributes>,
+    pub owner: Option<WindowId>,
+    pub modal: bool,
 }
 
 impl Default for PlatformSpecificWindowBuilderAttributes {
@@ -78,9 +98,7 @@ impl Default for PlatformSpecificWindowBuilderAttributes {
             visual_infos: None,
             #[cfg(feature = "x11")]
             screen_id: None,
-            #[cfg(feature = "x11")]
             resize_increments: None,
-            #[cfg(feature = "x11")]
             base_size: None,
             #[cfg(feature = "x11")]
             class: None,
@@ -92,6 +110,9 @@ impl Default for PlatformSpecificWindowBuilderAttributes {
             gtk_theme_variant: None,
             #[cfg(feature = "wayland")]
             app_id: None,
+            popup: None,
+            owner: None,
+            modal: false,
         }
     }
 }
@@ -125,11 +146,76 @@ impl fmt::Display for OsError {
     }
 }
 
+/// The reasons why a unix `EventLoop` could not be created.
+#[derive(Debug)]
+pub enum EventLoopError {
+    /// `WINIT_UNIX_BACKEND` is set to a value other than `x11`, `wayland` or `headless`, or names
+    /// a backend whose feature is disabled.
+    UnknownBackend(String),
+    /// The Wayland backend was requested, but connecting to the compositor failed.
+    #[cfg(feature = "wayland")]
+    Wayland(Box<dyn Error>),
+    /// The X11 backend was requested, but connecting to the X server failed.
+    #[cfg(feature = "x11")]
+    X11(XNotSupported),
+    /// No backend was requested, and every enabled backend failed to initialize.
+    NoBackend {
+        #[cfg(feature = "wayland")]
+        wayland: Box<dyn Error>,
+        #[cfg(feature = "x11")]
+        x11: XNotSupported,
+    },
+}
+
+impl fmt::Display for EventLoopError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match *self {
+            EventLoopError::UnknownBackend(ref value) => {
+                let backends: &[&str] = &[
+                    #[cfg(feature = "x11")]
+                    "`x11`",
+                    #[cfg(feature = "wayland")]
+                    "`wayland`",
+                    "`headless`",
+                ];
+                write!(
+                    f,
+                    "Unknown environment variable value for {}: `{}`, try one of the enabled \
+                     backends {}",
+                    BACKEND_PREFERENCE_ENV_VAR,
+                    value,
+                    backends.join(","),
+                )
+            }
+            #[cfg(feature = "wayland")]
+            EventLoopError::Wayland(ref e) => {
+                write!(f, "Failed to initialize Wayland backend: {}", e)
+            }
+            #[cfg(feature = "x11")]
+            EventLoopError::X11(ref e) => write!(f, "Failed to initialize X11 backend: {}", e),
+            EventLoopError::NoBackend {
+                #[cfg(feature = "wayland")]
+                ref wayland,
+                #[cfg(feature = "x11")]
+                ref x11,
+            } => {
+                f.write_str("Failed to initialize any backend!")?;
+                #[cfg(feature = "wayland")]
+                write!(f, " Wayland status: {}", wayland)?;
+                #[cfg(feature = "x11")]
+                write!(f, " X11 status: {}", x11)?;
+                Ok(())
+            }
+        }
+    }
+}
+
 pub enum Window {
     #[cfg(feature = "x11")]
     X(x11::Window),
     #[cfg(feature = "wayland")]
     Wayland(wayland::Window),
+    Headless(headless::Window),
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -138,6 +224,7 @@ pub enum WindowId {
     X(x11::WindowId),
     #[cfg(feature = "wayland")]
     Wayland(wayland::WindowId),
+    Headless(headless::WindowId),
 }
 
 impl WindowId {
@@ -147,6 +234,17 @@ impl WindowId {
         #[cfg(all(not(feature = "wayland"), feature = "x11"))]
         return WindowId::X(x11::WindowId::dummy());
     }
+
+    /// Returns the id used by the backend, as recorded by `EventRecorder`.
+    pub fn raw(&self) -> u64 {
+        match *self {
+            #[cfg(feature = "x11")]
+            WindowId::X(id) => id.raw(),
+            #[cfg(feature = "wayland")]
+            WindowId::Wayland(id) => id.raw(),
+            WindowId::Headless(id) => id.0,
+        }
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -155,6 +253,7 @@ pub enum DeviceId {
     X(x11::DeviceId),
     #[cfg(feature = "wayland")]
     Wayland(wayland::DeviceId),
+    Headless(headless::DeviceId),
 }
 
 impl DeviceId {
@@ -164,6 +263,17 @@ impl DeviceId {
         #[cfg(all(not(feature = "wayland"), feature = "x11"))]
         return DeviceId::X(x11::DeviceId::dummy());
     }
+
+    /// Returns the id used by the backend, as recorded by `EventRecorder`.
+    pub fn raw(&self) -> u64 {
+        match *self {
+            #[cfg(feature = "x11")]
+            DeviceId::X(id) => id.raw(),
+            #[cfg(feature = "wayland")]
+            DeviceId::Wayland(_) => 0,
+            DeviceId::Headless(id) => id.0,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -172,24 +282,27 @@ pub enum MonitorHandle {
     X(x11::MonitorHandle),
     #[cfg(feature = "wayland")]
     Wayland(wayland::MonitorHandle),
+    Headless(headless::MonitorHandle),
 }
 
-/// `x11_or_wayland!(match expr; Enum(foo) => foo.something())`
+/// `any_backend!(match expr; Enum(foo) => foo.something())`
 /// expands to the equivalent of
 /// ```ignore
 /// match self {
 ///    Enum::X(foo) => foo.something(),
 ///    Enum::Wayland(foo) => foo.something(),
+///    Enum::Headless(foo) => foo.something(),
 /// }
 /// ```
 /// The result can be converted to another enum by adding `; as AnotherEnum`
-macro_rules! x11_or_wayland {
+macro_rules! any_backend {
     (match $what:expr; $enum:ident ( $($c1:tt)* ) => $x:expr; as $enum2:ident ) => {
         match $what {
             #[cfg(feature = "x11")]
             $enum::X($($c1)*) => $enum2::X($x),
             #[cfg(feature = "wayland")]
             $enum::Wayland($($c1)*) => $enum2::Wayland($x),
+            $enum::Headless($($c1)*) => $enum2::Headless($x),
         }
     };
     (match $what:expr; $enum:ident ( $($c1:tt)* ) => $x:expr) => {
@@ -198,6 +311,7 @@ macro_rules! x11_or_wayland {
             $enum::X($($c1)*) => $x,
             #[cfg(feature = "wayland")]
             $enum::Wayland($($c1)*) => $x,
+            $enum::Headless($($c1)*) => $x,
         }
     };
 }
@@ -205,32 +319,32 @@ macro_rules! x11_or_wayland {
 impl MonitorHandle {
     #[inline]
     pub fn name(&self) -> Option<String> {
-        x11_or_wayland!(match self; MonitorHandle(m) => m.name())
+        any_backend!(match self; MonitorHandle(m) => m.name())
     }
 
     #[inline]
     pub fn native_identifier(&self) -> u32 {
-        x11_or_wayland!(match self; MonitorHandle(m) => m.native_identifier())
+        any_backend!(match self; MonitorHandle(m) => m.native_identifier())
     }
 
     #[inline]
     pub fn size(&self) -> PhysicalSize<u32> {
-        x11_or_wayland!(match self; MonitorHandle(m) => m.size())
+        any_backend!(match self; MonitorHandle(m) => m.size())
     }
 
     #[inline]
     pub fn position(&self) -> PhysicalPosition<i32> {
-        x11_or_wayland!(match self; MonitorHandle(m) => m.position())
+        any_backend!(match self; MonitorHandle(m) => m.position())
     }
 
     #[inline]
     pub fn scale_factor(&self) -> f64 {
-        x11_or_wayland!(match self; MonitorHandle(m) => m.scale_factor() as f64)
+        any_backend!(match self; MonitorHandle(m) => m.scale_factor() as f64)
     }
 
     #[inline]
     pub fn video_modes(&self) -> Box<dyn Iterator<Item = RootVideoMode>> {
-        x11_or_wayland!(match self; MonitorHandle(m) => Box::new(m.video_modes()))
+        any_backend!(match self; MonitorHandle(m) => Box::new(m.video_modes()))
     }
 }
 
@@ -240,27 +354,28 @@ pub enum VideoMode {
     X(x11::VideoMode),
     #[cfg(feature = "wayland")]
     Wayland(wayland::VideoMode),
+    Headless(headless::VideoMode),
 }
 
 impl VideoMode {
     #[inline]
     pub fn size(&self) -> PhysicalSize<u32> {
-        x11_or_wayland!(match self; VideoMode(m) => m.size())
+        any_backend!(match self; VideoMode(m) => m.size())
     }
 
     #[inline]
     pub fn bit_depth(&self) -> u16 {
-        x11_or_wayland!(match self; VideoMode(m) => m.bit_depth())
+        any_backend!(match self; VideoMode(m) => m.bit_depth())
     }
 
     #[inline]
     pub fn refresh_rate(&self) -> u16 {
-        x11_or_wayland!(match self; VideoMode(m) => m.refresh_rate())
+        any_backend!(match self; VideoMode(m) => m.refresh_rate())
     }
 
     #[inline]
     pub fn monitor(&self) -> RootMonitorHandle {
-        x11_or_wayland!(match self; VideoMode(m) => m.monitor())
+        any_backend!(match self; VideoMode(m) => m.monitor())
     }
 }
 
@@ -280,134 +395,208 @@ impl Window {
             EventLoopWindowTarget::X(ref window_target) => {
                 x11::Window::new(window_target, attribs, pl_attribs).map(Window::X)
             }
+            EventLoopWindowTarget::Headless(ref window_target) => {
+                headless::Window::new(window_target, attribs, pl_attribs).map(Window::Headless)
+            }
         }
     }
 
     #[inline]
     pub fn id(&self) -> WindowId {
-        x11_or_wayland!(match self; Window(w) => w.id(); as WindowId)
+        any_backend!(match self; Window(w) => w.id(); as WindowId)
     }
 
     #[inline]
     pub fn set_title(&self, title: &str) {
-        x11_or_wayland!(match self; Window(w) => w.set_title(title));
+        any_backend!(match self; Window(w) => w.set_title(title));
+    }
+
+    #[inline]
+    pub fn is_visible(&self) -> Option<bool> {
+        any_backend!(match self; Window(w) => w.is_visible())
     }
 
     #[inline]
     pub fn set_visible(&self, visible: bool) {
-        x11_or_wayland!(match self; Window(w) => w.set_visible(visible))
+        any_backend!(match self; Window(w) => w.set_visible(visible))
     }
 
     #[inline]
     pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
-        x11_or_wayland!(match self; Window(w) => w.outer_position())
+        any_backend!(match self; Window(w) => w.outer_position())
     }
 
     #[inline]
     pub fn inner_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
-        x11_or_wayland!(match self; Window(w) => w.inner_position())
+        any_backend!(match self; Window(w) => w.inner_position())
     }
 
     #[inline]
     pub fn set_outer_position(&self, position: Position) {
-        x11_or_wayland!(match self; Window(w) => w.set_outer_position(position))
+        any_backend!(match self; Window(w) => w.set_outer_position(position))
     }
 
     #[inline]
     pub fn inner_size(&self) -> PhysicalSize<u32> {
-        x11_or_wayland!(match self; Window(w) => w.inner_size())
+        any_backend!(match self; Window(w) => w.inner_size())
     }
 
     #[inline]
     pub fn outer_size(&self) -> PhysicalSize<u32> {
-        x11_or_wayland!(match self; Window(w) => w.outer_size())
+        any_backend!(match self; Window(w) => w.outer_size())
     }
 
     #[inline]
     pub fn set_inner_size(&self, size: Size) {
-        x11_or_wayland!(match self; Window(w) => w.set_inner_size(size))
+        any_backend!(match self; Window(w) => w.set_inner_size(size))
     }
 
     #[inline]
     pub fn set_min_inner_size(&self, dimensions: Option<Size>) {
-        x11_or_wayland!(match self; Window(w) => w.set_min_inner_size(dimensions))
+        any_backend!(match self; Window(w) => w.set_min_inner_size(dimensions))
     }
 
     #[inline]
     pub fn set_max_inner_size(&self, dimensions: Option<Size>) {
-        x11_or_wayland!(match self; Window(w) => w.set_max_inner_size(dimensions))
+        any_backend!(match self; Window(w) => w.set_max_inner_size(dimensions))
+    }
+
+    #[inline]
+    pub fn set_enabled_buttons(&self, buttons: WindowButtons) {
+        any_backend!(match self; Window(w) => w.set_enabled_buttons(buttons))
+    }
+
+    #[inline]
+    pub fn enabled_buttons(&self) -> WindowButtons {
+        any_backend!(match self; Window(w) => w.enabled_buttons())
+    }
+
+    #[inline]
+    pub fn set_resize_increments(&self, increments: Option<Size>) {
+        any_backend!(match self; Window(w) => w.set_resize_increments(increments))
+    }
+
+    #[inline]
+    pub fn is_resizable(&self) -> bool {
+        any_backend!(match self; Window(w) => w.is_resizable())
     }
 
     #[inline]
     pub fn set_resizable(&self, resizable: bool) {
-        x11_or_wayland!(match self; Window(w) => w.set_resizable(resizable))
+        any_backend!(match self; Window(w) => w.set_resizable(resizable))
     }
 
     #[inline]
     pub fn set_cursor_icon(&self, cursor: CursorIcon) {
-        x11_or_wayland!(match self; Window(w) => w.set_cursor_icon(cursor))
+        any_backend!(match self; Window(w) => w.set_cursor_icon(cursor))
+    }
+
+    #[inline]
+    pub fn set_custom_cursor(&self, cursor: CustomCursor) {
+        any_backend!(match self; Window(window) => window.set_custom_cursor(cursor))
+    }
+
+    #[inline]
+    pub fn set_cursor_theme(&self, name: &str, size: u32) {
+        any_backend!(match self; Window(window) => window.set_cursor_theme(name, size))
     }
 
     #[inline]
-    pub fn set_cursor_grab(&self, grab: bool) -> Result<(), ExternalError> {
-        x11_or_wayland!(match self; Window(window) => window.set_cursor_grab(grab))
+    pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
+        any_backend!(match self; Window(window) => window.set_cursor_grab(mode))
     }
 
     #[inline]
     pub fn set_cursor_visible(&self, visible: bool) {
-        x11_or_wayland!(match self; Window(window) => window.set_cursor_visible(visible))
+        any_backend!(match self; Window(window) => window.set_cursor_visible(visible))
+    }
+
+    #[inline]
+    pub fn set_blur(&self, blur: bool) {
+        any_backend!(match self; Window(window) => window.set_blur(blur))
+    }
+
+    #[inline]
+    pub fn set_blur_region(&self, position: Position, size: Size) {
+        any_backend!(match self; Window(window) => window.set_blur_region(position, size))
+    }
+
+    #[inline]
+    pub fn set_cursor_hittest(&self, hittest: bool) -> Result<(), ExternalError> {
+        any_backend!(match self; Window(window) => window.set_cursor_hittest(hittest))
     }
 
     #[inline]
     pub fn scale_factor(&self) -> f64 {
-        x11_or_wayland!(match self; Window(w) => w.scale_factor() as f64)
+        any_backend!(match self; Window(w) => w.scale_factor() as f64)
     }
 
     #[inline]
     pub fn set_cursor_position(&self, position: Position) -> Result<(), ExternalError> {
-        x11_or_wayland!(match self; Window(w) => w.set_cursor_position(position))
+        any_backend!(match self; Window(w) => w.set_cursor_position(position))
+    }
+
+    #[inline]
+    pub fn drag_window(&self) -> Result<(), ExternalError> {
+        any_backend!(match self; Window(w) => w.drag_window())
+    }
+
+    #[inline]
+    pub fn drag_resize_window(&self, direction: ResizeDirection) -> Result<(), ExternalError> {
+        any_backend!(match self; Window(w) => w.drag_resize_window(direction))
+    }
+
+    #[inline]
+    pub fn is_maximized(&self) -> bool {
+        any_backend!(match self; Window(w) => w.is_maximized())
     }
 
     #[inline]
     pub fn set_maximized(&self, maximized: bool) {
-        x11_or_wayland!(match self; Window(w) => w.set_maximized(maximized))
+        any_backend!(match self; Window(w) => w.set_maximized(maximized))
+    }
+
+    #[inline]
+    pub fn is_minimized(&self) -> Option<bool> {
+        any_backend!(match self; Window(w) => w.is_minimized())
     }
 
     #[inline]
     pub fn set_minimized(&self, minimized: bool) {
-        x11_or_wayland!(match self; Window(w) => w.set_minimized(minimized))
+        any_backend!(match self; Window(w) => w.set_minimized(minimized))
     }
 
     #[inline]
     pub fn fullscreen(&self) -> Option<Fullscreen> {
-        x11_or_wayland!(match self; Window(w) => w.fullscreen())
+        any_backend!(match self; Window(w) => w.fullscreen())
     }
 
     #[inline]
     pub fn set_fullscreen(&self, monitor: Option<Fullscreen>) {
-        x11_or_wayland!(match self; Window(w) => w.set_fullscreen(monitor))
+        any_backend!(match self; Window(w) => w.set_fullscreen(monitor))
+    }
+
+    #[inline]
+    pub fn is_decorated(&self) -> bool {
+        any_backend!(match self; Window(w) => w.is_decorated())
     }
 
     #[inline]
     pub fn set_decorations(&self, decorations: bool) {
-        x11_or_wayland!(match self; Window(w) => w.set_decorations(decorations))
+        any_backend!(match self; Window(w) => w.set_decorations(decorations))
     }
 
     #[inline]
-    pub fn set_always_on_top(&self, _always_on_top: bool) {
-        match self {
-            #[cfg(feature = "x11")]
-            &Window::X(ref w) => w.set_always_on_top(_always_on_top),
-            #[cfg(feature = "wayland")]
-            _ => (),
-        }
+    pub fn set_window_level(&self, level: WindowLevel) {
+        any_backend!(match self; Window(w) => w.set_window_level(level))
     }
 
     #[inline]
     pub fn set_window_icon(&self, _window_icon: Option<Icon>) {
-        match self {
+        match *self {
             #[cfg(feature = "x11")]
-            &Window::X(ref w) => w.set_window_icon(_window_icon),
+            Window::X(ref w) => w.set_window_icon(_window_icon),
+            Window::Headless(ref w) => w.set_window_icon(_window_icon),
             #[cfg(feature = "wayland")]
             _ => (),
         }
@@ -415,83 +604,117 @@ impl Window {
 
     #[inline]
     pub fn set_ime_position(&self, position: Position) {
-        x11_or_wayland!(match self; Window(w) => w.set_ime_position(position))
+        any_backend!(match self; Window(w) => w.set_ime_position(position))
     }
 
     #[inline]
     pub fn request_user_attention(&self, _request_type: Option<UserAttentionType>) {
-        match self {
+        match *self {
             #[cfg(feature = "x11")]
-            &Window::X(ref w) => w.request_user_attention(_request_type),
+            Window::X(ref w) => w.request_user_attention(_request_type),
+            Window::Headless(ref w) => w.request_user_attention(_request_type),
             #[cfg(feature = "wayland")]
             _ => (),
         }
     }
 
+    #[inline]
+    pub fn set_theme(&self, theme: Option<Theme>) {
+        any_backend!(match self; Window(w) => w.set_theme(theme))
+    }
+
+    #[inline]
+    pub fn theme(&self) -> Option<Theme> {
+        any_backend!(match self; Window(w) => w.theme())
+    }
+
     #[inline]
     pub fn request_redraw(&self) {
-        x11_or_wayland!(match self; Window(w) => w.request_redraw())
+        any_backend!(match self; Window(w) => w.request_redraw())
+    }
+
+    #[inline]
+    pub fn request_frame(&self) {
+        any_backend!(match self; Window(w) => w.request_frame())
     }
 
     #[inline]
     pub fn current_monitor(&self) -> Option<RootMonitorHandle> {
-        match self {
+        match *self {
             #[cfg(feature = "x11")]
-            &Window::X(ref window) => {
+            Window::X(ref window) => {
                 let current_monitor = MonitorHandle::X(window.current_monitor());
                 Some(RootMonitorHandle {
                     inner: current_monitor,
                 })
             }
             #[cfg(feature = "wayland")]
-            &Window::Wayland(ref window) => {
+            Window::Wayland(ref window) => {
                 let current_monitor = MonitorHandle::Wayland(window.current_monitor()?);
                 Some(RootMonitorHandle {
                     inner: current_monitor,
                 })
             }
+            Window::Headless(ref window) => {
+                let current_monitor = MonitorHandle::Headless(window.current_monitor());
+                Some(RootMonitorHandle {
+                    inner: current_monitor,
+                })
+            }
         }
     }
 
     #[inline]
     pub fn available_monitors(&self) -> VecDeque<MonitorHandle> {
-        match self {
+        match *self {
             #[cfg(feature = "x11")]
-            &Window::X(ref window) => window
+            Window::X(ref window) => window
                 .available_monitors()
                 .into_iter()
                 .map(MonitorHandle::X)
                 .collect(),
             #[cfg(feature = "wayland")]
-            &Window::Wayland(ref window) => window
+            Window::Wayland(ref window) => window
                 .available_monitors()
                 .into_iter()
                 .map(MonitorHandle::Wayland)
                 .collect(),
+            Window::Headless(ref window) => window
+                .available_monitors()
+                .into_iter()
+                .map(MonitorHandle::Headless)
+                .collect(),
         }
     }
 
     #[inline]
     pub fn primary_monitor(&self) -> Option<RootMonitorHandle> {
-        match self {
+        match *self {
             #[cfg(feature = "x11")]
-            &Window::X(ref window) => {
+            Window::X(ref window) => {
                 let primary_monitor = MonitorHandle::X(window.primary_monitor());
                 Some(RootMonitorHandle {
                     inner: primary_monitor,
                 })
             }
             #[cfg(feature = "wayland")]
-            &Window::Wayland(ref window) => window.primary_monitor(),
+            Window::Wayland(ref window) => window.primary_monitor(),
+            Window::Headless(ref window) => {
+                let primary_monitor = MonitorHandle::Headless(window.primary_monitor());
+                Some(RootMonitorHandle {
+                    inner: primary_monitor,
+                })
+            }
         }
     }
 
     pub fn raw_window_handle(&self) -> RawWindowHandle {
-        match self {
+        match *self {
             #[cfg(feature = "x11")]
-            &Window::X(ref window) => RawWindowHandle::Xlib(window.raw_window_handle()),
+            Window::X(ref window) => RawWindowHandle::Xlib(window.raw_window_handle()),
             #[cfg(feature = "wayland")]
-            &Window::Wayland(ref window) => RawWindowHandle::Wayland(window.raw_window_handle()),
+            Window::Wayland(ref window) => RawWindowHandle::Wayland(window.raw_window_handle()),
+            Window::Headless(ref window) => RawWindowHandle::Xlib(window.raw_window_handle()),
         }
     }
 }
@@ -534,6 +757,7 @@ pub enum EventLoop<T: 'static> {
     Wayland(wayland::EventLoop<T>),
     #[cfg(feature = "x11")]
     X(x11::EventLoop<T>),
+    Headless(headless::EventLoop<T>),
 }
 
 pub enum EventLoopProxy<T: 'static> {
@@ -541,73 +765,67 @@ pub enum EventLoopProxy<T: 'static> {
     X(x11::EventLoopProxy<T>),
     #[cfg(feature = "wayland")]
     Wayland(wayland::EventLoopProxy<T>),
+    Headless(headless::EventLoopProxy<T>),
 }
 
 impl<T: 'static> Clone for EventLoopProxy<T> {
     fn clone(&self) -> Self {
-        x11_or_wayland!(match self; EventLoopProxy(proxy) => proxy.clone(); as EventLoopProxy)
+        any_backend!(match self; EventLoopProxy(proxy) => proxy.clone(); as EventLoopProxy)
     }
 }
 
 impl<T: 'static> EventLoop<T> {
     pub fn new() -> EventLoop<T> {
-        assert_is_main_thread("new_any_thread");
+        assert_is_main_thread("new");
 
         EventLoop::new_any_thread()
     }
 
     pub fn new_any_thread() -> EventLoop<T> {
+        EventLoop::try_new_any_thread().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_new() -> Result<EventLoop<T>, EventLoopError> {
+        assert_is_main_thread("try_new");
+
+        EventLoop::try_new_any_thread()
+    }
+
+    pub fn try_new_any_thread() -> Result<EventLoop<T>, EventLoopError> {
         if let Ok(env_var) = env::var(BACKEND_PREFERENCE_ENV_VAR) {
-            match env_var.as_str() {
-                "x11" => {
-                    // TODO: propagate
-                    #[cfg(feature = "x11")]
-                    return EventLoop::new_x11_any_thread()
-                        .expect("Failed to initialize X11 backend");
-                    #[cfg(not(feature = "x11"))]
-                    panic!("x11 feature is not enabled")
-                }
-                "wayland" => {
-                    #[cfg(feature = "wayland")]
-                    return EventLoop::new_wayland_any_thread()
-                        .expect("Failed to initialize Wayland backend");
-                    #[cfg(not(feature = "wayland"))]
-                    panic!("wayland feature is not enabled");
-                }
-                _ => panic!(
-                    "Unknown environment variable value for {}, try one of `x11`,`wayland`",
-                    BACKEND_PREFERENCE_ENV_VAR,
-                ),
-            }
+            return match env_var.as_str() {
+                #[cfg(feature = "x11")]
+                "x11" => EventLoop::new_x11_any_thread().map_err(EventLoopError::X11),
+                #[cfg(feature = "wayland")]
+                "wayland" => EventLoop::new_wayland_any_thread().map_err(EventLoopError::Wayland),
+                "headless" => Ok(EventLoop::Headless(headless::EventLoop::new())),
+                _ => Err(EventLoopError::UnknownBackend(env_var)),
+            };
         }
 
         #[cfg(feature = "wayland")]
         let wayland_err = match EventLoop::new_wayland_any_thread() {
-            Ok(event_loop) => return event_loop,
+            Ok(event_loop) => return Ok(event_loop),
             Err(err) => err,
         };
 
         #[cfg(feature = "x11")]
         let x11_err = match EventLoop::new_x11_any_thread() {
-            Ok(event_loop) => return event_loop,
+            Ok(event_loop) => return Ok(event_loop),
             Err(err) => err,
         };
 
-        #[cfg(not(feature = "wayland"))]
-        let wayland_err = "backend disabled";
-        #[cfg(not(feature = "x11"))]
-        let x11_err = "backend disabled";
-
-        let err_string = format!(
-            "Failed to initialize any backend! Wayland status: {:?} X11 status: {:?}",
-            wayland_err, x11_err,
-        );
-        panic!(err_string);
+        Err(EventLoopError::NoBackend {
+            #[cfg(feature = "wayland")]
+            wayland: wayland_err,
+            #[cfg(feature = "x11")]
+            x11: x11_err,
+        })
     }
 
     #[cfg(feature = "wayland")]
     pub fn new_wayland() -> Result<EventLoop<T>, Box<dyn Error>> {
-        assert_is_main_thread("new_wayland_any_thread");
+        assert_is_main_thread("new_wayland");
 
         EventLoop::new_wayland_any_thread()
     }
@@ -619,7 +837,7 @@ impl<T: 'static> EventLoop<T> {
 
     #[cfg(feature = "x11")]
     pub fn new_x11() -> Result<EventLoop<T>, XNotSupported> {
-        assert_is_main_thread("new_x11_any_thread");
+        assert_is_main_thread("new_x11");
 
         EventLoop::new_x11_any_thread()
     }
@@ -635,31 +853,113 @@ impl<T: 'static> EventLoop<T> {
     }
 
     pub fn create_proxy(&self) -> EventLoopProxy<T> {
-        x11_or_wayland!(match self; EventLoop(evlp) => evlp.create_proxy(); as EventLoopProxy)
+        any_backend!(match self; EventLoop(evlp) => evlp.create_proxy(); as EventLoopProxy)
     }
 
-    pub fn run_return<F>(&mut self, callback: F)
+    pub fn create_proxy_bounded(&self, capacity: usize) -> EventLoopProxy<T> {
+        any_backend!(match self; EventLoop(evlp) => evlp.create_proxy_bounded(capacity); as EventLoopProxy)
+    }
+
+    pub fn run_return<F>(&mut self, callback: F) -> i32
     where
         F: FnMut(crate::event::Event<'_, T>, &RootELW<T>, &mut ControlFlow),
     {
-        x11_or_wayland!(match self; EventLoop(evlp) => evlp.run_return(callback))
+        any_backend!(match self; EventLoop(evlp) => evlp.run_return(callback))
     }
 
     pub fn run<F>(self, callback: F) -> !
     where
         F: 'static + FnMut(crate::event::Event<'_, T>, &RootELW<T>, &mut ControlFlow),
     {
-        x11_or_wayland!(match self; EventLoop(evlp) => evlp.run(callback))
+        any_backend!(match self; EventLoop(evlp) => evlp.run(callback))
+    }
+
+    pub fn pump_events<F>(&mut self, timeout: Option<Duration>, callback: F) -> PumpStatus
+    where
+        F: FnMut(crate::event::Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        any_backend!(match self; EventLoop(evlp) => evlp.pump_events(timeout, callback))
     }
 
     pub fn window_target(&self) -> &crate::event_loop::EventLoopWindowTarget<T> {
-        x11_or_wayland!(match self; EventLoop(evl) => evl.window_target())
+        any_backend!(match self; EventLoop(evl) => evl.window_target())
+    }
+}
+
+impl<T: 'static> AsRawFd for EventLoop<T> {
+    fn as_raw_fd(&self) -> RawFd {
+        any_backend!(match self; EventLoop(evlp) => evlp.as_raw_fd())
     }
 }
 
 impl<T: 'static> EventLoopProxy<T> {
     pub fn send_event(&self, event: T) -> Result<(), EventLoopClosed<T>> {
-        x11_or_wayland!(match self; EventLoopProxy(proxy) => proxy.send_event(event))
+        any_backend!(match self; EventLoopProxy(proxy) => proxy.send_event(event))
+    }
+
+    pub fn try_send_event(&self, event: T) -> Result<(), TrySendError<T>> {
+        any_backend!(match self; EventLoopProxy(proxy) => proxy.try_send_event(event))
+    }
+
+    pub fn wake_up(&self) {
+        any_backend!(match self; EventLoopProxy(proxy) => proxy.wake_up())
+    }
+}
+
+/// The capacity of a bounded `EventLoopProxy`, shared by all of its clones.
+#[derive(Clone, Debug)]
+pub struct ProxyBound(Arc<ProxyBoundState>);
+
+#[derive(Debug)]
+struct ProxyBoundState {
+    capacity: usize,
+    len: std::sync::Mutex<usize>,
+    released: Condvar,
+}
+
+/// Takes up a slot of a `ProxyBound` until the event loop receives the event it was sent with.
+#[derive(Debug)]
+pub struct ProxyPermit(Arc<ProxyBoundState>);
+
+impl ProxyBound {
+    pub fn new(capacity: usize) -> Self {
+        assert!(
+            capacity > 0,
+            "a bounded `EventLoopProxy` needs a capacity of at least 1"
+        );
+        ProxyBound(Arc::new(ProxyBoundState {
+            capacity,
+            len: std::sync::Mutex::new(0),
+            released: Condvar::new(),
+        }))
+    }
+
+    /// Takes a slot, or returns `None` if they are all taken.
+    pub fn try_acquire(&self) -> Option<ProxyPermit> {
+        let mut len = self.0.len.lock().unwrap();
+        if *len < self.0.capacity {
+            *len += 1;
+            Some(ProxyPermit(self.0.clone()))
+        } else {
+            None
+        }
+    }
+
+    /// Takes a slot, blocking until one is released if they are all taken.
+    pub fn acquire(&self) -> ProxyPermit {
+        let mut len = self.0.len.lock().unwrap();
+        while *len >= self.0.capacity {
+            len = self.0.released.wait(len).unwrap();
+        }
+        *len += 1;
+        ProxyPermit(self.0.clone())
+    }
+}
+
+impl Drop for ProxyPermit {
+    fn drop(&mut self) {
+        *self.0.len.lock().unwrap() -= 1;
+        self.0.released.notify_one();
     }
 }
 
@@ -668,6 +968,7 @@ pub enum EventLoopWindowTarget<T> {
     Wayland(wayland::EventLoopWindowTarget<T>),
     #[cfg(feature = "x11")]
     X(x11::EventLoopWindowTarget<T>),
+    Headless(headless::EventLoopWindowTarget<T>),
 }
 
 impl<T> EventLoopWindowTarget<T> {
@@ -676,7 +977,15 @@ impl<T> EventLoopWindowTarget<T> {
         match *self {
             #[cfg(feature = "wayland")]
             EventLoopWindowTarget::Wayland(_) => true,
+            _ => false,
+        }
+    }
+
+    #[inline]
+    pub fn is_x11(&self) -> bool {
+        match *self {
             #[cfg(feature = "x11")]
+            EventLoopWindowTarget::X(_) => true,
             _ => false,
         }
     }
@@ -697,7 +1006,68 @@ impl<T> EventLoopWindowTarget<T> {
                 .into_iter()
                 .map(MonitorHandle::X)
                 .collect(),
+            EventLoopWindowTarget::Headless(ref evlp) => evlp
+                .available_monitors()
+                .into_iter()
+                .map(MonitorHandle::Headless)
+                .collect(),
+        }
+    }
+
+    #[inline]
+    pub fn register_fd(&self, fd: RawFd, token: FdToken) -> io::Result<()> {
+        any_backend!(match self; EventLoopWindowTarget(evlp) => evlp.register_fd(fd, token))
+    }
+
+    #[inline]
+    pub fn unregister_fd(&self, fd: RawFd) -> io::Result<()> {
+        any_backend!(match self; EventLoopWindowTarget(evlp) => evlp.unregister_fd(fd))
+    }
+
+    #[inline]
+    pub fn set_timer(&self, deadline: Instant, id: TimerId) -> Result<(), NotSupportedError> {
+        match *self {
+            #[cfg(feature = "wayland")]
+            EventLoopWindowTarget::Wayland(ref evlp) => evlp.timers.borrow_mut().set(deadline, id),
+            #[cfg(feature = "x11")]
+            EventLoopWindowTarget::X(ref evlp) => evlp.timers.borrow_mut().set(deadline, id),
+            EventLoopWindowTarget::Headless(_) => return Err(NotSupportedError::new()),
         }
+        Ok(())
+    }
+
+    #[inline]
+    pub fn cancel_timer(&self, id: TimerId) {
+        match *self {
+            #[cfg(feature = "wayland")]
+            EventLoopWindowTarget::Wayland(ref evlp) => evlp.timers.borrow_mut().cancel(id),
+            #[cfg(feature = "x11")]
+            EventLoopWindowTarget::X(ref evlp) => evlp.timers.borrow_mut().cancel(id),
+            EventLoopWindowTarget::Headless(_) => (),
+        }
+    }
+
+    #[inline]
+    pub fn set_device_event_filter(&self, filter: DeviceEventFilter) {
+        any_backend!(match self; EventLoopWindowTarget(evlp) => evlp.set_device_event_filter(filter))
+    }
+
+    #[inline]
+    pub fn set_clipboard_contents(
+        &self,
+        kind: ClipboardKind,
+        contents: HashMap<String, Vec<u8>>,
+    ) -> Result<(), ExternalError> {
+        any_backend!(match self; EventLoopWindowTarget(evlp) => evlp.set_clipboard_contents(kind, contents))
+    }
+
+    #[inline]
+    pub fn clipboard_contents(
+        &self,
+        kind: ClipboardKind,
+        mime_type: &str,
+    ) -> Result<Option<Vec<u8>>, ExternalError> {
+        any_backend!(match self; EventLoopWindowTarget(evlp) => evlp.clipboard_contents(kind, mime_type))
     }
 
     #[inline]
@@ -712,6 +1082,12 @@ impl<T> EventLoopWindowTarget<T> {
                     inner: primary_monitor,
                 })
             }
+            EventLoopWindowTarget::Headless(ref evlp) => {
+                let primary_monitor = MonitorHandle::Headless(evlp.primary_monitor());
+                Some(RootMonitorHandle {
+                    inner: primary_monitor,
+                })
+            }
         }
     }
 }
@@ -724,10 +1100,11 @@ fn sticky_exit_callback<T, F>(
 ) where
     F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
 {
-    // make ControlFlow::Exit sticky by providing a dummy
-    // control flow reference if it is already Exit.
-    let mut dummy = ControlFlow::Exit;
-    let cf = if *control_flow == ControlFlow::Exit {
+    // make ControlFlow::ExitWithCode sticky by providing a dummy
+    // control flow reference if it is already ExitWithCode.
+    let mut dummy;
+    let cf = if let ControlFlow::ExitWithCode(code) = *control_flow {
+        dummy = ControlFlow::ExitWithCode(code);
         &mut dummy
     } else {
         control_flow
@@ -736,13 +1113,86 @@ fn sticky_exit_callback<T, F>(
     callback(evt, target, cf)
 }
 
-fn assert_is_main_thread(suggested_method: &str) {
+/// Whether a window event is user input, which is swallowed while the window has a modal child.
+pub(crate) fn is_input_event(event: &WindowEvent<'_>) -> bool {
+    matches!(
+        event,
+        WindowEvent::ReceivedCharacter(_)
+            | WindowEvent::KeyboardInput { .. }
+            | WindowEvent::CursorMoved { .. }
+            | WindowEvent::MouseWheel { .. }
+            | WindowEvent::MouseInput { .. }
+            | WindowEvent::TouchpadPressure { .. }
+            | WindowEvent::AxisMotion { .. }
+            | WindowEvent::Touch(_)
+    )
+}
+
+/// The pending one-shot timers of an event loop, set with `EventLoopWindowTarget::set_timer`.
+#[derive(Debug, Default)]
+pub struct Timers {
+    deadlines: HashMap<TimerId, Instant>,
+}
+
+impl Timers {
+    fn set(&mut self, deadline: Instant, id: TimerId) {
+        self.deadlines.insert(id, deadline);
+    }
+
+    fn cancel(&mut self, id: TimerId) {
+        self.deadlines.remove(&id);
+    }
+
+    /// Returns how long to wait until the earliest timer expires, or `None` if there is none.
+    pub fn timeout(&self, now: Instant) -> Option<Duration> {
+        self.deadlines
+            .values()
+            .min()
+            .map(|&deadline| deadline.saturating_duration_since(now))
+    }
+
+    /// Removes the timers which have expired at `now`, and returns them ordered by deadline.
+    pub fn take_expired(&mut self, now: Instant) -> Vec<TimerId> {
+        let mut expired: Vec<(Instant, TimerId)> = self
+            .deadlines
+            .iter()
+            .filter(|&(_, &deadline)| deadline <= now)
+            .map(|(&id, &deadline)| (deadline, id))
+            .collect();
+        expired.sort();
+        for (_, id) in &expired {
+            self.deadlines.remove(id);
+        }
+        expired.into_iter().map(|(_, id)| id).collect()
+    }
+}
+
+/// Returns the time between two refreshes of a monitor refreshing at `refresh_millihertz`, assuming
+/// 60Hz when the refresh rate is unknown.
+pub fn frame_interval(refresh_millihertz: Option<u32>) -> Duration {
+    match refresh_millihertz {
+        Some(refresh_millihertz) if refresh_millihertz > 0 => {
+            Duration::from_nanos(1_000_000_000_000 / u64::from(refresh_millihertz))
+        }
+        _ => Duration::from_nanos(1_000_000_000 / 60),
+    }
+}
+
+/// Returns the shortest of two optional timeouts, where `None` means waiting forever.
+fn min_timeout(a: Option<Duration>, b: Option<Duration>) -> Option<Duration> {
+    a.map_or(b, |a_timeout| {
+        b.map_or(Some(a_timeout), |b_timeout| Some(a_timeout.min(b_timeout)))
+    })
+}
+
+fn assert_is_main_thread(method: &str) {
     if !is_main_thread() {
         panic!(
-            "Initializing the event loop outside of the main thread is a significant \
-             cross-platform compatibility hazard. If you really, absolutely need to create an \
-             EventLoop on a different thread, please use the `EventLoopExtUnix::{}` function.",
-            suggested_method
+            "`EventLoop::{}` was called outside of the main thread. Initializing the event loop \
+             outside of the main thread is a significant cross-platform compatibility hazard. If \
+             you really, absolutely need to create an EventLoop on a different thread, please \
+             use the `EventLoopExtUnix::{}_any_thread` function.",
+            method, method
         );
     }
 }
@@ -765,3 +1215,56 @@ fn is_main_thread() -> bool {
 fn is_main_thread() -> bool {
     std::thread::current().name() == Some("main")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timers_expire_in_deadline_order() {
+        let now = Instant::now();
+        let mut timers = Timers::default();
+        timers.set(now + Duration::from_millis(20), TimerId(0));
+        timers.set(now + Duration::from_millis(10), TimerId(1));
+        timers.set(now + Duration::from_millis(30), TimerId(2));
+
+        assert_eq!(timers.timeout(now), Some(Duration::from_millis(10)));
+        assert_eq!(timers.take_expired(now), []);
+
+        let later = now + Duration::from_millis(25);
+        assert_eq!(timers.timeout(later), Some(Duration::from_millis(0)));
+        assert_eq!(timers.take_expired(later), [TimerId(1), TimerId(0)]);
+        assert_eq!(timers.timeout(later), Some(Duration::from_millis(5)));
+    }
+
+    #[test]
+    fn timers_cancel() {
+        let now = Instant::now();
+        let mut timers = Timers::default();
+        timers.set(now + Duration::from_millis(10), TimerId(0));
+        timers.set(now + Duration::from_millis(20), TimerId(1));
+
+        timers.cancel(TimerId(0));
+        assert_eq!(timers.timeout(now), Some(Duration::from_millis(20)));
+
+        timers.cancel(TimerId(1));
+        assert_eq!(timers.timeout(now), None);
+        assert_eq!(timers.take_expired(now + Duration::from_secs(1)), []);
+    }
+
+    #[test]
+    fn timers_with_equal_deadlines() {
+        let now = Instant::now();
+        let mut timers = Timers::default();
+        timers.set(now, TimerId(2));
+        timers.set(now, TimerId(0));
+        timers.set(now, TimerId(1));
+
+        // Timers expiring together are ordered by id.
+        assert_eq!(
+            timers.take_expired(now),
+            [TimerId(0), TimerId(1), TimerId(2)]
+        );
+        assert_eq!(timers.timeout(now), None);
+    }
+}
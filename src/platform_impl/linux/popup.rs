@@ -0,0 +1,116 @@
+use crate::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    platform::unix::{PopupAnchorRect, PopupConstraintAdjustment, PopupGravity},
+};
+
+use super::WindowId;
+
+/// The placement of a popup, set with `WindowBuilderExtUnix::with_popup`.
+#[derive(Debug, Clone)]
+pub struct PopupAttributes {
+    pub parent: WindowId,
+    pub anchor_rect: PopupAnchorRect,
+    pub gravity: PopupGravity,
+    pub constraint_adjustment: PopupConstraintAdjustment,
+    /// The scale factor of the parent when the popup was requested, which `anchor_rect` is in.
+    pub scale_factor: f64,
+}
+
+impl PopupAttributes {
+    /// Places a popup of the given size, given the position of the client area of its parent and
+    /// the area of the monitor it should stay on, like `xdg_positioner` does.
+    pub fn place(
+        &self,
+        parent_position: PhysicalPosition<i32>,
+        size: PhysicalSize<u32>,
+        bounds_position: PhysicalPosition<i32>,
+        bounds_size: PhysicalSize<u32>,
+    ) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
+        let anchor_position = self
+            .anchor_rect
+            .position
+            .to_physical::<i32>(self.scale_factor);
+        let anchor_size = self.anchor_rect.size.to_physical::<u32>(self.scale_factor);
+        let anchor_x = parent_position.x + anchor_position.x;
+        let anchor_y = parent_position.y + anchor_position.y;
+
+        let (x_direction, y_direction) = match self.gravity {
+            PopupGravity::None => (0, 0),
+            PopupGravity::Top => (0, -1),
+            PopupGravity::Bottom => (0, 1),
+            PopupGravity::Left => (-1, 0),
+            PopupGravity::Right => (1, 0),
+            PopupGravity::TopLeft => (-1, -1),
+            PopupGravity::BottomLeft => (-1, 1),
+            PopupGravity::TopRight => (1, -1),
+            PopupGravity::BottomRight => (1, 1),
+        };
+        let adjustment = self.constraint_adjustment;
+
+        let (x, width) = place_on_axis(
+            (anchor_x, anchor_x + anchor_size.width as i32),
+            (
+                bounds_position.x,
+                bounds_position.x + bounds_size.width as i32,
+            ),
+            size.width as i32,
+            x_direction,
+            adjustment.contains(PopupConstraintAdjustment::FLIP_X),
+            adjustment.contains(PopupConstraintAdjustment::SLIDE_X),
+            adjustment.contains(PopupConstraintAdjustment::RESIZE_X),
+        );
+        let (y, height) = place_on_axis(
+            (anchor_y, anchor_y + anchor_size.height as i32),
+            (
+                bounds_position.y,
+                bounds_position.y + bounds_size.height as i32,
+            ),
+            size.height as i32,
+            y_direction,
+            adjustment.contains(PopupConstraintAdjustment::FLIP_Y),
+            adjustment.contains(PopupConstraintAdjustment::SLIDE_Y),
+            adjustment.contains(PopupConstraintAdjustment::RESIZE_Y),
+        );
+
+        (
+            PhysicalPosition::new(x, y),
+            PhysicalSize::new(width as u32, height as u32),
+        )
+    }
+}
+
+/// Places a popup of length `len` on an axis, extending from the `anchor` range towards
+/// `direction` (negative, zero to center it, or positive), then applies the allowed adjustments if
+/// it doesn't fit in the `bounds` range. Returns the start and the length of the popup.
+fn place_on_axis(
+    anchor: (i32, i32),
+    bounds: (i32, i32),
+    len: i32,
+    direction: i32,
+    flip: bool,
+    slide: bool,
+    resize: bool,
+) -> (i32, i32) {
+    let start_towards = |direction: i32| match direction.signum() {
+        -1 => anchor.0 - len,
+        0 => (anchor.0 + anchor.1 - len) / 2,
+        _ => anchor.1,
+    };
+    let fits = |start: i32| start >= bounds.0 && start + len <= bounds.1;
+
+    let mut start = start_towards(direction);
+    if !fits(start) && flip && direction != 0 && fits(start_towards(-direction)) {
+        start = start_towards(-direction);
+    }
+    if !fits(start) && slide {
+        // A popup larger than the bounds keeps its start visible.
+        start = start.min(bounds.1 - len).max(bounds.0);
+    }
+
+    let mut end = start + len;
+    if resize && start.max(bounds.0) < end.min(bounds.1) {
+        start = start.max(bounds.0);
+        end = end.min(bounds.1);
+    }
+    (start, end - start)
+}
@@ -0,0 +1,558 @@
+//! The clipboard and the primary selection, which are set and read through the data devices of
+//! the seats.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::process;
+use std::rc::{Rc, Weak};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use sctk::reexports::client::protocol::wl_data_device::{self, WlDataDevice};
+use sctk::reexports::client::protocol::wl_data_device_manager::WlDataDeviceManager;
+use sctk::reexports::client::protocol::wl_data_offer::{self, WlDataOffer};
+use sctk::reexports::client::protocol::wl_data_source;
+use sctk::reexports::client::protocol::wl_seat::WlSeat;
+use sctk::reexports::client::{Attached, Display};
+use sctk::reexports::protocols::unstable::primary_selection::v1::client::zwp_primary_selection_device_manager_v1::ZwpPrimarySelectionDeviceManagerV1;
+use sctk::reexports::protocols::unstable::primary_selection::v1::client::zwp_primary_selection_device_v1::{self, ZwpPrimarySelectionDeviceV1};
+use sctk::reexports::protocols::unstable::primary_selection::v1::client::zwp_primary_selection_offer_v1::{self, ZwpPrimarySelectionOfferV1};
+use sctk::reexports::protocols::unstable::primary_selection::v1::client::zwp_primary_selection_source_v1;
+
+use crate::clipboard::ClipboardKind;
+use crate::error::{ExternalError, NotSupportedError};
+use crate::platform_impl::OsError;
+
+/// How long the owner of a selection has to send its contents.
+const RECEIVE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Tells the clipboards of the process apart, for their marker MIME types.
+static CLIPBOARD_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// The contents of a selection, by MIME type.
+type Contents = Rc<HashMap<String, Vec<u8>>>;
+
+/// The input on a seat, which the compositor checks before letting a client set its selections.
+#[derive(Debug, Default)]
+pub struct SelectionInput {
+    /// The serial of the latest key or button press, or keyboard focus.
+    pub serial: Cell<u32>,
+
+    /// Whether one of our windows has the keyboard focus.
+    pub keyboard_focus: Cell<bool>,
+}
+
+/// The clipboard and the primary selection, shared by the event loop and the seat manager.
+#[derive(Clone)]
+pub struct Clipboard {
+    inner: Rc<RefCell<ClipboardInner>>,
+}
+
+struct ClipboardInner {
+    /// Display, to send the requests before waiting for the contents.
+    display: Display,
+
+    data_device_manager: Option<Attached<WlDataDeviceManager>>,
+
+    primary_selection_manager: Option<Attached<ZwpPrimarySelectionDeviceManagerV1>>,
+
+    /// A MIME type which only our sources offer, to recognize our own offers.
+    marker_mime_type: String,
+
+    /// The data devices of the seats.
+    seats: Vec<SeatSelection>,
+
+    /// The contents of the clipboard which we offer.
+    clipboard: Option<OwnedSelection>,
+
+    /// The contents of the primary selection which we offer.
+    primary: Option<OwnedSelection>,
+}
+
+/// The contents of a selection which we offer, which are read without going through the
+/// compositor for as long as the selection is ours.
+struct OwnedSelection {
+    contents: Contents,
+
+    /// The seat whose selection was set.
+    seat: WlSeat,
+
+    /// Whether the compositor announced an offer which isn't ours since the selection was set.
+    replaced: bool,
+}
+
+/// The data devices of a seat.
+struct SeatSelection {
+    seat: WlSeat,
+
+    data_device: Option<WlDataDevice>,
+
+    primary_selection_device: Option<ZwpPrimarySelectionDeviceV1>,
+
+    /// The offers of the selections of the seat.
+    offers: Rc<RefCell<SeatOffers>>,
+
+    input: Rc<SelectionInput>,
+}
+
+/// The offers announced to a seat.
+#[derive(Default)]
+struct SeatOffers {
+    /// Offers which were announced, but not set as a selection yet.
+    pending: Vec<Offer>,
+
+    clipboard: Option<Offer>,
+
+    primary: Option<Offer>,
+}
+
+/// An offer of the contents of a selection, with the MIME types it announced.
+struct Offer {
+    proxy: OfferProxy,
+
+    mime_types: Rc<RefCell<Vec<String>>>,
+}
+
+#[derive(PartialEq)]
+enum OfferProxy {
+    Clipboard(WlDataOffer),
+    Primary(ZwpPrimarySelectionOfferV1),
+}
+
+impl Clipboard {
+    pub fn new(
+        display: Display,
+        data_device_manager: Option<Attached<WlDataDeviceManager>>,
+        primary_selection_manager: Option<Attached<ZwpPrimarySelectionDeviceManagerV1>>,
+    ) -> Self {
+        let marker_mime_type = format!(
+            "application/x-winit-selection-{}-{}",
+            process::id(),
+            CLIPBOARD_COUNT.fetch_add(1, Ordering::Relaxed)
+        );
+        let inner = ClipboardInner {
+            display,
+            data_device_manager,
+            primary_selection_manager,
+            marker_mime_type,
+            seats: Vec::new(),
+            clipboard: None,
+            primary: None,
+        };
+        Self {
+            inner: Rc::new(RefCell::new(inner)),
+        }
+    }
+
+    /// Creates the data devices of `seat`, unless it already has them.
+    pub fn add_seat(&self, seat: &Attached<WlSeat>, input: Rc<SelectionInput>) {
+        let mut inner = self.inner.borrow_mut();
+        if inner.seats.iter().any(|selection| selection.seat == **seat) {
+            return;
+        }
+
+        let offers = Rc::new(RefCell::new(SeatOffers::default()));
+        let data_device = inner.data_device_manager.as_ref().map(|manager| {
+            let device = manager.get_data_device(seat);
+            let clipboard = Rc::downgrade(&self.inner);
+            let seat = seat.detach();
+            let offers = offers.clone();
+            device.quick_assign(move |_, event, _| match event {
+                wl_data_device::Event::DataOffer { id } => {
+                    let offer = Offer::new(OfferProxy::Clipboard(id.detach()));
+                    let mime_types = offer.mime_types.clone();
+                    id.quick_assign(move |_, event, _| {
+                        if let wl_data_offer::Event::Offer { mime_type } = event {
+                            mime_types.borrow_mut().push(mime_type);
+                        }
+                    });
+                    offers.borrow_mut().pending.push(offer);
+                }
+                wl_data_device::Event::Selection { id } => {
+                    let mut offers = offers.borrow_mut();
+                    let offer = id.and_then(|id| offers.take(&OfferProxy::Clipboard(id)));
+                    selection_changed(&clipboard, ClipboardKind::Clipboard, &seat, offer.as_ref());
+                    offers.clipboard = offer;
+                }
+                // We don't accept drag and drop, so its offers are dropped right away.
+                wl_data_device::Event::Enter { id: Some(id), .. } => {
+                    offers.borrow_mut().take(&OfferProxy::Clipboard(id));
+                }
+                _ => (),
+            });
+            device.detach()
+        });
+        let primary_selection_device = inner.primary_selection_manager.as_ref().map(|manager| {
+            let device = manager.get_device(seat);
+            let clipboard = Rc::downgrade(&self.inner);
+            let seat = seat.detach();
+            let offers = offers.clone();
+            device.quick_assign(move |_, event, _| match event {
+                zwp_primary_selection_device_v1::Event::DataOffer { offer: id } => {
+                    let offer = Offer::new(OfferProxy::Primary(id.detach()));
+                    let mime_types = offer.mime_types.clone();
+                    id.quick_assign(move |_, event, _| {
+                        if let zwp_primary_selection_offer_v1::Event::Offer { mime_type } = event {
+                            mime_types.borrow_mut().push(mime_type);
+                        }
+                    });
+                    offers.borrow_mut().pending.push(offer);
+                }
+                zwp_primary_selection_device_v1::Event::Selection { id } => {
+                    let mut offers = offers.borrow_mut();
+                    let offer = id.and_then(|id| offers.take(&OfferProxy::Primary(id)));
+                    selection_changed(&clipboard, ClipboardKind::Primary, &seat, offer.as_ref());
+                    offers.primary = offer;
+                }
+                _ => (),
+            });
+            device.detach()
+        });
+
+        inner.seats.push(SeatSelection {
+            seat: seat.detach(),
+            data_device,
+            primary_selection_device,
+            offers,
+            input,
+        });
+    }
+
+    /// Drops the data devices of `seat`.
+    pub fn remove_seat(&self, seat: &WlSeat) {
+        self.inner
+            .borrow_mut()
+            .seats
+            .retain(|selection| selection.seat != *seat);
+    }
+
+    pub fn set_contents(
+        &self,
+        kind: ClipboardKind,
+        contents: HashMap<String, Vec<u8>>,
+    ) -> Result<(), ExternalError> {
+        let mut inner = self.inner.borrow_mut();
+        // The compositor ignores the request of a client without the keyboard focus.
+        let seat = match inner.latest_seat(true) {
+            Some(seat) => seat,
+            None => {
+                return Err(ExternalError::Os(os_error!(OsError::WaylandMisc(
+                    "No window has the keyboard focus to set the selection"
+                ))))
+            }
+        };
+        let serial = seat.input.serial.get();
+        let seat_proxy = seat.seat.clone();
+        let contents = if contents.is_empty() {
+            None
+        } else {
+            Some(Rc::new(contents))
+        };
+        let mut mime_types: Vec<String> = contents
+            .as_ref()
+            .map(|contents| contents.keys().cloned().collect())
+            .unwrap_or_default();
+        mime_types.push(inner.marker_mime_type.clone());
+
+        match kind {
+            ClipboardKind::Clipboard => {
+                let (manager, device) = match (&inner.data_device_manager, &seat.data_device) {
+                    (Some(manager), Some(device)) => (manager, device),
+                    _ => return Err(ExternalError::NotSupported(NotSupportedError::new())),
+                };
+                let source = contents.as_ref().map(|contents| {
+                    let source = manager.create_data_source();
+                    let clipboard = Rc::downgrade(&self.inner);
+                    let owned = contents.clone();
+                    source.quick_assign(move |source, event, _| match event {
+                        wl_data_source::Event::Send { mime_type, fd } => {
+                            send_contents(&owned, &mime_type, fd)
+                        }
+                        wl_data_source::Event::Cancelled => {
+                            source.destroy();
+                            disown(&clipboard, ClipboardKind::Clipboard, &owned);
+                        }
+                        _ => (),
+                    });
+                    for mime_type in mime_types.iter() {
+                        source.offer(mime_type.clone());
+                    }
+                    source.detach()
+                });
+                device.set_selection(source.as_ref(), serial);
+            }
+            ClipboardKind::Primary => {
+                let (manager, device) = match (
+                    &inner.primary_selection_manager,
+                    &seat.primary_selection_device,
+                ) {
+                    (Some(manager), Some(device)) => (manager, device),
+                    _ => return Err(ExternalError::NotSupported(NotSupportedError::new())),
+                };
+                let source = contents.as_ref().map(|contents| {
+                    let source = manager.create_source();
+                    let clipboard = Rc::downgrade(&self.inner);
+                    let owned = contents.clone();
+                    source.quick_assign(move |source, event, _| match event {
+                        zwp_primary_selection_source_v1::Event::Send { mime_type, fd } => {
+                            send_contents(&owned, &mime_type, fd)
+                        }
+                        zwp_primary_selection_source_v1::Event::Cancelled => {
+                            source.destroy();
+                            disown(&clipboard, ClipboardKind::Primary, &owned);
+                        }
+                        _ => (),
+                    });
+                    for mime_type in mime_types.iter() {
+                        source.offer(mime_type.clone());
+                    }
+                    source.detach()
+                });
+                device.set_selection(source.as_ref(), serial);
+            }
+        }
+
+        // Until the compositor announces another offer, reading the selection mustn't wait on
+        // ourselves.
+        *inner.owned_mut(kind) = contents.map(|contents| OwnedSelection {
+            contents,
+            seat: seat_proxy,
+            replaced: false,
+        });
+        let _ = inner.display.flush();
+
+        Ok(())
+    }
+
+    pub fn get_contents(
+        &self,
+        kind: ClipboardKind,
+        mime_type: &str,
+    ) -> Result<Option<Vec<u8>>, ExternalError> {
+        let inner = self.inner.borrow();
+        let owned = match kind {
+            ClipboardKind::Clipboard => &inner.clipboard,
+            ClipboardKind::Primary => &inner.primary,
+        };
+        match owned {
+            Some(owned) if !owned.replaced => return Ok(owned.contents.get(mime_type).cloned()),
+            _ => (),
+        }
+        let seat = match inner.latest_seat(false) {
+            Some(seat) => seat,
+            None => return Ok(None),
+        };
+        let supported = match kind {
+            ClipboardKind::Clipboard => seat.data_device.is_some(),
+            ClipboardKind::Primary => seat.primary_selection_device.is_some(),
+        };
+        if !supported {
+            return Err(ExternalError::NotSupported(NotSupportedError::new()));
+        }
+
+        let (read_fd, write_fd) = {
+            let offers = seat.offers.borrow();
+            let offer = match kind {
+                ClipboardKind::Clipboard => offers.clipboard.as_ref(),
+                ClipboardKind::Primary => offers.primary.as_ref(),
+            };
+            let offer = match offer.filter(|offer| offer.has_mime_type(mime_type)) {
+                Some(offer) => offer,
+                None => return Ok(None),
+            };
+            let (read_fd, write_fd) = pipe().map_err(|_| {
+                ExternalError::Os(os_error!(OsError::WaylandMisc(
+                    "Failed to create a pipe to receive the selection"
+                )))
+            })?;
+            offer.receive(mime_type, write_fd.as_raw_fd());
+            (read_fd, write_fd)
+        };
+
+        // The owner only learns about the request once it's sent, and only closes the pipe once
+        // we don't hold it anymore.
+        let flushed = inner.display.flush();
+        drop(write_fd);
+        flushed.map_err(|_| receive_error())?;
+        read_contents(read_fd)
+            .map(Some)
+            .map_err(|_| receive_error())
+    }
+}
+
+impl ClipboardInner {
+    /// Returns the seat with the latest input, which the compositor is the most likely to accept
+    /// the serial of, among those with the keyboard focus if `focused` is set.
+    fn latest_seat(&self, focused: bool) -> Option<&SeatSelection> {
+        self.seats
+            .iter()
+            .filter(|selection| !focused || selection.input.keyboard_focus.get())
+            .max_by_key(|selection| selection.input.serial.get())
+    }
+
+    fn owned_mut(&mut self, kind: ClipboardKind) -> &mut Option<OwnedSelection> {
+        match kind {
+            ClipboardKind::Clipboard => &mut self.clipboard,
+            ClipboardKind::Primary => &mut self.primary,
+        }
+    }
+}
+
+impl SeatOffers {
+    /// Removes the announced offer `proxy` from the pending ones.
+    fn take(&mut self, proxy: &OfferProxy) -> Option<Offer> {
+        let index = self
+            .pending
+            .iter()
+            .position(|offer| offer.proxy == *proxy)?;
+        Some(self.pending.swap_remove(index))
+    }
+}
+
+impl Offer {
+    fn new(proxy: OfferProxy) -> Self {
+        Self {
+            proxy,
+            mime_types: Default::default(),
+        }
+    }
+
+    fn has_mime_type(&self, mime_type: &str) -> bool {
+        self.mime_types.borrow().iter().any(|t| t == mime_type)
+    }
+
+    fn receive(&self, mime_type: &str, fd: RawFd) {
+        match self.proxy {
+            OfferProxy::Clipboard(ref offer) => offer.receive(mime_type.to_owned(), fd),
+            OfferProxy::Primary(ref offer) => offer.receive(mime_type.to_owned(), fd),
+        }
+    }
+}
+
+impl Drop for Offer {
+    fn drop(&mut self) {
+        match self.proxy {
+            OfferProxy::Clipboard(ref offer) => offer.destroy(),
+            OfferProxy::Primary(ref offer) => offer.destroy(),
+        }
+    }
+}
+
+impl Drop for SeatSelection {
+    fn drop(&mut self) {
+        if let Some(device) = self.data_device.take() {
+            if device.as_ref().version() >= 2 {
+                device.release();
+            }
+        }
+        if let Some(device) = self.primary_selection_device.take() {
+            device.destroy();
+        }
+    }
+}
+
+/// Keeps track of whether the selection of `seat` is still ours, once the compositor announced
+/// its new `offer`. The compositor ignores the selections set with an outdated serial, so this is
+/// what tells whether it accepted ours.
+fn selection_changed(
+    clipboard: &Weak<RefCell<ClipboardInner>>,
+    kind: ClipboardKind,
+    seat: &WlSeat,
+    offer: Option<&Offer>,
+) {
+    let clipboard = match clipboard.upgrade() {
+        Some(clipboard) => clipboard,
+        None => return,
+    };
+    let mut clipboard = clipboard.borrow_mut();
+    let ours = matches!(offer, Some(offer) if offer.has_mime_type(&clipboard.marker_mime_type));
+    if let Some(owned) = clipboard.owned_mut(kind) {
+        if owned.seat == *seat {
+            owned.replaced = !ours;
+        }
+    }
+}
+
+/// Forgets the contents of a selection once another client took it over, unless they were
+/// already replaced.
+fn disown(clipboard: &Weak<RefCell<ClipboardInner>>, kind: ClipboardKind, contents: &Contents) {
+    let clipboard = match clipboard.upgrade() {
+        Some(clipboard) => clipboard,
+        None => return,
+    };
+    let mut clipboard = clipboard.borrow_mut();
+    let owned = clipboard.owned_mut(kind);
+    if matches!(owned, Some(owned) if Rc::ptr_eq(&owned.contents, contents)) {
+        *owned = None;
+    }
+}
+
+/// Writes the contents of `mime_type` to `fd` on another thread, so that a slow reader doesn't
+/// block the event loop.
+fn send_contents(contents: &Contents, mime_type: &str, fd: RawFd) {
+    let mut pipe = unsafe { File::from_raw_fd(fd) };
+    let data = match contents.get(mime_type) {
+        Some(data) => data.clone(),
+        // Closing the pipe lets the reader know there's nothing to read.
+        None => return,
+    };
+    thread::spawn(move || {
+        if let Err(err) = pipe.write_all(&data) {
+            debug!("Failed to send the selection: {}", err);
+        }
+    });
+}
+
+/// Creates a pipe, returning its read and write ends.
+fn pipe() -> io::Result<(File, File)> {
+    let mut fds = [0; 2];
+    if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    unsafe { Ok((File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1]))) }
+}
+
+/// Reads `pipe` until the owner of the selection closes it, or `RECEIVE_TIMEOUT` passes without
+/// anything to read.
+fn read_contents(mut pipe: File) -> io::Result<Vec<u8>> {
+    let mut contents = Vec::new();
+    let mut buf = [0; 4096];
+    loop {
+        let mut fd = libc::pollfd {
+            fd: pipe.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let deadline = Instant::now() + RECEIVE_TIMEOUT;
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match unsafe { libc::poll(&mut fd, 1, timeout.as_millis() as libc::c_int) } {
+                0 => return Err(io::ErrorKind::TimedOut.into()),
+                n if n > 0 => break,
+                _ => {
+                    let err = io::Error::last_os_error();
+                    if err.kind() != io::ErrorKind::Interrupted {
+                        return Err(err);
+                    }
+                }
+            }
+        }
+
+        match pipe.read(&mut buf) {
+            Ok(0) => return Ok(contents),
+            Ok(len) => contents.extend_from_slice(&buf[..len]),
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => (),
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+fn receive_error() -> ExternalError {
+    ExternalError::Os(os_error!(OsError::WaylandMisc(
+        "Failed to receive the selection"
+    )))
+}
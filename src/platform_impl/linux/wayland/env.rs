@@ -1,23 +1,25 @@
 //! SCTK environment setup.
 
 use sctk::reexports::client::protocol::wl_compositor::WlCompositor;
+use sctk::reexports::client::protocol::wl_data_device_manager::WlDataDeviceManager;
 use sctk::reexports::client::protocol::wl_output::WlOutput;
-use sctk::reexports::protocols::unstable::xdg_shell::v6::client::zxdg_shell_v6::ZxdgShellV6;
 use sctk::reexports::client::protocol::wl_seat::WlSeat;
-use sctk::reexports::protocols::unstable::xdg_decoration::v1::client::zxdg_decoration_manager_v1::ZxdgDecorationManagerV1;
-use sctk::reexports::client::protocol::wl_shell::WlShell;
 use sctk::reexports::client::protocol::wl_subcompositor::WlSubcompositor;
-use sctk::reexports::client::{Attached, DispatchData};
+use sctk::reexports::client::protocol::wl_registry::WlRegistry;
+use sctk::reexports::client::{Attached, DispatchData, Interface};
 use sctk::reexports::client::protocol::wl_shm::WlShm;
-use sctk::reexports::protocols::xdg_shell::client::xdg_wm_base::XdgWmBase;
 use sctk::reexports::protocols::unstable::relative_pointer::v1::client::zwp_relative_pointer_manager_v1::ZwpRelativePointerManagerV1;
 use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_pointer_constraints_v1::ZwpPointerConstraintsV1;
 use sctk::reexports::protocols::unstable::text_input::v3::client::zwp_text_input_manager_v3::ZwpTextInputManagerV3;
+use sctk::reexports::protocols::unstable::primary_selection::v1::client::zwp_primary_selection_device_manager_v1::ZwpPrimarySelectionDeviceManagerV1;
 
-use sctk::environment::{Environment, SimpleGlobal};
+use super::protocols::blur::client::org_kde_kwin_blur_manager::OrgKdeKwinBlurManager;
+use super::protocols::xdg_decoration::client::zxdg_decoration_manager_v1::ZxdgDecorationManagerV1;
+use super::protocols::xdg_shell::client::xdg_wm_base::{self, XdgWmBase};
+
+use sctk::environment::{Environment, GlobalHandler, SimpleGlobal};
 use sctk::output::{OutputHandler, OutputHandling, OutputInfo, OutputStatusListener};
 use sctk::seat::{SeatData, SeatHandler, SeatHandling, SeatListener};
-use sctk::shell::{Shell, ShellHandler, ShellHandling};
 use sctk::shm::ShmHandler;
 
 /// Set of extra features that are supported by the compositor.
@@ -43,13 +45,14 @@ sctk::environment!(WinitEnv,
         WlShm => shm,
         WlCompositor => compositor,
         WlSubcompositor => subcompositor,
-        WlShell => shell,
-        XdgWmBase => shell,
-        ZxdgShellV6 => shell,
+        XdgWmBase => wm_base,
         ZxdgDecorationManagerV1 => decoration_manager,
         ZwpRelativePointerManagerV1 => relative_pointer_manager,
         ZwpPointerConstraintsV1 => pointer_constraints,
         ZwpTextInputManagerV3 => text_input_manager,
+        OrgKdeKwinBlurManager => blur_manager,
+        WlDataDeviceManager => data_device_manager,
+        ZwpPrimarySelectionDeviceManagerV1 => primary_selection_manager,
     ],
     multis = [
         WlSeat => seats,
@@ -69,7 +72,7 @@ pub struct WinitEnv {
 
     subcompositor: SimpleGlobal<WlSubcompositor>,
 
-    shell: ShellHandler,
+    wm_base: WmBaseHandler,
 
     relative_pointer_manager: SimpleGlobal<ZwpRelativePointerManagerV1>,
 
@@ -78,6 +81,12 @@ pub struct WinitEnv {
     text_input_manager: SimpleGlobal<ZwpTextInputManagerV3>,
 
     decoration_manager: SimpleGlobal<ZxdgDecorationManagerV1>,
+
+    blur_manager: SimpleGlobal<OrgKdeKwinBlurManager>,
+
+    data_device_manager: SimpleGlobal<WlDataDeviceManager>,
+
+    primary_selection_manager: SimpleGlobal<ZwpPrimarySelectionDeviceManagerV1>,
 }
 
 impl WinitEnv {
@@ -93,9 +102,8 @@ impl WinitEnv {
         let compositor = SimpleGlobal::new();
         let subcompositor = SimpleGlobal::new();
 
-        // Gracefully handle shell picking, since SCTK automatically supports multiple
-        // backends.
-        let shell = ShellHandler::new();
+        // The shell of the windows.
+        let wm_base = WmBaseHandler::new();
 
         // Server side decorations.
         let decoration_manager = SimpleGlobal::new();
@@ -109,24 +117,62 @@ impl WinitEnv {
         // IME handling.
         let text_input_manager = SimpleGlobal::new();
 
+        // Blur behind translucent windows on KDE.
+        let blur_manager = SimpleGlobal::new();
+
+        // Clipboard and primary selection.
+        let data_device_manager = SimpleGlobal::new();
+        let primary_selection_manager = SimpleGlobal::new();
+
         Self {
             seats,
             outputs,
             shm,
             compositor,
             subcompositor,
-            shell,
+            wm_base,
             decoration_manager,
             relative_pointer_manager,
             pointer_constraints,
             text_input_manager,
+            blur_manager,
+            data_device_manager,
+            primary_selection_manager,
         }
     }
 }
 
-impl ShellHandling for WinitEnv {
-    fn get_shell(&self) -> Option<Shell> {
-        self.shell.get_shell()
+/// Handler of `xdg_wm_base`, which answers the pings of the compositor.
+pub struct WmBaseHandler {
+    wm_base: Option<Attached<XdgWmBase>>,
+}
+
+impl WmBaseHandler {
+    pub fn new() -> Self {
+        Self { wm_base: None }
+    }
+}
+
+impl GlobalHandler<XdgWmBase> for WmBaseHandler {
+    fn created(
+        &mut self,
+        registry: Attached<WlRegistry>,
+        id: u32,
+        version: u32,
+        _: DispatchData<'_>,
+    ) {
+        // The compositor may advertise versions whose events we can't parse.
+        let version = version.min(XdgWmBase::VERSION);
+        let wm_base = registry.bind::<XdgWmBase>(version, id);
+        wm_base.quick_assign(|wm_base, event, _| {
+            let xdg_wm_base::Event::Ping { serial } = event;
+            wm_base.pong(serial);
+        });
+        self.wm_base = Some((*wm_base).clone());
+    }
+
+    fn get(&self) -> Option<Attached<XdgWmBase>> {
+        self.wm_base.clone()
     }
 }
 
@@ -1,27 +1,45 @@
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::error::Error;
+use std::io;
+use std::os::unix::io::{AsRawFd, RawFd};
 use std::process;
 use std::rc::Rc;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
+use mio::unix::EventedFd;
+use mio::{Poll, PollOpt, Ready, Registration, SetReadiness, Token};
+
 use sctk::reexports::client::protocol::wl_compositor::WlCompositor;
+use sctk::reexports::client::protocol::wl_data_device_manager::WlDataDeviceManager;
 use sctk::reexports::client::protocol::wl_shm::WlShm;
 use sctk::reexports::client::Display;
+use sctk::reexports::protocols::unstable::primary_selection::v1::client::zwp_primary_selection_device_manager_v1::ZwpPrimarySelectionDeviceManagerV1;
 
 use sctk::reexports::calloop;
+use sctk::reexports::calloop::generic::{Fd, Generic};
 
 use sctk::environment::Environment;
 use sctk::seat::pointer::{ThemeManager, ThemeSpec};
 use sctk::WaylandSource;
 
+use crate::clipboard::ClipboardKind;
+use crate::error::ExternalError;
 use crate::event::{Event, StartCause, WindowEvent};
-use crate::event_loop::{ControlFlow, EventLoopWindowTarget as RootEventLoopWindowTarget};
-use crate::platform_impl::platform::sticky_exit_callback;
-
+use crate::event_loop::{
+    ControlFlow, DeviceEventFilter, EventLoopWindowTarget as RootEventLoopWindowTarget, FdToken,
+};
+use crate::platform::pump_events::PumpStatus;
+use crate::platform_impl::platform::{
+    is_input_event, min_timeout, sticky_exit_callback, ProxyBound, ProxyPermit, Timers,
+};
+
+use super::clipboard::Clipboard;
 use super::env::{WindowingFeatures, WinitEnv};
 use super::output::OutputManager;
-use super::seat::SeatManager;
+use super::portal::SettingsPortal;
+use super::seat::{LatestPress, SeatManager};
 use super::window::shim::{self, WindowUpdate};
 use super::{DeviceId, WindowId};
 
@@ -29,7 +47,7 @@ mod proxy;
 mod sink;
 mod state;
 
-pub use proxy::EventLoopProxy;
+pub use proxy::{EventLoopAwakener, EventLoopProxy};
 pub use state::WinitState;
 
 use sink::EventSink;
@@ -54,7 +72,20 @@ pub struct EventLoopWindowTarget<T> {
     pub wayland_source: Rc<calloop::Source<WaylandSource>>,
 
     /// A proxy to wake up event loop.
-    pub event_loop_awakener: calloop::ping::Ping,
+    pub event_loop_awakener: EventLoopAwakener,
+
+    /// A poll that is readable whenever the event loop has work to do, for callers waiting on
+    /// the event loop from their own reactor.
+    ///
+    /// `calloop` doesn't expose its own poll, so the sources that can wake the event loop are
+    /// registered here as well.
+    pub poll: Poll,
+
+    /// Sources of file descriptors registered with `register_fd`.
+    pub fds: RefCell<HashMap<RawFd, calloop::Source<Generic<Fd>>>>,
+
+    /// Timers set with `set_timer`.
+    pub timers: RefCell<Timers>,
 
     /// The available windowing features.
     pub windowing_features: WindowingFeatures,
@@ -65,6 +96,12 @@ pub struct EventLoopWindowTarget<T> {
     /// multiple similar themes.
     pub theme_manager: ThemeManager,
 
+    /// The clipboard and the primary selection of the seats.
+    pub clipboard: Clipboard,
+
+    /// The latest key or button press on the seats, which popups grab.
+    pub latest_press: Rc<LatestPress>,
+
     _marker: std::marker::PhantomData<T>,
 }
 
@@ -78,8 +115,8 @@ pub struct EventLoop<T: 'static> {
     /// Pending user events.
     pending_user_events: Rc<RefCell<Vec<T>>>,
 
-    /// Sender of user events.
-    user_events_sender: calloop::channel::Sender<T>,
+    /// Sender of user events, along with the permits of bounded proxies.
+    user_events_sender: calloop::channel::Sender<(T, Option<ProxyPermit>)>,
 
     /// Wayland source of events.
     wayland_source: Rc<calloop::Source<WaylandSource>>,
@@ -89,8 +126,36 @@ pub struct EventLoop<T: 'static> {
 
     /// Output manager.
     _seat_manager: SeatManager,
+
+    /// Awakener that the proxies use along with `user_events_sender`.
+    event_loop_awakener: EventLoopAwakener,
+
+    /// Keeps the wakeup registration of `EventLoopWindowTarget::poll` alive.
+    _wakeup_registration: Registration,
+
+    /// Readiness of `EventLoopWindowTarget::poll`, which is set by `EventLoopAwakener`.
+    wakeup_readiness: SetReadiness,
+
+    /// Control flow requested by the user callback.
+    control_flow: ControlFlow,
+
+    /// Whether the `Init` iteration was dispatched and the loop hasn't exited since.
+    loop_running: bool,
 }
 
+/// Token of the Wayland socket in `EventLoopWindowTarget::poll`.
+const WAYLAND_TOKEN: Token = Token(0);
+
+/// Token of the `EventLoopAwakener` readiness in `EventLoopWindowTarget::poll`.
+const WAKEUP_TOKEN: Token = Token(1);
+
+/// Token of the settings portal connection in `EventLoopWindowTarget::poll`.
+const PORTAL_TOKEN: Token = Token(2);
+
+/// The tokens of file descriptors registered with `register_fd` are the file descriptors offset
+/// by this value, so that they never collide with the tokens above.
+const FD_TOKEN_OFFSET: usize = 1 << 16;
+
 impl<T: 'static> EventLoop<T> {
     pub fn new() -> Result<EventLoop<T>, Box<dyn Error>> {
         // Connect to wayland server and setup event queue.
@@ -111,10 +176,41 @@ impl<T: 'static> EventLoop<T> {
         let shm = env.require_global::<WlShm>();
         let theme_manager = ThemeManager::init(ThemeSpec::System, compositor, shm);
 
+        // The selections are set and read through the data devices of the seats.
+        let clipboard = Clipboard::new(
+            display.clone(),
+            env.get_global::<WlDataDeviceManager>(),
+            env.get_global::<ZwpPrimarySelectionDeviceManagerV1>(),
+        );
+
         // Setup theme seat and output managers.
-        let seat_manager = SeatManager::new(&env, event_loop.handle(), theme_manager.clone());
+        let latest_press = Rc::new(LatestPress::default());
+        let seat_manager = SeatManager::new(
+            &env,
+            event_loop.handle(),
+            theme_manager.clone(),
+            clipboard.clone(),
+            latest_press.clone(),
+        );
         let output_manager = OutputManager::new(&env);
 
+        // A poll for callers waiting on the event loop, which is readable when the Wayland socket
+        // is, or when the event loop got woken up.
+        let poll = Poll::new()?;
+        let (wakeup_registration, wakeup_readiness) = Registration::new2();
+        poll.register(
+            &EventedFd(&display.get_connection_fd()),
+            WAYLAND_TOKEN,
+            Ready::readable(),
+            PollOpt::level(),
+        )?;
+        poll.register(
+            &wakeup_registration,
+            WAKEUP_TOKEN,
+            Ready::readable(),
+            PollOpt::level(),
+        )?;
+
         // A source of events that we plug into our event loop.
         let wayland_source = WaylandSource::new(event_queue).quick_insert(event_loop.handle())?;
         let wayland_source = Rc::new(wayland_source);
@@ -128,13 +224,14 @@ impl<T: 'static> EventLoop<T> {
         event_loop
             .handle()
             .insert_source(user_events_channel, move |event, _, _| {
-                if let calloop::channel::Event::Msg(msg) = event {
+                if let calloop::channel::Event::Msg((msg, _permit)) = event {
                     pending_user_events_clone.borrow_mut().push(msg);
                 }
             })?;
 
         // An event's loop awakener to wake up for window events from winit's windows.
-        let (event_loop_awakener, event_loop_awakener_source) = calloop::ping::make_ping()?;
+        let (ping, event_loop_awakener_source) = calloop::ping::make_ping()?;
+        let event_loop_awakener = EventLoopAwakener::new(ping, wakeup_readiness.clone());
 
         // Handler of window requests.
         event_loop.handle().insert_source(
@@ -144,6 +241,43 @@ impl<T: 'static> EventLoop<T> {
             },
         )?;
 
+        // Follow the theme of the desktop, if there is a session bus to ask the settings portal.
+        match SettingsPortal::connect() {
+            Ok(portal) => {
+                poll.register(
+                    &EventedFd(&portal.as_raw_fd()),
+                    PORTAL_TOKEN,
+                    Ready::readable(),
+                    PollOpt::level(),
+                )?;
+
+                let handle = event_loop.handle();
+                let portal_source = Rc::new(RefCell::new(None));
+                let portal_source_clone = portal_source.clone();
+                let source =
+                    Generic::new(portal, calloop::Interest::Readable, calloop::Mode::Level);
+                let source =
+                    event_loop
+                        .handle()
+                        .insert_source(source, move |_, portal, winit_state| {
+                            match portal.dispatch() {
+                                Ok(Some(theme)) => winit_state.set_desktop_theme(theme),
+                                Ok(None) => (),
+                                Err(err) => {
+                                    warn!("Lost the connection to the session bus: {}", err);
+                                    // Closes the connection, which also removes it from `poll`.
+                                    if let Some(source) = portal_source_clone.borrow_mut().take() {
+                                        handle.kill(source);
+                                    }
+                                }
+                            }
+                            Ok(())
+                        })?;
+                *portal_source.borrow_mut() = Some(source);
+            }
+            Err(err) => debug!("Failed to connect to the session bus: {}", err),
+        }
+
         let event_loop_handle = event_loop.handle();
         let window_map = HashMap::new();
         let event_sink = EventSink::new();
@@ -157,13 +291,22 @@ impl<T: 'static> EventLoop<T> {
                 window_map,
                 event_sink,
                 window_updates,
+                ready_fds: VecDeque::new(),
+                device_event_filter: Default::default(),
+                focused_keyboards: 0,
+                desktop_theme: Arc::new(Mutex::new(None)),
             }),
             event_loop_handle,
             output_manager,
-            event_loop_awakener,
+            event_loop_awakener: event_loop_awakener.clone(),
+            poll,
+            fds: Default::default(),
+            timers: Default::default(),
             wayland_source: wayland_source.clone(),
             windowing_features,
             theme_manager,
+            clipboard,
+            latest_press,
             _marker: std::marker::PhantomData,
         };
 
@@ -175,6 +318,11 @@ impl<T: 'static> EventLoop<T> {
             wayland_source,
             _seat_manager: seat_manager,
             user_events_sender,
+            event_loop_awakener,
+            _wakeup_registration: wakeup_registration,
+            wakeup_readiness,
+            control_flow: ControlFlow::default(),
+            loop_running: false,
             window_target: RootEventLoopWindowTarget {
                 p: crate::platform_impl::EventLoopWindowTarget::Wayland(event_loop_window_target),
                 _marker: std::marker::PhantomData,
@@ -188,317 +336,446 @@ impl<T: 'static> EventLoop<T> {
     where
         F: FnMut(Event<'_, T>, &RootEventLoopWindowTarget<T>, &mut ControlFlow) + 'static,
     {
-        self.run_return(callback);
-        process::exit(0)
+        let exit_code = self.run_return(callback);
+        process::exit(exit_code)
     }
 
-    pub fn run_return<F>(&mut self, mut callback: F)
+    pub fn run_return<F>(&mut self, mut callback: F) -> i32
     where
         F: FnMut(Event<'_, T>, &RootEventLoopWindowTarget<T>, &mut ControlFlow),
     {
-        // Send pending events to the server.
-        let _ = self.display.flush();
+        loop {
+            if let PumpStatus::Exit(code) = self.pump_events(None, &mut callback) {
+                return code;
+            }
+        }
+    }
 
-        let mut control_flow = ControlFlow::default();
+    pub fn pump_events<F>(&mut self, timeout: Option<Duration>, mut callback: F) -> PumpStatus
+    where
+        F: FnMut(Event<'_, T>, &RootEventLoopWindowTarget<T>, &mut ControlFlow),
+    {
+        if !self.loop_running {
+            self.loop_running = true;
 
-        let pending_user_events = self.pending_user_events.clone();
+            // Changes to the control flow don't persist between runs.
+            self.control_flow = ControlFlow::default();
 
-        callback(
-            Event::NewEvents(StartCause::Init),
-            &self.window_target,
-            &mut control_flow,
-        );
+            // Send pending events to the server.
+            let _ = self.display.flush();
 
-        let mut window_updates: Vec<(WindowId, WindowUpdate)> = Vec::new();
-        let mut event_sink_back_buffer = Vec::new();
+            self.single_iteration(&mut callback, StartCause::Init);
+        }
 
-        // NOTE We break on errors from dispatches, since if we've got protocol error
-        // libwayland-client/wayland-rs will inform us anyway, but crashing downstream is not
-        // really an option. Instead we inform that the event loop got destroyed. We may
-        // communicate an error that something was terminated, but winit doesn't provide us
-        // with an API to do that via some event.
-        loop {
-            // Handle pending user events. We don't need back buffer, since we can't dispatch
-            // user events indirectly via callback to the user.
-            for user_event in pending_user_events.borrow_mut().drain(..) {
-                sticky_exit_callback(
-                    Event::UserEvent(user_event),
-                    &self.window_target,
-                    &mut control_flow,
-                    &mut callback,
-                );
-            }
+        // The `Init` iteration may already have requested an exit.
+        if !matches!(self.control_flow, ControlFlow::ExitWithCode(_)) {
+            self.poll_events_with_timeout(timeout, &mut callback);
+        }
 
-            // Process 'new' pending updates.
-            self.with_state(|state| {
-                window_updates.clear();
-                window_updates.extend(
-                    state
-                        .window_updates
-                        .iter_mut()
-                        .map(|(wid, window_update)| (*wid, window_update.take())),
-                );
-            });
+        if let ControlFlow::ExitWithCode(code) = self.control_flow {
+            self.loop_running = false;
+
+            sticky_exit_callback(
+                Event::LoopDestroyed,
+                &self.window_target,
+                &mut self.control_flow,
+                &mut callback,
+            );
 
-            for (window_id, window_update) in window_updates.iter_mut() {
-                if let Some(scale_factor) = window_update.scale_factor.map(|f| f as f64) {
-                    let mut physical_size = self.with_state(|state| {
-                        let window_handle = state.window_map.get(&window_id).unwrap();
-                        let mut size = window_handle.size.lock().unwrap();
+            PumpStatus::Exit(code)
+        } else {
+            PumpStatus::Continue
+        }
+    }
 
-                        // Update the new logical size if it was changed.
-                        let window_size = window_update.size.unwrap_or(*size);
-                        *size = window_size;
+    /// Waits for new events according to the control flow, but no longer than `timeout`, and
+    /// dispatches a single iteration of the event loop.
+    ///
+    /// NOTE We exit on errors from dispatches, since if we've got protocol error
+    /// libwayland-client/wayland-rs will inform us anyway, but crashing downstream is not
+    /// really an option. Instead we inform that the event loop got destroyed. We may
+    /// communicate an error that something was terminated, but winit doesn't provide us
+    /// with an API to do that via some event.
+    fn poll_events_with_timeout<F>(&mut self, timeout: Option<Duration>, callback: &mut F)
+    where
+        F: FnMut(Event<'_, T>, &RootEventLoopWindowTarget<T>, &mut ControlFlow),
+    {
+        // During the run of the user callback, some other code monitoring and reading the
+        // Wayland socket may have been run (mesa for example does this with vsync), if that
+        // is the case, some events may have been enqueued in our event queue.
+        //
+        // If some messages are there, the event loop needs to behave as if it was instantly
+        // woken up by messages arriving from the Wayland socket, to avoid delaying the
+        // dispatch of these events until we're woken up again.
+        // We're about to handle everything that woke up the event loop.
+        let _ = self.wakeup_readiness.set_readiness(Ready::empty());
+
+        let instant_wakeup = {
+            let handle = self.event_loop.handle();
+            let source = self.wayland_source.clone();
+            let dispatched = handle.with_source(&source, |wayland_source| {
+                let queue = wayland_source.queue();
+                self.with_state(|state| queue.dispatch_pending(state, |_, _, _| unimplemented!()))
+            });
 
-                        window_size.to_physical(scale_factor)
-                    });
+            if let Ok(dispatched) = dispatched {
+                // Don't block while there are ready file descriptors left to report.
+                dispatched > 0 || self.with_state(|state| !state.ready_fds.is_empty())
+            } else {
+                self.control_flow = ControlFlow::Exit;
+                return;
+            }
+        };
 
-                    sticky_exit_callback(
-                        Event::WindowEvent {
-                            window_id: crate::window::WindowId(
-                                crate::platform_impl::WindowId::Wayland(*window_id),
-                            ),
-                            event: WindowEvent::ScaleFactorChanged {
-                                scale_factor,
-                                new_inner_size: &mut physical_size,
-                            },
-                        },
-                        &self.window_target,
-                        &mut control_flow,
-                        &mut callback,
-                    );
+        // Wake up in time for the earliest timer, to notice windows starved of frames, and to
+        // animate the cursors.
+        let now = Instant::now();
+        let timer_timeout = self.with_timers(|timers| timers.timeout(now));
+        let occlusion_timeout = self
+            .with_state(|state| state.occlusion_deadline())
+            .map(|deadline| deadline.saturating_duration_since(now));
+        let cursor_animation_timeout = self
+            .with_state(|state| state.cursor_animation_deadline())
+            .map(|deadline| deadline.saturating_duration_since(now));
+        let timeout = min_timeout(
+            min_timeout(timer_timeout, occlusion_timeout),
+            min_timeout(cursor_animation_timeout, timeout),
+        );
 
-                    // We don't update size on a window handle since we'll do that later
-                    // when handling size update.
-                    let new_logical_size = physical_size.to_logical(scale_factor);
-                    window_update.size = Some(new_logical_size);
+        let mut cause = match self.control_flow {
+            ControlFlow::ExitWithCode(_) => return,
+            ControlFlow::Poll => {
+                // Non-blocking dispatch.
+                let timeout = Duration::from_millis(0);
+                if self.loop_dispatch(Some(timeout)).is_err() {
+                    self.control_flow = ControlFlow::Exit;
+                    return;
                 }
 
-                if let Some(size) = window_update.size.take() {
-                    let physical_size = self.with_state(|state| {
-                        let window_handle = state.window_map.get_mut(&window_id).unwrap();
-                        let mut window_size = window_handle.size.lock().unwrap();
-
-                        // Always issue resize event on scale factor change.
-                        let physical_size =
-                            if window_update.scale_factor.is_none() && *window_size == size {
-                                // The size hasn't changed, don't inform downstream about that.
-                                None
-                            } else {
-                                *window_size = size;
-                                let scale_factor =
-                                    sctk::get_surface_scale_factor(&window_handle.window.surface());
-                                let physical_size = size.to_physical(scale_factor as f64);
-                                Some(physical_size)
-                            };
-
-                        // We still perform all of those resize related logic even if the size
-                        // hasn't changed, since GNOME relies on `set_geometry` calls after
-                        // configures.
-                        window_handle.window.resize(size.width, size.height);
-                        window_handle.window.refresh();
-
-                        // Mark that refresh isn't required, since we've done it right now.
-                        window_update.refresh_frame = false;
-
-                        physical_size
-                    });
-
-                    if let Some(physical_size) = physical_size {
-                        sticky_exit_callback(
-                            Event::WindowEvent {
-                                window_id: crate::window::WindowId(
-                                    crate::platform_impl::WindowId::Wayland(*window_id),
-                                ),
-                                event: WindowEvent::Resized(physical_size),
-                            },
-                            &self.window_target,
-                            &mut control_flow,
-                            &mut callback,
-                        );
-                    }
+                StartCause::Poll
+            }
+            ControlFlow::Wait => {
+                let wait_timeout = if instant_wakeup {
+                    Some(Duration::from_millis(0))
+                } else {
+                    None
+                };
+
+                if self
+                    .loop_dispatch(min_timeout(wait_timeout, timeout))
+                    .is_err()
+                {
+                    self.control_flow = ControlFlow::Exit;
+                    return;
                 }
 
-                if window_update.close_window {
-                    sticky_exit_callback(
-                        Event::WindowEvent {
-                            window_id: crate::window::WindowId(
-                                crate::platform_impl::WindowId::Wayland(*window_id),
-                            ),
-                            event: WindowEvent::CloseRequested,
-                        },
-                        &self.window_target,
-                        &mut control_flow,
-                        &mut callback,
-                    );
+                StartCause::WaitCancelled {
+                    start: Instant::now(),
+                    requested_resume: None,
                 }
             }
+            ControlFlow::WaitUntil(deadline) => {
+                let start = Instant::now();
 
-            // The purpose of the back buffer and that swap is to not hold borrow_mut when
-            // we're doing callback to the user, since we can double borrow if the user decides
-            // to create a window in one of those callbacks.
-            self.with_state(|state| {
-                std::mem::swap(
-                    &mut event_sink_back_buffer,
-                    &mut state.event_sink.window_events,
-                )
-            });
+                // Compute the amount of time we'll block for.
+                let duration = if deadline > start && !instant_wakeup {
+                    deadline - start
+                } else {
+                    Duration::from_millis(0)
+                };
+
+                if self
+                    .loop_dispatch(min_timeout(Some(duration), timeout))
+                    .is_err()
+                {
+                    self.control_flow = ControlFlow::Exit;
+                    return;
+                }
 
-            // Handle pending window events.
-            for event in event_sink_back_buffer.drain(..) {
-                let event = event.map_nonuser_event().unwrap();
-                sticky_exit_callback(event, &self.window_target, &mut control_flow, &mut callback);
-            }
+                let now = Instant::now();
 
-            // Send events cleared.
-            sticky_exit_callback(
-                Event::MainEventsCleared,
-                &self.window_target,
-                &mut control_flow,
-                &mut callback,
-            );
-
-            // Handle RedrawRequested events.
-            for (window_id, window_update) in window_updates.iter() {
-                // Handle refresh of the frame.
-                if window_update.refresh_frame {
-                    self.with_state(|state| {
-                        let window_handle = state.window_map.get_mut(&window_id).unwrap();
-                        window_handle.window.refresh();
-                        if !window_update.redraw_requested {
-                            window_handle.window.surface().commit();
-                        }
-                    });
+                if now < deadline {
+                    StartCause::WaitCancelled {
+                        start,
+                        requested_resume: Some(deadline),
+                    }
+                } else {
+                    StartCause::ResumeTimeReached {
+                        start,
+                        requested_resume: deadline,
+                    }
                 }
+            }
+        };
 
-                // Handle redraw request.
-                if window_update.redraw_requested {
-                    sticky_exit_callback(
-                        Event::RedrawRequested(crate::window::WindowId(
-                            crate::platform_impl::WindowId::Wayland(*window_id),
-                        )),
-                        &self.window_target,
-                        &mut control_flow,
-                        &mut callback,
-                    );
+        self.with_state(|state| state.check_frame_starvation(Instant::now()));
+        self.with_state(|state| state.advance_cursor_animations(Instant::now()));
+
+        // A reached `WaitUntil` deadline takes precedence, expired timers are then reported on
+        // the next iteration.
+        match cause {
+            StartCause::Poll | StartCause::WaitCancelled { .. } => {
+                let expired = self.with_timers(|timers| timers.take_expired(Instant::now()));
+                if !expired.is_empty() {
+                    cause = StartCause::TimerFired(expired);
+                } else if let Some(token) = self.with_state(|state| state.ready_fds.pop_front()) {
+                    cause = StartCause::FdReady(token);
                 }
             }
+            _ => (),
+        }
+
+        self.single_iteration(callback, cause);
+    }
+
+    /// Dispatches `NewEvents(cause)` followed by every pending event, up to and including
+    /// `RedrawEventsCleared`, and flushes the requests to the server afterwards.
+    fn single_iteration<F>(&mut self, callback: &mut F, cause: StartCause)
+    where
+        F: FnMut(Event<'_, T>, &RootEventLoopWindowTarget<T>, &mut ControlFlow),
+    {
+        let mut control_flow = self.control_flow;
 
-            // Send RedrawEventCleared.
+        sticky_exit_callback(
+            Event::NewEvents(cause),
+            &self.window_target,
+            &mut control_flow,
+            callback,
+        );
+
+        let pending_user_events = self.pending_user_events.clone();
+        let mut window_updates: Vec<(WindowId, WindowUpdate)> = Vec::new();
+        let mut event_sink_back_buffer = Vec::new();
+
+        // Handle pending user events. We don't need back buffer, since we can't dispatch
+        // user events indirectly via callback to the user.
+        for user_event in pending_user_events.borrow_mut().drain(..) {
             sticky_exit_callback(
-                Event::RedrawEventsCleared,
+                Event::UserEvent(user_event),
                 &self.window_target,
                 &mut control_flow,
-                &mut callback,
+                callback,
             );
+        }
 
-            // Send pending events to the server.
-            let _ = self.display.flush();
+        // Process 'new' pending updates.
+        self.with_state(|state| {
+            window_updates.clear();
+            window_updates.extend(
+                state
+                    .window_updates
+                    .iter_mut()
+                    .map(|(wid, window_update)| (*wid, window_update.take())),
+            );
+        });
+
+        for (window_id, window_update) in window_updates.iter_mut() {
+            if let Some(scale_factor) = window_update.scale_factor.map(|f| f as f64) {
+                let mut physical_size = self.with_state(|state| {
+                    let window_handle = state.window_map.get(&window_id).unwrap();
+                    // The cursor is loaded at the size for the new scale factor.
+                    window_handle.reload_cursor();
+
+                    let mut size = window_handle.size.lock().unwrap();
 
-            // During the run of the user callback, some other code monitoring and reading the
-            // Wayland socket may have been run (mesa for example does this with vsync), if that
-            // is the case, some events may have been enqueued in our event queue.
-            //
-            // If some messages are there, the event loop needs to behave as if it was instantly
-            // woken up by messages arriving from the Wayland socket, to avoid delaying the
-            // dispatch of these events until we're woken up again.
-            let instant_wakeup = {
-                let handle = self.event_loop.handle();
-                let source = self.wayland_source.clone();
-                let dispatched = handle.with_source(&source, |wayland_source| {
-                    let queue = wayland_source.queue();
-                    self.with_state(|state| {
-                        queue.dispatch_pending(state, |_, _, _| unimplemented!())
-                    })
+                    // Update the new logical size if it was changed.
+                    let window_size = window_update.size.unwrap_or(*size);
+                    *size = window_size;
+
+                    window_size.to_physical(scale_factor)
                 });
 
-                if let Ok(dispatched) = dispatched {
-                    dispatched > 0
-                } else {
-                    break;
-                }
-            };
+                sticky_exit_callback(
+                    Event::WindowEvent {
+                        window_id: crate::window::WindowId(
+                            crate::platform_impl::WindowId::Wayland(*window_id),
+                        ),
+                        event: WindowEvent::ScaleFactorChanged {
+                            scale_factor,
+                            new_inner_size: &mut physical_size,
+                        },
+                    },
+                    &self.window_target,
+                    &mut control_flow,
+                    callback,
+                );
 
-            match control_flow {
-                ControlFlow::Exit => break,
-                ControlFlow::Poll => {
-                    // Non-blocking dispatch.
-                    let timeout = Duration::from_millis(0);
-                    if self.loop_dispatch(Some(timeout)).is_err() {
-                        break;
-                    }
+                // We don't update size on a window handle since we'll do that later
+                // when handling size update.
+                let new_logical_size = physical_size.to_logical(scale_factor);
+                window_update.size = Some(new_logical_size);
+            }
 
-                    callback(
-                        Event::NewEvents(StartCause::Poll),
+            if let Some(size) = window_update.size.take() {
+                let physical_size = self.with_state(|state| {
+                    let window_handle = state.window_map.get_mut(&window_id).unwrap();
+                    let mut window_size = window_handle.size.lock().unwrap();
+
+                    // Always issue resize event on scale factor change.
+                    let physical_size =
+                        if window_update.scale_factor.is_none() && *window_size == size {
+                            // The size hasn't changed, don't inform downstream about that.
+                            None
+                        } else {
+                            *window_size = size;
+                            let scale_factor =
+                                sctk::get_surface_scale_factor(&window_handle.window.surface());
+                            let physical_size = size.to_physical(scale_factor as f64);
+                            Some(physical_size)
+                        };
+
+                    // We still perform all of those resize related logic even if the size
+                    // hasn't changed, since GNOME relies on `set_geometry` calls after
+                    // configures.
+                    window_handle.window.resize(size.width, size.height);
+                    window_handle.window.refresh();
+
+                    // Mark that refresh isn't required, since we've done it right now.
+                    window_update.refresh_frame = false;
+
+                    physical_size
+                });
+
+                if let Some(physical_size) = physical_size {
+                    sticky_exit_callback(
+                        Event::WindowEvent {
+                            window_id: crate::window::WindowId(
+                                crate::platform_impl::WindowId::Wayland(*window_id),
+                            ),
+                            event: WindowEvent::Resized(physical_size),
+                        },
                         &self.window_target,
                         &mut control_flow,
+                        callback,
                     );
                 }
-                ControlFlow::Wait => {
-                    let timeout = if instant_wakeup {
-                        Some(Duration::from_millis(0))
-                    } else {
-                        None
-                    };
-
-                    if self.loop_dispatch(timeout).is_err() {
-                        break;
-                    }
+            }
 
-                    callback(
-                        Event::NewEvents(StartCause::WaitCancelled {
-                            start: Instant::now(),
-                            requested_resume: None,
-                        }),
-                        &self.window_target,
-                        &mut control_flow,
-                    );
+            if window_update.close_window {
+                sticky_exit_callback(
+                    Event::WindowEvent {
+                        window_id: crate::window::WindowId(
+                            crate::platform_impl::WindowId::Wayland(*window_id),
+                        ),
+                        event: WindowEvent::CloseRequested,
+                    },
+                    &self.window_target,
+                    &mut control_flow,
+                    callback,
+                );
+            }
+        }
+
+        // The purpose of the back buffer and that swap is to not hold borrow_mut when
+        // we're doing callback to the user, since we can double borrow if the user decides
+        // to create a window in one of those callbacks.
+        self.with_state(|state| {
+            std::mem::swap(
+                &mut event_sink_back_buffer,
+                &mut state.event_sink.window_events,
+            )
+        });
+
+        // Handle pending window events.
+        for event in event_sink_back_buffer.drain(..) {
+            let event = event.map_nonuser_event().unwrap();
+
+            // Input to a window is swallowed while it has a modal child.
+            if let Event::WindowEvent {
+                window_id:
+                    crate::window::WindowId(crate::platform_impl::WindowId::Wayland(window_id)),
+                ref event,
+            } = event
+            {
+                if is_input_event(event)
+                    && self.with_state(|state| state.has_modal_child(window_id))
+                {
+                    continue;
                 }
-                ControlFlow::WaitUntil(deadline) => {
-                    let start = Instant::now();
-
-                    // Compute the amount of time we'll block for.
-                    let duration = if deadline > start && !instant_wakeup {
-                        deadline - start
-                    } else {
-                        Duration::from_millis(0)
-                    };
-
-                    if self.loop_dispatch(Some(duration)).is_err() {
-                        break;
-                    }
+            }
+
+            // The application presents its frame while handling `FrameReady`.
+            let presented_window = match event {
+                Event::WindowEvent {
+                    window_id:
+                        crate::window::WindowId(crate::platform_impl::WindowId::Wayland(window_id)),
+                    event: WindowEvent::FrameReady { .. },
+                } => Some(window_id),
+                _ => None,
+            };
 
-                    let now = Instant::now();
-
-                    if now < deadline {
-                        callback(
-                            Event::NewEvents(StartCause::WaitCancelled {
-                                start,
-                                requested_resume: Some(deadline),
-                            }),
-                            &self.window_target,
-                            &mut control_flow,
-                        )
-                    } else {
-                        callback(
-                            Event::NewEvents(StartCause::ResumeTimeReached {
-                                start,
-                                requested_resume: deadline,
-                            }),
-                            &self.window_target,
-                            &mut control_flow,
-                        )
+            sticky_exit_callback(event, &self.window_target, &mut control_flow, callback);
+
+            if let Some(window_id) = presented_window {
+                self.frame_committed(window_id);
+            }
+        }
+
+        // Send events cleared.
+        sticky_exit_callback(
+            Event::MainEventsCleared,
+            &self.window_target,
+            &mut control_flow,
+            callback,
+        );
+
+        // Handle RedrawRequested events.
+        for (window_id, window_update) in window_updates.iter() {
+            // Handle refresh of the frame.
+            if window_update.refresh_frame {
+                self.with_state(|state| {
+                    let window_handle = state.window_map.get_mut(&window_id).unwrap();
+                    window_handle.window.refresh();
+                    if !window_update.redraw_requested {
+                        window_handle.window.surface().commit();
+                        window_handle.frame_committed(Instant::now());
                     }
-                }
+                });
+            }
+
+            // Handle redraw request.
+            if window_update.redraw_requested {
+                sticky_exit_callback(
+                    Event::RedrawRequested(crate::window::WindowId(
+                        crate::platform_impl::WindowId::Wayland(*window_id),
+                    )),
+                    &self.window_target,
+                    &mut control_flow,
+                    callback,
+                );
+                self.frame_committed(*window_id);
             }
         }
 
-        callback(Event::LoopDestroyed, &self.window_target, &mut control_flow);
+        // Send RedrawEventCleared.
+        sticky_exit_callback(
+            Event::RedrawEventsCleared,
+            &self.window_target,
+            &mut control_flow,
+            callback,
+        );
+
+        // Send pending events to the server.
+        let _ = self.display.flush();
+
+        self.control_flow = control_flow;
     }
 
     #[inline]
     pub fn create_proxy(&self) -> EventLoopProxy<T> {
-        EventLoopProxy::new(self.user_events_sender.clone())
+        self.create_proxy_with_bound(None)
+    }
+
+    pub fn create_proxy_bounded(&self, capacity: usize) -> EventLoopProxy<T> {
+        self.create_proxy_with_bound(Some(ProxyBound::new(capacity)))
+    }
+
+    fn create_proxy_with_bound(&self, bound: Option<ProxyBound>) -> EventLoopProxy<T> {
+        EventLoopProxy::new(
+            self.user_events_sender.clone(),
+            bound,
+            self.event_loop_awakener.clone(),
+        )
     }
 
     #[inline]
@@ -506,18 +783,38 @@ impl<T: 'static> EventLoop<T> {
         &self.window_target
     }
 
+    /// Restarts the deadline of the frame callback of a window, after the application had the
+    /// chance to present a frame.
+    fn frame_committed(&mut self, window_id: WindowId) {
+        self.with_state(|state| {
+            if let Some(window_handle) = state.window_map.get(&window_id) {
+                window_handle.frame_committed(Instant::now());
+            }
+        });
+    }
+
     fn with_state<U, F: FnOnce(&mut WinitState) -> U>(&mut self, f: F) -> U {
         let state = match &mut self.window_target.p {
             crate::platform_impl::EventLoopWindowTarget::Wayland(ref mut window_target) => {
                 window_target.state.get_mut()
             }
-            #[cfg(feature = "x11")]
             _ => unreachable!(),
         };
 
         f(state)
     }
 
+    fn with_timers<U, F: FnOnce(&mut Timers) -> U>(&mut self, f: F) -> U {
+        let timers = match &mut self.window_target.p {
+            crate::platform_impl::EventLoopWindowTarget::Wayland(ref mut window_target) => {
+                window_target.timers.get_mut()
+            }
+            _ => unreachable!(),
+        };
+
+        f(timers)
+    }
+
     fn loop_dispatch<D: Into<Option<std::time::Duration>>>(
         &mut self,
         timeout: D,
@@ -526,10 +823,88 @@ impl<T: 'static> EventLoop<T> {
             crate::platform_impl::EventLoopWindowTarget::Wayland(ref mut window_target) => {
                 window_target.state.get_mut()
             }
-            #[cfg(feature = "x11")]
             _ => unreachable!(),
         };
 
         self.event_loop.dispatch(timeout, &mut state)
     }
 }
+
+impl<T: 'static> AsRawFd for EventLoop<T> {
+    fn as_raw_fd(&self) -> RawFd {
+        match &self.window_target.p {
+            crate::platform_impl::EventLoopWindowTarget::Wayland(window_target) => {
+                window_target.poll.as_raw_fd()
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<T> EventLoopWindowTarget<T> {
+    pub fn register_fd(&self, fd: RawFd, token: FdToken) -> io::Result<()> {
+        // A negative file descriptor doesn't map to a token.
+        if fd < 0 {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput));
+        }
+
+        let mut fds = self.fds.borrow_mut();
+        if fds.contains_key(&fd) {
+            return Err(io::Error::from(io::ErrorKind::AlreadyExists));
+        }
+
+        self.poll.register(
+            &EventedFd(&fd),
+            Token(FD_TOKEN_OFFSET + fd as usize),
+            Ready::readable(),
+            PollOpt::level(),
+        )?;
+
+        let source = Generic::from_fd(fd, calloop::Interest::Readable, calloop::Mode::Level);
+        let source = self
+            .event_loop_handle
+            .insert_source(source, move |_, _, winit_state| {
+                if !winit_state.ready_fds.contains(&token) {
+                    winit_state.ready_fds.push_back(token);
+                }
+                Ok(())
+            })
+            .map_err(|err| {
+                let _ = self.poll.deregister(&EventedFd(&fd));
+                err.error
+            })?;
+
+        fds.insert(fd, source);
+        Ok(())
+    }
+
+    pub fn unregister_fd(&self, fd: RawFd) -> io::Result<()> {
+        let source = match self.fds.borrow_mut().remove(&fd) {
+            Some(source) => source,
+            None => return Err(io::Error::from(io::ErrorKind::NotFound)),
+        };
+
+        self.event_loop_handle.remove(source);
+        self.poll.deregister(&EventedFd(&fd))
+    }
+
+    pub fn set_device_event_filter(&self, filter: DeviceEventFilter) {
+        self.state.borrow_mut().device_event_filter = filter;
+    }
+
+    pub fn set_clipboard_contents(
+        &self,
+        kind: ClipboardKind,
+        contents: HashMap<String, Vec<u8>>,
+    ) -> Result<(), ExternalError> {
+        self.clipboard.set_contents(kind, contents)
+    }
+
+    pub fn clipboard_contents(
+        &self,
+        kind: ClipboardKind,
+        mime_type: &str,
+    ) -> Result<Option<Vec<u8>>, ExternalError> {
+        self.clipboard.get_contents(kind, mime_type)
+    }
+}
@@ -2,31 +2,93 @@
 
 use std::sync::mpsc::SendError;
 
+use mio::{Ready, SetReadiness};
+
 use sctk::reexports::calloop::channel::Sender;
+use sctk::reexports::calloop::ping::Ping;
 
-use crate::event_loop::EventLoopClosed;
+use crate::event_loop::{EventLoopClosed, TrySendError};
+use crate::platform_impl::platform::{ProxyBound, ProxyPermit};
 
 /// A handle that can be sent across the threads and used to wake up the `EventLoop`.
 pub struct EventLoopProxy<T: 'static> {
-    user_events_sender: Sender<T>,
+    user_events_sender: Sender<(T, Option<ProxyPermit>)>,
+    bound: Option<ProxyBound>,
+    event_loop_awakener: EventLoopAwakener,
 }
 
 impl<T: 'static> Clone for EventLoopProxy<T> {
     fn clone(&self) -> Self {
         EventLoopProxy {
             user_events_sender: self.user_events_sender.clone(),
+            bound: self.bound.clone(),
+            event_loop_awakener: self.event_loop_awakener.clone(),
         }
     }
 }
 
 impl<T: 'static> EventLoopProxy<T> {
-    pub fn new(user_events_sender: Sender<T>) -> Self {
-        Self { user_events_sender }
+    pub fn new(
+        user_events_sender: Sender<(T, Option<ProxyPermit>)>,
+        bound: Option<ProxyBound>,
+        event_loop_awakener: EventLoopAwakener,
+    ) -> Self {
+        Self {
+            user_events_sender,
+            bound,
+            event_loop_awakener,
+        }
     }
 
     pub fn send_event(&self, event: T) -> Result<(), EventLoopClosed<T>> {
+        let permit = self.bound.as_ref().map(ProxyBound::acquire);
+        self.user_events_sender
+            .send((event, permit))
+            .map_err(|SendError((error, _))| EventLoopClosed(error))?;
+        self.event_loop_awakener.mark_ready();
+        Ok(())
+    }
+
+    pub fn try_send_event(&self, event: T) -> Result<(), TrySendError<T>> {
+        let permit = match self.bound {
+            Some(ref bound) => match bound.try_acquire() {
+                Some(permit) => Some(permit),
+                None => return Err(TrySendError::Full(event)),
+            },
+            None => None,
+        };
         self.user_events_sender
-            .send(event)
-            .map_err(|SendError(error)| EventLoopClosed(error))
+            .send((event, permit))
+            .map_err(|SendError((error, _))| TrySendError::Closed(error))?;
+        self.event_loop_awakener.mark_ready();
+        Ok(())
+    }
+
+    pub fn wake_up(&self) {
+        self.event_loop_awakener.ping();
+    }
+}
+
+/// Wakes up the event loop, and marks it as ready for callers waiting on its file descriptor.
+#[derive(Clone)]
+pub struct EventLoopAwakener {
+    ping: Ping,
+    readiness: SetReadiness,
+}
+
+impl EventLoopAwakener {
+    pub fn new(ping: Ping, readiness: SetReadiness) -> Self {
+        Self { ping, readiness }
+    }
+
+    /// Wake up the event loop to handle the window requests.
+    pub fn ping(&self) {
+        self.mark_ready();
+        self.ping.ping();
+    }
+
+    /// Mark the event loop's file descriptor as readable.
+    fn mark_ready(&self) {
+        let _ = self.readiness.set_readiness(Ready::readable());
     }
 }
@@ -1,10 +1,15 @@
 //! A state that we pass around in a dispatch.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 use super::EventSink;
+use crate::event::WindowEvent;
+use crate::event_loop::{DeviceEventFilter, FdToken};
 use crate::platform_impl::wayland::window::shim::{WindowHandle, WindowUpdate};
 use crate::platform_impl::wayland::WindowId;
+use crate::window::Theme;
 
 /// Wrapper to carry winit's state.
 pub struct WinitState {
@@ -22,4 +27,91 @@ pub struct WinitState {
     /// and requests from winit's windows are being forwarded to them either via
     /// `WindowUpdate` or buffer on the associated with it `WindowHandle`.
     pub window_map: HashMap<WindowId, WindowHandle>,
+
+    /// Tokens of the registered file descriptors that became ready, which are reported one per
+    /// event loop iteration.
+    pub ready_fds: VecDeque<FdToken>,
+
+    /// Filter for the device events, set with `set_device_event_filter`.
+    pub device_event_filter: DeviceEventFilter,
+
+    /// The number of keyboards focused on one of our windows.
+    pub focused_keyboards: usize,
+
+    /// The theme of the desktop, which is `None` until the settings portal reported it.
+    ///
+    /// It's shared with the windows, to answer `Window::theme`.
+    pub desktop_theme: Arc<Mutex<Option<Theme>>>,
+}
+
+impl WinitState {
+    /// Whether device events are currently delivered, according to the device event filter.
+    pub fn device_events_enabled(&self) -> bool {
+        match self.device_event_filter {
+            DeviceEventFilter::Always => false,
+            DeviceEventFilter::Unfocused => self.focused_keyboards > 0,
+            DeviceEventFilter::Never => true,
+        }
+    }
+
+    /// Whether the window has a modal child, which swallows its input.
+    pub fn has_modal_child(&self, window_id: WindowId) -> bool {
+        self.window_map
+            .values()
+            .any(|window_handle| window_handle.modal && window_handle.owner == Some(window_id))
+    }
+
+    /// Returns the earliest time at which a window will be considered occluded, if it's starved
+    /// of frame callbacks.
+    pub fn occlusion_deadline(&self) -> Option<Instant> {
+        self.window_map
+            .values()
+            .filter_map(WindowHandle::occlusion_deadline)
+            .min()
+    }
+
+    /// Sends `Occluded(true)` to the windows starved of frame callbacks.
+    pub fn check_frame_starvation(&mut self, now: Instant) {
+        for (window_id, window_handle) in self.window_map.iter() {
+            if window_handle.check_frame_starvation(now) {
+                self.event_sink
+                    .push_window_event(WindowEvent::Occluded(true), *window_id);
+            }
+        }
+    }
+
+    /// Returns the earliest time at which the next frame of an animated cursor is due.
+    pub fn cursor_animation_deadline(&self) -> Option<Instant> {
+        self.window_map
+            .values()
+            .filter_map(WindowHandle::cursor_animation_deadline)
+            .min()
+    }
+
+    /// Shows the next frame of the animated cursors which are due.
+    pub fn advance_cursor_animations(&self, now: Instant) {
+        for window_handle in self.window_map.values() {
+            window_handle.advance_cursor_animation(now);
+        }
+    }
+
+    /// Updates the theme of the desktop, sending `ThemeChanged` to the windows following it.
+    pub fn set_desktop_theme(&mut self, theme: Theme) {
+        if self.desktop_theme.lock().unwrap().replace(theme) == Some(theme) {
+            return;
+        }
+
+        for (window_id, window_handle) in self.window_map.iter_mut() {
+            if window_handle.preferred_theme.lock().unwrap().is_some() {
+                continue;
+            }
+
+            if window_handle.update_frame_theme(Some(theme)) {
+                let window_update = self.window_updates.get_mut(window_id).unwrap();
+                window_update.refresh_frame = true;
+            }
+            self.event_sink
+                .push_window_event(WindowEvent::ThemeChanged(theme), *window_id);
+        }
+    }
 }
@@ -12,9 +12,12 @@ pub use event_loop::{EventLoop, EventLoopProxy, EventLoopWindowTarget};
 pub use output::{MonitorHandle, VideoMode};
 pub use window::Window;
 
+mod clipboard;
 mod env;
 mod event_loop;
 mod output;
+mod portal;
+mod protocols;
 mod seat;
 mod window;
 
@@ -34,6 +37,10 @@ impl WindowId {
     pub unsafe fn dummy() -> Self {
         WindowId(0)
     }
+
+    pub fn raw(&self) -> u64 {
+        self.0 as u64
+    }
 }
 
 #[inline]
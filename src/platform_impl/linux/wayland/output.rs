@@ -167,6 +167,18 @@ impl MonitorHandle {
             .into()
     }
 
+    /// Returns the refresh rate of the current mode, in mHz.
+    #[inline]
+    pub(crate) fn refresh_rate_millihertz(&self) -> Option<u32> {
+        sctk::output::with_output_info(&self.proxy, |info| {
+            info.modes
+                .iter()
+                .find(|mode| mode.is_current)
+                .map(|mode| mode.refresh_rate as u32)
+        })
+        .flatten()
+    }
+
     #[inline]
     pub fn scale_factor(&self) -> i32 {
         sctk::output::with_output_info(&self.proxy, |info| info.scale_factor).unwrap_or(1)
@@ -0,0 +1,663 @@
+//! Reads the desktop color scheme from the settings portal of `xdg-desktop-portal`.
+//!
+//! This speaks just enough of the D-Bus protocol to call `org.freedesktop.portal.Settings.Read`
+//! and to receive its `SettingChanged` signal, which doesn't warrant linking to `libdbus`. See
+//! https://dbus.freedesktop.org/doc/dbus-specification.html for the wire format.
+
+use std::convert::TryInto;
+use std::env;
+use std::io::{self, Read, Write};
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::window::Theme;
+
+const PORTAL_DESTINATION: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+const SETTINGS_INTERFACE: &str = "org.freedesktop.portal.Settings";
+const APPEARANCE_NAMESPACE: &str = "org.freedesktop.appearance";
+const COLOR_SCHEME_KEY: &str = "color-scheme";
+
+const METHOD_CALL: u8 = 1;
+const METHOD_RETURN: u8 = 2;
+const SIGNAL: u8 = 4;
+
+const FIELD_PATH: u8 = 1;
+const FIELD_INTERFACE: u8 = 2;
+const FIELD_MEMBER: u8 = 3;
+const FIELD_REPLY_SERIAL: u8 = 5;
+const FIELD_DESTINATION: u8 = 6;
+const FIELD_SIGNATURE: u8 = 8;
+
+/// How long to wait for the bus to authenticate us, which only involves the bus daemon.
+const AUTH_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A connection to the session bus, on which the color scheme of the desktop is requested and
+/// watched.
+pub struct SettingsPortal {
+    stream: UnixStream,
+    /// Bytes received but not parsed yet.
+    buffer: Vec<u8>,
+    /// Serial of the `Read` call, whose reply carries the initial color scheme.
+    read_serial: u32,
+}
+
+impl SettingsPortal {
+    /// Connects to the session bus and requests the color scheme.
+    ///
+    /// The replies are received asynchronously with `dispatch`, so a missing or slow portal
+    /// doesn't delay the caller.
+    pub fn connect() -> io::Result<Self> {
+        let mut stream = connect_session_bus()?;
+        authenticate(&mut stream)?;
+
+        let mut portal = SettingsPortal {
+            stream,
+            buffer: Vec::new(),
+            read_serial: 0,
+        };
+        portal.method_call(
+            1,
+            "org.freedesktop.DBus",
+            "/org/freedesktop/DBus",
+            "org.freedesktop.DBus",
+            "Hello",
+            &[],
+        )?;
+        let rule = format!(
+            "type='signal',interface='{}',member='SettingChanged',path='{}',arg0='{}',arg1='{}'",
+            SETTINGS_INTERFACE, PORTAL_PATH, APPEARANCE_NAMESPACE, COLOR_SCHEME_KEY,
+        );
+        portal.method_call(
+            2,
+            "org.freedesktop.DBus",
+            "/org/freedesktop/DBus",
+            "org.freedesktop.DBus",
+            "AddMatch",
+            &[&rule],
+        )?;
+        portal.read_serial = 3;
+        portal.method_call(
+            portal.read_serial,
+            PORTAL_DESTINATION,
+            PORTAL_PATH,
+            SETTINGS_INTERFACE,
+            "Read",
+            &[APPEARANCE_NAMESPACE, COLOR_SCHEME_KEY],
+        )?;
+
+        portal.stream.set_nonblocking(true)?;
+        Ok(portal)
+    }
+
+    /// Reads the messages received from the bus, returning the latest color scheme they
+    /// reported, if any.
+    ///
+    /// Returns an error once the bus closed the connection.
+    pub fn dispatch(&mut self) -> io::Result<Option<Theme>> {
+        let mut chunk = [0; 4096];
+        loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(len) => self.buffer.extend_from_slice(&chunk[..len]),
+                Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => (),
+                Err(err) => return Err(err),
+            }
+        }
+
+        let mut theme = None;
+        let mut start = 0;
+        while let Some(len) = message_len(&self.buffer[start..]) {
+            if self.buffer.len() - start < len {
+                break;
+            }
+            let message = &self.buffer[start..start + len];
+            if let Some(color_scheme) = parse_color_scheme(message, self.read_serial) {
+                theme = Some(theme_from_color_scheme(color_scheme));
+            }
+            start += len;
+        }
+        self.buffer.drain(..start);
+
+        Ok(theme)
+    }
+
+    fn method_call(
+        &mut self,
+        serial: u32,
+        destination: &str,
+        path: &str,
+        interface: &str,
+        member: &str,
+        args: &[&str],
+    ) -> io::Result<()> {
+        let message = method_call_message(serial, destination, path, interface, member, args);
+        self.stream.write_all(&message)
+    }
+}
+
+impl AsRawFd for SettingsPortal {
+    fn as_raw_fd(&self) -> RawFd {
+        self.stream.as_raw_fd()
+    }
+}
+
+/// Builds a method call whose arguments are all strings.
+fn method_call_message(
+    serial: u32,
+    destination: &str,
+    path: &str,
+    interface: &str,
+    member: &str,
+    args: &[&str],
+) -> Vec<u8> {
+    let mut body = Writer::default();
+    for arg in args {
+        body.write_string(arg);
+    }
+
+    let mut message = Writer::default();
+    message.buffer.extend_from_slice(&[b'l', METHOD_CALL, 0, 1]);
+    message.write_u32(body.buffer.len() as u32);
+    message.write_u32(serial);
+
+    // The header fields are an array of `(yv)` structs, whose length is patched below.
+    message.write_u32(0);
+    message.write_field(FIELD_PATH, "o", path);
+    message.write_field(FIELD_DESTINATION, "s", destination);
+    message.write_field(FIELD_INTERFACE, "s", interface);
+    message.write_field(FIELD_MEMBER, "s", member);
+    if !args.is_empty() {
+        message.write_field(FIELD_SIGNATURE, "g", &"s".repeat(args.len()));
+    }
+    let fields_len = (message.buffer.len() - 16) as u32;
+    message.buffer[12..16].copy_from_slice(&fields_len.to_le_bytes());
+
+    message.align(8);
+    message.buffer.extend_from_slice(&body.buffer);
+    message.buffer
+}
+
+/// Returns the color scheme carried by the reply to the `Read` call whose serial is
+/// `read_serial`, or by `SettingChanged`.
+fn parse_color_scheme(message: &[u8], read_serial: u32) -> Option<u32> {
+    let header = Header::parse(message)?;
+    let mut body = Reader {
+        message,
+        position: header.body_start,
+        big_endian: header.big_endian,
+    };
+
+    match header.message_type {
+        METHOD_RETURN if header.reply_serial == Some(read_serial) => {
+            if header.signature.as_deref() != Some("v") {
+                return None;
+            }
+            body.read_variant_u32()
+        }
+        SIGNAL
+            if header.interface.as_deref() == Some(SETTINGS_INTERFACE)
+                && header.member.as_deref() == Some("SettingChanged") =>
+        {
+            if header.signature.as_deref() != Some("ssv") {
+                return None;
+            }
+            let namespace = body.read_string()?;
+            let key = body.read_string()?;
+            if namespace != APPEARANCE_NAMESPACE || key != COLOR_SCHEME_KEY {
+                return None;
+            }
+            body.read_variant_u32()
+        }
+        _ => None,
+    }
+}
+
+/// Maps the values of `color-scheme`: 1 prefers dark, 2 prefers light and 0 has no preference.
+fn theme_from_color_scheme(color_scheme: u32) -> Theme {
+    match color_scheme {
+        1 => Theme::Dark,
+        _ => Theme::Light,
+    }
+}
+
+fn connect_session_bus() -> io::Result<UnixStream> {
+    let address = match env::var("DBUS_SESSION_BUS_ADDRESS") {
+        Ok(address) => address,
+        Err(_) => {
+            let runtime_dir = env::var_os("XDG_RUNTIME_DIR").ok_or(io::ErrorKind::NotFound)?;
+            return UnixStream::connect(PathBuf::from(runtime_dir).join("bus"));
+        }
+    };
+
+    let mut error = io::Error::from(io::ErrorKind::NotFound);
+    for address in address.split(';') {
+        let params = match address.strip_prefix("unix:") {
+            Some(params) => params,
+            None => continue,
+        };
+        for param in params.split(',') {
+            let result = if let Some(path) = param.strip_prefix("path=") {
+                UnixStream::connect(unescape(path))
+            } else if let Some(name) = param.strip_prefix("abstract=") {
+                connect_abstract(&unescape(name))
+            } else {
+                continue;
+            };
+            match result {
+                Ok(stream) => return Ok(stream),
+                Err(err) => error = err,
+            }
+        }
+    }
+    Err(error)
+}
+
+#[cfg(target_os = "linux")]
+fn connect_abstract(name: &str) -> io::Result<UnixStream> {
+    use std::os::linux::net::SocketAddrExt;
+    use std::os::unix::net::SocketAddr;
+
+    UnixStream::connect_addr(&SocketAddr::from_abstract_name(name)?)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn connect_abstract(_name: &str) -> io::Result<UnixStream> {
+    Err(io::ErrorKind::NotFound.into())
+}
+
+/// Decodes the `%XX` escapes of a D-Bus address value.
+fn unescape(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut unescaped = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(byte) => {
+                unescaped.push(byte);
+                i += 3;
+            }
+            None => {
+                unescaped.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&unescaped).into_owned()
+}
+
+/// Authenticates with the credentials of the socket, which is what every bus accepts.
+fn authenticate(stream: &mut UnixStream) -> io::Result<()> {
+    let uid = unsafe { libc::getuid() }.to_string();
+    let hex_uid: String = uid.bytes().map(|byte| format!("{:02x}", byte)).collect();
+    stream.write_all(format!("\0AUTH EXTERNAL {}\r\n", hex_uid).as_bytes())?;
+
+    stream.set_read_timeout(Some(AUTH_TIMEOUT))?;
+    let mut reply = Vec::new();
+    let mut byte = [0];
+    while !reply.ends_with(b"\r\n") {
+        if stream.read(&mut byte)? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        reply.push(byte[0]);
+    }
+    stream.set_read_timeout(None)?;
+
+    if !reply.starts_with(b"OK ") {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "the session bus rejected the authentication",
+        ));
+    }
+    stream.write_all(b"BEGIN\r\n")
+}
+
+/// Returns the length of the message at the start of `buffer`, once its fixed header arrived.
+fn message_len(buffer: &[u8]) -> Option<usize> {
+    let reader = Reader {
+        message: buffer,
+        position: 0,
+        big_endian: *buffer.first()? == b'B',
+    };
+    let body_len = reader.u32_at(4)? as usize;
+    let fields_len = reader.u32_at(12)? as usize;
+    Some(align(16 + fields_len, 8) + body_len)
+}
+
+fn align(position: usize, alignment: usize) -> usize {
+    (position + alignment - 1) & !(alignment - 1)
+}
+
+/// The header fields of a received message which are relevant to us.
+struct Header {
+    big_endian: bool,
+    message_type: u8,
+    reply_serial: Option<u32>,
+    interface: Option<String>,
+    member: Option<String>,
+    signature: Option<String>,
+    body_start: usize,
+}
+
+impl Header {
+    fn parse(message: &[u8]) -> Option<Self> {
+        let mut reader = Reader {
+            message,
+            position: 12,
+            big_endian: message[0] == b'B',
+        };
+        let fields_len = reader.read_u32()? as usize;
+        let fields_end = 16 + fields_len;
+
+        let mut header = Header {
+            big_endian: reader.big_endian,
+            message_type: message[1],
+            reply_serial: None,
+            interface: None,
+            member: None,
+            signature: None,
+            body_start: align(fields_end, 8),
+        };
+        while reader.position < fields_end {
+            reader.align(8);
+            let code = reader.read_u8()?;
+            let signature = reader.read_signature()?;
+            match (code, signature.as_str()) {
+                (FIELD_REPLY_SERIAL, "u") => header.reply_serial = Some(reader.read_u32()?),
+                (FIELD_INTERFACE, "s") => header.interface = Some(reader.read_string()?),
+                (FIELD_MEMBER, "s") => header.member = Some(reader.read_string()?),
+                (FIELD_SIGNATURE, "g") => header.signature = Some(reader.read_signature()?),
+                (_, "u") => {
+                    reader.read_u32()?;
+                }
+                (_, "s") | (_, "o") => {
+                    reader.read_string()?;
+                }
+                (_, "g") => {
+                    reader.read_signature()?;
+                }
+                _ => return None,
+            }
+        }
+        Some(header)
+    }
+}
+
+/// Reads values from a message, whose alignment is relative to the start of the message.
+struct Reader<'a> {
+    message: &'a [u8],
+    position: usize,
+    big_endian: bool,
+}
+
+impl Reader<'_> {
+    fn align(&mut self, alignment: usize) {
+        self.position = align(self.position, alignment);
+    }
+
+    fn u32_at(&self, position: usize) -> Option<u32> {
+        let bytes = self.message.get(position..position + 4)?.try_into().ok()?;
+        Some(if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    }
+
+    fn read_u8(&mut self) -> Option<u8> {
+        let byte = *self.message.get(self.position)?;
+        self.position += 1;
+        Some(byte)
+    }
+
+    fn read_u32(&mut self) -> Option<u32> {
+        self.align(4);
+        let value = self.u32_at(self.position)?;
+        self.position += 4;
+        Some(value)
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Option<String> {
+        let bytes = self.message.get(self.position..self.position + len)?;
+        // Skips the nul terminator as well.
+        self.position += len + 1;
+        String::from_utf8(bytes.to_vec()).ok()
+    }
+
+    fn read_string(&mut self) -> Option<String> {
+        let len = self.read_u32()? as usize;
+        self.read_bytes(len)
+    }
+
+    fn read_signature(&mut self) -> Option<String> {
+        let len = self.read_u8()? as usize;
+        self.read_bytes(len)
+    }
+
+    /// Reads a variant holding a `u`, possibly nested in other variants, as the `Read` method
+    /// wraps its value in an extra variant.
+    fn read_variant_u32(&mut self) -> Option<u32> {
+        match self.read_signature()?.as_str() {
+            "v" => self.read_variant_u32(),
+            "u" => self.read_u32(),
+            _ => None,
+        }
+    }
+}
+
+/// Writes the values of a little-endian message.
+#[derive(Default)]
+struct Writer {
+    buffer: Vec<u8>,
+}
+
+impl Writer {
+    fn align(&mut self, alignment: usize) {
+        let len = align(self.buffer.len(), alignment);
+        self.buffer.resize(len, 0);
+    }
+
+    fn write_u32(&mut self, value: u32) {
+        self.align(4);
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn write_string(&mut self, value: &str) {
+        self.write_u32(value.len() as u32);
+        self.buffer.extend_from_slice(value.as_bytes());
+        self.buffer.push(0);
+    }
+
+    fn write_signature(&mut self, value: &str) {
+        self.buffer.push(value.len() as u8);
+        self.buffer.extend_from_slice(value.as_bytes());
+        self.buffer.push(0);
+    }
+
+    /// Writes a header field whose value is a string-like type.
+    fn write_field(&mut self, code: u8, signature: &str, value: &str) {
+        self.align(8);
+        self.buffer.push(code);
+        self.write_signature(signature);
+        match signature {
+            "g" => self.write_signature(value),
+            _ => self.write_string(value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a message received from the bus, with a body of the given signature.
+    fn received_message(
+        message_type: u8,
+        reply_serial: Option<u32>,
+        interface: &str,
+        member: &str,
+        signature: &str,
+        body: Writer,
+    ) -> Vec<u8> {
+        let mut message = Writer::default();
+        message
+            .buffer
+            .extend_from_slice(&[b'l', message_type, 0, 1]);
+        message.write_u32(body.buffer.len() as u32);
+        message.write_u32(7);
+        message.write_u32(0);
+        if let Some(reply_serial) = reply_serial {
+            message.align(8);
+            message.buffer.push(FIELD_REPLY_SERIAL);
+            message.write_signature("u");
+            message.write_u32(reply_serial);
+        }
+        message.write_field(FIELD_INTERFACE, "s", interface);
+        message.write_field(FIELD_MEMBER, "s", member);
+        message.write_field(FIELD_SIGNATURE, "g", signature);
+        let fields_len = (message.buffer.len() - 16) as u32;
+        message.buffer[12..16].copy_from_slice(&fields_len.to_le_bytes());
+
+        message.align(8);
+        message.buffer.extend_from_slice(&body.buffer);
+        message.buffer
+    }
+
+    #[test]
+    fn values_round_trip() {
+        let mut writer = Writer::default();
+        writer.write_signature("v");
+        writer.write_u32(0xdead_beef);
+        writer.write_string("org.freedesktop.appearance");
+        writer.write_signature("");
+        writer.write_string("");
+        writer.write_u32(42);
+
+        // Each `u32` is aligned to 4 bytes, after the signatures and strings of odd lengths.
+        assert_eq!(
+            &writer.buffer[..8],
+            &[1, b'v', 0, 0, 0xef, 0xbe, 0xad, 0xde]
+        );
+
+        let mut reader = Reader {
+            message: &writer.buffer,
+            position: 0,
+            big_endian: false,
+        };
+        assert_eq!(reader.read_signature().as_deref(), Some("v"));
+        assert_eq!(reader.read_u32(), Some(0xdead_beef));
+        assert_eq!(
+            reader.read_string().as_deref(),
+            Some("org.freedesktop.appearance")
+        );
+        assert_eq!(reader.read_signature().as_deref(), Some(""));
+        assert_eq!(reader.read_string().as_deref(), Some(""));
+        assert_eq!(reader.read_u32(), Some(42));
+        assert_eq!(reader.position, writer.buffer.len());
+        assert_eq!(reader.read_u8(), None);
+    }
+
+    #[test]
+    fn big_endian_values() {
+        let message = [0, 0, 0, 3, b'a', b'b', b'c', 0, 0, 0, 1, 0];
+        let mut reader = Reader {
+            message: &message,
+            position: 0,
+            big_endian: true,
+        };
+        assert_eq!(reader.read_string().as_deref(), Some("abc"));
+        assert_eq!(reader.read_u32(), Some(256));
+    }
+
+    #[test]
+    fn truncated_values() {
+        let mut writer = Writer::default();
+        writer.write_string("color-scheme");
+        writer.buffer.truncate(8);
+
+        let mut reader = Reader {
+            message: &writer.buffer,
+            position: 0,
+            big_endian: false,
+        };
+        assert_eq!(reader.read_string(), None);
+        assert_eq!(message_len(&writer.buffer), None);
+    }
+
+    #[test]
+    fn method_call_round_trip() {
+        let message = method_call_message(
+            3,
+            PORTAL_DESTINATION,
+            PORTAL_PATH,
+            SETTINGS_INTERFACE,
+            "Read",
+            &[APPEARANCE_NAMESPACE, COLOR_SCHEME_KEY],
+        );
+        assert_eq!(message_len(&message), Some(message.len()));
+
+        let header = Header::parse(&message).unwrap();
+        assert!(!header.big_endian);
+        assert_eq!(header.message_type, METHOD_CALL);
+        assert_eq!(header.reply_serial, None);
+        assert_eq!(header.interface.as_deref(), Some(SETTINGS_INTERFACE));
+        assert_eq!(header.member.as_deref(), Some("Read"));
+        assert_eq!(header.signature.as_deref(), Some("ss"));
+
+        let mut body = Reader {
+            message: &message,
+            position: header.body_start,
+            big_endian: header.big_endian,
+        };
+        assert_eq!(body.read_string().as_deref(), Some(APPEARANCE_NAMESPACE));
+        assert_eq!(body.read_string().as_deref(), Some(COLOR_SCHEME_KEY));
+        assert_eq!(body.position, message.len());
+    }
+
+    #[test]
+    fn color_scheme_from_read_reply() {
+        // `Read` wraps the value in an extra variant.
+        let mut body = Writer::default();
+        body.write_signature("v");
+        body.write_signature("u");
+        body.write_u32(1);
+        let message = received_message(METHOD_RETURN, Some(3), "", "", "v", body);
+
+        assert_eq!(message_len(&message), Some(message.len()));
+        assert_eq!(parse_color_scheme(&message, 3), Some(1));
+        assert_eq!(parse_color_scheme(&message, 4), None);
+    }
+
+    #[test]
+    fn color_scheme_from_signal() {
+        let signal = |namespace: &str, key: &str| {
+            let mut body = Writer::default();
+            body.write_string(namespace);
+            body.write_string(key);
+            body.write_signature("u");
+            body.write_u32(2);
+            received_message(
+                SIGNAL,
+                None,
+                SETTINGS_INTERFACE,
+                "SettingChanged",
+                "ssv",
+                body,
+            )
+        };
+
+        let message = signal(APPEARANCE_NAMESPACE, COLOR_SCHEME_KEY);
+        assert_eq!(message_len(&message), Some(message.len()));
+        assert_eq!(parse_color_scheme(&message, 3), Some(2));
+
+        let message = signal(APPEARANCE_NAMESPACE, "accent-color");
+        assert_eq!(parse_color_scheme(&message, 3), None);
+    }
+}
@@ -0,0 +1,71 @@
+//! Protocols that aren't part of `wayland-protocols`, see `build.rs`.
+
+pub mod blur {
+    //! KDE's protocol to blur what is behind a surface.
+
+    #![allow(dead_code, non_camel_case_types, unused_unsafe, unused_variables)]
+    #![allow(non_upper_case_globals, non_snake_case, unused_imports)]
+    #![allow(missing_docs, static_mut_refs, clippy::all)]
+
+    pub mod client {
+        //! Client-side API of this protocol
+        pub(crate) use sctk::reexports::client::protocol::{wl_region, wl_surface};
+        pub(crate) use sctk::reexports::client::sys;
+        pub(crate) use sctk::reexports::client::{
+            AnonymousObject, Attached, Main, Proxy, ProxyMap,
+        };
+        pub(crate) use wayland_commons::map::{Object, ObjectMetadata};
+        pub(crate) use wayland_commons::smallvec;
+        pub(crate) use wayland_commons::wire::{Argument, ArgumentType, Message, MessageDesc};
+        pub(crate) use wayland_commons::{Interface, MessageGroup};
+
+        include!(concat!(env!("OUT_DIR"), "/blur_client_api.rs"));
+    }
+}
+
+pub mod xdg_shell {
+    //! The xdg-shell protocol, generated by us so that we can bind newer versions of it than
+    //! `wayland-protocols` and hand the `xdg_surface` of windows to other protocols.
+
+    #![allow(dead_code, non_camel_case_types, unused_unsafe, unused_variables)]
+    #![allow(non_upper_case_globals, non_snake_case, unused_imports)]
+    #![allow(missing_docs, static_mut_refs, clippy::all)]
+
+    pub mod client {
+        //! Client-side API of this protocol
+        pub(crate) use sctk::reexports::client::protocol::{wl_output, wl_seat, wl_surface};
+        pub(crate) use sctk::reexports::client::sys;
+        pub(crate) use sctk::reexports::client::{
+            AnonymousObject, Attached, Main, Proxy, ProxyMap,
+        };
+        pub(crate) use wayland_commons::map::{Object, ObjectMetadata};
+        pub(crate) use wayland_commons::smallvec;
+        pub(crate) use wayland_commons::wire::{Argument, ArgumentType, Message, MessageDesc};
+        pub(crate) use wayland_commons::{Interface, MessageGroup};
+
+        include!(concat!(env!("OUT_DIR"), "/xdg_shell_client_api.rs"));
+    }
+}
+
+pub mod xdg_decoration {
+    //! The xdg-decoration protocol, against our own `xdg_toplevel`.
+
+    #![allow(dead_code, non_camel_case_types, unused_unsafe, unused_variables)]
+    #![allow(non_upper_case_globals, non_snake_case, unused_imports)]
+    #![allow(missing_docs, static_mut_refs, clippy::all)]
+
+    pub mod client {
+        //! Client-side API of this protocol
+        pub(crate) use super::super::xdg_shell::client::xdg_toplevel;
+        pub(crate) use sctk::reexports::client::sys;
+        pub(crate) use sctk::reexports::client::{
+            AnonymousObject, Attached, Main, Proxy, ProxyMap,
+        };
+        pub(crate) use wayland_commons::map::{Object, ObjectMetadata};
+        pub(crate) use wayland_commons::smallvec;
+        pub(crate) use wayland_commons::wire::{Argument, ArgumentType, Message, MessageDesc};
+        pub(crate) use wayland_commons::{Interface, MessageGroup};
+
+        include!(concat!(env!("OUT_DIR"), "/xdg_decoration_client_api.rs"));
+    }
+}
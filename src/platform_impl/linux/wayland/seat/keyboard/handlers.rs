@@ -19,9 +19,16 @@ pub(super) fn handle_keyboard(
 ) {
     let event_sink = &mut winit_state.event_sink;
     match event {
-        KeyboardEvent::Enter { surface, .. } => {
+        KeyboardEvent::Enter {
+            surface, serial, ..
+        } => {
             let window_id = wayland::make_wid(&surface);
 
+            inner.selection_input.serial.set(serial);
+            inner.selection_input.keyboard_focus.set(true);
+
+            winit_state.focused_keyboards += 1;
+
             // Window gained focus.
             event_sink.push_window_event(WindowEvent::Focused(true), window_id);
 
@@ -36,6 +43,10 @@ pub(super) fn handle_keyboard(
         KeyboardEvent::Leave { surface, .. } => {
             let window_id = wayland::make_wid(&surface);
 
+            inner.selection_input.keyboard_focus.set(false);
+
+            winit_state.focused_keyboards = winit_state.focused_keyboards.saturating_sub(1);
+
             // Notify that no modifiers are being pressed.
             if !inner.modifiers_state.borrow().is_empty() {
                 event_sink.push_window_event(
@@ -55,8 +66,14 @@ pub(super) fn handle_keyboard(
             keysym,
             state,
             utf8,
+            serial,
             ..
         } => {
+            if state == KeyState::Pressed {
+                inner.selection_input.serial.set(serial);
+                inner.latest_press.set(&inner.seat, serial);
+            }
+
             let window_id = match inner.target_window_id {
                 Some(window_id) => window_id,
                 None => return,
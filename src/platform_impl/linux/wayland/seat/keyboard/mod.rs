@@ -12,7 +12,9 @@ use sctk::reexports::calloop::{LoopHandle, Source};
 use sctk::seat::keyboard::{self, RepeatSource};
 
 use crate::event::ModifiersState;
+use crate::platform_impl::wayland::clipboard::SelectionInput;
 use crate::platform_impl::wayland::event_loop::WinitState;
+use crate::platform_impl::wayland::seat::LatestPress;
 use crate::platform_impl::wayland::WindowId;
 
 mod handlers;
@@ -33,8 +35,15 @@ impl Keyboard {
         seat: &Attached<WlSeat>,
         loop_handle: LoopHandle<WinitState>,
         modifiers_state: Rc<RefCell<ModifiersState>>,
+        selection_input: Rc<SelectionInput>,
+        latest_press: Rc<LatestPress>,
     ) -> Option<Self> {
-        let mut inner = KeyboardInner::new(modifiers_state);
+        let mut inner = KeyboardInner::new(
+            seat.detach(),
+            modifiers_state,
+            selection_input,
+            latest_press,
+        );
         let keyboard_data = keyboard::map_keyboard_repeat(
             loop_handle.clone(),
             &seat,
@@ -81,14 +90,31 @@ struct KeyboardInner {
 
     /// Current state of modifiers keys.
     modifiers_state: Rc<RefCell<ModifiersState>>,
+
+    /// The latest input on the seat, to set the selections.
+    selection_input: Rc<SelectionInput>,
+
+    /// The latest press on the seats, for popups to grab.
+    latest_press: Rc<LatestPress>,
+
+    /// Seat the keyboard belongs to.
+    seat: WlSeat,
 }
 
 impl KeyboardInner {
-    fn new(modifiers_state: Rc<RefCell<ModifiersState>>) -> Self {
+    fn new(
+        seat: WlSeat,
+        modifiers_state: Rc<RefCell<ModifiersState>>,
+        selection_input: Rc<SelectionInput>,
+        latest_press: Rc<LatestPress>,
+    ) -> Self {
         Self {
             target_window_id: None,
             pending_modifers_state: None,
             modifiers_state,
+            selection_input,
+            latest_press,
+            seat,
         }
     }
 }
@@ -7,7 +7,9 @@ use sctk::reexports::protocols::unstable::relative_pointer::v1::client::zwp_rela
 use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_pointer_constraints_v1::ZwpPointerConstraintsV1;
 use sctk::reexports::protocols::unstable::text_input::v3::client::zwp_text_input_manager_v3::ZwpTextInputManagerV3;
 
+use sctk::reexports::client::protocol::wl_compositor::WlCompositor;
 use sctk::reexports::client::protocol::wl_seat::WlSeat;
+use sctk::reexports::client::protocol::wl_shm::WlShm;
 use sctk::reexports::client::Attached;
 
 use sctk::environment::Environment;
@@ -15,6 +17,7 @@ use sctk::reexports::calloop::LoopHandle;
 use sctk::seat::pointer::ThemeManager;
 use sctk::seat::{SeatData, SeatListener};
 
+use super::clipboard::{Clipboard, SelectionInput};
 use super::env::WinitEnv;
 use super::event_loop::WinitState;
 use crate::event::ModifiersState;
@@ -29,6 +32,32 @@ use pointer::Pointers;
 use text_input::TextInput;
 use touch::Touch;
 
+/// The latest key or button press on one of the seats, along with that seat.
+///
+/// The compositor only lets popups grab a seat in response to a press, so the serials of the
+/// keyboard focus aren't tracked here.
+#[derive(Default)]
+pub struct LatestPress(RefCell<Option<(WlSeat, u32)>>);
+
+impl LatestPress {
+    /// Returns the seat with the latest press, along with the serial of the press.
+    pub fn get(&self) -> Option<(WlSeat, u32)> {
+        self.0.borrow().clone()
+    }
+
+    fn set(&self, seat: &WlSeat, serial: u32) {
+        *self.0.borrow_mut() = Some((seat.clone(), serial));
+    }
+
+    /// Forgets the latest press if it was on `seat`, which was removed.
+    fn forget(&self, seat: &WlSeat) {
+        let mut latest_press = self.0.borrow_mut();
+        if matches!(*latest_press, Some((ref press_seat, _)) if press_seat == seat) {
+            *latest_press = None;
+        }
+    }
+}
+
 pub struct SeatManager {
     /// Listener for seats.
     _seat_listener: SeatListener,
@@ -39,17 +68,25 @@ impl SeatManager {
         env: &Environment<WinitEnv>,
         loop_handle: LoopHandle<WinitState>,
         theme_manager: ThemeManager,
+        clipboard: Clipboard,
+        latest_press: Rc<LatestPress>,
     ) -> Self {
         let relative_pointer_manager = env.get_global::<ZwpRelativePointerManagerV1>();
         let pointer_constraints = env.get_global::<ZwpPointerConstraintsV1>();
         let text_input_manager = env.get_global::<ZwpTextInputManagerV3>();
+        let compositor = env.require_global::<WlCompositor>();
+        let shm = env.require_global::<WlShm>();
 
         let mut inner = SeatManagerInner::new(
             theme_manager,
+            compositor,
+            shm,
             relative_pointer_manager,
             pointer_constraints,
             text_input_manager,
             loop_handle,
+            clipboard,
+            latest_press,
         );
 
         // Handle existing seats.
@@ -91,15 +128,32 @@ struct SeatManagerInner {
 
     /// A theme manager.
     theme_manager: ThemeManager,
+
+    /// Compositor, to create the surfaces of custom cursors.
+    compositor: Attached<WlCompositor>,
+
+    /// Shm, to create the buffers of custom cursors.
+    shm: Attached<WlShm>,
+
+    /// Clipboard, which needs the data devices of the seats.
+    clipboard: Clipboard,
+
+    /// The latest press on the seats, for popups to grab.
+    latest_press: Rc<LatestPress>,
 }
 
 impl SeatManagerInner {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         theme_manager: ThemeManager,
+        compositor: Attached<WlCompositor>,
+        shm: Attached<WlShm>,
         relative_pointer_manager: Option<Attached<ZwpRelativePointerManagerV1>>,
         pointer_constraints: Option<Attached<ZwpPointerConstraintsV1>>,
         text_input_manager: Option<Attached<ZwpTextInputManagerV3>>,
         loop_handle: LoopHandle<WinitState>,
+        clipboard: Clipboard,
+        latest_press: Rc<LatestPress>,
     ) -> Self {
         Self {
             seats: Vec::new(),
@@ -108,6 +162,10 @@ impl SeatManagerInner {
             pointer_constraints,
             text_input_manager,
             theme_manager,
+            compositor,
+            shm,
+            clipboard,
+            latest_press,
         }
     }
 
@@ -129,9 +187,13 @@ impl SeatManagerInner {
                 seat_info.pointer = Some(Pointers::new(
                     &seat,
                     &self.theme_manager,
+                    &self.compositor,
+                    &self.shm,
                     &self.relative_pointer_manager,
                     &self.pointer_constraints,
                     seat_info.modifiers_state.clone(),
+                    seat_info.selection_input.clone(),
+                    self.latest_press.clone(),
                 ));
             }
         } else {
@@ -145,6 +207,8 @@ impl SeatManagerInner {
                     &seat,
                     self.loop_handle.clone(),
                     seat_info.modifiers_state.clone(),
+                    seat_info.selection_input.clone(),
+                    self.latest_press.clone(),
                 );
             }
         } else {
@@ -168,6 +232,15 @@ impl SeatManagerInner {
                 seat_info.text_input = Some(TextInput::new(&seat, &text_input_manager));
             }
         }
+
+        // Handle selections.
+        if seat_data.defunct {
+            self.latest_press.forget(&seat_info.seat);
+            self.clipboard.remove_seat(&seat_info.seat);
+        } else {
+            self.clipboard
+                .add_seat(seat, seat_info.selection_input.clone());
+        }
     }
 }
 
@@ -192,6 +265,11 @@ struct SeatInfo {
     ///
     /// We keep modifiers state on a seat, since it's being used by pointer events as well.
     modifiers_state: Rc<RefCell<ModifiersState>>,
+
+    /// The latest input on a seat, and whether it has the keyboard focus.
+    ///
+    /// Compositors only let a focused client set the selections in response to input.
+    selection_input: Rc<SelectionInput>,
 }
 
 impl SeatInfo {
@@ -203,6 +281,7 @@ impl SeatInfo {
             touch: None,
             text_input: None,
             modifiers_state: Rc::new(RefCell::new(ModifiersState::default())),
+            selection_input: Default::default(),
         }
     }
 }
@@ -0,0 +1,182 @@
+//! Cursors of the cursor theme, and custom cursors, which are drawn into `wl_shm` buffers.
+
+use std::io;
+
+use sctk::reexports::client::protocol::wl_compositor::WlCompositor;
+use sctk::reexports::client::protocol::wl_shm::{Format, WlShm};
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::reexports::client::Attached;
+
+use sctk::shm::MemPool;
+
+use crate::platform_impl::platform::cursor_theme::CursorTheme;
+use crate::window::CustomCursor;
+
+/// Returns the cursor theme set with `XCURSOR_THEME` and `XCURSOR_SIZE`, which is also what SCTK
+/// uses for the cursors of the frame.
+pub fn default_cursor_theme() -> CursorTheme {
+    let (name, size) = CursorTheme::from_env();
+    CursorTheme {
+        name: name.unwrap_or_else(|| CursorTheme::DEFAULT_NAME.to_owned()),
+        size: size.unwrap_or(CursorTheme::DEFAULT_SIZE),
+    }
+}
+
+/// The surface of a pointer which shows cursors.
+pub(super) struct CursorSurface {
+    /// The surface itself.
+    pub surface: WlSurface,
+
+    /// Shm to create the pools.
+    shm: Attached<WlShm>,
+
+    /// The images of the current custom cursor.
+    images: Option<CursorImages>,
+
+    /// The themes loaded so far, by their name and size in physical pixels.
+    themes: Vec<(CursorTheme, wayland_cursor::CursorTheme)>,
+}
+
+/// The images of a cursor, scaled and stored in a pool.
+struct CursorImages {
+    cursor: CustomCursor,
+
+    scale: i32,
+
+    pool: MemPool,
+
+    /// Where each frame starts in the pool.
+    offsets: Vec<usize>,
+}
+
+impl CursorSurface {
+    pub fn new(compositor: &Attached<WlCompositor>, shm: Attached<WlShm>) -> Self {
+        let surface = compositor.create_surface();
+        // The surface is notified about the outputs it enters, which we don't need.
+        surface.quick_assign(|_, _, _| {});
+
+        Self {
+            surface: surface.detach(),
+            shm,
+            images: None,
+            themes: Vec::new(),
+        }
+    }
+
+    /// Shows the first cursor of `names` which is in `theme`, loaded at the size for `scale`.
+    ///
+    /// Returns the hotspot in surface coordinates, or `None` if the theme has none of the cursors.
+    pub fn show_icon(
+        &mut self,
+        names: &[&str],
+        theme: &CursorTheme,
+        scale: i32,
+    ) -> Option<(i32, i32)> {
+        let theme = CursorTheme {
+            size: theme.scaled_size(scale as f64),
+            ..theme.clone()
+        };
+        let index = match self.themes.iter().position(|(loaded, _)| *loaded == theme) {
+            Some(index) => index,
+            None => {
+                let loaded =
+                    wayland_cursor::CursorTheme::load_from_name(&theme.name, theme.size, &self.shm);
+                self.themes.push((theme, loaded));
+                self.themes.len() - 1
+            }
+        };
+
+        // Themed cursors can be animated as well, but only their first frame is shown.
+        let loaded = &mut self.themes[index].1;
+        let name = names
+            .iter()
+            .find(|name| loaded.get_cursor(name).is_some())?;
+        let image = &loaded.get_cursor(name)?[0];
+        let (hotspot_x, hotspot_y) = image.hotspot();
+
+        // The buffers belong to the theme, which is kept for as long as the surface.
+        self.surface.set_buffer_scale(scale);
+        self.surface.attach(Some(image), 0, 0);
+        self.surface.damage(0, 0, i32::MAX, i32::MAX);
+        self.surface.commit();
+
+        Some((hotspot_x as i32 / scale, hotspot_y as i32 / scale))
+    }
+
+    /// Shows `frame` of `cursor` on the surface, with its buffer scaled by `scale`, so that the
+    /// cursor has the same size on every output.
+    pub fn show(&mut self, cursor: &CustomCursor, frame: usize, scale: i32) -> io::Result<()> {
+        let uploaded = match self.images {
+            Some(ref images) => images.cursor == *cursor && images.scale == scale,
+            None => false,
+        };
+        if !uploaded {
+            self.images = Some(CursorImages::new(self.shm.clone(), cursor, scale)?);
+        }
+        let images = self.images.as_ref().unwrap();
+
+        let image = &cursor.frames[frame];
+        let width = image.width as i32 * scale;
+        let height = image.height as i32 * scale;
+        // The pool destroys the buffer once the compositor releases it.
+        let buffer = images.pool.buffer(
+            images.offsets[frame] as i32,
+            width,
+            height,
+            width * 4,
+            Format::Argb8888,
+        );
+
+        self.surface.set_buffer_scale(scale);
+        self.surface.attach(Some(&buffer), 0, 0);
+        self.surface.damage(0, 0, i32::MAX, i32::MAX);
+        self.surface.commit();
+
+        Ok(())
+    }
+}
+
+impl Drop for CursorSurface {
+    fn drop(&mut self) {
+        self.surface.destroy();
+    }
+}
+
+impl CursorImages {
+    fn new(shm: Attached<WlShm>, cursor: &CustomCursor, scale: i32) -> io::Result<Self> {
+        let scale_usize = scale as usize;
+        let len = cursor
+            .frames
+            .iter()
+            .map(|image| (image.width * image.height) as usize * scale_usize * scale_usize * 4)
+            .sum();
+
+        let mut pool = MemPool::new(shm, |_| {})?;
+        pool.resize(len)?;
+
+        let mut offsets = Vec::with_capacity(cursor.frames.len());
+        let mmap = pool.mmap();
+        let mut offset = 0;
+        for image in cursor.frames.iter() {
+            offsets.push(offset);
+
+            // Each pixel of the image becomes a square of `scale` pixels in the buffer.
+            let pixels: Vec<u32> = image.argb_pixels().collect();
+            let width = image.width as usize;
+            for y in 0..image.height as usize * scale_usize {
+                let row = &pixels[y / scale_usize * width..][..width];
+                for x in 0..width * scale_usize {
+                    mmap[offset..offset + 4].copy_from_slice(&row[x / scale_usize].to_le_bytes());
+                    offset += 4;
+                }
+            }
+        }
+
+        Ok(Self {
+            cursor: cursor.clone(),
+            scale,
+            pool,
+            offsets,
+        })
+    }
+}
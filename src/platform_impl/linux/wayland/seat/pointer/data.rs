@@ -3,12 +3,18 @@
 use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
+use sctk::reexports::client::protocol::wl_seat::WlSeat;
 use sctk::reexports::client::protocol::wl_surface::WlSurface;
 use sctk::reexports::client::Attached;
 use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_pointer_constraints_v1::{ZwpPointerConstraintsV1};
 use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_confined_pointer_v1::ZwpConfinedPointerV1;
+use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_locked_pointer_v1::ZwpLockedPointerV1;
 
 use crate::event::{ModifiersState, TouchPhase};
+use crate::platform_impl::wayland::clipboard::SelectionInput;
+use crate::platform_impl::wayland::seat::LatestPress;
+
+use super::CursorSurface;
 
 /// A data being used by pointer handlers.
 pub(super) struct PointerData {
@@ -26,26 +32,51 @@ pub(super) struct PointerData {
 
     pub confined_pointer: Rc<RefCell<Option<ZwpConfinedPointerV1>>>,
 
+    pub locked_pointer: Rc<RefCell<Option<ZwpLockedPointerV1>>>,
+
     /// A latest event serial.
     pub latest_serial: Rc<Cell<u32>>,
 
+    /// The latest input on the seat, to set the selections.
+    pub selection_input: Rc<SelectionInput>,
+
+    /// The latest press on the seats, for popups to grab.
+    pub latest_press: Rc<LatestPress>,
+
     /// The currently accumulated axis data on a pointer.
     pub axis_data: AxisData,
+
+    /// Seat the pointer belongs to.
+    pub seat: WlSeat,
+
+    /// Surface to show custom cursors.
+    pub cursor_surface: Rc<RefCell<CursorSurface>>,
 }
 
 impl PointerData {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         confined_pointer: Rc<RefCell<Option<ZwpConfinedPointerV1>>>,
+        locked_pointer: Rc<RefCell<Option<ZwpLockedPointerV1>>>,
         pointer_constraints: Option<Attached<ZwpPointerConstraintsV1>>,
         modifiers_state: Rc<RefCell<ModifiersState>>,
+        selection_input: Rc<SelectionInput>,
+        latest_press: Rc<LatestPress>,
+        seat: WlSeat,
+        cursor_surface: Rc<RefCell<CursorSurface>>,
     ) -> Self {
         Self {
             surface: None,
             latest_serial: Rc::new(Cell::new(0)),
+            selection_input,
+            latest_press,
             confined_pointer,
+            locked_pointer,
             modifiers_state,
             pointer_constraints,
             axis_data: AxisData::new(),
+            seat,
+            cursor_surface,
         }
     }
 }
@@ -57,8 +57,11 @@ pub(super) fn handle_pointer(
             let winit_pointer = WinitPointer {
                 pointer,
                 confined_pointer: Rc::downgrade(&pointer_data.confined_pointer),
+                locked_pointer: Rc::downgrade(&pointer_data.locked_pointer),
                 pointer_constraints: pointer_data.pointer_constraints.clone(),
                 latest_serial: pointer_data.latest_serial.clone(),
+                seat: pointer_data.seat.clone(),
+                cursor_surface: pointer_data.cursor_surface.clone(),
             };
             window_handle.pointer_entered(winit_pointer);
 
@@ -99,8 +102,11 @@ pub(super) fn handle_pointer(
             let winit_pointer = WinitPointer {
                 pointer,
                 confined_pointer: Rc::downgrade(&pointer_data.confined_pointer),
+                locked_pointer: Rc::downgrade(&pointer_data.locked_pointer),
                 pointer_constraints: pointer_data.pointer_constraints.clone(),
                 latest_serial: pointer_data.latest_serial.clone(),
+                seat: pointer_data.seat.clone(),
+                cursor_surface: pointer_data.cursor_surface.clone(),
             };
             window_handle.pointer_left(winit_pointer);
 
@@ -146,6 +152,10 @@ pub(super) fn handle_pointer(
             ..
         } => {
             pointer_data.latest_serial.replace(serial);
+            if state == wl_pointer::ButtonState::Pressed {
+                pointer_data.selection_input.serial.set(serial);
+                pointer_data.latest_press.set(&pointer_data.seat, serial);
+            }
             let window_id = match pointer_data.surface.as_ref().map(wayland::make_wid) {
                 Some(window_id) => window_id,
                 None => return,
@@ -293,6 +303,10 @@ pub(super) fn handle_pointer(
 
 #[inline]
 pub(super) fn handle_relative_pointer(event: RelativePointerEvent, winit_state: &mut WinitState) {
+    if !winit_state.device_events_enabled() {
+        return;
+    }
+
     if let RelativePointerEvent::RelativeMotion { dx, dy, .. } = event {
         winit_state
             .event_sink
@@ -3,24 +3,34 @@
 use std::cell::{Cell, RefCell};
 use std::rc::{Rc, Weak};
 
+use sctk::reexports::client::protocol::wl_compositor::WlCompositor;
 use sctk::reexports::client::protocol::wl_pointer::WlPointer;
 use sctk::reexports::client::protocol::wl_seat::WlSeat;
+use sctk::reexports::client::protocol::wl_shm::WlShm;
 use sctk::reexports::client::protocol::wl_surface::WlSurface;
 use sctk::reexports::client::Attached;
 use sctk::reexports::protocols::unstable::relative_pointer::v1::client::zwp_relative_pointer_manager_v1::ZwpRelativePointerManagerV1;
 use sctk::reexports::protocols::unstable::relative_pointer::v1::client::zwp_relative_pointer_v1::ZwpRelativePointerV1;
 use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_pointer_constraints_v1::{ZwpPointerConstraintsV1, Lifetime};
 use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_confined_pointer_v1::ZwpConfinedPointerV1;
+use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_locked_pointer_v1::ZwpLockedPointerV1;
 
 use sctk::seat::pointer::{ThemeManager, ThemedPointer};
 
 use crate::event::ModifiersState;
+use crate::platform_impl::platform::cursor_theme::CursorTheme;
+use crate::platform_impl::wayland::clipboard::SelectionInput;
 use crate::platform_impl::wayland::event_loop::WinitState;
-use crate::window::CursorIcon;
+use crate::platform_impl::wayland::seat::LatestPress;
+use crate::platform_impl::wayland::window::xdg::XdgWindow;
+use crate::window::{CursorIcon, CustomCursor, ResizeDirection};
 
+mod cursor;
 mod data;
 mod handlers;
 
+pub use cursor::default_cursor_theme;
+use cursor::CursorSurface;
 use data::PointerData;
 
 /// A proxy to Wayland pointer, which serves requests from a `WindowHandle`.
@@ -33,8 +43,17 @@ pub struct WinitPointer {
     /// Cursor to handle confine requests.
     confined_pointer: Weak<RefCell<Option<ZwpConfinedPointerV1>>>,
 
+    /// Cursor to handle lock requests.
+    locked_pointer: Weak<RefCell<Option<ZwpLockedPointerV1>>>,
+
     /// Latest observed serial in pointer events.
     latest_serial: Rc<Cell<u32>>,
+
+    /// Seat the pointer belongs to.
+    seat: WlSeat,
+
+    /// Surface to show custom cursors.
+    cursor_surface: Rc<RefCell<CursorSurface>>,
 }
 
 impl PartialEq for WinitPointer {
@@ -46,10 +65,10 @@ impl PartialEq for WinitPointer {
 impl Eq for WinitPointer {}
 
 impl WinitPointer {
-    /// Set the cursor icon.
+    /// Set the cursor icon from `theme`, loaded at the size for `scale`.
     ///
     /// Providing `None` will hide the cursor.
-    pub fn set_cursor(&self, cursor_icon: Option<CursorIcon>) {
+    pub fn set_cursor(&self, cursor_icon: Option<CursorIcon>, theme: &CursorTheme, scale: i32) {
         let cursor_icon = match cursor_icon {
             Some(cursor_icon) => cursor_icon,
             None => {
@@ -102,12 +121,43 @@ impl WinitPointer {
             CursorIcon::ZoomOut => &["zoom-out"],
         };
 
-        let serial = Some(self.latest_serial.get());
-        for cursor in cursors {
-            if self.pointer.set_cursor(cursor, serial).is_ok() {
-                break;
-            }
+        let mut cursor_surface = self.cursor_surface.borrow_mut();
+        if let Some((hotspot_x, hotspot_y)) = cursor_surface.show_icon(cursors, theme, scale) {
+            (*self.pointer).set_cursor(
+                self.latest_serial.get(),
+                Some(&cursor_surface.surface),
+                hotspot_x,
+                hotspot_y,
+            );
+        }
+    }
+
+    /// Set a custom cursor, showing `frame` of it scaled by `scale`.
+    pub fn set_custom_cursor(&self, cursor: &CustomCursor, frame: usize, scale: i32) {
+        let mut cursor_surface = self.cursor_surface.borrow_mut();
+        if let Err(err) = cursor_surface.show(cursor, frame, scale) {
+            warn!("Failed to create the buffers of a custom cursor: {}", err);
+            return;
         }
+
+        // The hotspot is in surface coordinates, which aren't affected by the buffer scale.
+        let image = &cursor.frames[frame];
+        (*self.pointer).set_cursor(
+            self.latest_serial.get(),
+            Some(&cursor_surface.surface),
+            image.hotspot_x as i32,
+            image.hotspot_y as i32,
+        );
+    }
+
+    /// Start an interactive move of the window with this pointer.
+    pub fn drag_window(&self, window: &XdgWindow) {
+        window.start_interactive_move(&self.seat, self.latest_serial.get());
+    }
+
+    /// Start an interactive resize of the window with this pointer.
+    pub fn drag_resize_window(&self, window: &XdgWindow, direction: ResizeDirection) {
+        window.start_interactive_resize(&self.seat, self.latest_serial.get(), direction);
     }
 
     /// Confine the pointer to a surface.
@@ -144,6 +194,58 @@ impl WinitPointer {
             confined_pointer.destroy();
         }
     }
+
+    /// Lock the pointer in place over a surface.
+    pub fn lock(&self, surface: &WlSurface) {
+        let pointer_constraints = match &self.pointer_constraints {
+            Some(pointer_constraints) => pointer_constraints,
+            None => return,
+        };
+
+        let locked_pointer = match self.locked_pointer.upgrade() {
+            Some(locked_pointer) => locked_pointer,
+            // A pointer is gone.
+            None => return,
+        };
+
+        *locked_pointer.borrow_mut() = Some(init_locked_pointer(
+            pointer_constraints,
+            surface,
+            &self.pointer,
+        ));
+    }
+
+    /// Tries to unlock the pointer if the current pointer is locked.
+    pub fn unlock(&self) {
+        let locked_pointer = match self.locked_pointer.upgrade() {
+            Some(locked_pointer) => locked_pointer,
+            // A pointer is gone.
+            None => return,
+        };
+
+        let mut locked_pointer = locked_pointer.borrow_mut();
+
+        if let Some(locked_pointer) = locked_pointer.take() {
+            locked_pointer.destroy();
+        }
+    }
+
+    /// Hint where the cursor should be when the pointer is unlocked, in surface coordinates.
+    ///
+    /// The hint is applied on the next commit of the surface.
+    pub fn set_cursor_position_hint(&self, x: f64, y: f64) {
+        let locked_pointer = match self.locked_pointer.upgrade() {
+            Some(locked_pointer) => locked_pointer,
+            // A pointer is gone.
+            None => return,
+        };
+
+        let locked_pointer = locked_pointer.borrow();
+
+        if let Some(locked_pointer) = locked_pointer.as_ref() {
+            locked_pointer.set_cursor_position_hint(x, y);
+        }
+    }
 }
 
 /// A pointer wrapper for easy releasing and managing pointers.
@@ -156,21 +258,36 @@ pub(super) struct Pointers {
 
     /// Confined pointer.
     confined_pointer: Rc<RefCell<Option<ZwpConfinedPointerV1>>>,
+
+    /// Locked pointer.
+    locked_pointer: Rc<RefCell<Option<ZwpLockedPointerV1>>>,
 }
 
 impl Pointers {
+    #[allow(clippy::too_many_arguments)]
     pub(super) fn new(
         seat: &Attached<WlSeat>,
         theme_manager: &ThemeManager,
+        compositor: &Attached<WlCompositor>,
+        shm: &Attached<WlShm>,
         relative_pointer_manager: &Option<Attached<ZwpRelativePointerManagerV1>>,
         pointer_constraints: &Option<Attached<ZwpPointerConstraintsV1>>,
         modifiers_state: Rc<RefCell<ModifiersState>>,
+        selection_input: Rc<SelectionInput>,
+        latest_press: Rc<LatestPress>,
     ) -> Self {
         let confined_pointer = Rc::new(RefCell::new(None));
+        let locked_pointer = Rc::new(RefCell::new(None));
+        let cursor_surface = Rc::new(RefCell::new(CursorSurface::new(compositor, shm.clone())));
         let pointer_data = Rc::new(RefCell::new(PointerData::new(
             confined_pointer.clone(),
+            locked_pointer.clone(),
             pointer_constraints.clone(),
             modifiers_state,
+            selection_input,
+            latest_press,
+            seat.detach(),
+            cursor_surface,
         )));
         let pointer = theme_manager.theme_pointer_with_impl(
             seat,
@@ -192,6 +309,7 @@ impl Pointers {
             pointer,
             relative_pointer,
             confined_pointer,
+            locked_pointer,
         }
     }
 }
@@ -208,6 +326,11 @@ impl Drop for Pointers {
             confined_pointer.destroy();
         }
 
+        // Drop locked pointer.
+        if let Some(locked_pointer) = self.locked_pointer.borrow_mut().take() {
+            locked_pointer.destroy();
+        }
+
         // Drop the pointer itself in case it's possible.
         if self.pointer.as_ref().version() >= 3 {
             self.pointer.release();
@@ -240,3 +363,16 @@ pub(super) fn init_confined_pointer(
 
     confined_pointer.detach()
 }
+
+pub(super) fn init_locked_pointer(
+    pointer_constraints: &Attached<ZwpPointerConstraintsV1>,
+    surface: &WlSurface,
+    pointer: &WlPointer,
+) -> ZwpLockedPointerV1 {
+    let locked_pointer =
+        pointer_constraints.lock_pointer(surface, pointer, None, Lifetime::Persistent.to_raw());
+
+    locked_pointer.quick_assign(move |_, _, _| {});
+
+    locked_pointer.detach()
+}
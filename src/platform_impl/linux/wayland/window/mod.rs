@@ -2,35 +2,40 @@ use std::collections::VecDeque;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
+use sctk::reexports::client::protocol::wl_compositor::WlCompositor;
 use sctk::reexports::client::protocol::wl_surface::WlSurface;
-use sctk::reexports::client::Display;
+use sctk::reexports::client::{DispatchData, Display};
 
-use sctk::reexports::calloop;
-
-use sctk::window::{
-    ARGBColor, ButtonColorSpec, ColorSpec, ConceptConfig, ConceptFrame, Decorations,
-};
+use sctk::window::{ARGBColor, ButtonColorSpec, ColorSpec, ConceptConfig};
 
 use raw_window_handle::unix::WaylandHandle;
 
 use crate::dpi::{LogicalSize, PhysicalPosition, PhysicalSize, Position, Size};
 use crate::error::{ExternalError, NotSupportedError, OsError as RootOsError};
+use crate::event::WindowEvent;
 use crate::monitor::MonitorHandle as RootMonitorHandle;
 use crate::platform::unix::{ARGBColor as LocalARGBColor, Button, ButtonState, Element, Theme};
 use crate::platform_impl::{
-    MonitorHandle as PlatformMonitorHandle, OsError,
-    PlatformSpecificWindowBuilderAttributes as PlatformAttributes,
+    platform::cursor_theme::CursorTheme, MonitorHandle as PlatformMonitorHandle, OsError,
+    PlatformSpecificWindowBuilderAttributes as PlatformAttributes, WindowId as PlatformWindowId,
+};
+use crate::window::{
+    CursorGrabMode, CursorIcon, CustomCursor, Fullscreen, ResizeDirection, Theme as WindowTheme,
+    WindowAttributes, WindowButtons, WindowLevel,
 };
-use crate::window::{CursorIcon, Fullscreen, WindowAttributes};
 
 use super::env::WindowingFeatures;
-use super::event_loop::WinitState;
+use super::event_loop::{EventLoopAwakener, WinitState};
 use super::output::{MonitorHandle, OutputManagerHandle};
+use super::protocols::blur::client::org_kde_kwin_blur_manager::OrgKdeKwinBlurManager;
+use super::protocols::xdg_shell::client::xdg_wm_base::XdgWmBase;
 use super::{EventLoopWindowTarget, WindowId};
 
 pub mod shim;
+pub mod xdg;
 
-use shim::{WindowHandle, WindowRequest, WindowUpdate};
+use shim::{FrameCallbackState, WindowHandle, WindowRequest, WindowUpdate};
+use xdg::XdgWindow;
 
 pub struct Window {
     /// Window id.
@@ -49,16 +54,49 @@ pub struct Window {
     output_manager_handle: OutputManagerHandle,
 
     /// Event loop proxy to wake it up.
-    event_loop_awakener: calloop::ping::Ping,
+    event_loop_awakener: EventLoopAwakener,
 
     /// Fullscreen state.
     fullscreen: Arc<AtomicBool>,
 
+    /// Maximized state, from the last configure.
+    maximized: Arc<AtomicBool>,
+
+    /// Whether decorations were requested.
+    decorated: AtomicBool,
+
+    /// Whether the window was made resizable.
+    resizable: AtomicBool,
+
+    /// The buttons of the frame which are enabled.
+    enabled_buttons: Mutex<WindowButtons>,
+
+    /// The last mode set with `set_cursor_grab`.
+    cursor_grab_mode: Mutex<CursorGrabMode>,
+
+    /// The resize increments, which the sizes suggested by the compositor are snapped to.
+    resize_increments: Arc<Mutex<Option<LogicalSize<u32>>>>,
+
+    /// The size which the resize increments are counted from.
+    base_size: LogicalSize<u32>,
+
+    /// The minimum size, which the sizes snapped to the resize increments are clamped to.
+    min_size: Arc<Mutex<Option<LogicalSize<u32>>>>,
+
     /// Available windowing features.
     windowing_features: WindowingFeatures,
 
     /// Requests that SCTK window should perform.
     window_requests: Arc<Mutex<Vec<WindowRequest>>>,
+
+    /// The frame callback of the surface, shared with the window handle.
+    frame_callback: Arc<Mutex<FrameCallbackState>>,
+
+    /// The theme set with `set_theme`.
+    preferred_theme: Arc<Mutex<Option<WindowTheme>>>,
+
+    /// The theme of the desktop.
+    desktop_theme: Arc<Mutex<Option<WindowTheme>>>,
 }
 
 impl Window {
@@ -67,6 +105,51 @@ impl Window {
         attributes: WindowAttributes,
         platform_attributes: PlatformAttributes,
     ) -> Result<Self, RootOsError> {
+        let popup = match platform_attributes.popup {
+            Some(ref popup) => match popup.parent {
+                PlatformWindowId::Wayland(parent) => Some((popup, parent)),
+                _ => {
+                    return Err(os_error!(OsError::WaylandMisc(
+                        "the parent of the popup is not a Wayland window."
+                    )))
+                }
+            },
+            None => None,
+        };
+        if let Some((_, parent)) = popup {
+            if !event_loop_window_target
+                .state
+                .borrow()
+                .window_map
+                .contains_key(&parent)
+            {
+                return Err(os_error!(OsError::WaylandMisc(
+                    "the parent of the popup was closed."
+                )));
+            }
+        }
+        let owner = match platform_attributes.owner {
+            Some(PlatformWindowId::Wayland(owner)) => Some(owner),
+            Some(_) => {
+                return Err(os_error!(OsError::WaylandMisc(
+                    "the owner is not a Wayland window."
+                )))
+            }
+            None => None,
+        };
+        if let Some(owner) = owner {
+            if !event_loop_window_target
+                .state
+                .borrow()
+                .window_map
+                .contains_key(&owner)
+            {
+                return Err(os_error!(OsError::WaylandMisc(
+                    "the owner window was closed."
+                )));
+            }
+        }
+
         let surface = event_loop_window_target
             .env
             .create_surface_with_scale_callback(move |scale, surface, mut dispatch_data| {
@@ -89,53 +172,112 @@ impl Window {
         let window_id = super::make_wid(&surface);
         let fullscreen = Arc::new(AtomicBool::new(false));
         let fullscreen_clone = fullscreen.clone();
+        let maximized = Arc::new(AtomicBool::new(false));
+        let maximized_clone = maximized.clone();
+
+        let to_logical = |size: Size| size.to_logical::<u32>(scale_factor as f64);
+        let resize_increments = Arc::new(Mutex::new(
+            platform_attributes.resize_increments.map(to_logical),
+        ));
+        let resize_increments_clone = resize_increments.clone();
+        let base_size = platform_attributes
+            .base_size
+            .map(to_logical)
+            .unwrap_or_else(|| LogicalSize::new(0, 0));
+        let min_inner_size = Arc::new(Mutex::new(attributes.min_inner_size.map(to_logical)));
+        let min_inner_size_clone = min_inner_size.clone();
 
         let (width, height) = attributes
             .inner_size
             .map(|size| size.to_logical::<f64>(scale_factor as f64).into())
             .unwrap_or((800, 600));
 
-        let theme_manager = event_loop_window_target.theme_manager.clone();
-        let mut window = event_loop_window_target
+        let wm_base = event_loop_window_target
             .env
-            .create_window::<ConceptFrame, _>(
-                surface.clone(),
-                Some(theme_manager),
-                (width, height),
-                move |event, mut dispatch_data| {
-                    use sctk::window::{Event, State};
-
-                    let winit_state = dispatch_data.get::<WinitState>().unwrap();
-                    let mut window_update = winit_state.window_updates.get_mut(&window_id).unwrap();
-
-                    match event {
-                        Event::Refresh => {
-                            window_update.refresh_frame = true;
+            .get_global::<XdgWmBase>()
+            .ok_or_else(|| {
+                os_error!(OsError::WaylandMisc(
+                    "the compositor doesn't support xdg_wm_base."
+                ))
+            })?;
+        let event_handler = move |event, mut dispatch_data: DispatchData<'_>| {
+            use xdg::{Event, State};
+
+            let winit_state = dispatch_data.get::<WinitState>().unwrap();
+            let mut window_update = winit_state.window_updates.get_mut(&window_id).unwrap();
+
+            match event {
+                Event::Refresh => {
+                    window_update.refresh_frame = true;
+                }
+                Event::Configure { new_size, states } => {
+                    let is_fullscreen = states.contains(&State::Fullscreen);
+                    fullscreen_clone.store(is_fullscreen, Ordering::Relaxed);
+                    let is_maximized = states.contains(&State::Maximized);
+                    maximized_clone.store(is_maximized, Ordering::Relaxed);
+
+                    let is_suspended = states.contains(&State::Suspended);
+                    if let Some(window_handle) = winit_state.window_map.get(&window_id) {
+                        if window_handle.set_suspended(is_suspended) {
+                            winit_state
+                                .event_sink
+                                .push_window_event(WindowEvent::Occluded(is_suspended), window_id);
                         }
-                        Event::Configure { new_size, states } => {
-                            let is_fullscreen = states.contains(&State::Fullscreen);
-                            fullscreen_clone.store(is_fullscreen, Ordering::Relaxed);
-
-                            window_update.refresh_frame = true;
-                            window_update.redraw_requested = true;
-                            if let Some((w, h)) = new_size {
-                                window_update.size = Some(LogicalSize::new(w, h));
+                    }
+
+                    window_update.refresh_frame = true;
+                    window_update.redraw_requested = true;
+                    if let Some((w, h)) = new_size {
+                        let mut size = LogicalSize::new(w, h);
+                        // A maximized or fullscreen window must have the exact size.
+                        if !is_fullscreen && !is_maximized {
+                            if let Some(increments) = *resize_increments_clone.lock().unwrap() {
+                                let min_size = *min_inner_size_clone.lock().unwrap();
+                                size = snap_to_increments(size, increments, base_size, min_size);
                             }
                         }
-                        Event::Close => {
-                            window_update.close_window = true;
-                        }
+                        window_update.size = Some(size);
                     }
-                },
-            )
-            .map_err(|_| os_error!(OsError::WaylandMisc("failed to create window.")))?;
+                }
+                Event::Close => {
+                    window_update.close_window = true;
+                }
+                Event::Dismissed => {
+                    winit_state
+                        .event_sink
+                        .push_window_event(WindowEvent::PopupDismissed, window_id);
+                }
+            }
+        };
+        let window = match popup {
+            Some((popup, parent)) => {
+                let winit_state = event_loop_window_target.state.borrow();
+                XdgWindow::new_popup(
+                    &event_loop_window_target.env,
+                    &wm_base,
+                    surface.clone(),
+                    &winit_state.window_map[&parent].window,
+                    popup,
+                    (width, height),
+                    // The compositor dismisses the popup right away without a valid grab.
+                    event_loop_window_target.latest_press.get(),
+                    event_handler,
+                )
+            }
+            None => XdgWindow::new_toplevel(
+                &event_loop_window_target.env,
+                &wm_base,
+                surface.clone(),
+                Some(event_loop_window_target.theme_manager.clone()),
+                (width, height),
+                event_handler,
+            ),
+        };
+        let mut window =
+            window.ok_or_else(|| os_error!(OsError::WaylandMisc("failed to create window.")))?;
 
         // Set decorations.
-        if attributes.decorations {
-            window.set_decorate(Decorations::FollowServer);
-        } else {
-            window.set_decorate(Decorations::None);
-        }
+        window.set_decorated(attributes.decorations);
 
         // Min dimensions.
         let min_size = attributes
@@ -161,6 +303,10 @@ impl Window {
         window.set_resizable(attributes.resizable);
         window.set_title(attributes.title);
 
+        if attributes.window_level != WindowLevel::Normal {
+            warn!("`WindowAttributes::window_level` is not supported on Wayland");
+        }
+
         // Set fullscreen/maximized if so was requested.
         match attributes.fullscreen {
             Some(Fullscreen::Exclusive(_)) => {
@@ -170,8 +316,7 @@ impl Window {
                 let monitor =
                     monitor.and_then(|RootMonitorHandle { inner: monitor }| match monitor {
                         PlatformMonitorHandle::Wayland(monitor) => Some(monitor.proxy),
-                        #[cfg(feature = "x11")]
-                        PlatformMonitorHandle::X(_) => None,
+                        _ => None,
                     });
 
                 window.set_fullscreen(monitor.as_ref());
@@ -194,10 +339,44 @@ impl Window {
         let window_requests = Arc::new(Mutex::new(Vec::with_capacity(64)));
 
         // Create a handle that performs all the requests on underlying sctk a window.
-        let window_handle = WindowHandle::new(window, size.clone(), window_requests.clone());
+        let preferred_theme = Arc::new(Mutex::new(None));
+        let mut window_handle = WindowHandle::new(
+            window,
+            size.clone(),
+            window_requests.clone(),
+            preferred_theme.clone(),
+            event_loop_window_target
+                .env
+                .require_global::<WlCompositor>(),
+            event_loop_window_target
+                .env
+                .get_global::<OrgKdeKwinBlurManager>(),
+        );
+        let frame_callback = window_handle.frame_callback.clone();
 
         let mut winit_state = event_loop_window_target.state.borrow_mut();
 
+        // Match the frame with the theme of the desktop.
+        let desktop_theme = winit_state.desktop_theme.clone();
+        window_handle.update_frame_theme(*desktop_theme.lock().unwrap());
+        if attributes.enabled_buttons != WindowButtons::all() {
+            window_handle.set_enabled_buttons(attributes.enabled_buttons);
+        }
+
+        if attributes.blur {
+            window_handle.set_blur(true, None);
+        }
+
+        if let Some(owner) = owner {
+            // The owner was checked above, and it can't be closed from here meanwhile.
+            let owner_handle = &winit_state.window_map[&owner];
+            window_handle.window.set_parent(Some(&owner_handle.window));
+            window_handle.owner = Some(owner);
+            window_handle.modal = platform_attributes.modal;
+        }
+
+        window_handle.popup_parent = popup.map(|(_, parent)| parent);
+
         winit_state.window_map.insert(window_id, window_handle);
 
         winit_state
@@ -211,7 +390,7 @@ impl Window {
         let event_loop_handle = &event_loop_window_target.event_loop_handle;
 
         // To make our window usable for drawing right away we must `ack` a `configure`
-        // from the server, the acking part here is done by SCTK window frame, so we just
+        // from the server, the acking part here is done by the toplevel, so we just
         // need to sync with server so it'll be done automatically for us.
         event_loop_handle.with_source(&wayland_source, |event_queue| {
             let event_queue = event_queue.queue();
@@ -235,7 +414,18 @@ impl Window {
             window_requests,
             event_loop_awakener: event_loop_window_target.event_loop_awakener.clone(),
             fullscreen,
+            maximized,
+            decorated: AtomicBool::new(attributes.decorations),
+            resizable: AtomicBool::new(attributes.resizable),
+            enabled_buttons: Mutex::new(attributes.enabled_buttons),
+            cursor_grab_mode: Mutex::new(CursorGrabMode::None),
+            resize_increments,
+            base_size,
+            min_size: min_inner_size,
             windowing_features,
+            frame_callback,
+            preferred_theme,
+            desktop_theme,
         };
 
         Ok(window)
@@ -255,6 +445,12 @@ impl Window {
         self.event_loop_awakener.ping();
     }
 
+    #[inline]
+    pub fn is_visible(&self) -> Option<bool> {
+        // Not possible on Wayland.
+        None
+    }
+
     #[inline]
     pub fn set_visible(&self, _visible: bool) {
         // Not possible on Wayland.
@@ -289,6 +485,11 @@ impl Window {
         self.event_loop_awakener.ping();
     }
 
+    #[inline]
+    pub fn request_frame(&self) {
+        shim::request_frame(&self.surface, &self.frame_callback, self.window_id);
+    }
+
     #[inline]
     pub fn outer_size(&self) -> PhysicalSize<u32> {
         self.size
@@ -316,6 +517,7 @@ impl Window {
     pub fn set_min_inner_size(&self, dimensions: Option<Size>) {
         let scale_factor = self.scale_factor() as f64;
         let size = dimensions.map(|size| size.to_logical::<u32>(scale_factor));
+        *self.min_size.lock().unwrap() = size;
 
         let min_size_request = WindowRequest::MinSize(size);
         self.window_requests.lock().unwrap().push(min_size_request);
@@ -332,8 +534,52 @@ impl Window {
         self.event_loop_awakener.ping();
     }
 
+    #[inline]
+    pub fn set_enabled_buttons(&self, buttons: WindowButtons) {
+        *self.enabled_buttons.lock().unwrap() = buttons;
+        let buttons_request = WindowRequest::EnabledButtons(buttons);
+        self.window_requests.lock().unwrap().push(buttons_request);
+        self.event_loop_awakener.ping();
+    }
+
+    #[inline]
+    pub fn enabled_buttons(&self) -> WindowButtons {
+        *self.enabled_buttons.lock().unwrap()
+    }
+
+    #[inline]
+    pub fn set_resize_increments(&self, increments: Option<Size>) {
+        let scale_factor = self.scale_factor() as f64;
+        let increments = increments.map(|increments| increments.to_logical::<u32>(scale_factor));
+        *self.resize_increments.lock().unwrap() = increments;
+
+        // Snap the current size right away, instead of on the next configure. A maximized or
+        // fullscreen window must keep the exact size.
+        let increments = match increments {
+            Some(increments) => increments,
+            None => return,
+        };
+        if self.fullscreen.load(Ordering::Relaxed) || self.maximized.load(Ordering::Relaxed) {
+            return;
+        }
+        let size = *self.size.lock().unwrap();
+        let min_size = *self.min_size.lock().unwrap();
+        let snapped_size = snap_to_increments(size, increments, self.base_size, min_size);
+        if snapped_size != size {
+            let resize_request = WindowRequest::Resize(snapped_size);
+            self.window_requests.lock().unwrap().push(resize_request);
+            self.event_loop_awakener.ping();
+        }
+    }
+
+    #[inline]
+    pub fn is_resizable(&self) -> bool {
+        self.resizable.load(Ordering::Relaxed)
+    }
+
     #[inline]
     pub fn set_resizable(&self, resizable: bool) {
+        self.resizable.store(resizable, Ordering::Relaxed);
         let resizeable_request = WindowRequest::Resizeable(resizable);
         self.window_requests
             .lock()
@@ -349,13 +595,25 @@ impl Window {
         sctk::get_surface_scale_factor(&self.surface) as u32
     }
 
+    #[inline]
+    pub fn is_decorated(&self) -> bool {
+        self.decorated.load(Ordering::Relaxed)
+    }
+
     #[inline]
     pub fn set_decorations(&self, decorate: bool) {
+        self.decorated.store(decorate, Ordering::Relaxed);
         let decorate_request = WindowRequest::Decorate(decorate);
         self.window_requests.lock().unwrap().push(decorate_request);
         self.event_loop_awakener.ping();
     }
 
+    #[inline]
+    pub fn set_window_level(&self, _level: WindowLevel) {
+        // Not possible on Wayland.
+        warn!("`Window::set_window_level` is not supported on Wayland");
+    }
+
     #[inline]
     pub fn set_minimized(&self, minimized: bool) {
         // You can't unminimize the window on Wayland.
@@ -368,6 +626,17 @@ impl Window {
         self.event_loop_awakener.ping();
     }
 
+    #[inline]
+    pub fn is_minimized(&self) -> Option<bool> {
+        // Not possible on Wayland.
+        None
+    }
+
+    #[inline]
+    pub fn is_maximized(&self) -> bool {
+        self.maximized.load(Ordering::Relaxed)
+    }
+
     #[inline]
     pub fn set_maximized(&self, maximized: bool) {
         let maximize_request = WindowRequest::Maximize(maximized);
@@ -399,8 +668,7 @@ impl Window {
                 let monitor =
                     monitor.and_then(|RootMonitorHandle { inner: monitor }| match monitor {
                         PlatformMonitorHandle::Wayland(monitor) => Some(monitor.proxy),
-                        #[cfg(feature = "x11")]
-                        PlatformMonitorHandle::X(_) => None,
+                        _ => None,
                     });
 
                 WindowRequest::Fullscreen(monitor)
@@ -416,7 +684,7 @@ impl Window {
     }
 
     #[inline]
-    pub fn set_theme<T: Theme>(&self, theme: T) {
+    pub fn set_csd_theme<T: Theme>(&self, theme: T) {
         // First buttons is minimize, then maximize, and then close.
         let buttons: Vec<(ButtonColorSpec, ButtonColorSpec)> =
             [Button::Minimize, Button::Maximize, Button::Close]
@@ -538,6 +806,22 @@ impl Window {
         self.event_loop_awakener.ping();
     }
 
+    #[inline]
+    pub fn set_theme(&self, theme: Option<WindowTheme>) {
+        *self.preferred_theme.lock().unwrap() = theme;
+        self.window_requests
+            .lock()
+            .unwrap()
+            .push(WindowRequest::PreferredTheme);
+        self.event_loop_awakener.ping();
+    }
+
+    #[inline]
+    pub fn theme(&self) -> Option<WindowTheme> {
+        let desktop_theme = *self.desktop_theme.lock().unwrap();
+        self.preferred_theme.lock().unwrap().or(desktop_theme)
+    }
+
     #[inline]
     pub fn set_cursor_icon(&self, cursor: CursorIcon) {
         let cursor_icon_request = WindowRequest::NewCursorIcon(cursor);
@@ -548,6 +832,30 @@ impl Window {
         self.event_loop_awakener.ping();
     }
 
+    #[inline]
+    pub fn set_custom_cursor(&self, cursor: CustomCursor) {
+        let custom_cursor_request = WindowRequest::NewCustomCursor(cursor);
+        self.window_requests
+            .lock()
+            .unwrap()
+            .push(custom_cursor_request);
+        self.event_loop_awakener.ping();
+    }
+
+    #[inline]
+    pub fn set_cursor_theme(&self, name: &str, size: u32) {
+        let theme = CursorTheme {
+            name: name.to_owned(),
+            size,
+        };
+        let cursor_theme_request = WindowRequest::NewCursorTheme(theme);
+        self.window_requests
+            .lock()
+            .unwrap()
+            .push(cursor_theme_request);
+        self.event_loop_awakener.ping();
+    }
+
     #[inline]
     pub fn set_cursor_visible(&self, visible: bool) {
         let cursor_visible_request = WindowRequest::ShowCursor(visible);
@@ -559,12 +867,46 @@ impl Window {
     }
 
     #[inline]
-    pub fn set_cursor_grab(&self, grab: bool) -> Result<(), ExternalError> {
+    pub fn set_blur(&self, blur: bool) {
+        self.window_requests
+            .lock()
+            .unwrap()
+            .push(WindowRequest::Blur(blur));
+        self.event_loop_awakener.ping();
+    }
+
+    #[inline]
+    pub fn set_blur_region(&self, position: Position, size: Size) {
+        let scale_factor = self.scale_factor() as f64;
+        let position = position.to_logical(scale_factor);
+        let size = size.to_logical(scale_factor);
+        self.window_requests
+            .lock()
+            .unwrap()
+            .push(WindowRequest::BlurRegion(position, size));
+        self.event_loop_awakener.ping();
+    }
+
+    #[inline]
+    pub fn set_cursor_hittest(&self, hittest: bool) -> Result<(), ExternalError> {
+        let cursor_hittest_request = WindowRequest::CursorHittest(hittest);
+        self.window_requests
+            .lock()
+            .unwrap()
+            .push(cursor_hittest_request);
+        self.event_loop_awakener.ping();
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
         if !self.windowing_features.cursor_grab() {
             return Err(ExternalError::NotSupported(NotSupportedError::new()));
         }
 
-        let cursor_grab_request = WindowRequest::GrabCursor(grab);
+        *self.cursor_grab_mode.lock().unwrap() = mode;
+        let cursor_grab_request = WindowRequest::GrabCursor(mode);
         self.window_requests
             .lock()
             .unwrap()
@@ -575,15 +917,49 @@ impl Window {
     }
 
     #[inline]
-    pub fn set_cursor_position(&self, _: Position) -> Result<(), ExternalError> {
-        // XXX This is possible if the locked pointer is being used. We don't have any
-        // API for that right now, but it could be added in
-        // https://github.com/rust-windowing/winit/issues/1677.
-        //
-        // This function is essential for the locked pointer API.
+    pub fn set_cursor_position(&self, position: Position) -> Result<(), ExternalError> {
+        // Wayland doesn't let clients move the cursor, but a locked pointer takes a hint of
+        // where the cursor should be once it's unlocked.
         //
         // See pointer-constraints-unstable-v1.xml.
-        Err(ExternalError::NotSupported(NotSupportedError::new()))
+        if *self.cursor_grab_mode.lock().unwrap() != CursorGrabMode::Locked {
+            return Err(ExternalError::NotSupported(NotSupportedError::new()));
+        }
+
+        let scale_factor = self.scale_factor() as f64;
+        let position = position.to_logical(scale_factor);
+        let cursor_position_request = WindowRequest::CursorPositionHint(position);
+        self.window_requests
+            .lock()
+            .unwrap()
+            .push(cursor_position_request);
+        self.event_loop_awakener.ping();
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn drag_window(&self) -> Result<(), ExternalError> {
+        let drag_window_request = WindowRequest::DragWindow;
+        self.window_requests
+            .lock()
+            .unwrap()
+            .push(drag_window_request);
+        self.event_loop_awakener.ping();
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn drag_resize_window(&self, direction: ResizeDirection) -> Result<(), ExternalError> {
+        let drag_resize_window_request = WindowRequest::DragResizeWindow(direction);
+        self.window_requests
+            .lock()
+            .unwrap()
+            .push(drag_resize_window_request);
+        self.event_loop_awakener.ping();
+
+        Ok(())
     }
 
     #[inline]
@@ -637,6 +1013,28 @@ impl Window {
     }
 }
 
+/// Snaps a size down to the resize increments, counted from the base size, without going below
+/// the minimum size.
+fn snap_to_increments(
+    size: LogicalSize<u32>,
+    increments: LogicalSize<u32>,
+    base_size: LogicalSize<u32>,
+    min_size: Option<LogicalSize<u32>>,
+) -> LogicalSize<u32> {
+    let snap = |len: u32, increment: u32, base: u32| {
+        if increment == 0 || len <= base {
+            len
+        } else {
+            base + (len - base) / increment * increment
+        }
+    };
+    let min_size = min_size.unwrap_or_else(|| LogicalSize::new(0, 0));
+    LogicalSize::new(
+        snap(size.width, increments.width, base_size.width).max(min_size.width),
+        snap(size.height, increments.height, base_size.height).max(min_size.height),
+    )
+}
+
 impl From<LocalARGBColor> for ARGBColor {
     fn from(color: LocalARGBColor) -> Self {
         let a = color.a;
@@ -654,3 +1052,50 @@ impl Drop for Window {
         self.event_loop_awakener.ping();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snap_to_increments_from_base_size() {
+        let size = snap_to_increments(
+            LogicalSize::new(107, 64),
+            LogicalSize::new(10, 20),
+            LogicalSize::new(5, 2),
+            None,
+        );
+        assert_eq!(size, LogicalSize::new(105, 62));
+
+        // Sizes below the base size are left as they are.
+        let size = snap_to_increments(
+            LogicalSize::new(3, 1),
+            LogicalSize::new(10, 20),
+            LogicalSize::new(5, 2),
+            None,
+        );
+        assert_eq!(size, LogicalSize::new(3, 1));
+    }
+
+    #[test]
+    fn snap_to_zero_increments() {
+        let size = snap_to_increments(
+            LogicalSize::new(107, 64),
+            LogicalSize::new(0, 20),
+            LogicalSize::new(0, 0),
+            None,
+        );
+        assert_eq!(size, LogicalSize::new(107, 60));
+    }
+
+    #[test]
+    fn snap_to_increments_above_min_size() {
+        let size = snap_to_increments(
+            LogicalSize::new(107, 64),
+            LogicalSize::new(50, 50),
+            LogicalSize::new(0, 0),
+            Some(LogicalSize::new(105, 30)),
+        );
+        assert_eq!(size, LogicalSize::new(105, 50));
+    }
+}
@@ -1,18 +1,79 @@
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
+use sctk::reexports::client::protocol::wl_callback;
+use sctk::reexports::client::protocol::wl_compositor::WlCompositor;
 use sctk::reexports::client::protocol::wl_output::WlOutput;
-
-use sctk::window::{ConceptConfig, ConceptFrame, Decorations, Window};
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::reexports::client::Attached;
+use sctk::window::{ButtonColorSpec, ColorSpec, ConceptConfig};
 
 use crate::dpi::{LogicalPosition, LogicalSize};
 
 use crate::event::WindowEvent;
+use crate::platform_impl::platform::cursor_theme::CursorTheme;
+use crate::platform_impl::platform::frame_interval;
 use crate::platform_impl::wayland::event_loop::WinitState;
-use crate::platform_impl::wayland::seat::pointer::WinitPointer;
+use crate::platform_impl::wayland::protocols::blur::client::org_kde_kwin_blur_manager::OrgKdeKwinBlurManager;
+use crate::platform_impl::wayland::seat::pointer::{self, WinitPointer};
 use crate::platform_impl::wayland::seat::text_input::TextInputHandler;
+use crate::platform_impl::wayland::MonitorHandle;
 use crate::platform_impl::wayland::WindowId;
-use crate::window::CursorIcon;
+use crate::window::{
+    CursorGrabMode, CursorIcon, CustomCursor, ResizeDirection, Theme, WindowButtons,
+};
+
+use super::xdg::XdgWindow;
+
+/// How long a committed frame callback may be pending before the window is considered occluded.
+///
+/// Compositors stop sending frame callbacks to surfaces which aren't visible. This is only used
+/// when the compositor doesn't support the `suspended` state of `xdg_toplevel`, which is more
+/// direct.
+const FRAME_STARVATION_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The frame callback of a surface, shared by the `Window` which requests it and the event loop
+/// which watches it for starvation.
+#[derive(Debug, Default)]
+pub struct FrameCallbackState {
+    /// Whether a frame callback was requested and isn't done yet.
+    pending: bool,
+
+    /// When the pending callback was last committed, which starts its deadline.
+    ///
+    /// The application commits its frames itself, so the commits are only known from where it
+    /// presents them: while handling `RedrawRequested` or `FrameReady`, or when winit commits the
+    /// surface to refresh the frame.
+    committed_at: Option<Instant>,
+}
+
+impl FrameCallbackState {
+    /// Marks a frame callback as requested, returning whether none was pending.
+    pub fn request(&mut self) -> bool {
+        !std::mem::replace(&mut self.pending, true)
+    }
+
+    /// Restarts the deadline of the pending callback, which a commit of the surface scheduled.
+    pub fn committed(&mut self, now: Instant) {
+        if self.pending {
+            self.committed_at = Some(now);
+        }
+    }
+
+    /// Clears the pending callback once the compositor is done with it.
+    pub fn done(&mut self) {
+        self.pending = false;
+        self.committed_at = None;
+    }
+
+    /// Returns when the surface is starved if the pending callback isn't done by then.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.committed_at
+            .map(|committed_at| committed_at + FRAME_STARVATION_TIMEOUT)
+    }
+}
 
 /// A request to SCTK window from Winit window.
 #[derive(Debug, Clone)]
@@ -31,8 +92,32 @@ pub enum WindowRequest {
     /// Change the cursor icon.
     NewCursorIcon(CursorIcon),
 
+    /// Change the cursor to a custom one.
+    NewCustomCursor(CustomCursor),
+
+    /// Change the cursor theme.
+    NewCursorTheme(CursorTheme),
+
     /// Grab cursor.
-    GrabCursor(bool),
+    GrabCursor(CursorGrabMode),
+
+    /// Hint where the locked cursor should be once it's unlocked.
+    CursorPositionHint(LogicalPosition<f64>),
+
+    /// Blur behind the whole window.
+    Blur(bool),
+
+    /// Blur behind a part of the window.
+    BlurRegion(LogicalPosition<i32>, LogicalSize<u32>),
+
+    /// Whether the window catches the cursor events.
+    CursorHittest(bool),
+
+    /// Drag window.
+    DragWindow,
+
+    /// Drag window border to resize it.
+    DragResizeWindow(ResizeDirection),
 
     /// Maximize the window.
     Maximize(bool),
@@ -58,6 +143,9 @@ pub enum WindowRequest {
     /// New frame size.
     FrameSize(LogicalSize<u32>),
 
+    /// New size which the application is notified about, like the size of a configure.
+    Resize(LogicalSize<u32>),
+
     /// Set IME window position.
     IMEPosition(LogicalPosition<u32>),
 
@@ -67,6 +155,12 @@ pub enum WindowRequest {
     /// A new theme for a concept frame was requested.
     Theme(ConceptConfig),
 
+    /// The buttons of the frame which are enabled.
+    EnabledButtons(WindowButtons),
+
+    /// The theme preferred by the window changed.
+    PreferredTheme,
+
     /// Window should be closed.
     Close,
 }
@@ -128,7 +222,7 @@ impl WindowUpdate {
 /// and react to events.
 pub struct WindowHandle {
     /// An actual window.
-    pub window: Window<ConceptFrame>,
+    pub window: XdgWindow,
 
     /// The current size of the window.
     pub size: Arc<Mutex<LogicalSize<u32>>>,
@@ -136,55 +230,282 @@ pub struct WindowHandle {
     /// A pending requests to SCTK window.
     pub pending_window_requests: Arc<Mutex<Vec<WindowRequest>>>,
 
+    /// The compositor, to create input regions.
+    compositor: Attached<WlCompositor>,
+
+    /// KDE's blur manager, if the compositor advertises it.
+    blur_manager: Option<Attached<OrgKdeKwinBlurManager>>,
+
     /// Current cursor icon.
     pub cursor_icon: Cell<CursorIcon>,
 
+    /// The custom cursor, which replaces `cursor_icon` while it's set.
+    custom_cursor: RefCell<Option<CustomCursor>>,
+
+    /// The frame of the custom cursor which is shown, and when the next one is due if the cursor
+    /// is animated.
+    cursor_frame: Cell<(usize, Option<Instant>)>,
+
+    /// The theme of the cursor icons, whose size is scaled by the scale factor of the window.
+    cursor_theme: RefCell<CursorTheme>,
+
     /// Visible cursor or not.
     cursor_visible: Cell<bool>,
 
-    /// Cursor confined to the surface.
-    confined: Cell<bool>,
+    /// How the cursor is grabbed by the surface.
+    cursor_grab_mode: Cell<CursorGrabMode>,
 
     /// Pointers over the current surface.
     pointers: Vec<WinitPointer>,
 
     /// Text inputs on the current surface.
     text_inputs: Vec<TextInputHandler>,
+
+    /// The frame callback of the surface, shared with the `Window` which requests it.
+    pub frame_callback: Arc<Mutex<FrameCallbackState>>,
+
+    /// Whether the window was reported as occluded, because the compositor suspended it or
+    /// starved it of frame callbacks.
+    occluded: Rc<Cell<bool>>,
+
+    /// The theme set with `set_theme`, or `None` to follow the desktop theme.
+    pub preferred_theme: Arc<Mutex<Option<Theme>>>,
+
+    /// The theme of the frame colors, which is light by default.
+    frame_theme: Theme,
+
+    /// Whether the frame colors were set by the user, in which case they're kept as is.
+    custom_frame_config: bool,
+
+    /// The frame colors, before the disabled buttons are removed.
+    frame_config: ConceptConfig,
+
+    /// The buttons of the frame which are enabled.
+    enabled_buttons: WindowButtons,
+
+    /// The owner of the window.
+    pub owner: Option<WindowId>,
+
+    /// Whether the input to the owner is swallowed while the window is open.
+    pub modal: bool,
+
+    /// The parent of the window if it's a popup.
+    pub popup_parent: Option<WindowId>,
 }
 
 impl WindowHandle {
     pub fn new(
-        window: Window<ConceptFrame>,
+        window: XdgWindow,
         size: Arc<Mutex<LogicalSize<u32>>>,
         pending_window_requests: Arc<Mutex<Vec<WindowRequest>>>,
+        preferred_theme: Arc<Mutex<Option<Theme>>>,
+        compositor: Attached<WlCompositor>,
+        blur_manager: Option<Attached<OrgKdeKwinBlurManager>>,
     ) -> Self {
         Self {
             window,
             size,
             pending_window_requests,
+            compositor,
+            blur_manager,
             cursor_icon: Cell::new(CursorIcon::Default),
-            confined: Cell::new(false),
+            custom_cursor: RefCell::new(None),
+            cursor_frame: Cell::new((0, None)),
+            cursor_theme: RefCell::new(pointer::default_cursor_theme()),
+            cursor_grab_mode: Cell::new(CursorGrabMode::None),
             cursor_visible: Cell::new(true),
             pointers: Vec::new(),
             text_inputs: Vec::new(),
+            frame_callback: Default::default(),
+            occluded: Rc::new(Cell::new(false)),
+            preferred_theme,
+            frame_theme: Theme::Light,
+            custom_frame_config: false,
+            frame_config: ConceptConfig::default(),
+            enabled_buttons: WindowButtons::all(),
+            owner: None,
+            modal: false,
+            popup_parent: None,
+        }
+    }
+
+    /// Returns the theme of the window, given the theme of the desktop.
+    pub fn theme(&self, desktop_theme: Option<Theme>) -> Option<Theme> {
+        self.preferred_theme.lock().unwrap().or(desktop_theme)
+    }
+
+    /// Matches the frame colors with the theme of the window, returning whether they changed.
+    pub fn update_frame_theme(&mut self, desktop_theme: Option<Theme>) -> bool {
+        let theme = self.theme(desktop_theme).unwrap_or(Theme::Light);
+        if self.custom_frame_config || self.frame_theme == theme {
+            return false;
         }
+
+        self.frame_theme = theme;
+        self.set_frame_config(concept_config(theme));
+        true
     }
 
-    pub fn set_cursor_grab(&self, grab: bool) {
-        // The new requested state matches the current confine status, return.
-        if self.confined.get() == grab {
+    /// Sets the frame colors, without the buttons which aren't enabled.
+    pub fn set_frame_config(&mut self, config: ConceptConfig) {
+        let mut frame_config = config.clone();
+        if !self.enabled_buttons.contains(WindowButtons::CLOSE) {
+            frame_config.close_button = None;
+        }
+        if !self.enabled_buttons.contains(WindowButtons::MAXIMIZE) {
+            frame_config.maximize_button = None;
+        }
+        if !self.enabled_buttons.contains(WindowButtons::MINIMIZE) {
+            frame_config.minimize_button = None;
+        }
+        self.window.set_frame_config(frame_config);
+        self.frame_config = config;
+    }
+
+    pub fn set_enabled_buttons(&mut self, buttons: WindowButtons) {
+        self.enabled_buttons = buttons;
+        self.set_frame_config(self.frame_config.clone());
+    }
+
+    /// Clears the pending frame callback once the compositor is done with it, returning whether
+    /// the window was occluded because it was starved of frame callbacks.
+    pub fn frame_done(&self) -> bool {
+        self.frame_callback.lock().unwrap().done();
+        // The occlusion follows the frame callbacks only without the `suspended` state.
+        !self.window.supports_suspended() && self.occluded.replace(false)
+    }
+
+    /// Returns when the window will be considered occluded if the pending frame callback isn't
+    /// done by then.
+    pub fn occlusion_deadline(&self) -> Option<Instant> {
+        if self.occluded.get() || self.window.supports_suspended() {
+            return None;
+        }
+
+        self.frame_callback.lock().unwrap().deadline()
+    }
+
+    /// Restarts the deadline of the pending frame callback, after the surface was committed.
+    pub fn frame_committed(&self, now: Instant) {
+        self.frame_callback.lock().unwrap().committed(now);
+    }
+
+    /// Marks the window as occluded if it was starved of frame callbacks, returning whether it
+    /// wasn't already.
+    pub fn check_frame_starvation(&self, now: Instant) -> bool {
+        match self.occlusion_deadline() {
+            Some(deadline) if deadline <= now => !self.occluded.replace(true),
+            _ => false,
+        }
+    }
+
+    /// Marks the window as occluded while the compositor suspends it, returning whether that
+    /// changed.
+    pub fn set_suspended(&self, suspended: bool) -> bool {
+        self.occluded.replace(suspended) != suspended
+    }
+
+    pub fn set_cursor_grab(&self, mode: CursorGrabMode) {
+        // The new requested state matches the current grab status, return.
+        if self.cursor_grab_mode.get() == mode {
             return;
         }
 
-        self.confined.replace(grab);
+        // Apply the cursor position hint before unlocking the pointer, since an application
+        // which stopped drawing while the pointer was locked won't commit the surface itself.
+        if self.cursor_grab_mode.get() == CursorGrabMode::Locked {
+            self.window.surface().commit();
+        }
+
+        self.cursor_grab_mode.replace(mode);
 
         for pointer in self.pointers.iter() {
-            if self.confined.get() {
-                let surface = self.window.surface();
-                pointer.confine(&surface);
-            } else {
-                pointer.unconfine();
+            self.apply_cursor_grab(pointer);
+        }
+    }
+
+    /// Confines or locks `pointer` according to the grab mode, releasing its previous grab.
+    fn apply_cursor_grab(&self, pointer: &WinitPointer) {
+        pointer.unconfine();
+        pointer.unlock();
+
+        let surface = self.window.surface();
+        match self.cursor_grab_mode.get() {
+            CursorGrabMode::None => (),
+            CursorGrabMode::Confined => pointer.confine(&surface),
+            CursorGrabMode::Locked => pointer.lock(&surface),
+        }
+    }
+
+    pub fn set_cursor_position_hint(&self, position: LogicalPosition<f64>) {
+        for pointer in self.pointers.iter() {
+            pointer.set_cursor_position_hint(position.x, position.y);
+        }
+
+        // The hint is double buffered state of the surface, which the application's next commit
+        // applies, or `set_cursor_grab` right before unlocking the pointer.
+    }
+
+    pub fn set_cursor_hittest(&self, hittest: bool) {
+        let surface = self.window.surface();
+        if hittest {
+            surface.set_input_region(None);
+        } else {
+            // An empty input region lets the cursor events through.
+            let region = self.compositor.create_region();
+            surface.set_input_region(Some(&region));
+            region.destroy();
+        }
+    }
+
+    /// Blurs behind `region` of the window, or the whole window if it's `None`.
+    pub fn set_blur(&self, blur: bool, region: Option<(LogicalPosition<i32>, LogicalSize<u32>)>) {
+        let blur_manager = match self.blur_manager {
+            Some(ref blur_manager) => blur_manager,
+            None => {
+                warn!(
+                    "Blur is not supported by the compositor, org_kde_kwin_blur_manager is missing"
+                );
+                return;
             }
+        };
+
+        let surface = self.window.surface();
+        if !blur {
+            blur_manager.unset(surface);
+            return;
+        }
+
+        let blur = blur_manager.create(surface);
+        match region {
+            Some((position, size)) => {
+                let region = self.compositor.create_region();
+                region.add(
+                    position.x,
+                    position.y,
+                    size.width as i32,
+                    size.height as i32,
+                );
+                blur.set_region(Some(&region));
+                region.destroy();
+            }
+            None => blur.set_region(None),
+        }
+
+        // The blur stays on the surface after the object is released.
+        blur.commit();
+        blur.release();
+    }
+
+    pub fn drag_window(&self) {
+        for pointer in self.pointers.iter() {
+            pointer.drag_window(&self.window);
+        }
+    }
+
+    pub fn drag_resize_window(&self, direction: ResizeDirection) {
+        for pointer in self.pointers.iter() {
+            pointer.drag_resize_window(&self.window, direction);
         }
     }
 
@@ -193,9 +514,8 @@ impl WindowHandle {
         let position = self.pointers.iter().position(|p| *p == pointer);
 
         if position.is_none() {
-            if self.confined.get() {
-                let surface = self.window.surface();
-                pointer.confine(&surface);
+            if self.cursor_grab_mode.get() != CursorGrabMode::None {
+                self.apply_cursor_grab(&pointer);
             }
             self.pointers.push(pointer);
         }
@@ -211,10 +531,9 @@ impl WindowHandle {
         if let Some(position) = position {
             let pointer = self.pointers.remove(position);
 
-            // Drop the confined pointer.
-            if self.confined.get() {
-                pointer.unconfine();
-            }
+            // Drop the confined or locked pointer.
+            pointer.unconfine();
+            pointer.unlock();
         }
     }
 
@@ -247,38 +566,154 @@ impl WindowHandle {
 
     pub fn set_cursor_visible(&self, visible: bool) {
         self.cursor_visible.replace(visible);
-        let cursor_icon = match visible {
-            true => Some(self.cursor_icon.get()),
-            false => None,
-        };
 
         for pointer in self.pointers.iter() {
-            pointer.set_cursor(cursor_icon)
+            self.apply_cursor(pointer);
         }
     }
 
     pub fn set_cursor_icon(&self, cursor_icon: CursorIcon) {
         self.cursor_icon.replace(cursor_icon);
+        self.custom_cursor.replace(None);
+
+        if !self.cursor_visible.get() {
+            return;
+        }
+
+        for pointer in self.pointers.iter() {
+            self.apply_cursor(pointer);
+        }
+    }
+
+    pub fn set_cursor_theme(&self, theme: CursorTheme) {
+        self.cursor_theme.replace(theme);
+        self.reload_cursor();
+    }
+
+    /// Shows the cursor again, after the cursor theme or the scale factor changed.
+    pub fn reload_cursor(&self) {
+        for pointer in self.pointers.iter() {
+            self.apply_cursor(pointer);
+        }
+    }
+
+    pub fn set_custom_cursor(&self, cursor: CustomCursor) {
+        let next_frame_at = if cursor.is_animated() {
+            Some(Instant::now() + cursor.frames[0].delay)
+        } else {
+            None
+        };
+        self.cursor_frame.replace((0, next_frame_at));
+        self.custom_cursor.replace(Some(cursor));
 
         if !self.cursor_visible.get() {
             return;
         }
 
         for pointer in self.pointers.iter() {
-            pointer.set_cursor(Some(cursor_icon));
+            self.apply_cursor(pointer);
         }
     }
+
+    /// Shows the current cursor of the window with `pointer`.
+    fn apply_cursor(&self, pointer: &WinitPointer) {
+        let theme = self.cursor_theme.borrow();
+        let scale = sctk::get_surface_scale_factor(self.window.surface());
+        if !self.cursor_visible.get() {
+            pointer.set_cursor(None, &theme, scale);
+            return;
+        }
+
+        match *self.custom_cursor.borrow() {
+            Some(ref cursor) => {
+                pointer.set_custom_cursor(cursor, self.cursor_frame.get().0, scale);
+            }
+            None => pointer.set_cursor(Some(self.cursor_icon.get()), &theme, scale),
+        }
+    }
+
+    /// Returns when the next frame of the animated cursor is due, if it's shown over the window.
+    pub fn cursor_animation_deadline(&self) -> Option<Instant> {
+        if !self.cursor_visible.get() || self.pointers.is_empty() {
+            return None;
+        }
+
+        self.cursor_frame.get().1
+    }
+
+    /// Shows the next frame of the animated cursor if it's due.
+    pub fn advance_cursor_animation(&self, now: Instant) {
+        match self.cursor_animation_deadline() {
+            Some(deadline) if deadline <= now => (),
+            _ => return,
+        }
+
+        if let Some(ref cursor) = *self.custom_cursor.borrow() {
+            let frame = (self.cursor_frame.get().0 + 1) % cursor.frames.len();
+            self.cursor_frame
+                .replace((frame, Some(now + cursor.frames[frame].delay)));
+        }
+
+        for pointer in self.pointers.iter() {
+            self.apply_cursor(pointer);
+        }
+    }
+}
+
+/// Requests a frame callback on `surface`, which emits `FrameReady` once the compositor is ready
+/// for a new frame, unless one is already pending.
+///
+/// The callback is double buffered state of the surface, which is only scheduled by the next
+/// commit. That commit is left to the application presenting its frame, so the callback is
+/// requested right away from the thread of the application, rather than from the event loop.
+pub fn request_frame(
+    surface: &WlSurface,
+    frame_callback: &Mutex<FrameCallbackState>,
+    window_id: WindowId,
+) {
+    if !frame_callback.lock().unwrap().request() {
+        return;
+    }
+
+    let callback = surface.frame();
+    let surface = surface.clone();
+    callback.quick_assign(move |_, event, mut dispatch_data| {
+        if let wl_callback::Event::Done { .. } = event {
+            let winit_state = dispatch_data.get::<WinitState>().unwrap();
+            let window_handle = match winit_state.window_map.get(&window_id) {
+                Some(window_handle) => window_handle,
+                None => return,
+            };
+
+            if window_handle.frame_done() {
+                winit_state
+                    .event_sink
+                    .push_window_event(WindowEvent::Occluded(false), window_id);
+            }
+
+            let refresh_rate = sctk::get_surface_outputs(&surface)
+                .last()
+                .and_then(|output| MonitorHandle::new(output.clone()).refresh_rate_millihertz());
+            let target_time = Instant::now() + frame_interval(refresh_rate);
+
+            winit_state
+                .event_sink
+                .push_window_event(WindowEvent::FrameReady { target_time }, window_id);
+        }
+    });
 }
 
 #[inline]
 pub fn handle_window_requests(winit_state: &mut WinitState) {
     let window_map = &mut winit_state.window_map;
     let window_updates = &mut winit_state.window_updates;
+    let desktop_theme = *winit_state.desktop_theme.lock().unwrap();
     let mut windows_to_close: Vec<WindowId> = Vec::new();
 
     // Process the rest of the events.
     for (window_id, window_handle) in window_map.iter_mut() {
-        let mut requests = window_handle.pending_window_requests.lock().unwrap();
+        let pending_window_requests = window_handle.pending_window_requests.clone();
+        let mut requests = pending_window_requests.lock().unwrap();
         for request in requests.drain(..) {
             match request {
                 WindowRequest::Fullscreen(fullscreen) => {
@@ -293,11 +728,47 @@ pub fn handle_window_requests(winit_state: &mut WinitState) {
                 WindowRequest::NewCursorIcon(cursor_icon) => {
                     window_handle.set_cursor_icon(cursor_icon);
                 }
+                WindowRequest::NewCustomCursor(cursor) => {
+                    window_handle.set_custom_cursor(cursor);
+                }
+                WindowRequest::NewCursorTheme(theme) => {
+                    window_handle.set_cursor_theme(theme);
+                }
                 WindowRequest::IMEPosition(position) => {
                     window_handle.set_ime_position(position);
                 }
-                WindowRequest::GrabCursor(grab) => {
-                    window_handle.set_cursor_grab(grab);
+                WindowRequest::GrabCursor(mode) => {
+                    window_handle.set_cursor_grab(mode);
+                }
+                WindowRequest::CursorPositionHint(position) => {
+                    window_handle.set_cursor_position_hint(position);
+                }
+                WindowRequest::Blur(blur) => {
+                    window_handle.set_blur(blur, None);
+
+                    // The blur is applied with the next commit of the surface.
+                    let window_update = window_updates.get_mut(window_id).unwrap();
+                    window_update.redraw_requested = true;
+                }
+                WindowRequest::BlurRegion(position, size) => {
+                    window_handle.set_blur(true, Some((position, size)));
+
+                    // The blur is applied with the next commit of the surface.
+                    let window_update = window_updates.get_mut(window_id).unwrap();
+                    window_update.redraw_requested = true;
+                }
+                WindowRequest::CursorHittest(hittest) => {
+                    window_handle.set_cursor_hittest(hittest);
+
+                    // The input region is applied with the next commit of the surface.
+                    let window_update = window_updates.get_mut(window_id).unwrap();
+                    window_update.redraw_requested = true;
+                }
+                WindowRequest::DragWindow => {
+                    window_handle.drag_window();
+                }
+                WindowRequest::DragResizeWindow(direction) => {
+                    window_handle.drag_resize_window(direction);
                 }
                 WindowRequest::Maximize(maximize) => {
                     if maximize {
@@ -310,12 +781,7 @@ pub fn handle_window_requests(winit_state: &mut WinitState) {
                     window_handle.window.set_minimized();
                 }
                 WindowRequest::Decorate(decorate) => {
-                    let decorations = match decorate {
-                        true => Decorations::FollowServer,
-                        false => Decorations::None,
-                    };
-
-                    window_handle.window.set_decorate(decorations);
+                    window_handle.window.set_decorated(decorate);
 
                     // We should refresh the frame to apply decorations change.
                     let window_update = window_updates.get_mut(&window_id).unwrap();
@@ -357,32 +823,162 @@ pub fn handle_window_requests(winit_state: &mut WinitState) {
                     let window_update = window_updates.get_mut(&window_id).unwrap();
                     window_update.refresh_frame = true;
                 }
+                WindowRequest::Resize(size) => {
+                    let window_update = window_updates.get_mut(window_id).unwrap();
+                    window_update.size = Some(size);
+                    window_update.redraw_requested = true;
+                }
                 WindowRequest::Redraw => {
                     let window_update = window_updates.get_mut(&window_id).unwrap();
                     window_update.redraw_requested = true;
                 }
                 WindowRequest::Theme(concept_config) => {
-                    window_handle.window.set_frame_config(concept_config);
+                    window_handle.set_frame_config(concept_config);
+                    window_handle.custom_frame_config = true;
 
                     // We should refresh the frame to apply new theme.
                     let window_update = window_updates.get_mut(&window_id).unwrap();
                     window_update.refresh_frame = true;
                 }
+                WindowRequest::EnabledButtons(buttons) => {
+                    window_handle.set_enabled_buttons(buttons);
+
+                    // We should refresh the frame to update the buttons.
+                    let window_update = window_updates.get_mut(window_id).unwrap();
+                    window_update.refresh_frame = true;
+                }
+                WindowRequest::PreferredTheme => {
+                    if window_handle.update_frame_theme(desktop_theme) {
+                        let window_update = window_updates.get_mut(window_id).unwrap();
+                        window_update.refresh_frame = true;
+                    }
+                }
                 WindowRequest::Close => {
                     // The window was requested to be closed.
                     windows_to_close.push(*window_id);
-
-                    // Send event that the window was destroyed.
-                    let event_sink = &mut winit_state.event_sink;
-                    event_sink.push_window_event(WindowEvent::Destroyed, *window_id);
                 }
             };
         }
     }
 
     // Close the windows.
-    for window in windows_to_close {
-        let _ = window_map.remove(&window);
-        let _ = window_updates.remove(&window);
+    for window_id in windows_to_close {
+        close_window(winit_state, window_id);
+    }
+}
+
+/// Closes a window, after the popups on top of it since xdg-shell only lets the topmost popup be
+/// destroyed.
+fn close_window(winit_state: &mut WinitState, window_id: WindowId) {
+    let children: Vec<WindowId> = winit_state
+        .window_map
+        .iter()
+        .filter(|(_, window_handle)| window_handle.popup_parent == Some(window_id))
+        .map(|(child_id, _)| *child_id)
+        .collect();
+    for child_id in children {
+        close_window(winit_state, child_id);
+    }
+
+    // The window may have been closed along with its parent already.
+    if winit_state.window_map.remove(&window_id).is_some() {
+        let _ = winit_state.window_updates.remove(&window_id);
+
+        // Send event that the window was destroyed.
+        let event_sink = &mut winit_state.event_sink;
+        event_sink.push_window_event(WindowEvent::Destroyed, window_id);
+    }
+}
+
+/// Returns the frame colors for a theme.
+fn concept_config(theme: Theme) -> ConceptConfig {
+    match theme {
+        Theme::Light => ConceptConfig::default(),
+        Theme::Dark => {
+            let icon_spec = ButtonColorSpec {
+                idle: ColorSpec::identical([0xFF, 0xE6, 0xE6, 0xE6].into()),
+                hovered: ColorSpec::identical([0xFF, 0xFF, 0xFF, 0xFF].into()),
+                disabled: ColorSpec::invisible(),
+            };
+            let button_spec = |hovered: [u8; 4]| {
+                (
+                    icon_spec,
+                    ButtonColorSpec {
+                        idle: ColorSpec::invisible(),
+                        hovered: ColorSpec::identical(hovered.into()),
+                        disabled: ColorSpec::invisible(),
+                    },
+                )
+            };
+
+            ConceptConfig {
+                primary_color: ColorSpec {
+                    active: [0xFF, 0x30, 0x30, 0x30].into(),
+                    inactive: [0xFF, 0x24, 0x24, 0x24].into(),
+                },
+                secondary_color: ColorSpec {
+                    active: [0xFF, 0x1A, 0x1A, 0x1A].into(),
+                    inactive: [0xFF, 0x48, 0x48, 0x48].into(),
+                },
+                // The same accents as the light theme.
+                close_button: Some(button_spec([0xFF, 0xD9, 0x43, 0x52])),
+                maximize_button: Some(button_spec([0xFF, 0x2D, 0xCB, 0x70])),
+                minimize_button: Some(button_spec([0xFF, 0x3C, 0xAD, 0xE8])),
+                title_font: Some(("sans".into(), 17.0)),
+                title_color: ColorSpec {
+                    active: [0xFF, 0xFF, 0xFF, 0xFF].into(),
+                    inactive: [0xFF, 0xB4, 0xB4, 0xB4].into(),
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_callback_deadline_starts_on_commit() {
+        let now = Instant::now();
+        let mut frame_callback = FrameCallbackState::default();
+
+        // A commit without a pending callback doesn't schedule anything.
+        frame_callback.committed(now);
+        assert_eq!(frame_callback.deadline(), None);
+
+        // The callback only counts once a commit scheduled it.
+        assert!(frame_callback.request());
+        assert!(!frame_callback.request());
+        assert_eq!(frame_callback.deadline(), None);
+        frame_callback.committed(now);
+        assert_eq!(
+            frame_callback.deadline(),
+            Some(now + FRAME_STARVATION_TIMEOUT)
+        );
+
+        // A later commit restarts the deadline.
+        let later = now + Duration::from_millis(500);
+        frame_callback.committed(later);
+        assert_eq!(
+            frame_callback.deadline(),
+            Some(later + FRAME_STARVATION_TIMEOUT)
+        );
+    }
+
+    #[test]
+    fn frame_callback_done_clears_deadline() {
+        let now = Instant::now();
+        let mut frame_callback = FrameCallbackState::default();
+        frame_callback.request();
+        frame_callback.committed(now);
+
+        frame_callback.done();
+        assert_eq!(frame_callback.deadline(), None);
+        frame_callback.committed(now);
+        assert_eq!(frame_callback.deadline(), None);
+
+        // The next callback can be requested once the previous one is done.
+        assert!(frame_callback.request());
     }
 }
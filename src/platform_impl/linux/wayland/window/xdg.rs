@@ -0,0 +1,761 @@
+//! The xdg-shell surfaces of winit windows and their frame.
+//!
+//! This follows SCTK's `Window`, which keeps the `xdg_surface` and the `xdg_toplevel` of the
+//! window to itself, while we need them to start interactive resizes, to parent windows to their
+//! owner and to create popups.
+
+use std::cell::RefCell;
+use std::cmp;
+use std::convert::TryInto;
+use std::rc::Rc;
+
+use sctk::reexports::client::protocol::wl_compositor::WlCompositor;
+use sctk::reexports::client::protocol::wl_output::WlOutput;
+use sctk::reexports::client::protocol::wl_seat::WlSeat;
+use sctk::reexports::client::protocol::wl_shm::WlShm;
+use sctk::reexports::client::protocol::wl_subcompositor::WlSubcompositor;
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::reexports::client::{DispatchData, Main};
+use sctk::reexports::protocols::xdg_shell::client::xdg_toplevel::State as FrameState;
+
+use sctk::environment::Environment;
+use sctk::seat::pointer::ThemeManager;
+use sctk::seat::{self, SeatListener};
+use sctk::window::{ConceptConfig, ConceptFrame, Frame, FrameRequest};
+
+use crate::platform::unix::{PopupConstraintAdjustment, PopupGravity};
+use crate::platform_impl::wayland::env::WinitEnv;
+use crate::platform_impl::wayland::protocols::xdg_decoration::client::zxdg_decoration_manager_v1::ZxdgDecorationManagerV1;
+use crate::platform_impl::wayland::protocols::xdg_decoration::client::zxdg_toplevel_decoration_v1::{
+    self, ZxdgToplevelDecorationV1,
+};
+use crate::platform_impl::wayland::protocols::xdg_shell::client::xdg_popup::{self, XdgPopup};
+use crate::platform_impl::wayland::protocols::xdg_shell::client::xdg_positioner::{
+    Anchor, ConstraintAdjustment, Gravity, XdgPositioner,
+};
+use crate::platform_impl::wayland::protocols::xdg_shell::client::xdg_surface::{self, XdgSurface};
+use crate::platform_impl::wayland::protocols::xdg_shell::client::xdg_toplevel::{
+    self, ResizeEdge, XdgToplevel,
+};
+use crate::platform_impl::wayland::protocols::xdg_shell::client::xdg_wm_base::XdgWmBase;
+use crate::platform_impl::PopupAttributes;
+use crate::window::ResizeDirection;
+
+pub use crate::platform_impl::wayland::protocols::xdg_shell::client::xdg_toplevel::State;
+
+/// The minimum size of the window, 2 pixels wide to work around a bug in mutter, see
+/// https://gitlab.gnome.org/GNOME/mutter/issues/259.
+const MIN_WINDOW_SIZE: (u32, u32) = (2, 1);
+
+/// An event of the window.
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// The compositor configured the window.
+    Configure {
+        /// The new size of the content of the window, without the frame, if the compositor
+        /// picked one.
+        new_size: Option<(u32, u32)>,
+
+        /// The states of the toplevel, which are always empty for popups.
+        states: Vec<State>,
+    },
+
+    /// The window was requested to be closed.
+    Close,
+
+    /// The popup was dismissed by the compositor, which already hid it.
+    Dismissed,
+
+    /// The frame needs to be redrawn.
+    Refresh,
+}
+
+type EventHandler = Box<dyn FnMut(Event, DispatchData<'_>)>;
+
+/// The configure which is applied once the `xdg_surface` configure ends the sequence.
+type PendingConfigure = Rc<RefCell<Option<(Option<(u32, u32)>, Vec<State>)>>>;
+
+/// The role of the `xdg_surface`.
+#[derive(Clone)]
+enum Role {
+    Toplevel(XdgToplevel),
+    Popup(XdgPopup),
+}
+
+impl Role {
+    fn xdg_toplevel(&self) -> Option<&XdgToplevel> {
+        match self {
+            Role::Toplevel(xdg_toplevel) => Some(xdg_toplevel),
+            Role::Popup(_) => None,
+        }
+    }
+}
+
+/// State shared with the handlers of the window and the frame.
+struct XdgWindowInner {
+    frame: Rc<RefCell<ConceptFrame>>,
+    role: Role,
+    event_handler: EventHandler,
+    min_size: (u32, u32),
+    max_size: Option<(u32, u32)>,
+    current_size: (u32, u32),
+
+    /// The size to go back to once the window isn't maximized, fullscreen or tiled anymore.
+    old_size: Option<(u32, u32)>,
+    decorated: bool,
+}
+
+/// A window with the `xdg_toplevel` or the `xdg_popup` role, and a `ConceptFrame` when the
+/// compositor doesn't decorate a toplevel.
+///
+/// The requests which only make sense for toplevels are ignored by popups.
+pub struct XdgWindow {
+    frame: Rc<RefCell<ConceptFrame>>,
+    surface: WlSurface,
+    xdg_surface: XdgSurface,
+    role: Role,
+    decoration: Option<ZxdgToplevelDecorationV1>,
+    inner: Rc<RefCell<Option<XdgWindowInner>>>,
+    _seat_listener: SeatListener,
+}
+
+impl XdgWindow {
+    /// Gives `surface` the toplevel role, with `initial_dims` as the size of its content.
+    ///
+    /// Returns `None` if the frame couldn't be created.
+    pub fn new_toplevel<F>(
+        env: &Environment<WinitEnv>,
+        wm_base: &XdgWmBase,
+        surface: WlSurface,
+        theme_manager: Option<ThemeManager>,
+        initial_dims: (u32, u32),
+        event_handler: F,
+    ) -> Option<Self>
+    where
+        F: FnMut(Event, DispatchData<'_>) + 'static,
+    {
+        Self::new(
+            env,
+            wm_base,
+            surface,
+            theme_manager,
+            initial_dims,
+            event_handler,
+            |xdg_surface, pending_configure, inner| {
+                let xdg_toplevel = xdg_surface.get_toplevel();
+                xdg_toplevel.quick_assign(move |_, event, dispatch_data| match event {
+                    xdg_toplevel::Event::Configure {
+                        width,
+                        height,
+                        states,
+                    } => {
+                        // A zero width or height leaves the size up to us.
+                        let new_size = if width == 0 || height == 0 {
+                            None
+                        } else {
+                            Some((width as u32, height as u32))
+                        };
+                        let states = states
+                            .chunks_exact(4)
+                            .map(|state| u32::from_ne_bytes(state.try_into().unwrap()))
+                            .flat_map(State::from_raw)
+                            .collect();
+                        *pending_configure.borrow_mut() = Some((new_size, states));
+                    }
+                    xdg_toplevel::Event::Close => {
+                        if let Some(ref mut inner) = *inner.borrow_mut() {
+                            (inner.event_handler)(Event::Close, dispatch_data);
+                        }
+                    }
+                    // The bounds and the capabilities of the compositor aren't used.
+                    _ => (),
+                });
+                Role::Toplevel(xdg_toplevel.detach())
+            },
+        )
+    }
+
+    /// Gives `surface` the popup role, placed relative to `parent` as set by `attributes`.
+    ///
+    /// The popup grabs the input of `grab`, a seat along with the serial of its latest press,
+    /// which lets the compositor dismiss it when the user clicks outside of it.
+    ///
+    /// Returns `None` if the frame couldn't be created.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_popup<F>(
+        env: &Environment<WinitEnv>,
+        wm_base: &XdgWmBase,
+        surface: WlSurface,
+        parent: &XdgWindow,
+        attributes: &PopupAttributes,
+        initial_dims: (u32, u32),
+        grab: Option<(WlSeat, u32)>,
+        event_handler: F,
+    ) -> Option<Self>
+    where
+        F: FnMut(Event, DispatchData<'_>) + 'static,
+    {
+        let positioner = create_positioner(wm_base, parent, attributes, initial_dims);
+        let popup = Self::new(
+            env,
+            wm_base,
+            surface,
+            None,
+            initial_dims,
+            event_handler,
+            |xdg_surface, pending_configure, inner| {
+                let xdg_popup = xdg_surface.get_popup(Some(&parent.xdg_surface), &positioner);
+                xdg_popup.quick_assign(move |_, event, dispatch_data| match event {
+                    xdg_popup::Event::Configure { width, height, .. } => {
+                        let new_size = (width.max(1) as u32, height.max(1) as u32);
+                        *pending_configure.borrow_mut() = Some((Some(new_size), Vec::new()));
+                    }
+                    xdg_popup::Event::PopupDone => {
+                        if let Some(ref mut inner) = *inner.borrow_mut() {
+                            (inner.event_handler)(Event::Dismissed, dispatch_data);
+                        }
+                    }
+                    // The popup is never repositioned.
+                    xdg_popup::Event::Repositioned { .. } => (),
+                });
+
+                // The grab must be taken before the initial commit.
+                if let Some((seat, serial)) = grab {
+                    xdg_popup.grab(&seat, serial);
+                }
+                Role::Popup(xdg_popup.detach())
+            },
+        );
+        positioner.destroy();
+        popup
+    }
+
+    fn new<F, R>(
+        env: &Environment<WinitEnv>,
+        wm_base: &XdgWmBase,
+        surface: WlSurface,
+        theme_manager: Option<ThemeManager>,
+        initial_dims: (u32, u32),
+        event_handler: F,
+        create_role: R,
+    ) -> Option<Self>
+    where
+        F: FnMut(Event, DispatchData<'_>) + 'static,
+        R: FnOnce(&Main<XdgSurface>, PendingConfigure, Rc<RefCell<Option<XdgWindowInner>>>) -> Role,
+    {
+        let compositor = env.require_global::<WlCompositor>();
+        let subcompositor = env.require_global::<WlSubcompositor>();
+        let shm = env.require_global::<WlShm>();
+
+        let inner = Rc::new(RefCell::new(None::<XdgWindowInner>));
+        let frame_inner = inner.clone();
+        let mut frame = ConceptFrame::init(
+            &surface,
+            &compositor,
+            &subcompositor,
+            &shm,
+            theme_manager,
+            Box::new(move |request, serial, dispatch_data| {
+                if let Some(ref mut inner) = *frame_inner.borrow_mut() {
+                    inner.handle_frame_request(request, serial, dispatch_data);
+                }
+            }),
+        )
+        .ok()?;
+
+        let pending_configure = PendingConfigure::default();
+        let surface_pending_configure = pending_configure.clone();
+        let surface_inner = inner.clone();
+        let xdg_surface = wm_base.get_xdg_surface(&surface);
+        xdg_surface.quick_assign(move |xdg_surface, event, dispatch_data| {
+            let xdg_surface::Event::Configure { serial } = event;
+            xdg_surface.ack_configure(serial);
+            let configure = surface_pending_configure.borrow_mut().take();
+            if let (Some((new_size, states)), Some(ref mut inner)) =
+                (configure, &mut *surface_inner.borrow_mut())
+            {
+                inner.configure(new_size, states, dispatch_data);
+            }
+        });
+
+        let role = create_role(&xdg_surface, pending_configure, inner.clone());
+        surface.commit();
+        let xdg_surface = xdg_surface.detach();
+
+        // Popups are never decorated.
+        let decoration_manager = role
+            .xdg_toplevel()
+            .and(env.get_global::<ZxdgDecorationManagerV1>());
+        if role.xdg_toplevel().is_some() && decoration_manager.is_none() {
+            // The compositor won't decorate the window, so we draw the frame.
+            frame.set_hidden(false);
+        }
+
+        frame.resize(initial_dims);
+        let frame = Rc::new(RefCell::new(frame));
+
+        {
+            let frame = frame.borrow();
+            if let Some(xdg_toplevel) = role.xdg_toplevel() {
+                let (min_width, min_height) =
+                    frame.add_borders(MIN_WINDOW_SIZE.0 as i32, MIN_WINDOW_SIZE.1 as i32);
+                xdg_toplevel.set_min_size(min_width, min_height);
+            }
+            let (width, height) = frame.add_borders(initial_dims.0 as i32, initial_dims.1 as i32);
+            let (x, y) = frame.location();
+            xdg_surface.set_window_geometry(x, y, width, height);
+        }
+
+        // The frame reacts to the pointers of all the seats.
+        let mut seats = Vec::<WlSeat>::new();
+        for seat in env.get_all_seats() {
+            seat::with_seat_data(&seat, |seat_data| {
+                if seat_data.has_pointer && !seat_data.defunct {
+                    seats.push(seat.detach());
+                    frame.borrow_mut().new_seat(&seat);
+                }
+            });
+        }
+
+        let seat_frame = frame.clone();
+        let seat_listener = env.listen_for_seats(move |seat, seat_data, _| {
+            let is_known = seats.contains(&seat);
+            if !is_known && seat_data.has_pointer && !seat_data.defunct {
+                seat_frame.borrow_mut().new_seat(&seat);
+                seats.push(seat.detach());
+            } else if is_known && (!seat_data.has_pointer || seat_data.defunct) {
+                seat_frame.borrow_mut().remove_seat(&seat);
+                seats.retain(|known_seat| known_seat != &*seat);
+            }
+        });
+
+        *inner.borrow_mut() = Some(XdgWindowInner {
+            frame: frame.clone(),
+            role: role.clone(),
+            event_handler: Box::new(event_handler),
+            min_size: MIN_WINDOW_SIZE,
+            max_size: None,
+            current_size: initial_dims,
+            old_size: None,
+            decorated: true,
+        });
+
+        let decoration = decoration_manager.map(|decoration_manager| {
+            let xdg_toplevel = role.xdg_toplevel().unwrap();
+            let decoration = decoration_manager.get_toplevel_decoration(xdg_toplevel);
+            let decoration_frame = frame.clone();
+            let decoration_inner = inner.clone();
+            decoration.quick_assign(move |_, event, _| {
+                use zxdg_toplevel_decoration_v1::{Event, Mode};
+
+                let Event::Configure { mode } = event;
+                let hidden = match mode {
+                    Mode::ServerSide => true,
+                    Mode::ClientSide => !decoration_inner
+                        .borrow()
+                        .as_ref()
+                        .is_some_and(|inner| inner.decorated),
+                };
+                decoration_frame.borrow_mut().set_hidden(hidden);
+            });
+            decoration.detach()
+        });
+
+        Some(Self {
+            frame,
+            surface,
+            xdg_surface,
+            role,
+            decoration,
+            inner,
+            _seat_listener: seat_listener,
+        })
+    }
+
+    pub fn surface(&self) -> &WlSurface {
+        &self.surface
+    }
+
+    pub fn is_popup(&self) -> bool {
+        matches!(self.role, Role::Popup(_))
+    }
+
+    /// Redraws the frame, which is needed after every change of its size or state.
+    pub fn refresh(&mut self) {
+        self.frame.borrow_mut().redraw();
+    }
+
+    pub fn set_title(&self, mut title: String) {
+        // Keep the title from blowing up the protocol messages.
+        if title.len() > 1024 {
+            let mut len = 1024;
+            while !title.is_char_boundary(len) {
+                len -= 1;
+            }
+            title.truncate(len);
+        }
+        self.frame.borrow_mut().set_title(title.clone());
+        if let Some(xdg_toplevel) = self.role.xdg_toplevel() {
+            xdg_toplevel.set_title(title);
+        }
+    }
+
+    pub fn set_app_id(&self, app_id: String) {
+        if let Some(xdg_toplevel) = self.role.xdg_toplevel() {
+            xdg_toplevel.set_app_id(app_id);
+        }
+    }
+
+    /// Sets whether the window is decorated, by the compositor if it wants to or by our frame.
+    ///
+    /// The decoration object can't be recreated, so undecorated windows ask for client side
+    /// decorations and hide the frame.
+    pub fn set_decorated(&mut self, decorated: bool) {
+        use zxdg_toplevel_decoration_v1::Mode;
+
+        if self.is_popup() {
+            return;
+        }
+
+        if let Some(ref mut inner) = *self.inner.borrow_mut() {
+            inner.decorated = decorated;
+        }
+
+        match self.decoration {
+            Some(ref decoration) if decorated => decoration.unset_mode(),
+            Some(ref decoration) => {
+                decoration.set_mode(Mode::ClientSide);
+                self.frame.borrow_mut().set_hidden(true);
+            }
+            None => self.frame.borrow_mut().set_hidden(!decorated),
+        }
+    }
+
+    /// Sets whether the window may be resized by the user, by locking its minimum and maximum
+    /// sizes to the current size.
+    pub fn set_resizable(&self, resizable: bool) {
+        let xdg_toplevel = match self.role.xdg_toplevel() {
+            Some(xdg_toplevel) => xdg_toplevel,
+            None => return,
+        };
+
+        let mut frame = self.frame.borrow_mut();
+        frame.set_resizable(resizable);
+        if let Some(ref inner) = *self.inner.borrow() {
+            let (min_size, max_size) = if resizable {
+                (inner.min_size, inner.max_size)
+            } else {
+                (inner.current_size, Some(inner.current_size))
+            };
+            let (min_width, min_height) = frame.add_borders(min_size.0 as i32, min_size.1 as i32);
+            xdg_toplevel.set_min_size(min_width, min_height);
+            let (max_width, max_height) = max_size.map_or((0, 0), |(width, height)| {
+                frame.add_borders(width as i32, height as i32)
+            });
+            xdg_toplevel.set_max_size(max_width, max_height);
+        }
+    }
+
+    /// Resizes the frame and the window geometry to a new size of the content.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        let (width, height) = (cmp::max(width, 1), cmp::max(height, 1));
+        if let Some(ref mut inner) = *self.inner.borrow_mut() {
+            inner.current_size = (width, height);
+        }
+        let mut frame = self.frame.borrow_mut();
+        frame.resize((width, height));
+        let (width, height) = frame.add_borders(width as i32, height as i32);
+        let (x, y) = frame.location();
+        self.xdg_surface.set_window_geometry(x, y, width, height);
+    }
+
+    pub fn set_maximized(&self) {
+        if let Some(xdg_toplevel) = self.role.xdg_toplevel() {
+            xdg_toplevel.set_maximized();
+        }
+    }
+
+    pub fn unset_maximized(&self) {
+        if let Some(xdg_toplevel) = self.role.xdg_toplevel() {
+            xdg_toplevel.unset_maximized();
+        }
+    }
+
+    pub fn set_minimized(&self) {
+        if let Some(xdg_toplevel) = self.role.xdg_toplevel() {
+            xdg_toplevel.set_minimized();
+        }
+    }
+
+    pub fn set_fullscreen(&self, output: Option<&WlOutput>) {
+        if let Some(xdg_toplevel) = self.role.xdg_toplevel() {
+            xdg_toplevel.set_fullscreen(output);
+        }
+    }
+
+    pub fn unset_fullscreen(&self) {
+        if let Some(xdg_toplevel) = self.role.xdg_toplevel() {
+            xdg_toplevel.unset_fullscreen();
+        }
+    }
+
+    /// Sets the minimum size of the content, or resets it with `None`.
+    pub fn set_min_size(&mut self, size: Option<(u32, u32)>) {
+        let size = size.unwrap_or(MIN_WINDOW_SIZE);
+        if let Some(xdg_toplevel) = self.role.xdg_toplevel() {
+            let (width, height) = self
+                .frame
+                .borrow()
+                .add_borders(size.0 as i32, size.1 as i32);
+            xdg_toplevel.set_min_size(width, height);
+        }
+        if let Some(ref mut inner) = *self.inner.borrow_mut() {
+            inner.min_size = size;
+        }
+    }
+
+    /// Sets the maximum size of the content, or removes it with `None`.
+    pub fn set_max_size(&mut self, size: Option<(u32, u32)>) {
+        if let Some(xdg_toplevel) = self.role.xdg_toplevel() {
+            // A maximum size of zero means no maximum.
+            let (width, height) = size.map_or((0, 0), |(width, height)| {
+                self.frame.borrow().add_borders(width as i32, height as i32)
+            });
+            xdg_toplevel.set_max_size(width, height);
+        }
+        if let Some(ref mut inner) = *self.inner.borrow_mut() {
+            inner.max_size = size;
+        }
+    }
+
+    pub fn set_frame_config(&mut self, config: ConceptConfig) {
+        self.frame.borrow_mut().set_config(config);
+    }
+
+    /// Whether the compositor tells when the window is `Suspended`, which is new in version 6.
+    pub fn supports_suspended(&self) -> bool {
+        self.role
+            .xdg_toplevel()
+            .is_some_and(|xdg_toplevel| xdg_toplevel.as_ref().version() >= 6)
+    }
+
+    /// Makes `parent` the parent of the window, which the compositor keeps the window above.
+    ///
+    /// Only toplevels have parents that way, popups keep the parent they were created with.
+    pub fn set_parent(&self, parent: Option<&XdgWindow>) {
+        if let Some(xdg_toplevel) = self.role.xdg_toplevel() {
+            let parent = parent.and_then(|parent| parent.role.xdg_toplevel());
+            xdg_toplevel.set_parent(parent);
+        }
+    }
+
+    /// Starts an interactive move, in response to the input event with `serial`.
+    pub fn start_interactive_move(&self, seat: &WlSeat, serial: u32) {
+        if let Some(xdg_toplevel) = self.role.xdg_toplevel() {
+            xdg_toplevel._move(seat, serial);
+        }
+    }
+
+    /// Starts an interactive resize, in response to the input event with `serial`.
+    pub fn start_interactive_resize(&self, seat: &WlSeat, serial: u32, direction: ResizeDirection) {
+        if let Some(xdg_toplevel) = self.role.xdg_toplevel() {
+            xdg_toplevel.resize(seat, serial, direction.into());
+        }
+    }
+}
+
+impl Drop for XdgWindow {
+    fn drop(&mut self) {
+        self.inner.borrow_mut().take();
+        if let Some(decoration) = self.decoration.take() {
+            decoration.destroy();
+        }
+        match self.role {
+            Role::Toplevel(ref xdg_toplevel) => xdg_toplevel.destroy(),
+            Role::Popup(ref xdg_popup) => xdg_popup.destroy(),
+        }
+        self.xdg_surface.destroy();
+    }
+}
+
+impl XdgWindowInner {
+    fn configure(
+        &mut self,
+        new_size: Option<(u32, u32)>,
+        states: Vec<State>,
+        mut dispatch_data: DispatchData<'_>,
+    ) {
+        let mut frame = self.frame.borrow_mut();
+
+        // The frame must know whether the window is fullscreen before its borders are subtracted.
+        let frame_states = states
+            .iter()
+            .flat_map(|state| FrameState::from_raw(state.to_raw()))
+            .collect::<Vec<_>>();
+        let need_refresh = frame.set_states(&frame_states);
+
+        let mut new_size = new_size.map(|(width, height)| {
+            let (width, height) = frame.subtract_borders(width as i32, height as i32);
+            let (mut width, mut height) = (width.max(1) as u32, height.max(1) as u32);
+            width = width.max(self.min_size.0);
+            height = height.max(self.min_size.1);
+            if let Some((max_width, max_height)) = self.max_size {
+                width = width.min(max_width);
+                height = height.min(max_height);
+            }
+            (width, height)
+        });
+        drop(frame);
+
+        let constrained = states.iter().any(|state| {
+            matches!(
+                state,
+                State::Maximized
+                    | State::Fullscreen
+                    | State::TiledTop
+                    | State::TiledRight
+                    | State::TiledBottom
+                    | State::TiledLeft
+            )
+        });
+        if constrained {
+            // Remember the size to restore once the window is free again.
+            if self.old_size.is_none() {
+                self.old_size = Some(self.current_size);
+            }
+        } else if new_size.is_none() {
+            new_size = self.old_size.take();
+        } else {
+            self.old_size = None;
+        }
+
+        if need_refresh {
+            (self.event_handler)(Event::Refresh, dispatch_data.reborrow());
+        }
+        (self.event_handler)(Event::Configure { new_size, states }, dispatch_data);
+    }
+
+    fn handle_frame_request(
+        &mut self,
+        request: FrameRequest,
+        serial: u32,
+        dispatch_data: DispatchData<'_>,
+    ) {
+        let xdg_toplevel = match self.role.xdg_toplevel() {
+            Some(xdg_toplevel) => xdg_toplevel,
+            // Popups have no frame.
+            None => return,
+        };
+
+        match request {
+            FrameRequest::Minimize => xdg_toplevel.set_minimized(),
+            FrameRequest::Maximize => xdg_toplevel.set_maximized(),
+            FrameRequest::UnMaximize => xdg_toplevel.unset_maximized(),
+            FrameRequest::Move(seat) => xdg_toplevel._move(&seat, serial),
+            FrameRequest::Resize(seat, edge) => {
+                if let Some(edge) = ResizeEdge::from_raw(edge.to_raw()) {
+                    xdg_toplevel.resize(&seat, serial, edge);
+                }
+            }
+            FrameRequest::ShowMenu(seat, x, y) => {
+                xdg_toplevel.show_window_menu(&seat, serial, x, y)
+            }
+            FrameRequest::Close => (self.event_handler)(Event::Close, dispatch_data),
+            FrameRequest::Refresh => (self.event_handler)(Event::Refresh, dispatch_data),
+        }
+    }
+}
+
+/// Creates the positioner of a popup of `parent` of size `size`, in logical pixels.
+fn create_positioner(
+    wm_base: &XdgWmBase,
+    parent: &XdgWindow,
+    attributes: &PopupAttributes,
+    size: (u32, u32),
+) -> Main<XdgPositioner> {
+    let positioner = wm_base.create_positioner();
+
+    // The positioner rejects empty sizes.
+    positioner.set_size(size.0.max(1) as i32, size.1.max(1) as i32);
+
+    let position = attributes
+        .anchor_rect
+        .position
+        .to_logical::<i32>(attributes.scale_factor);
+    let anchor_size = attributes
+        .anchor_rect
+        .size
+        .to_logical::<u32>(attributes.scale_factor);
+    // The anchor rectangle is relative to the window geometry, which includes the frame.
+    let (x, y) = parent.frame.borrow().location();
+    positioner.set_anchor_rect(
+        position.x - x,
+        position.y - y,
+        anchor_size.width.max(1) as i32,
+        anchor_size.height.max(1) as i32,
+    );
+
+    // The popup extends from the matching edge or corner of the anchor rectangle.
+    let (anchor, gravity) = match attributes.gravity {
+        PopupGravity::None => (Anchor::None, Gravity::None),
+        PopupGravity::Top => (Anchor::Top, Gravity::Top),
+        PopupGravity::Bottom => (Anchor::Bottom, Gravity::Bottom),
+        PopupGravity::Left => (Anchor::Left, Gravity::Left),
+        PopupGravity::Right => (Anchor::Right, Gravity::Right),
+        PopupGravity::TopLeft => (Anchor::TopLeft, Gravity::TopLeft),
+        PopupGravity::BottomLeft => (Anchor::BottomLeft, Gravity::BottomLeft),
+        PopupGravity::TopRight => (Anchor::TopRight, Gravity::TopRight),
+        PopupGravity::BottomRight => (Anchor::BottomRight, Gravity::BottomRight),
+    };
+    positioner.set_anchor(anchor);
+    positioner.set_gravity(gravity);
+
+    let adjustments = [
+        (
+            PopupConstraintAdjustment::SLIDE_X,
+            ConstraintAdjustment::SlideX,
+        ),
+        (
+            PopupConstraintAdjustment::SLIDE_Y,
+            ConstraintAdjustment::SlideY,
+        ),
+        (
+            PopupConstraintAdjustment::FLIP_X,
+            ConstraintAdjustment::FlipX,
+        ),
+        (
+            PopupConstraintAdjustment::FLIP_Y,
+            ConstraintAdjustment::FlipY,
+        ),
+        (
+            PopupConstraintAdjustment::RESIZE_X,
+            ConstraintAdjustment::ResizeX,
+        ),
+        (
+            PopupConstraintAdjustment::RESIZE_Y,
+            ConstraintAdjustment::ResizeY,
+        ),
+    ];
+    let constraint_adjustment = adjustments
+        .iter()
+        .filter(|(adjustment, _)| attributes.constraint_adjustment.contains(*adjustment))
+        .fold(ConstraintAdjustment::None, |flags, (_, flag)| flags | *flag);
+    positioner.set_constraint_adjustment(constraint_adjustment.bits());
+
+    positioner
+}
+
+impl From<ResizeDirection> for ResizeEdge {
+    fn from(direction: ResizeDirection) -> Self {
+        match direction {
+            ResizeDirection::East => ResizeEdge::Right,
+            ResizeDirection::North => ResizeEdge::Top,
+            ResizeDirection::NorthEast => ResizeEdge::TopRight,
+            ResizeDirection::NorthWest => ResizeEdge::TopLeft,
+            ResizeDirection::South => ResizeEdge::Bottom,
+            ResizeDirection::SouthEast => ResizeEdge::BottomRight,
+            ResizeDirection::SouthWest => ResizeEdge::BottomLeft,
+            ResizeDirection::West => ResizeEdge::Left,
+        }
+    }
+}
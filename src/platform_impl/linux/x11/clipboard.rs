@@ -0,0 +1,424 @@
+use std::{cell::RefCell, collections::HashMap, ffi::CString, os::raw::*, rc::Rc, sync::Arc};
+
+use super::{ffi, util, XConnection, XError};
+use crate::{clipboard::ClipboardKind, error::ExternalError, platform_impl::platform::OsError};
+
+/// The MIME type of text, which older applications know as `UTF8_STRING`.
+const TEXT_MIME_TYPE: &str = "text/plain;charset=utf-8";
+
+#[derive(Debug)]
+struct ClipboardAtoms {
+    clipboard: ffi::Atom,
+    targets: ffi::Atom,
+    timestamp: ffi::Atom,
+    incr: ffi::Atom,
+    utf8_string: ffi::Atom,
+    /// The property of our window which receives the contents of the selections.
+    selection: ffi::Atom,
+    /// The property of our window which is appended to, to get the time of the server.
+    time: ffi::Atom,
+}
+
+/// The contents of a selection we own.
+struct OwnedSelection {
+    contents: Rc<HashMap<String, Vec<c_uchar>>>,
+    /// The targets the contents are offered as, with the MIME type of each.
+    targets: Vec<(ffi::Atom, String)>,
+    /// When we took over the selection.
+    time: ffi::Time,
+}
+
+/// Contents which are sent incrementally, because they don't fit in a single request.
+struct Transfer {
+    contents: Rc<HashMap<String, Vec<c_uchar>>>,
+    mime_type: String,
+    target: ffi::Atom,
+    /// How much was sent so far.
+    offset: usize,
+}
+
+/// The clipboard and the primary selection, which are owned by a window that is never mapped, so
+/// that they outlive the windows of the application.
+pub struct Clipboard {
+    xconn: Arc<XConnection>,
+    window: ffi::Window,
+    atoms: ClipboardAtoms,
+    /// The selections we own.
+    owned: RefCell<HashMap<ffi::Atom, OwnedSelection>>,
+    /// The incremental transfers in progress, by requestor window and property.
+    transfers: RefCell<HashMap<(ffi::Window, ffi::Atom), Transfer>>,
+    /// The size of the largest contents which are sent at once.
+    max_chunk_size: usize,
+}
+
+impl Clipboard {
+    pub fn new(xconn: Arc<XConnection>, root: ffi::Window) -> Result<Self, XError> {
+        let names = [
+            b"CLIPBOARD\0".as_ptr() as *mut c_char,
+            b"TARGETS\0".as_ptr() as *mut c_char,
+            b"TIMESTAMP\0".as_ptr() as *mut c_char,
+            b"INCR\0".as_ptr() as *mut c_char,
+            b"UTF8_STRING\0".as_ptr() as *mut c_char,
+            b"_WINIT_SELECTION\0".as_ptr() as *mut c_char,
+            b"_WINIT_TIME\0".as_ptr() as *mut c_char,
+        ];
+        let atoms = unsafe { xconn.get_atoms(&names) }?;
+        let atoms = ClipboardAtoms {
+            clipboard: atoms[0],
+            targets: atoms[1],
+            timestamp: atoms[2],
+            incr: atoms[3],
+            utf8_string: atoms[4],
+            selection: atoms[5],
+            time: atoms[6],
+        };
+
+        let window = unsafe {
+            let window = (xconn.xlib.XCreateSimpleWindow)(xconn.display, root, 0, 0, 1, 1, 0, 0, 0);
+            (xconn.xlib.XSelectInput)(xconn.display, window, ffi::PropertyChangeMask);
+            window
+        };
+        xconn.check_errors()?;
+
+        // The maximum request size is in 4-byte units, and a quarter of it leaves plenty of room
+        // for the rest of the request.
+        let max_chunk_size = unsafe { (xconn.xlib.XMaxRequestSize)(xconn.display) } as usize;
+
+        Ok(Clipboard {
+            xconn,
+            window,
+            atoms,
+            owned: Default::default(),
+            transfers: Default::default(),
+            max_chunk_size,
+        })
+    }
+
+    fn selection(&self, kind: ClipboardKind) -> ffi::Atom {
+        match kind {
+            ClipboardKind::Clipboard => self.atoms.clipboard,
+            ClipboardKind::Primary => ffi::XA_PRIMARY,
+        }
+    }
+
+    fn mime_type_atom(&self, mime_type: &str) -> Option<ffi::Atom> {
+        CString::new(mime_type)
+            .ok()
+            .map(|mime_type| self.xconn.get_atom(mime_type))
+    }
+
+    /// Returns the current time of the server, which `XSetSelectionOwner` should be given instead
+    /// of `CurrentTime`.
+    fn server_time(&self) -> Result<ffi::Time, util::SelectionError> {
+        // Appending nothing to a property still notifies about it, with the time of the change.
+        self.xconn
+            .change_property::<c_uchar>(
+                self.window,
+                self.atoms.time,
+                ffi::XA_INTEGER,
+                util::PropMode::Append,
+                &[],
+            )
+            .flush()?;
+        let (window, time) = (self.window, self.atoms.time);
+        let event = self.xconn.wait_for_event(
+            std::time::Instant::now() + util::SELECTION_TIMEOUT,
+            |event| {
+                if event.get_type() != ffi::PropertyNotify {
+                    return false;
+                }
+                let event: &ffi::XPropertyEvent = event.as_ref();
+                event.window == window && event.atom == time
+            },
+        )?;
+        let event: &ffi::XPropertyEvent = event.as_ref();
+        Ok(event.time)
+    }
+
+    pub fn set_contents(
+        &self,
+        kind: ClipboardKind,
+        contents: HashMap<String, Vec<u8>>,
+    ) -> Result<(), ExternalError> {
+        let selection = self.selection(kind);
+        if contents.is_empty() {
+            if self.owned.borrow_mut().remove(&selection).is_some() {
+                unsafe {
+                    (self.xconn.xlib.XSetSelectionOwner)(
+                        self.xconn.display,
+                        selection,
+                        0,
+                        ffi::CurrentTime,
+                    );
+                }
+            }
+            return self.xconn.flush_requests().map_err(to_external_error);
+        }
+
+        let mut targets = Vec::with_capacity(contents.len() + 1);
+        for mime_type in contents.keys() {
+            if let Some(atom) = self.mime_type_atom(mime_type) {
+                targets.push((atom, mime_type.clone()));
+            }
+            if mime_type == TEXT_MIME_TYPE {
+                targets.push((self.atoms.utf8_string, mime_type.clone()));
+            }
+        }
+
+        let time = self.server_time().map_err(selection_error)?;
+        let owner = unsafe {
+            (self.xconn.xlib.XSetSelectionOwner)(self.xconn.display, selection, self.window, time);
+            (self.xconn.xlib.XGetSelectionOwner)(self.xconn.display, selection)
+        };
+        self.xconn.check_errors().map_err(to_external_error)?;
+        if owner != self.window {
+            return Err(ExternalError::Os(os_error!(OsError::XMisc(
+                "Failed to take over the selection"
+            ))));
+        }
+
+        self.owned.borrow_mut().insert(
+            selection,
+            OwnedSelection {
+                contents: Rc::new(contents),
+                targets,
+                time,
+            },
+        );
+        Ok(())
+    }
+
+    pub fn get_contents(
+        &self,
+        kind: ClipboardKind,
+        mime_type: &str,
+    ) -> Result<Option<Vec<u8>>, ExternalError> {
+        let selection = self.selection(kind);
+        // Converting our own selection would wait for ourselves.
+        if let Some(owned) = self.owned.borrow().get(&selection) {
+            return Ok(owned.contents.get(mime_type).cloned());
+        }
+
+        let target = match self.mime_type_atom(mime_type) {
+            Some(target) => target,
+            None => return Ok(None),
+        };
+        match self.convert(selection, target)? {
+            None if mime_type == TEXT_MIME_TYPE => self.convert(selection, self.atoms.utf8_string),
+            contents => Ok(contents),
+        }
+    }
+
+    /// Reads `selection` converted to `target`, which is `None` if the owner can't convert it.
+    fn convert(
+        &self,
+        selection: ffi::Atom,
+        target: ffi::Atom,
+    ) -> Result<Option<Vec<u8>>, ExternalError> {
+        self.xconn
+            .convert_selection(
+                selection,
+                target,
+                self.atoms.selection,
+                self.window,
+                ffi::CurrentTime,
+            )
+            .flush()
+            .map_err(to_external_error)?;
+        let property = match self
+            .xconn
+            .wait_for_selection(self.window, selection, target)
+            .map_err(selection_error)?
+        {
+            Some(property) => property,
+            None => return Ok(None),
+        };
+        let contents = self
+            .xconn
+            .read_selection(self.window, property)
+            .map_err(selection_error)?;
+        Ok(contents.map(|(_, contents)| contents))
+    }
+
+    /// Answers another application which asks for the contents of a selection we own.
+    pub fn handle_selection_request(&self, request: &ffi::XSelectionRequestEvent) {
+        if request.owner != self.window {
+            return;
+        }
+
+        // Obsolete clients don't specify a property, and expect the target to be used.
+        let property = if request.property == 0 {
+            request.target
+        } else {
+            request.property
+        };
+        let accepted = match self.owned.borrow().get(&request.selection) {
+            // Requests from before we took over the selection are for the previous owner.
+            Some(owned) if request.time == ffi::CurrentTime || request.time >= owned.time => {
+                self.send_target(owned, request.requestor, request.target, property)
+            }
+            _ => false,
+        };
+
+        let notify = ffi::XSelectionEvent {
+            type_: ffi::SelectionNotify,
+            serial: 0,
+            send_event: ffi::True,
+            display: self.xconn.display,
+            requestor: request.requestor,
+            selection: request.selection,
+            target: request.target,
+            property: if accepted { property } else { 0 },
+            time: request.time,
+        };
+        self.xconn
+            .send_event(request.requestor, None, notify)
+            .queue();
+        // The requestor may be gone already, which is none of our business.
+        let _ = self.xconn.sync_with_server();
+    }
+
+    /// Stores the contents of `owned` converted to `target` in `property` of `requestor`, and
+    /// returns whether the selection can be converted.
+    fn send_target(
+        &self,
+        owned: &OwnedSelection,
+        requestor: ffi::Window,
+        target: ffi::Atom,
+        property: ffi::Atom,
+    ) -> bool {
+        if target == self.atoms.targets {
+            let mut targets = vec![self.atoms.targets, self.atoms.timestamp];
+            targets.extend(owned.targets.iter().map(|&(atom, _)| atom));
+            self.xconn
+                .change_property(
+                    requestor,
+                    property,
+                    ffi::XA_ATOM,
+                    util::PropMode::Replace,
+                    &targets,
+                )
+                .queue();
+            return true;
+        }
+
+        if target == self.atoms.timestamp {
+            self.xconn
+                .change_property(
+                    requestor,
+                    property,
+                    ffi::XA_INTEGER,
+                    util::PropMode::Replace,
+                    &[owned.time as c_ulong],
+                )
+                .queue();
+            return true;
+        }
+
+        let mime_type = match owned.targets.iter().find(|&&(atom, _)| atom == target) {
+            Some((_, mime_type)) => mime_type,
+            None => return false,
+        };
+        let data = &owned.contents[mime_type];
+        if data.len() <= self.max_chunk_size {
+            self.xconn
+                .change_property(requestor, property, target, util::PropMode::Replace, data)
+                .queue();
+            return true;
+        }
+
+        // The requestor deletes the property whenever it's ready for the next chunk.
+        unsafe {
+            (self.xconn.xlib.XSelectInput)(self.xconn.display, requestor, ffi::PropertyChangeMask);
+        }
+        self.xconn
+            .change_property(
+                requestor,
+                property,
+                self.atoms.incr,
+                util::PropMode::Replace,
+                &[data.len() as c_ulong],
+            )
+            .queue();
+        self.transfers.borrow_mut().insert(
+            (requestor, property),
+            Transfer {
+                contents: Rc::clone(&owned.contents),
+                mime_type: mime_type.clone(),
+                target,
+                offset: 0,
+            },
+        );
+        true
+    }
+
+    /// Sends the next chunk of an incremental transfer, once the requestor deleted the previous
+    /// one.
+    pub fn handle_property_notify(&self, event: &ffi::XPropertyEvent) {
+        if event.state != ffi::PropertyDelete {
+            return;
+        }
+
+        let key = (event.window, event.atom);
+        let mut transfers = self.transfers.borrow_mut();
+        let transfer = match transfers.get_mut(&key) {
+            Some(transfer) => transfer,
+            None => return,
+        };
+
+        // The transfer ends with an empty chunk.
+        let data = &transfer.contents[&transfer.mime_type];
+        let end = data.len().min(transfer.offset + self.max_chunk_size);
+        self.xconn
+            .change_property(
+                event.window,
+                event.atom,
+                transfer.target,
+                util::PropMode::Replace,
+                &data[transfer.offset..end],
+            )
+            .queue();
+        let done = transfer.offset == end;
+        transfer.offset = end;
+
+        if done {
+            transfers.remove(&key);
+        }
+        // Only the transfers to the requestor need its events.
+        if !transfers.keys().any(|&(window, _)| window == event.window) {
+            unsafe {
+                (self.xconn.xlib.XSelectInput)(self.xconn.display, event.window, ffi::NoEventMask);
+            }
+        }
+        let _ = self.xconn.sync_with_server();
+    }
+
+    /// Forgets the contents of a selection which another application took over.
+    pub fn handle_selection_clear(&self, event: &ffi::XSelectionClearEvent) {
+        if event.window == self.window {
+            self.owned.borrow_mut().remove(&event.selection);
+        }
+    }
+}
+
+impl Drop for Clipboard {
+    fn drop(&mut self) {
+        unsafe {
+            (self.xconn.xlib.XDestroyWindow)(self.xconn.display, self.window);
+        }
+        let _ = self.xconn.flush_requests();
+    }
+}
+
+fn to_external_error(error: XError) -> ExternalError {
+    ExternalError::Os(os_error!(OsError::XError(error)))
+}
+
+fn selection_error(error: util::SelectionError) -> ExternalError {
+    match error {
+        util::SelectionError::XError(error) => to_external_error(error),
+        util::SelectionError::Timeout => ExternalError::Os(os_error!(OsError::XMisc(
+            "The owner of the selection didn't answer in time"
+        ))),
+    }
+}
@@ -169,23 +169,24 @@ impl Dnd {
             .get_property(source_window, self.atoms.type_list, ffi::XA_ATOM)
     }
 
-    pub unsafe fn convert_selection(&self, window: c_ulong, time: c_ulong) {
-        (self.xconn.xlib.XConvertSelection)(
-            self.xconn.display,
-            self.atoms.selection,
-            self.atoms.uri_list,
-            self.atoms.selection,
-            window,
-            time,
-        );
+    pub fn convert_selection(&self, window: c_ulong, time: c_ulong) {
+        self.xconn
+            .convert_selection(
+                self.atoms.selection,
+                self.atoms.uri_list,
+                self.atoms.selection,
+                window,
+                time,
+            )
+            .queue();
     }
 
-    pub unsafe fn read_data(
-        &self,
-        window: c_ulong,
-    ) -> Result<Vec<c_uchar>, util::GetPropertyError> {
-        self.xconn
-            .get_property(window, self.atoms.selection, self.atoms.uri_list)
+    /// Reads the dropped URIs, which is `None` if the source sent something else.
+    pub fn read_data(&self, window: c_ulong) -> Result<Option<Vec<c_uchar>>, util::SelectionError> {
+        match self.xconn.read_selection(window, self.atoms.selection)? {
+            Some((property_type, data)) if property_type == self.atoms.uri_list => Ok(Some(data)),
+            _ => Ok(None),
+        }
     }
 
     pub fn parse_data(&self, data: &mut Vec<c_uchar>) -> Result<Vec<PathBuf>, DndDataParseError> {
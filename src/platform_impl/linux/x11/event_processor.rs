@@ -1,4 +1,11 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc, slice, sync::Arc};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+    slice,
+    sync::{Arc, Weak},
+    time::Instant,
+};
 
 use libc::{c_char, c_int, c_long, c_uint, c_ulong};
 
@@ -37,6 +44,8 @@ pub(super) struct EventProcessor<T: 'static> {
     pub(super) first_touch: Option<u64>,
     // Currently focused window belonging to this process
     pub(super) active_window: Option<ffi::Window>,
+    // Window of the XSETTINGS manager, which owns the desktop settings
+    pub(super) xsettings_window: Cell<Option<ffi::Window>>,
 }
 
 impl<T: 'static> EventProcessor<T> {
@@ -78,6 +87,73 @@ impl<T: 'static> EventProcessor<T> {
         self.with_window(window_id, |_| ()).is_some()
     }
 
+    /// Finds the current XSETTINGS manager and reads the desktop theme from it, sending
+    /// `ThemeChanged` to the windows following the desktop theme if it changed. The cursors of
+    /// the windows are reloaded if the cursor theme changed.
+    pub(super) fn update_xsettings<F>(&self, mut callback: F)
+    where
+        F: FnMut(Event<'_, T>),
+    {
+        let wt = get_xtarget(&self.target);
+        let xsettings_window = wt.xconn.xsettings_window();
+        self.xsettings_window.set(xsettings_window);
+        if let Some(xsettings_window) = xsettings_window {
+            // `PropertyNotify` tells when the settings change, and `DestroyNotify` when the
+            // manager exits.
+            unsafe {
+                (wt.xconn.xlib.XSelectInput)(
+                    wt.xconn.display,
+                    xsettings_window,
+                    ffi::PropertyChangeMask | ffi::StructureNotifyMask,
+                );
+            }
+            // The manager may have exited in the meantime, which isn't an error.
+            let _ = wt.xconn.sync_with_server();
+        }
+
+        // The lock is released before the windows read the settings back.
+        let (cursor_theme_changed, desktop_theme_changed) = {
+            let mut xsettings = wt.xsettings.lock();
+            (
+                wt.xconn
+                    .update_cached_cursor_theme(&mut xsettings, xsettings_window),
+                wt.xconn
+                    .update_cached_desktop_theme(&mut xsettings, xsettings_window),
+            )
+        };
+
+        if cursor_theme_changed {
+            for window in wt.windows.borrow().values() {
+                if let Some(window) = window.upgrade() {
+                    window.reload_cursor();
+                }
+            }
+        }
+
+        if !desktop_theme_changed {
+            return;
+        }
+        let theme = match wt.xsettings.lock().desktop_theme {
+            Some(theme) => theme,
+            None => return,
+        };
+
+        let window_ids: Vec<_> = wt
+            .windows
+            .borrow()
+            .values()
+            .filter_map(|window| window.upgrade())
+            .filter(|window| window.shared_state.lock().preferred_theme.is_none())
+            .map(|window| window.id())
+            .collect();
+        for window_id in window_ids {
+            callback(Event::WindowEvent {
+                window_id: mkwid(window_id.0),
+                event: WindowEvent::ThemeChanged(theme),
+            });
+        }
+    }
+
     pub(super) fn poll(&self) -> bool {
         let wt = get_xtarget(&self.target);
         let result = unsafe { (wt.xconn.xlib.XPending)(wt.xconn.display) };
@@ -132,6 +208,27 @@ impl<T: 'static> EventProcessor<T> {
             return;
         }
 
+        // Input to a window is swallowed while it has a modal child.
+        let mut callback = |event: Event<'_, T>| {
+            if let Event::WindowEvent {
+                window_id: crate::window::WindowId(super::super::WindowId::X(WindowId(owner))),
+                ref event,
+            } = event
+            {
+                if super::super::is_input_event(event)
+                    && wt
+                        .windows
+                        .borrow()
+                        .values()
+                        .filter_map(Weak::upgrade)
+                        .any(|window| window.is_modal_for(owner))
+                {
+                    return;
+                }
+            }
+            callback(event)
+        };
+
         // We can't call a `&mut self` method because of the above borrow,
         // so we use this macro for repeated modifier state updates.
         macro_rules! update_modifiers {
@@ -153,8 +250,31 @@ impl<T: 'static> EventProcessor<T> {
             }};
         }
 
+        let xwindow = {
+            let xev: &ffi::XAnyEvent = xev.as_ref();
+            xev.window
+        };
+
         let event_type = xev.get_type();
         match event_type {
+            ffi::PropertyNotify | ffi::DestroyNotify
+                if Some(xwindow) == self.xsettings_window.get() =>
+            {
+                self.update_xsettings(&mut callback);
+            }
+
+            ffi::ClientMessage if xwindow == wt.root => {
+                let client_msg: &ffi::XClientMessageEvent = xev.as_ref();
+
+                // A new XSETTINGS manager announces itself with `MANAGER`.
+                let manager_atom = unsafe { wt.xconn.get_atom_unchecked(b"MANAGER\0") };
+                if client_msg.message_type == manager_atom
+                    && client_msg.data.get_long(1) as ffi::Atom == wt.xconn.xsettings_selection()
+                {
+                    self.update_xsettings(&mut callback);
+                }
+            }
+
             ffi::MappingNotify => {
                 let mapping: &ffi::XMappingEvent = xev.as_ref();
 
@@ -311,7 +431,7 @@ impl<T: 'static> EventProcessor<T> {
                     let mut result = None;
 
                     // This is where we receive data from drag and drop
-                    if let Ok(mut data) = unsafe { self.dnd.read_data(window) } {
+                    if let Ok(Some(mut data)) = self.dnd.read_data(window) {
                         let parse_result = self.dnd.parse_data(&mut data);
                         if let Ok(ref path_list) = parse_result {
                             for path in path_list {
@@ -328,6 +448,18 @@ impl<T: 'static> EventProcessor<T> {
                 }
             }
 
+            ffi::SelectionRequest => {
+                wt.clipboard.handle_selection_request(xev.as_ref());
+            }
+
+            ffi::SelectionClear => {
+                wt.clipboard.handle_selection_clear(xev.as_ref());
+            }
+
+            ffi::PropertyNotify => {
+                wt.clipboard.handle_property_notify(xev.as_ref());
+            }
+
             ffi::ConfigureNotify => {
                 let xev: &ffi::XConfigureEvent = xev.as_ref();
                 let xwindow = xev.window;
@@ -446,6 +578,8 @@ impl<T: 'static> EventProcessor<T> {
                                         new_inner_size: &mut new_inner_size,
                                     },
                                 });
+                                // The cursor theme is loaded at the size for the new scale factor.
+                                window.reload_cursor();
                             });
 
                             if new_inner_size != old_inner_size {
@@ -510,6 +644,7 @@ impl<T: 'static> EventProcessor<T> {
                 // In the event that the window's been destroyed without being dropped first, we
                 // cleanup again here.
                 wt.windows.borrow_mut().remove(&WindowId(window));
+                wt.pending_frames.borrow_mut().remove(&WindowId(window));
 
                 // Since all XIM stuff needs to happen from the same thread, we destroy the input
                 // context here instead of when dropping the window.
@@ -528,9 +663,31 @@ impl<T: 'static> EventProcessor<T> {
                 let xev: &ffi::XVisibilityEvent = xev.as_ref();
                 let xwindow = xev.window;
 
+                let occluded = xev.state == ffi::VisibilityFullyObscured;
+                if self.with_window(xwindow, |window| window.set_occluded(occluded)) == Some(true) {
+                    callback(Event::WindowEvent {
+                        window_id: mkwid(xwindow),
+                        event: WindowEvent::Occluded(occluded),
+                    });
+                }
+
                 self.with_window(xwindow, |window| window.visibility_notify());
             }
 
+            ffi::UnmapNotify => {
+                let xev: &ffi::XUnmapEvent = xev.as_ref();
+                let xwindow = xev.window;
+
+                // A minimized window is unmapped, and gets a `VisibilityNotify` once it's mapped
+                // again.
+                if self.with_window(xwindow, |window| window.set_occluded(true)) == Some(true) {
+                    callback(Event::WindowEvent {
+                        window_id: mkwid(xwindow),
+                        event: WindowEvent::Occluded(true),
+                    });
+                }
+            }
+
             ffi::Expose => {
                 let xev: &ffi::XExposeEvent = xev.as_ref();
 
@@ -619,6 +776,22 @@ impl<T: 'static> EventProcessor<T> {
                     return;
                 };
                 let xev = &guard.cookie;
+                if wt.present_opcode == Some(xev.extension) {
+                    if xev.evtype == ffi::PresentCompleteNotify {
+                        let xev: &ffi::XPresentCompleteNotifyEvent =
+                            unsafe { &*(xev.data as *const _) };
+                        let window_id = WindowId(xev.window);
+
+                        if wt.pending_frames.borrow_mut().remove(&window_id).is_some() {
+                            let target_time = Instant::now() + wt.frame_interval(window_id);
+                            callback(Event::WindowEvent {
+                                window_id: mkwid(xev.window),
+                                event: WindowEvent::FrameReady { target_time },
+                            });
+                        }
+                    }
+                    return;
+                }
                 if self.xi2ext.opcode != xev.extension {
                     return;
                 }
@@ -644,6 +817,19 @@ impl<T: 'static> EventProcessor<T> {
                             return;
                         }
 
+                        // A popup grabs the pointer, so presses outside of it are reported to it.
+                        if xev.evtype == ffi::XI_ButtonPress
+                            && self.with_window(xev.event, |window| {
+                                window.dismiss_popup(xev.event_x, xev.event_y)
+                            }) == Some(true)
+                        {
+                            callback(Event::WindowEvent {
+                                window_id,
+                                event: WindowEvent::PopupDismissed,
+                            });
+                            return;
+                        }
+
                         let modifiers = ModifiersState::from_x11(&xev.mods);
                         update_modifiers!(modifiers, None);
 
@@ -725,6 +911,12 @@ impl<T: 'static> EventProcessor<T> {
                         update_modifiers!(modifiers, None);
 
                         let cursor_moved = self.with_window(xev.event, |window| {
+                            // A locked cursor doesn't move, although `RawMotion` still reports
+                            // the motion of the mouse.
+                            if window.is_cursor_locked() {
+                                window.recenter_locked_cursor(Some(new_cursor_pos));
+                                return false;
+                            }
                             let mut shared_state_lock = window.shared_state.lock();
                             util::maybe_change(&mut shared_state_lock.cursor_pos, new_cursor_pos)
                         });
@@ -890,6 +1082,9 @@ impl<T: 'static> EventProcessor<T> {
                         if self.active_window != Some(xev.event) {
                             self.active_window = Some(xev.event);
 
+                            wt.focused.set(true);
+                            wt.update_device_event_filter();
+
                             let window_id = mkwid(xev.event);
                             let position = PhysicalPosition::new(xev.event_x, xev.event_y);
 
@@ -947,6 +1142,9 @@ impl<T: 'static> EventProcessor<T> {
                         if self.active_window.take() == Some(xev.event) {
                             let window_id = mkwid(xev.event);
 
+                            wt.focused.set(false);
+                            wt.update_device_event_filter();
+
                             // Issue key release events for all pressed keys
                             Self::handle_pressed_keys(
                                 &wt,
@@ -1,4 +1,10 @@
+use std::os::raw::c_int;
+
+pub use x11_dl::xfixes::{Xlib as XFixes, XserverRegion};
 pub use x11_dl::{
     error::OpenError, keysym::*, xcursor::*, xinput::*, xinput2::*, xlib::*, xlib_xcb::*,
-    xrandr::*, xrender::*,
+    xpresent::*, xrandr::*, xrender::*,
 };
+
+/// The input shape of a window, from `X11/extensions/shape.h`.
+pub const SHAPE_INPUT: c_int = 2;
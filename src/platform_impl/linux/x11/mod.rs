@@ -6,6 +6,7 @@
     target_os = "openbsd"
 ))]
 
+mod clipboard;
 mod dnd;
 mod event_processor;
 mod events;
@@ -23,12 +24,16 @@ pub use self::{
 };
 
 use std::{
-    cell::RefCell,
-    collections::{HashMap, HashSet},
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet, VecDeque},
     ffi::CStr,
+    io,
     mem::{self, MaybeUninit},
     ops::Deref,
-    os::raw::*,
+    os::{
+        raw::*,
+        unix::io::{AsRawFd, RawFd},
+    },
     ptr,
     rc::Rc,
     slice,
@@ -38,27 +43,46 @@ use std::{
 
 use libc::{self, setlocale, LC_CTYPE};
 
-use mio::{unix::EventedFd, Events, Poll, PollOpt, Ready, Token};
+use mio::{unix::EventedFd, Events, Poll, PollOpt, Ready, Registration, SetReadiness, Token};
 
 use mio_extras::channel::{channel, Receiver, SendError, Sender};
 
+use parking_lot::Mutex;
+
 use self::{
+    clipboard::Clipboard,
     dnd::{Dnd, DndState},
     event_processor::EventProcessor,
     ime::{Ime, ImeCreationError, ImeReceiver, ImeSender},
     util::modifiers::ModifierKeymap,
 };
 use crate::{
-    error::OsError as RootOsError,
-    event::{Event, StartCause},
-    event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootELW},
-    platform_impl::{platform::sticky_exit_callback, PlatformSpecificWindowBuilderAttributes},
+    clipboard::ClipboardKind,
+    error::{ExternalError, OsError as RootOsError},
+    event::{Event, StartCause, WindowEvent},
+    event_loop::{
+        ControlFlow, DeviceEventFilter, EventLoopClosed, EventLoopWindowTarget as RootELW, FdToken,
+        TrySendError,
+    },
+    platform::pump_events::PumpStatus,
+    platform_impl::{
+        platform::{
+            frame_interval, min_timeout, sticky_exit_callback, ProxyBound, ProxyPermit, Timers,
+        },
+        PlatformSpecificWindowBuilderAttributes,
+    },
     window::WindowAttributes,
 };
 
 const X_TOKEN: Token = Token(0);
 const USER_TOKEN: Token = Token(1);
 const REDRAW_TOKEN: Token = Token(2);
+const WAKEUP_TOKEN: Token = Token(3);
+const FRAME_TOKEN: Token = Token(4);
+
+/// The tokens of file descriptors registered with `register_fd` are the file descriptors offset
+/// by this value, so that they never collide with the tokens above.
+const FD_TOKEN_OFFSET: usize = 1 << 16;
 
 pub struct EventLoopWindowTarget<T> {
     xconn: Arc<XConnection>,
@@ -69,26 +93,49 @@ pub struct EventLoopWindowTarget<T> {
     ime: RefCell<Ime>,
     windows: RefCell<HashMap<WindowId, Weak<UnownedWindow>>>,
     redraw_sender: Sender<WindowId>,
+    frame_sender: Sender<WindowId>,
+    /// Frames requested by windows, with the deadline of the fallback timer when the Present
+    /// extension isn't available.
+    pub(super) pending_frames: RefCell<HashMap<WindowId, Option<Instant>>>,
+    pub(super) present_opcode: Option<c_int>,
+    poll: Poll,
+    fds: RefCell<HashMap<RawFd, FdToken>>,
+    pub(super) timers: RefCell<Timers>,
+    device_event_filter: Cell<DeviceEventFilter>,
+    /// Whether a window of the application has the input focus.
+    pub(super) focused: Cell<bool>,
+    pub(super) clipboard: Clipboard,
+    /// The settings of the XSETTINGS manager, which the windows read too.
+    pub(super) xsettings: Arc<Mutex<util::XSettings>>,
     _marker: ::std::marker::PhantomData<T>,
 }
 
 pub struct EventLoop<T: 'static> {
-    poll: Poll,
     event_processor: EventProcessor<T>,
     redraw_channel: Receiver<WindowId>,
-    user_channel: Receiver<T>,
-    user_sender: Sender<T>,
+    frame_channel: Receiver<WindowId>,
+    user_channel: Receiver<(T, Option<ProxyPermit>)>,
+    user_sender: Sender<(T, Option<ProxyPermit>)>,
+    _wakeup_registration: Registration,
+    wakeup_readiness: SetReadiness,
     target: Rc<RootELW<T>>,
+    control_flow: ControlFlow,
+    loop_running: bool,
+    pending_fds: VecDeque<FdToken>,
 }
 
 pub struct EventLoopProxy<T: 'static> {
-    user_sender: Sender<T>,
+    user_sender: Sender<(T, Option<ProxyPermit>)>,
+    bound: Option<ProxyBound>,
+    wakeup_readiness: SetReadiness,
 }
 
 impl<T: 'static> Clone for EventLoopProxy<T> {
     fn clone(&self) -> Self {
         EventLoopProxy {
             user_sender: self.user_sender.clone(),
+            bound: self.bound.clone(),
+            wakeup_readiness: self.wakeup_readiness.clone(),
         }
     }
 }
@@ -104,6 +151,9 @@ impl<T: 'static> EventLoop<T> {
         let dnd = Dnd::new(Arc::clone(&xconn))
             .expect("Failed to call XInternAtoms when initializing drag and drop");
 
+        let clipboard = Clipboard::new(Arc::clone(&xconn), root)
+            .expect("Failed to create the window owning the clipboard");
+
         let (ime_sender, ime_receiver) = mpsc::channel();
         // Input methods will open successfully without setting the locale, but it won't be
         // possible to actually commit pre-edit sequences.
@@ -174,6 +224,23 @@ impl<T: 'static> EventLoop<T> {
             }
         }
 
+        let present_opcode = xconn.xpresent.as_ref().and_then(|xpresent| unsafe {
+            let mut ext = XExtension::default();
+
+            let res = (xpresent.XPresentQueryExtension)(
+                xconn.display,
+                &mut ext.opcode,
+                &mut ext.first_event_id,
+                &mut ext.first_error_id,
+            );
+
+            if res == ffi::False {
+                None
+            } else {
+                Some(ext.opcode)
+            }
+        });
+
         xconn.update_cached_wm_info(root);
 
         let mut mod_keymap = ModifierKeymap::new();
@@ -183,6 +250,8 @@ impl<T: 'static> EventLoop<T> {
 
         let (user_sender, user_channel) = channel();
         let (redraw_sender, redraw_channel) = channel();
+        let (frame_sender, frame_channel) = channel();
+        let (wakeup_registration, wakeup_readiness) = Registration::new2();
 
         poll.register(
             &EventedFd(&xconn.x11_fd),
@@ -208,6 +277,22 @@ impl<T: 'static> EventLoop<T> {
         )
         .unwrap();
 
+        poll.register(
+            &frame_channel,
+            FRAME_TOKEN,
+            Ready::readable(),
+            PollOpt::level(),
+        )
+        .unwrap();
+
+        poll.register(
+            &wakeup_registration,
+            WAKEUP_TOKEN,
+            Ready::readable(),
+            PollOpt::level(),
+        )
+        .unwrap();
+
         let target = Rc::new(RootELW {
             p: super::EventLoopWindowTarget::X(EventLoopWindowTarget {
                 ime,
@@ -219,6 +304,16 @@ impl<T: 'static> EventLoop<T> {
                 wm_delete_window,
                 net_wm_ping,
                 redraw_sender,
+                frame_sender,
+                pending_frames: Default::default(),
+                present_opcode,
+                poll,
+                fds: Default::default(),
+                timers: Default::default(),
+                device_event_filter: Default::default(),
+                focused: Cell::new(false),
+                clipboard,
+                xsettings: Default::default(),
             }),
             _marker: ::std::marker::PhantomData,
         });
@@ -235,6 +330,7 @@ impl<T: 'static> EventLoop<T> {
             num_touch: 0,
             first_touch: None,
             active_window: None,
+            xsettings_window: Cell::new(None),
         };
 
         // Register for device hotplug events
@@ -246,13 +342,25 @@ impl<T: 'static> EventLoop<T> {
 
         event_processor.init_device(ffi::XIAllDevices);
 
+        // A new XSETTINGS manager announces itself to the root window with `MANAGER`.
+        unsafe {
+            let xconn = &get_xtarget(&target).xconn;
+            (xconn.xlib.XSelectInput)(xconn.display, root, ffi::StructureNotifyMask);
+        }
+        event_processor.update_xsettings(|_| ());
+
         let result = EventLoop {
-            poll,
             redraw_channel,
+            frame_channel,
             user_channel,
             user_sender,
+            _wakeup_registration: wakeup_registration,
+            wakeup_readiness,
             event_processor,
             target,
+            control_flow: ControlFlow::default(),
+            loop_running: false,
+            pending_fds: VecDeque::new(),
         };
 
         result
@@ -261,6 +369,15 @@ impl<T: 'static> EventLoop<T> {
     pub fn create_proxy(&self) -> EventLoopProxy<T> {
         EventLoopProxy {
             user_sender: self.user_sender.clone(),
+            bound: None,
+            wakeup_readiness: self.wakeup_readiness.clone(),
+        }
+    }
+
+    pub fn create_proxy_bounded(&self, capacity: usize) -> EventLoopProxy<T> {
+        EventLoopProxy {
+            bound: Some(ProxyBound::new(capacity)),
+            ..self.create_proxy()
         }
     }
 
@@ -268,135 +385,303 @@ impl<T: 'static> EventLoop<T> {
         &self.target
     }
 
-    pub fn run_return<F>(&mut self, mut callback: F)
+    pub fn run_return<F>(&mut self, mut callback: F) -> i32
     where
         F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
     {
-        let mut control_flow = ControlFlow::default();
-        let mut events = Events::with_capacity(8);
-        let mut cause = StartCause::Init;
-
         loop {
+            if let PumpStatus::Exit(code) = self.pump_events(None, &mut callback) {
+                return code;
+            }
+        }
+    }
+
+    pub fn run<F>(mut self, callback: F) -> !
+    where
+        F: 'static + FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        let exit_code = self.run_return(callback);
+        ::std::process::exit(exit_code);
+    }
+
+    pub fn pump_events<F>(&mut self, timeout: Option<Duration>, mut callback: F) -> PumpStatus
+    where
+        F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        if !self.loop_running {
+            self.loop_running = true;
+
+            // Changes to the control flow don't persist between runs.
+            self.control_flow = ControlFlow::default();
+            self.single_iteration(&mut callback, StartCause::Init);
+        }
+
+        // The `Init` iteration may already have requested an exit.
+        if !matches!(self.control_flow, ControlFlow::ExitWithCode(_)) {
+            self.poll_events_with_timeout(timeout, &mut callback);
+        }
+
+        if let ControlFlow::ExitWithCode(code) = self.control_flow {
+            self.loop_running = false;
+
             sticky_exit_callback(
-                crate::event::Event::NewEvents(cause),
+                crate::event::Event::LoopDestroyed,
                 &self.target,
-                &mut control_flow,
+                &mut self.control_flow,
                 &mut callback,
             );
 
-            // Process all pending events
-            self.drain_events(&mut callback, &mut control_flow);
+            PumpStatus::Exit(code)
+        } else {
+            PumpStatus::Continue
+        }
+    }
 
-            // Empty the user event buffer
-            {
-                while let Ok(event) = self.user_channel.try_recv() {
-                    sticky_exit_callback(
-                        crate::event::Event::UserEvent(event),
-                        &self.target,
-                        &mut control_flow,
-                        &mut callback,
-                    );
+    /// Waits for new events according to the control flow, but no longer than `timeout`, and
+    /// dispatches a single iteration of the event loop.
+    fn poll_events_with_timeout<F>(&mut self, timeout: Option<Duration>, callback: &mut F)
+    where
+        F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        let mut events = Events::with_capacity(8);
+        let start = Instant::now();
+        let (deadline, control_flow_timeout, mut cause);
+
+        match self.control_flow {
+            ControlFlow::ExitWithCode(_) => return,
+            ControlFlow::Poll => {
+                cause = StartCause::Poll;
+                deadline = None;
+                control_flow_timeout = Some(Duration::from_millis(0));
+            }
+            ControlFlow::Wait => {
+                cause = StartCause::WaitCancelled {
+                    start,
+                    requested_resume: None,
+                };
+                deadline = None;
+                control_flow_timeout = None;
+            }
+            ControlFlow::WaitUntil(wait_deadline) => {
+                cause = StartCause::ResumeTimeReached {
+                    start,
+                    requested_resume: wait_deadline,
+                };
+                control_flow_timeout = if wait_deadline > start {
+                    Some(wait_deadline - start)
+                } else {
+                    Some(Duration::from_millis(0))
+                };
+                deadline = Some(wait_deadline);
+            }
+        }
+
+        // Wake up in time for the earliest timer and frame, and don't block while there are ready
+        // file descriptors left to report.
+        self.schedule_frames();
+        let timer_timeout = get_xtarget(&self.target).timers.borrow().timeout(start);
+        let frame_timeout = self.frame_timeout(start);
+        let timeout = if self.pending_fds.is_empty() {
+            min_timeout(
+                min_timeout(control_flow_timeout, timer_timeout),
+                min_timeout(frame_timeout, timeout),
+            )
+        } else {
+            Some(Duration::from_millis(0))
+        };
+
+        // If the XConnection already contains buffered events, we don't
+        // need to wait for data on the socket.
+        if !self.event_processor.poll() {
+            let wt = get_xtarget(&self.target);
+            wt.poll.poll(&mut events, timeout).unwrap();
+
+            let fds = wt.fds.borrow();
+            for event in events.iter() {
+                let token = match event.token().0.checked_sub(FD_TOKEN_OFFSET) {
+                    Some(fd) => fds.get(&(fd as RawFd)),
+                    None => None,
+                };
+                if let Some(&token) = token {
+                    if !self.pending_fds.contains(&token) {
+                        self.pending_fds.push_back(token);
+                    }
                 }
             }
-            // send MainEventsCleared
-            {
+            events.clear();
+        }
+
+        self.schedule_frames();
+
+        // The iteration below handles the wakeups requested by `EventLoopProxy::wake_up` so far.
+        let _ = self.wakeup_readiness.set_readiness(Ready::empty());
+
+        let now = Instant::now();
+        let wait_cancelled = deadline.map_or(false, |deadline| now < deadline);
+
+        // A reached `WaitUntil` deadline takes precedence, expired timers are then reported on
+        // the next iteration.
+        if wait_cancelled || deadline.is_none() {
+            let expired = get_xtarget(&self.target)
+                .timers
+                .borrow_mut()
+                .take_expired(now);
+            if !expired.is_empty() {
+                cause = StartCause::TimerFired(expired);
+            } else if let Some(token) = self.pending_fds.pop_front() {
+                cause = StartCause::FdReady(token);
+            } else if wait_cancelled {
+                cause = StartCause::WaitCancelled {
+                    start,
+                    requested_resume: deadline,
+                };
+            }
+        }
+
+        self.single_iteration(callback, cause);
+    }
+
+    /// Dispatches `NewEvents(cause)` followed by every pending event, up to and including
+    /// `RedrawEventsCleared`.
+    fn single_iteration<F>(&mut self, callback: &mut F, cause: StartCause)
+    where
+        F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        let mut control_flow = self.control_flow;
+
+        sticky_exit_callback(
+            crate::event::Event::NewEvents(cause),
+            &self.target,
+            &mut control_flow,
+            callback,
+        );
+
+        // Process all pending events
+        self.drain_events(callback, &mut control_flow);
+
+        // Empty the user event buffer
+        {
+            while let Ok((event, _permit)) = self.user_channel.try_recv() {
                 sticky_exit_callback(
-                    crate::event::Event::MainEventsCleared,
+                    crate::event::Event::UserEvent(event),
                     &self.target,
                     &mut control_flow,
-                    &mut callback,
+                    callback,
                 );
             }
-            // Empty the redraw requests
-            {
-                let mut windows = HashSet::new();
+        }
+        // Report the frames paced by the fallback timer
+        {
+            let now = Instant::now();
+            let wt = get_xtarget(&self.target);
+            let mut ready = Vec::new();
+
+            wt.pending_frames
+                .borrow_mut()
+                .retain(|&window_id, deadline| match *deadline {
+                    Some(deadline) if deadline <= now => {
+                        ready.push((window_id, deadline));
+                        false
+                    }
+                    _ => true,
+                });
 
-                while let Ok(window_id) = self.redraw_channel.try_recv() {
-                    windows.insert(window_id);
-                }
+            for (window_id, deadline) in ready {
+                let target_time = deadline + wt.frame_interval(window_id);
+                sticky_exit_callback(
+                    Event::WindowEvent {
+                        window_id: mkwid(window_id.0),
+                        event: WindowEvent::FrameReady { target_time },
+                    },
+                    &self.target,
+                    &mut control_flow,
+                    callback,
+                );
+            }
+        }
+        // send MainEventsCleared
+        {
+            sticky_exit_callback(
+                crate::event::Event::MainEventsCleared,
+                &self.target,
+                &mut control_flow,
+                callback,
+            );
+        }
+        // Empty the redraw requests
+        {
+            let mut windows = HashSet::new();
 
-                for window_id in windows {
-                    let window_id = crate::window::WindowId(super::WindowId::X(window_id));
-                    sticky_exit_callback(
-                        Event::RedrawRequested(window_id),
-                        &self.target,
-                        &mut control_flow,
-                        &mut callback,
-                    );
-                }
+            while let Ok(window_id) = self.redraw_channel.try_recv() {
+                windows.insert(window_id);
             }
-            // send RedrawEventsCleared
-            {
+
+            for window_id in windows {
+                let window_id = crate::window::WindowId(super::WindowId::X(window_id));
                 sticky_exit_callback(
-                    crate::event::Event::RedrawEventsCleared,
+                    Event::RedrawRequested(window_id),
                     &self.target,
                     &mut control_flow,
-                    &mut callback,
+                    callback,
                 );
             }
+        }
+        // send RedrawEventsCleared
+        {
+            sticky_exit_callback(
+                crate::event::Event::RedrawEventsCleared,
+                &self.target,
+                &mut control_flow,
+                callback,
+            );
+        }
+
+        self.control_flow = control_flow;
+    }
 
-            let start = Instant::now();
-            let (deadline, timeout);
+    /// Schedules the frames requested through `Window::request_frame`, using the Present
+    /// extension when available and a timer based on the monitor refresh rate otherwise.
+    fn schedule_frames(&self) {
+        let wt = get_xtarget(&self.target);
+        let mut pending_frames = wt.pending_frames.borrow_mut();
+        let mut flush = false;
 
-            match control_flow {
-                ControlFlow::Exit => break,
-                ControlFlow::Poll => {
-                    cause = StartCause::Poll;
-                    deadline = None;
-                    timeout = Some(Duration::from_millis(0));
-                }
-                ControlFlow::Wait => {
-                    cause = StartCause::WaitCancelled {
-                        start,
-                        requested_resume: None,
+        while let Ok(window_id) = self.frame_channel.try_recv() {
+            if pending_frames.contains_key(&window_id) {
+                continue;
+            }
+
+            match (&wt.xconn.xpresent, wt.present_opcode) {
+                (Some(xpresent), Some(_)) => {
+                    unsafe {
+                        (xpresent.XPresentNotifyMSC)(wt.xconn.display, window_id.0, 0, 0, 1, 0)
                     };
-                    deadline = None;
-                    timeout = None;
+                    pending_frames.insert(window_id, None);
+                    flush = true;
                 }
-                ControlFlow::WaitUntil(wait_deadline) => {
-                    cause = StartCause::ResumeTimeReached {
-                        start,
-                        requested_resume: wait_deadline,
-                    };
-                    timeout = if wait_deadline > start {
-                        Some(wait_deadline - start)
-                    } else {
-                        Some(Duration::from_millis(0))
-                    };
-                    deadline = Some(wait_deadline);
+                _ => {
+                    let deadline = Instant::now() + wt.frame_interval(window_id);
+                    pending_frames.insert(window_id, Some(deadline));
                 }
             }
-
-            // If the XConnection already contains buffered events, we don't
-            // need to wait for data on the socket.
-            if !self.event_processor.poll() {
-                self.poll.poll(&mut events, timeout).unwrap();
-                events.clear();
-            }
-
-            let wait_cancelled = deadline.map_or(false, |deadline| Instant::now() < deadline);
-
-            if wait_cancelled {
-                cause = StartCause::WaitCancelled {
-                    start,
-                    requested_resume: deadline,
-                };
-            }
         }
 
-        callback(
-            crate::event::Event::LoopDestroyed,
-            &self.target,
-            &mut control_flow,
-        );
+        if flush {
+            wt.xconn
+                .flush_requests()
+                .expect("Failed to call XFlush when requesting a frame");
+        }
     }
 
-    pub fn run<F>(mut self, callback: F) -> !
-    where
-        F: 'static + FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
-    {
-        self.run_return(callback);
-        ::std::process::exit(0);
+    /// Returns the time left until the earliest frame paced by the fallback timer is due.
+    fn frame_timeout(&self, now: Instant) -> Option<Duration> {
+        get_xtarget(&self.target)
+            .pending_frames
+            .borrow()
+            .values()
+            .filter_map(|&deadline| deadline)
+            .min()
+            .map(|deadline| deadline.saturating_duration_since(now))
     }
 
     fn drain_events<F>(&mut self, callback: &mut F, control_flow: &mut ControlFlow)
@@ -434,29 +719,150 @@ impl<T: 'static> EventLoop<T> {
 pub(crate) fn get_xtarget<T>(target: &RootELW<T>) -> &EventLoopWindowTarget<T> {
     match target.p {
         super::EventLoopWindowTarget::X(ref target) => target,
-        #[cfg(feature = "wayland")]
         _ => unreachable!(),
     }
 }
 
+impl<T: 'static> AsRawFd for EventLoop<T> {
+    fn as_raw_fd(&self) -> RawFd {
+        get_xtarget(&self.target).poll.as_raw_fd()
+    }
+}
+
 impl<T> EventLoopWindowTarget<T> {
     /// Returns the `XConnection` of this events loop.
     #[inline]
     pub fn x_connection(&self) -> &Arc<XConnection> {
         &self.xconn
     }
+
+    /// Returns the refresh interval of the monitor the window is on.
+    pub(super) fn frame_interval(&self, window_id: WindowId) -> Duration {
+        let refresh_rate = self
+            .windows
+            .borrow()
+            .get(&window_id)
+            .and_then(Weak::upgrade)
+            .and_then(|window| window.current_monitor().refresh_rate_millihertz());
+        frame_interval(refresh_rate)
+    }
+
+    pub fn register_fd(&self, fd: RawFd, token: FdToken) -> io::Result<()> {
+        // A negative file descriptor doesn't map to a token.
+        if fd < 0 {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput));
+        }
+
+        let mut fds = self.fds.borrow_mut();
+        if fds.contains_key(&fd) {
+            return Err(io::Error::from(io::ErrorKind::AlreadyExists));
+        }
+
+        self.poll.register(
+            &EventedFd(&fd),
+            Token(FD_TOKEN_OFFSET + fd as usize),
+            Ready::readable(),
+            PollOpt::level(),
+        )?;
+        fds.insert(fd, token);
+        Ok(())
+    }
+
+    pub fn unregister_fd(&self, fd: RawFd) -> io::Result<()> {
+        if self.fds.borrow_mut().remove(&fd).is_none() {
+            return Err(io::Error::from(io::ErrorKind::NotFound));
+        }
+
+        self.poll.deregister(&EventedFd(&fd))
+    }
+
+    pub fn set_device_event_filter(&self, filter: DeviceEventFilter) {
+        self.device_event_filter.set(filter);
+        self.update_device_event_filter();
+    }
+
+    pub fn set_clipboard_contents(
+        &self,
+        kind: ClipboardKind,
+        contents: HashMap<String, Vec<u8>>,
+    ) -> Result<(), ExternalError> {
+        self.clipboard.set_contents(kind, contents)
+    }
+
+    pub fn clipboard_contents(
+        &self,
+        kind: ClipboardKind,
+        mime_type: &str,
+    ) -> Result<Option<Vec<u8>>, ExternalError> {
+        self.clipboard.get_contents(kind, mime_type)
+    }
+
+    /// Returns the XInput2 mask of the raw events to select on physical devices, according to
+    /// the device event filter and the focus.
+    pub(super) fn raw_event_mask(&self) -> i32 {
+        let filtered = match self.device_event_filter.get() {
+            DeviceEventFilter::Always => true,
+            DeviceEventFilter::Unfocused => !self.focused.get(),
+            DeviceEventFilter::Never => false,
+        };
+        if filtered {
+            0
+        } else {
+            ffi::XI_RawMotionMask
+                | ffi::XI_RawButtonPressMask
+                | ffi::XI_RawButtonReleaseMask
+                | ffi::XI_RawKeyPressMask
+                | ffi::XI_RawKeyReleaseMask
+        }
+    }
+
+    /// Selects the raw events of every physical device, after the device event filter or the
+    /// focus changed.
+    pub(super) fn update_device_event_filter(&self) {
+        let mask = self.raw_event_mask();
+        if let Some(info) = DeviceInfo::get(&self.xconn, ffi::XIAllDevices) {
+            for info in info.iter().filter(|info| Device::physical_device(info)) {
+                // The request buffer is flushed when we poll for events
+                self.xconn
+                    .select_xinput_events(self.root, info.deviceid, mask)
+                    .queue();
+            }
+        }
+    }
 }
 
 impl<T: 'static> EventLoopProxy<T> {
     pub fn send_event(&self, event: T) -> Result<(), EventLoopClosed<T>> {
-        self.user_sender.send(event).map_err(|e| {
-            EventLoopClosed(if let SendError::Disconnected(x) = e {
+        let permit = self.bound.as_ref().map(ProxyBound::acquire);
+        self.user_sender.send((event, permit)).map_err(|e| {
+            EventLoopClosed(if let SendError::Disconnected((x, _)) = e {
                 x
             } else {
                 unreachable!()
             })
         })
     }
+
+    pub fn try_send_event(&self, event: T) -> Result<(), TrySendError<T>> {
+        let permit = match self.bound {
+            Some(ref bound) => match bound.try_acquire() {
+                Some(permit) => Some(permit),
+                None => return Err(TrySendError::Full(event)),
+            },
+            None => None,
+        };
+        self.user_sender.send((event, permit)).map_err(|e| {
+            TrySendError::Closed(if let SendError::Disconnected((x, _)) = e {
+                x
+            } else {
+                unreachable!()
+            })
+        })
+    }
+
+    pub fn wake_up(&self) {
+        let _ = self.wakeup_readiness.set_readiness(Ready::readable());
+    }
 }
 
 struct DeviceInfo<'a> {
@@ -506,6 +912,10 @@ impl WindowId {
     pub unsafe fn dummy() -> Self {
         WindowId(0)
     }
+
+    pub fn raw(&self) -> u64 {
+        self.0
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -515,6 +925,10 @@ impl DeviceId {
     pub unsafe fn dummy() -> Self {
         DeviceId(0)
     }
+
+    pub fn raw(&self) -> u64 {
+        self.0 as u64
+    }
 }
 
 pub struct Window(Arc<UnownedWindow>);
@@ -629,12 +1043,8 @@ impl Device {
         let wt = get_xtarget(&el.target);
 
         if Device::physical_device(info) {
-            // Register for global raw events
-            let mask = ffi::XI_RawMotionMask
-                | ffi::XI_RawButtonPressMask
-                | ffi::XI_RawButtonReleaseMask
-                | ffi::XI_RawKeyPressMask
-                | ffi::XI_RawKeyReleaseMask;
+            // Register for global raw events, unless they are filtered out
+            let mask = wt.raw_event_mask();
             // The request buffer is flushed when we poll for events
             wt.xconn
                 .select_xinput_events(wt.root, info.deviceid, mask)
@@ -78,6 +78,8 @@ pub struct MonitorHandle {
     pub(crate) rect: util::AaRect,
     /// Supported video modes on this monitor
     video_modes: Vec<VideoMode>,
+    /// The refresh rate of the current video mode, if known
+    refresh_rate_millihertz: Option<u32>,
 }
 
 impl PartialEq for MonitorHandle {
@@ -118,6 +120,12 @@ impl MonitorHandle {
         let dimensions = unsafe { ((*crtc).width as u32, (*crtc).height as u32) };
         let position = unsafe { ((*crtc).x as i32, (*crtc).y as i32) };
         let rect = util::AaRect::new(position, dimensions);
+        let current_mode = unsafe { (*crtc).mode };
+        let refresh_rate_millihertz = video_modes
+            .iter()
+            .find(|mode| mode.native_mode == current_mode)
+            .map(|mode| u32::from(mode.refresh_rate) * 1000)
+            .filter(|&refresh_rate| refresh_rate > 0);
         Some(MonitorHandle {
             id,
             name,
@@ -127,6 +135,7 @@ impl MonitorHandle {
             primary,
             rect,
             video_modes,
+            refresh_rate_millihertz,
         })
     }
 
@@ -140,6 +149,7 @@ impl MonitorHandle {
             primary: true,
             rect: util::AaRect::new((0, 0), (1, 1)),
             video_modes: Vec::new(),
+            refresh_rate_millihertz: None,
         }
     }
 
@@ -170,6 +180,11 @@ impl MonitorHandle {
         self.scale_factor
     }
 
+    #[inline]
+    pub(crate) fn refresh_rate_millihertz(&self) -> Option<u32> {
+        self.refresh_rate_millihertz
+    }
+
     #[inline]
     pub fn video_modes(&self) -> impl Iterator<Item = RootVideoMode> {
         let monitor = self.clone();
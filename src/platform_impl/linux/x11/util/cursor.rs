@@ -1,18 +1,117 @@
-use crate::window::CursorIcon;
+use std::{
+    ffi::{CStr, CString},
+    slice,
+};
+
+use crate::platform_impl::platform::cursor_theme::CursorTheme;
+use crate::window::{CursorIcon, CustomCursor};
 
 use super::*;
 
 impl XConnection {
-    pub fn set_cursor_icon(&self, window: ffi::Window, cursor: Option<CursorIcon>) {
+    /// Shows `cursor` from `theme`, whose size is in physical pixels.
+    pub fn set_cursor_icon(
+        &self,
+        window: ffi::Window,
+        cursor: Option<CursorIcon>,
+        theme: &CursorTheme,
+    ) {
         let cursor = *self
             .cursor_cache
             .lock()
-            .entry(cursor)
-            .or_insert_with(|| self.get_cursor(cursor));
+            .entry((cursor, theme.clone()))
+            .or_insert_with(|| self.get_cursor(cursor, theme));
 
         self.update_cursor(window, cursor);
     }
 
+    /// Returns the cursor theme of the desktop, whose size is for a scale factor of 1.
+    ///
+    /// `XCURSOR_THEME` and `XCURSOR_SIZE` come first, then the settings of the XSETTINGS manager,
+    /// then the `Xcursor.theme` and `Xcursor.size` resources.
+    pub fn default_cursor_theme(&self, xsettings: &XSettings) -> CursorTheme {
+        let (env_name, env_size) = CursorTheme::from_env();
+        let (xsettings_name, xsettings_size) = xsettings.cursor_theme.clone();
+        let name = env_name
+            .or(xsettings_name)
+            .or_else(|| self.get_default(b"Xcursor\0", b"theme\0"))
+            .unwrap_or_else(|| CursorTheme::DEFAULT_NAME.to_owned());
+        let size = env_size
+            .or(xsettings_size)
+            .or_else(|| {
+                self.get_default(b"Xcursor\0", b"size\0")?
+                    .parse()
+                    .ok()
+                    .filter(|&size| size > 0)
+            })
+            .unwrap_or(CursorTheme::DEFAULT_SIZE);
+        CursorTheme { name, size }
+    }
+
+    /// Reads a resource of the X resource database.
+    fn get_default(&self, program: &[u8], option: &[u8]) -> Option<String> {
+        unsafe {
+            let value = (self.xlib.XGetDefault)(
+                self.display,
+                program.as_ptr() as *const c_char,
+                option.as_ptr() as *const c_char,
+            );
+            if value.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(value).to_string_lossy().into_owned())
+            }
+        }
+    }
+
+    pub fn set_custom_cursor(&self, window: ffi::Window, cursor: &CustomCursor) {
+        let cursor = self.create_custom_cursor(cursor);
+
+        self.update_cursor(window, cursor);
+
+        // The server keeps the cursor for as long as the window uses it.
+        if cursor != 0 {
+            unsafe { (self.xlib.XFreeCursor)(self.display, cursor) };
+        }
+    }
+
+    /// Creates a cursor from the images, instead of looking up a name in the cursor theme.
+    fn create_custom_cursor(&self, cursor: &CustomCursor) -> ffi::Cursor {
+        unsafe {
+            let images = (self.xcursor.XcursorImagesCreate)(cursor.frames.len() as c_int);
+            if images.is_null() {
+                return 0;
+            }
+
+            for frame in cursor.frames.iter() {
+                let image =
+                    (self.xcursor.XcursorImageCreate)(frame.width as c_int, frame.height as c_int);
+                if image.is_null() {
+                    (self.xcursor.XcursorImagesDestroy)(images);
+                    return 0;
+                }
+
+                (*image).xhot = frame.hotspot_x;
+                (*image).yhot = frame.hotspot_y;
+                (*image).delay = frame.delay.as_millis() as c_uint;
+                let pixels = slice::from_raw_parts_mut(
+                    (*image).pixels,
+                    (frame.width * frame.height) as usize,
+                );
+                for (pixel, argb) in pixels.iter_mut().zip(frame.argb_pixels()) {
+                    *pixel = argb;
+                }
+
+                *(*images).images.add((*images).nimage as usize) = image;
+                (*images).nimage += 1;
+            }
+
+            let cursor = (self.xcursor.XcursorImagesLoadCursor)(self.display, images);
+            (self.xcursor.XcursorImagesDestroy)(images);
+            cursor
+        }
+    }
+
     fn create_empty_cursor(&self) -> ffi::Cursor {
         let data = 0;
         let pixmap = unsafe {
@@ -44,15 +143,34 @@ impl XConnection {
         }
     }
 
-    fn load_cursor(&self, name: &[u8]) -> ffi::Cursor {
+    fn load_cursor(&self, name: &[u8], theme: &CursorTheme) -> ffi::Cursor {
         unsafe {
-            (self.xcursor.XcursorLibraryLoadCursor)(self.display, name.as_ptr() as *const c_char)
+            let theme_name = match CString::new(theme.name.as_str()) {
+                Ok(theme_name) => theme_name,
+                Err(_) => return 0,
+            };
+            let images = (self.xcursor.XcursorLibraryLoadImages)(
+                name.as_ptr() as *const c_char,
+                theme_name.as_ptr(),
+                theme.size as c_int,
+            );
+            if images.is_null() {
+                // Falls back to the default theme, and to the core cursor font.
+                return (self.xcursor.XcursorLibraryLoadCursor)(
+                    self.display,
+                    name.as_ptr() as *const c_char,
+                );
+            }
+
+            let cursor = (self.xcursor.XcursorImagesLoadCursor)(self.display, images);
+            (self.xcursor.XcursorImagesDestroy)(images);
+            cursor
         }
     }
 
-    fn load_first_existing_cursor(&self, names: &[&[u8]]) -> ffi::Cursor {
+    fn load_first_existing_cursor(&self, names: &[&[u8]], theme: &CursorTheme) -> ffi::Cursor {
         for name in names.iter() {
-            let xcursor = self.load_cursor(name);
+            let xcursor = self.load_cursor(name, theme);
             if xcursor != 0 {
                 return xcursor;
             }
@@ -60,15 +178,15 @@ impl XConnection {
         0
     }
 
-    fn get_cursor(&self, cursor: Option<CursorIcon>) -> ffi::Cursor {
+    fn get_cursor(&self, cursor: Option<CursorIcon>, theme: &CursorTheme) -> ffi::Cursor {
         let cursor = match cursor {
             Some(cursor) => cursor,
             None => return self.create_empty_cursor(),
         };
 
-        let load = |name: &[u8]| self.load_cursor(name);
+        let load = |name: &[u8]| self.load_cursor(name, theme);
 
-        let loadn = |names: &[&[u8]]| self.load_first_existing_cursor(names);
+        let loadn = |names: &[&[u8]]| self.load_first_existing_cursor(names, theme);
 
         // Try multiple names in some cases where the name
         // differs on the desktop environments or themes.
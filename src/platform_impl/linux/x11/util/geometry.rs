@@ -188,6 +188,16 @@ impl XConnection {
         Ok(geometry)
     }
 
+    /// Whether the window and all of its ancestors are mapped.
+    pub fn is_viewable(&self, window: ffi::Window) -> Result<bool, XError> {
+        let mut attributes: ffi::XWindowAttributes = unsafe { mem::zeroed() };
+        unsafe {
+            (self.xlib.XGetWindowAttributes)(self.display, window, &mut attributes);
+        }
+        self.check_errors()?;
+        Ok(attributes.map_state == ffi::IsViewable)
+    }
+
     fn get_frame_extents(&self, window: ffi::Window) -> Option<FrameExtents> {
         let extents_atom = unsafe { self.get_atom_unchecked(b"_NET_FRAME_EXTENTS\0") };
 
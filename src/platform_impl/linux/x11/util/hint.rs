@@ -2,6 +2,7 @@ use std::slice;
 use std::sync::Arc;
 
 use super::*;
+use crate::window::ResizeDirection;
 
 #[derive(Debug)]
 #[allow(dead_code)]
@@ -21,6 +22,35 @@ impl From<bool> for StateOperation {
     }
 }
 
+/// The direction of a `_NET_WM_MOVERESIZE` request.
+#[derive(Debug, Clone, Copy)]
+pub enum MoveResizeDirection {
+    SizeTopLeft = 0,     // _NET_WM_MOVERESIZE_SIZE_TOPLEFT
+    SizeTop = 1,         // _NET_WM_MOVERESIZE_SIZE_TOP
+    SizeTopRight = 2,    // _NET_WM_MOVERESIZE_SIZE_TOPRIGHT
+    SizeRight = 3,       // _NET_WM_MOVERESIZE_SIZE_RIGHT
+    SizeBottomRight = 4, // _NET_WM_MOVERESIZE_SIZE_BOTTOMRIGHT
+    SizeBottom = 5,      // _NET_WM_MOVERESIZE_SIZE_BOTTOM
+    SizeBottomLeft = 6,  // _NET_WM_MOVERESIZE_SIZE_BOTTOMLEFT
+    SizeLeft = 7,        // _NET_WM_MOVERESIZE_SIZE_LEFT
+    Move = 8,            // _NET_WM_MOVERESIZE_MOVE
+}
+
+impl From<ResizeDirection> for MoveResizeDirection {
+    fn from(direction: ResizeDirection) -> Self {
+        match direction {
+            ResizeDirection::NorthWest => MoveResizeDirection::SizeTopLeft,
+            ResizeDirection::North => MoveResizeDirection::SizeTop,
+            ResizeDirection::NorthEast => MoveResizeDirection::SizeTopRight,
+            ResizeDirection::East => MoveResizeDirection::SizeRight,
+            ResizeDirection::SouthEast => MoveResizeDirection::SizeBottomRight,
+            ResizeDirection::South => MoveResizeDirection::SizeBottom,
+            ResizeDirection::SouthWest => MoveResizeDirection::SizeBottomLeft,
+            ResizeDirection::West => MoveResizeDirection::SizeLeft,
+        }
+    }
+}
+
 /// X window type. Maps directly to
 /// [`_NET_WM_WINDOW_TYPE`](https://specifications.freedesktop.org/wm-spec/wm-spec-1.5.html).
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -136,6 +166,11 @@ impl MotifHints {
         }
     }
 
+    /// Whether the window manager is asked to decorate the window, which it does by default.
+    pub fn decorations(&self) -> bool {
+        self.hints.flags & mwm::MWM_HINTS_DECORATIONS == 0 || self.hints.decorations != 0
+    }
+
     pub fn set_decorations(&mut self, decorations: bool) {
         self.hints.flags |= mwm::MWM_HINTS_DECORATIONS;
         self.hints.decorations = decorations as c_ulong;
@@ -149,6 +184,22 @@ impl MotifHints {
         }
     }
 
+    pub fn set_minimizable(&mut self, minimizable: bool) {
+        if minimizable {
+            self.add_func(mwm::MWM_FUNC_MINIMIZE);
+        } else {
+            self.remove_func(mwm::MWM_FUNC_MINIMIZE);
+        }
+    }
+
+    pub fn set_closable(&mut self, closable: bool) {
+        if closable {
+            self.add_func(mwm::MWM_FUNC_CLOSE);
+        } else {
+            self.remove_func(mwm::MWM_FUNC_CLOSE);
+        }
+    }
+
     fn add_func(&mut self, func: c_ulong) {
         if self.hints.flags & mwm::MWM_HINTS_FUNCTIONS != 0 {
             if self.hints.functions & mwm::MWM_FUNC_ALL != 0 {
@@ -231,6 +282,15 @@ impl<'a> NormalHints<'a> {
         }
     }
 
+    /// Whether the window manager lets the user resize the window, which it doesn't when the
+    /// minimum and maximum sizes are the same.
+    pub fn resizable(&self) -> bool {
+        let fixed_flags = ffi::PMinSize | ffi::PMaxSize;
+        self.size_hints.flags & fixed_flags != fixed_flags
+            || self.size_hints.min_width != self.size_hints.max_width
+            || self.size_hints.min_height != self.size_hints.max_height
+    }
+
     pub fn set_base_size(&mut self, base_size: Option<(u32, u32)>) {
         if let Some((base_width, base_height)) = base_size {
             self.size_hints.flags |= ffi::PBaseSize;
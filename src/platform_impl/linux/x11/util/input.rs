@@ -81,6 +81,37 @@ impl XConnection {
         Flusher::new(self)
     }
 
+    /// Grabs a pointer, so that all of its events matching `mask` are reported to `window`.
+    /// Returns whether the grab succeeded.
+    pub fn grab_xinput_pointer(&self, window: c_ulong, device_id: c_int, mask: i32) -> bool {
+        let mut event_mask = ffi::XIEventMask {
+            deviceid: device_id,
+            mask: &mask as *const _ as *mut c_uchar,
+            mask_len: mem::size_of_val(&mask) as c_int,
+        };
+        let status = unsafe {
+            (self.xinput2.XIGrabDevice)(
+                self.display,
+                device_id,
+                window,
+                ffi::CurrentTime,
+                0,
+                ffi::GrabModeAsync,
+                ffi::GrabModeAsync,
+                ffi::False,
+                &mut event_mask as *mut ffi::XIEventMask,
+            )
+        };
+        status == ffi::GrabSuccess
+    }
+
+    pub fn ungrab_xinput_pointer(&self, device_id: c_int) -> Flusher<'_> {
+        unsafe {
+            (self.xinput2.XIUngrabDevice)(self.display, device_id, ffi::CurrentTime);
+        }
+        Flusher::new(self)
+    }
+
     #[allow(dead_code)]
     pub fn select_xkb_events(&self, device_id: c_uint, mask: c_ulong) -> Option<Flusher<'_>> {
         let status = unsafe { (self.xlib.XkbSelectEvents)(self.display, device_id, mask, mask) };
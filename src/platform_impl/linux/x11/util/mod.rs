@@ -13,12 +13,14 @@ pub mod keys;
 mod memory;
 pub mod modifiers;
 mod randr;
+mod selection;
 mod window_property;
 mod wm;
+mod xsettings;
 
 pub use self::{
     atom::*, client_msg::*, format::*, geometry::*, hint::*, icon::*, input::*, memory::*,
-    randr::*, window_property::*, wm::*,
+    randr::*, selection::*, window_property::*, wm::*, xsettings::*,
 };
 
 use std::{
@@ -0,0 +1,213 @@
+use std::{
+    slice,
+    time::{Duration, Instant},
+};
+
+use super::*;
+
+/// How long the owner of a selection has to answer a conversion, or to send the next chunk of an
+/// incremental transfer.
+pub const SELECTION_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone)]
+pub enum SelectionError {
+    XError(XError),
+    /// The owner of the selection didn't answer in time.
+    Timeout,
+}
+
+impl From<XError> for SelectionError {
+    fn from(e: XError) -> Self {
+        SelectionError::XError(e)
+    }
+}
+
+impl XConnection {
+    /// Asks the owner of `selection` to convert it to `target`, and to store the result in
+    /// `property` of `window`. The owner answers with a `SelectionNotify` event.
+    pub fn convert_selection(
+        &self,
+        selection: ffi::Atom,
+        target: ffi::Atom,
+        property: ffi::Atom,
+        window: ffi::Window,
+        time: ffi::Time,
+    ) -> Flusher<'_> {
+        unsafe {
+            (self.xlib.XConvertSelection)(self.display, selection, target, property, window, time);
+        }
+        Flusher::new(self)
+    }
+
+    /// Removes the first event which `predicate` accepts from the event queue, waiting for it
+    /// until `deadline`. Other events are left in the queue for the event loop.
+    pub fn wait_for_event<F>(
+        &self,
+        deadline: Instant,
+        mut predicate: F,
+    ) -> Result<ffi::XEvent, SelectionError>
+    where
+        F: FnMut(&ffi::XEvent) -> bool,
+    {
+        unsafe extern "C" fn check<F>(
+            _display: *mut ffi::Display,
+            event: *mut ffi::XEvent,
+            arg: *mut c_char,
+        ) -> c_int
+        where
+            F: FnMut(&ffi::XEvent) -> bool,
+        {
+            let predicate = &mut *(arg as *mut F);
+            predicate(&*event) as c_int
+        }
+
+        loop {
+            let mut event = MaybeUninit::uninit();
+            // This reads the events which are available on the connection, and flushes the
+            // requests if none matched.
+            let found = unsafe {
+                (self.xlib.XCheckIfEvent)(
+                    self.display,
+                    event.as_mut_ptr(),
+                    Some(check::<F>),
+                    &mut predicate as *mut F as *mut c_char,
+                )
+            };
+            if found != ffi::False {
+                return Ok(unsafe { event.assume_init() });
+            }
+            self.check_errors()?;
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(SelectionError::Timeout);
+            }
+            let timeout = (deadline - now).as_millis().min(c_int::MAX as u128) as c_int;
+            let mut fd = libc::pollfd {
+                fd: self.x11_fd,
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            unsafe { libc::poll(&mut fd, 1, timeout.max(1)) };
+        }
+    }
+
+    /// Waits for the answer of the owner of `selection` to a `convert_selection` on `window`, and
+    /// returns the property holding the converted selection, or `None` if the owner refused.
+    pub fn wait_for_selection(
+        &self,
+        window: ffi::Window,
+        selection: ffi::Atom,
+        target: ffi::Atom,
+    ) -> Result<Option<ffi::Atom>, SelectionError> {
+        let event = self.wait_for_event(Instant::now() + SELECTION_TIMEOUT, |event| {
+            if event.get_type() != ffi::SelectionNotify {
+                return false;
+            }
+            let event: &ffi::XSelectionEvent = event.as_ref();
+            event.requestor == window && event.selection == selection && event.target == target
+        })?;
+        let event: &ffi::XSelectionEvent = event.as_ref();
+        if event.property == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(event.property))
+        }
+    }
+
+    /// Reads the converted selection from `property` of `window`, and returns it along with its
+    /// type. The property is deleted, which lets the owner know that the transfer is done.
+    ///
+    /// If the owner sends the selection incrementally, the chunks are gathered until the last
+    /// one, which requires `PropertyChangeMask` to be selected on `window`.
+    pub fn read_selection(
+        &self,
+        window: ffi::Window,
+        property: ffi::Atom,
+    ) -> Result<Option<(ffi::Atom, Vec<c_uchar>)>, SelectionError> {
+        let is_new_value = |event: &ffi::XEvent| {
+            if event.get_type() != ffi::PropertyNotify {
+                return false;
+            }
+            let event: &ffi::XPropertyEvent = event.as_ref();
+            event.window == window && event.atom == property && event.state == ffi::PropertyNewValue
+        };
+
+        // The owner stored the property before notifying us, so its notification is already in
+        // the queue, and would be mistaken for the first chunk.
+        while self.wait_for_event(Instant::now(), is_new_value).is_ok() {}
+
+        let (property_type, data) = match self.take_property(window, property)? {
+            Some(property) => property,
+            None => return Ok(None),
+        };
+        let incr = unsafe { self.get_atom_unchecked(b"INCR\0") };
+        if property_type != incr {
+            return Ok(Some((property_type, data)));
+        }
+
+        // Deleting the `INCR` property asked for the first chunk, and the transfer ends with an
+        // empty one.
+        let mut contents = Vec::new();
+        loop {
+            self.wait_for_event(Instant::now() + SELECTION_TIMEOUT, is_new_value)?;
+            if let Some((chunk_type, chunk)) = self.take_property(window, property)? {
+                if chunk.is_empty() {
+                    return Ok(Some((chunk_type, contents)));
+                }
+                contents.extend_from_slice(&chunk);
+            }
+        }
+    }
+
+    /// Reads and deletes `property` of `window`, whatever its type is. Returns `None` if the
+    /// property doesn't exist.
+    fn take_property(
+        &self,
+        window: ffi::Window,
+        property: ffi::Atom,
+    ) -> Result<Option<(ffi::Atom, Vec<c_uchar>)>, XError> {
+        let mut actual_type = 0;
+        let mut actual_format = 0;
+        let mut quantity_returned = 0;
+        let mut bytes_after = 0;
+        let mut buf: *mut c_uchar = ptr::null_mut();
+        unsafe {
+            (self.xlib.XGetWindowProperty)(
+                self.display,
+                window,
+                property,
+                0,
+                // The length is in 32-bit chunks. The whole property is read at once, since it's
+                // only deleted once nothing is left to read.
+                c_int::MAX as c_long / 4,
+                ffi::True,
+                ffi::AnyPropertyType as ffi::Atom,
+                &mut actual_type,
+                &mut actual_format,
+                &mut quantity_returned,
+                &mut bytes_after,
+                &mut buf,
+            );
+        }
+        self.check_errors()?;
+
+        if buf.is_null() {
+            return Ok(if actual_type == 0 {
+                None
+            } else {
+                Some((actual_type, Vec::new()))
+            });
+        }
+        let size = match Format::from_format(actual_format as usize) {
+            Some(format) => format.get_actual_size(),
+            None => 1,
+        };
+        let data = unsafe {
+            let data = slice::from_raw_parts(buf, quantity_returned as usize * size).to_vec();
+            (self.xlib.XFree)(buf as _);
+            data
+        };
+        Ok(Some((actual_type, data)))
+    }
+}
@@ -72,7 +72,8 @@ impl XConnection {
         // Querying the same property on the child window we were given, we should get this child
         // window's ID again.
         let child_window_wm_check = {
-            let result = self.get_property(root_window_wm_check, check_atom, ffi::XA_WINDOW);
+            let result =
+                self.get_property::<ffi::Window>(root_window_wm_check, check_atom, ffi::XA_WINDOW);
 
             let wm_check = result.ok().and_then(|wm_check| wm_check.get(0).cloned());
 
@@ -0,0 +1,288 @@
+use std::{convert::TryInto, ffi::CString};
+
+use super::*;
+use crate::window::Theme;
+
+/// The settings of the XSETTINGS manager which winit follows, shared by the event loop and its
+/// windows.
+#[derive(Debug, Default)]
+pub struct XSettings {
+    /// The theme of the desktop.
+    pub desktop_theme: Option<Theme>,
+
+    /// The name and size of the cursor theme of the desktop.
+    pub cursor_theme: (Option<String>, Option<u32>),
+}
+
+impl XConnection {
+    /// Returns the selection owned by the XSETTINGS manager of the default screen.
+    pub fn xsettings_selection(&self) -> ffi::Atom {
+        let screen = unsafe { (self.xlib.XDefaultScreen)(self.display) };
+        self.get_atom(CString::new(format!("_XSETTINGS_S{}", screen)).unwrap())
+    }
+
+    /// Returns the window of the XSETTINGS manager of the default screen, if there is one.
+    pub fn xsettings_window(&self) -> Option<ffi::Window> {
+        let selection = self.xsettings_selection();
+        let owner = unsafe { (self.xlib.XGetSelectionOwner)(self.display, selection) };
+        if owner == 0 {
+            None
+        } else {
+            Some(owner)
+        }
+    }
+
+    /// Reads the desktop theme from the settings of the XSETTINGS manager into `xsettings`, and
+    /// returns whether it changed.
+    pub fn update_cached_desktop_theme(
+        &self,
+        xsettings: &mut XSettings,
+        xsettings_window: Option<ffi::Window>,
+    ) -> bool {
+        let theme = xsettings_window.and_then(|window| self.get_desktop_theme(window));
+        let changed = xsettings.desktop_theme != theme;
+        xsettings.desktop_theme = theme;
+        changed
+    }
+
+    /// Reads the cursor theme from the settings of the XSETTINGS manager into `xsettings`, and
+    /// returns whether it changed.
+    pub fn update_cached_cursor_theme(
+        &self,
+        xsettings: &mut XSettings,
+        xsettings_window: Option<ffi::Window>,
+    ) -> bool {
+        let settings = xsettings_window.and_then(|window| self.get_xsettings(window));
+        let theme = match settings {
+            Some(settings) => {
+                let name = match find_setting(&settings, b"Gtk/CursorThemeName") {
+                    Some(Setting::String(name)) if !name.is_empty() => Some(name),
+                    _ => None,
+                };
+                // GTK settings daemons multiply the size by the scale of the desktop, which winit
+                // applies itself.
+                let scale = match find_setting(&settings, b"Gdk/WindowScalingFactor") {
+                    Some(Setting::Integer(scale)) if scale > 0 => scale as u32,
+                    _ => 1,
+                };
+                let size = match find_setting(&settings, b"Gtk/CursorThemeSize") {
+                    Some(Setting::Integer(size)) if size > 0 => Some(size as u32 / scale),
+                    _ => None,
+                };
+                (name, size.filter(|&size| size > 0))
+            }
+            None => (None, None),
+        };
+        let changed = xsettings.cursor_theme != theme;
+        xsettings.cursor_theme = theme;
+        changed
+    }
+
+    fn get_xsettings(&self, xsettings_window: ffi::Window) -> Option<Vec<c_uchar>> {
+        let settings_atom = unsafe { self.get_atom_unchecked(b"_XSETTINGS_SETTINGS\0") };
+        self.get_property(xsettings_window, settings_atom, settings_atom)
+            .ok()
+    }
+
+    fn get_desktop_theme(&self, xsettings_window: ffi::Window) -> Option<Theme> {
+        let settings = self.get_xsettings(xsettings_window)?;
+        let theme_name = match find_setting(&settings, b"Net/ThemeName")? {
+            Setting::String(theme_name) => theme_name,
+            _ => return None,
+        };
+
+        // Dark variants of GTK themes are named after the theme, e.g. `Adwaita-dark`.
+        let is_dark = theme_name
+            .split(&['-', '_', ':'][..])
+            .any(|part| part.eq_ignore_ascii_case("dark"));
+        Some(if is_dark { Theme::Dark } else { Theme::Light })
+    }
+}
+
+/// The value of a setting, leaving out colors, which we don't read.
+enum Setting {
+    Integer(i32),
+    String(String),
+}
+
+/// Finds a setting in the value of `_XSETTINGS_SETTINGS`, following
+/// https://specifications.freedesktop.org/xsettings-spec/xsettings-spec-0.5.html.
+fn find_setting(settings: &[u8], name: &[u8]) -> Option<Setting> {
+    const TYPE_INTEGER: u8 = 0;
+    const TYPE_STRING: u8 = 1;
+    const TYPE_COLOR: u8 = 2;
+
+    let big_endian = *settings.first()? != 0;
+    let read_u16 = |offset: usize| -> Option<usize> {
+        let bytes = settings.get(offset..offset + 2)?.try_into().ok()?;
+        Some(if big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        } as usize)
+    };
+    let read_u32 = |offset: usize| -> Option<usize> {
+        let bytes = settings.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        } as usize)
+    };
+    let pad = |len: usize| (len + 3) & !3;
+
+    // The byte order and its padding, then the serial.
+    let count = read_u32(8)?;
+    let mut offset = 12;
+    for _ in 0..count {
+        let setting_type = *settings.get(offset)?;
+        let name_len = read_u16(offset + 2)?;
+        let setting_name = settings.get(offset + 4..offset + 4 + name_len)?;
+        // Skips the name and the serial of the last change.
+        offset += 4 + pad(name_len) + 4;
+
+        match setting_type {
+            TYPE_INTEGER => {
+                if setting_name == name {
+                    return Some(Setting::Integer(read_u32(offset)? as u32 as i32));
+                }
+                offset += 4;
+            }
+            TYPE_STRING => {
+                let value_len = read_u32(offset)?;
+                if setting_name == name {
+                    let value = settings.get(offset + 4..offset + 4 + value_len)?;
+                    return Some(Setting::String(String::from_utf8_lossy(value).into_owned()));
+                }
+                offset += 4 + pad(value_len);
+            }
+            TYPE_COLOR => offset += 8,
+            _ => return None,
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds the value of `_XSETTINGS_SETTINGS` in the given byte order, from settings given as
+    /// their type, name and value.
+    fn settings(big_endian: bool, entries: &[(u8, &[u8], &[u8])]) -> Vec<u8> {
+        let u16_bytes = |value: u16| {
+            if big_endian {
+                value.to_be_bytes()
+            } else {
+                value.to_le_bytes()
+            }
+        };
+        let u32_bytes = |value: u32| {
+            if big_endian {
+                value.to_be_bytes()
+            } else {
+                value.to_le_bytes()
+            }
+        };
+        let pad = |buffer: &mut Vec<u8>| buffer.resize((buffer.len() + 3) & !3, 0);
+
+        let mut buffer = vec![big_endian as u8, 0, 0, 0];
+        buffer.extend_from_slice(&u32_bytes(1));
+        buffer.extend_from_slice(&u32_bytes(entries.len() as u32));
+        for &(setting_type, name, value) in entries {
+            buffer.extend_from_slice(&[setting_type, 0]);
+            buffer.extend_from_slice(&u16_bytes(name.len() as u16));
+            buffer.extend_from_slice(name);
+            pad(&mut buffer);
+            // The serial of the last change.
+            buffer.extend_from_slice(&u32_bytes(0));
+            buffer.extend_from_slice(value);
+            pad(&mut buffer);
+        }
+        buffer
+    }
+
+    fn integer(big_endian: bool, value: i32) -> Vec<u8> {
+        if big_endian {
+            value.to_be_bytes().to_vec()
+        } else {
+            value.to_le_bytes().to_vec()
+        }
+    }
+
+    fn string(big_endian: bool, value: &str) -> Vec<u8> {
+        let mut bytes = integer(big_endian, value.len() as i32);
+        bytes.extend_from_slice(value.as_bytes());
+        bytes
+    }
+
+    fn assert_integer(setting: Option<Setting>, expected: i32) {
+        match setting {
+            Some(Setting::Integer(value)) => assert_eq!(value, expected),
+            _ => panic!("expected the integer {}", expected),
+        }
+    }
+
+    fn assert_string(setting: Option<Setting>, expected: &str) {
+        match setting {
+            Some(Setting::String(value)) => assert_eq!(value, expected),
+            _ => panic!("expected the string {:?}", expected),
+        }
+    }
+
+    #[test]
+    fn finds_settings_of_each_type() {
+        for &big_endian in &[false, true] {
+            // The names and values of odd lengths are padded to 4 bytes.
+            let color = [0xff, 0xff, 0x80, 0x80, 0x00, 0x00, 0xff, 0xff];
+            let scale = integer(big_endian, -2);
+            let theme = string(big_endian, "Adwaita-dark");
+            let cursor_theme = string(big_endian, "Bibata");
+            let settings = settings(
+                big_endian,
+                &[
+                    (2, b"Gtk/Color", &color),
+                    (0, b"Gdk/WindowScalingFactor", &scale),
+                    (1, b"Net/ThemeName", &theme),
+                    (1, b"Gtk/CursorThemeName", &cursor_theme),
+                ],
+            );
+
+            assert_integer(find_setting(&settings, b"Gdk/WindowScalingFactor"), -2);
+            assert_string(find_setting(&settings, b"Net/ThemeName"), "Adwaita-dark");
+            assert_string(find_setting(&settings, b"Gtk/CursorThemeName"), "Bibata");
+            // Colors are skipped, and a missing setting isn't an error.
+            assert!(find_setting(&settings, b"Gtk/Color").is_none());
+            assert!(find_setting(&settings, b"Gtk/CursorThemeSize").is_none());
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_settings() {
+        let theme = string(false, "Adwaita");
+        let size = integer(false, 24);
+        let settings = settings(
+            false,
+            &[
+                (1, b"Net/ThemeName", &theme),
+                (0, b"Gtk/CursorThemeSize", &size),
+            ],
+        );
+
+        for len in 0..settings.len() {
+            let truncated = &settings[..len];
+            assert!(find_setting(truncated, b"Gtk/CursorThemeSize").is_none());
+        }
+        assert_integer(find_setting(&settings, b"Gtk/CursorThemeSize"), 24);
+    }
+
+    #[test]
+    fn stops_at_unknown_types() {
+        let size = integer(false, 24);
+        let settings = settings(
+            false,
+            &[(3, b"Unknown", &[]), (0, b"Gtk/CursorThemeSize", &size)],
+        );
+        assert!(find_setting(&settings, b"Gtk/CursorThemeSize").is_none());
+    }
+}
@@ -18,11 +18,15 @@ use crate::{
     error::{ExternalError, NotSupportedError, OsError as RootOsError},
     monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
     platform_impl::{
+        platform::cursor_theme::CursorTheme,
         x11::{ime::ImeContextCreationError, MonitorHandle as X11MonitorHandle},
         MonitorHandle as PlatformMonitorHandle, OsError, PlatformSpecificWindowBuilderAttributes,
-        VideoMode as PlatformVideoMode,
+        VideoMode as PlatformVideoMode, WindowId as PlatformWindowId,
+    },
+    window::{
+        Cursor, CursorGrabMode, CursorIcon, CustomCursor, Fullscreen, Icon, ResizeDirection, Theme,
+        UserAttentionType, WindowAttributes, WindowButtons, WindowLevel,
     },
-    window::{CursorIcon, Fullscreen, Icon, UserAttentionType, WindowAttributes},
 };
 
 use super::{ffi, util, EventLoopWindowTarget, ImeSender, WindowId, XConnection, XError};
@@ -49,6 +53,12 @@ pub struct SharedState {
     pub resize_increments: Option<Size>,
     pub base_size: Option<Size>,
     pub visibility: Visibility,
+    pub is_resizable: bool,
+    // The theme set with `set_theme`, or `None` to follow the desktop theme
+    pub preferred_theme: Option<Theme>,
+    // Whether the window was last reported as occluded
+    pub occluded: bool,
+    pub enabled_buttons: WindowButtons,
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -86,6 +96,10 @@ impl SharedState {
             max_inner_size: None,
             resize_increments: None,
             base_size: None,
+            is_resizable: true,
+            preferred_theme: None,
+            occluded: false,
+            enabled_buttons: WindowButtons::all(),
         })
     }
 }
@@ -98,12 +112,20 @@ pub struct UnownedWindow {
     xwindow: ffi::Window,        // never changes
     root: ffi::Window,           // never changes
     screen_id: i32,              // never changes
-    cursor: Mutex<CursorIcon>,
-    cursor_grabbed: Mutex<bool>,
+    is_popup: bool,              // never changes
+    owner: Option<ffi::Window>,  // never changes
+    modal: bool,                 // never changes
+    cursor: Mutex<Cursor>,
+    /// The theme set with `set_cursor_theme`, which replaces the theme of the desktop.
+    cursor_theme: Mutex<Option<CursorTheme>>,
+    /// The settings of the XSETTINGS manager, which the event loop keeps up to date.
+    xsettings: Arc<Mutex<util::XSettings>>,
+    cursor_grab: Mutex<CursorGrabMode>,
     cursor_visible: Mutex<bool>,
     ime_sender: Mutex<ImeSender>,
     pub shared_state: Mutex<SharedState>,
     redraw_sender: Sender<WindowId>,
+    frame_sender: Sender<WindowId>,
 }
 
 impl UnownedWindow {
@@ -170,6 +192,57 @@ impl UnownedWindow {
             dimensions
         };
 
+        let xwindow_of = |window_id: &PlatformWindowId, error: &'static str| match *window_id {
+            PlatformWindowId::X(WindowId(xwindow)) => Ok(xwindow),
+            #[allow(unreachable_patterns)]
+            _ => Err(os_error!(OsError::XMisc(error))),
+        };
+        let owner = pl_attribs
+            .owner
+            .as_ref()
+            .map(|owner| xwindow_of(owner, "The owner of a window isn't an X11 window"))
+            .transpose()?;
+
+        // Popups are placed relative to their parent, and kept on the monitor of their anchor.
+        let popup_parent = pl_attribs
+            .popup
+            .as_ref()
+            .map(|popup| {
+                xwindow_of(&popup.parent, "The parent of a popup isn't an X11 window")
+                    .map(|parent| (popup, parent))
+            })
+            .transpose()?;
+        let (position, dimensions) = match popup_parent {
+            Some((popup, parent)) => {
+                let coords = xconn
+                    .translate_coords(parent, root)
+                    .map_err(|x_err| os_error!(OsError::XError(x_err)))?;
+                let parent_position = PhysicalPosition::new(coords.x_rel_root, coords.y_rel_root);
+                let anchor = popup
+                    .anchor_rect
+                    .position
+                    .to_physical::<i32>(popup.scale_factor);
+                let (anchor_x, anchor_y) = (
+                    (parent_position.x + anchor.x) as i64,
+                    (parent_position.y + anchor.y) as i64,
+                );
+                let monitor = xconn
+                    .available_monitors()
+                    .into_iter()
+                    .find(|monitor| monitor.rect.contains_point(anchor_x, anchor_y))
+                    .unwrap_or_else(|| xconn.primary_monitor());
+                let (position, size) = popup.place(
+                    parent_position,
+                    dimensions.into(),
+                    monitor.position(),
+                    monitor.size(),
+                );
+                (position.into(), size.into())
+            }
+            None => ((0, 0), dimensions),
+        };
+        let override_redirect = pl_attribs.override_redirect || popup_parent.is_some();
+
         let screen_id = match pl_attribs.screen_id {
             Some(id) => id,
             None => unsafe { (xconn.xlib.XDefaultScreen)(xconn.display) },
@@ -194,15 +267,16 @@ impl UnownedWindow {
                 | ffi::KeymapStateMask
                 | ffi::ButtonPressMask
                 | ffi::ButtonReleaseMask
-                | ffi::PointerMotionMask;
+                | ffi::PointerMotionMask
+                | ffi::PropertyChangeMask;
             swa.border_pixel = 0;
-            swa.override_redirect = pl_attribs.override_redirect as c_int;
+            swa.override_redirect = override_redirect as c_int;
             swa
         };
 
         let mut window_attributes = ffi::CWBorderPixel | ffi::CWColormap | ffi::CWEventMask;
 
-        if pl_attribs.override_redirect {
+        if override_redirect {
             window_attributes |= ffi::CWOverrideRedirect;
         }
 
@@ -211,8 +285,8 @@ impl UnownedWindow {
             (xconn.xlib.XCreateWindow)(
                 xconn.display,
                 root,
-                0,
-                0,
+                position.0,
+                position.1,
                 dimensions.0 as c_uint,
                 dimensions.1 as c_uint,
                 0,
@@ -243,12 +317,18 @@ impl UnownedWindow {
             xwindow,
             root,
             screen_id,
+            is_popup: popup_parent.is_some(),
+            owner,
+            modal: pl_attribs.modal,
             cursor: Default::default(),
-            cursor_grabbed: Mutex::new(false),
+            cursor_theme: Mutex::new(None),
+            xsettings: event_loop.xsettings.clone(),
+            cursor_grab: Mutex::new(CursorGrabMode::None),
             cursor_visible: Mutex::new(true),
             ime_sender: Mutex::new(event_loop.ime_sender.clone()),
             shared_state: SharedState::new(guessed_monitor, window_attrs.visible),
             redraw_sender: event_loop.redraw_sender.clone(),
+            frame_sender: event_loop.frame_sender.clone(),
         };
 
         // Title must be set before mapping. Some tiling window managers (i.e. i3) use the window
@@ -258,6 +338,10 @@ impl UnownedWindow {
         window
             .set_decorations_inner(window_attrs.decorations)
             .queue();
+        window.shared_state.get_mut().enabled_buttons = window_attrs.enabled_buttons;
+        window
+            .set_functions_inner(window_attrs.resizable, window_attrs.enabled_buttons)
+            .queue();
 
         {
             // Enable drag and drop (TODO: extend API to make this toggleable)
@@ -313,9 +397,52 @@ impl UnownedWindow {
 
             window.set_pid().map(|flusher| flusher.queue());
 
-            window.set_window_types(pl_attribs.x11_window_types).queue();
+            let window_types = if pl_attribs.x11_window_types != [util::WindowType::Normal] {
+                pl_attribs.x11_window_types
+            } else if popup_parent.is_some() {
+                vec![util::WindowType::PopupMenu]
+            } else if owner.is_some() {
+                vec![util::WindowType::Dialog]
+            } else {
+                pl_attribs.x11_window_types
+            };
+            window.set_window_types(window_types).queue();
+
+            if let Some(transient_for) = popup_parent.map(|(_, parent)| parent).or(owner) {
+                unsafe {
+                    (xconn.xlib.XSetTransientForHint)(xconn.display, window.xwindow, transient_for);
+                }
+            }
+
+            // The initial state is read by the window manager when the window is mapped.
+            if window.modal && owner.is_some() {
+                let state_atom = unsafe { xconn.get_atom_unchecked(b"_NET_WM_STATE\0") };
+                let modal_atom = unsafe { xconn.get_atom_unchecked(b"_NET_WM_STATE_MODAL\0") };
+                xconn
+                    .change_property(
+                        window.xwindow,
+                        state_atom,
+                        ffi::XA_ATOM,
+                        util::PropMode::Replace,
+                        &[modal_atom],
+                    )
+                    .queue();
+            }
+
+            if window_attrs.blur {
+                window.set_blur_inner(Some(&[])).queue();
+            }
+
+            // Without a cursor, the window shows the one of its parent, which isn't loaded at
+            // the size for our scale factor.
+            window.update_cursor(Some(&Cursor::default()));
 
             if let Some(variant) = pl_attribs.gtk_theme_variant {
+                window.shared_state.get_mut().preferred_theme = match variant.as_str() {
+                    "dark" => Some(Theme::Dark),
+                    "light" => Some(Theme::Light),
+                    _ => None,
+                };
                 window.set_gtk_theme_variant(variant).queue();
             }
 
@@ -338,11 +465,14 @@ impl UnownedWindow {
                         let mut shared_state = window.shared_state.get_mut();
                         shared_state.min_inner_size = window_attrs.min_inner_size;
                         shared_state.max_inner_size = window_attrs.max_inner_size;
-                        shared_state.resize_increments = pl_attribs.resize_increments;
-                        shared_state.base_size = pl_attribs.base_size;
+                        shared_state.is_resizable = false;
                     }
                 }
 
+                let shared_state = window.shared_state.get_mut();
+                shared_state.resize_increments = pl_attribs.resize_increments;
+                shared_state.base_size = pl_attribs.base_size;
+
                 let mut normal_hints = util::NormalHints::new(xconn);
                 normal_hints.set_size(Some(dimensions));
                 normal_hints.set_min_size(min_inner_size.map(Into::into));
@@ -418,6 +548,21 @@ impl UnownedWindow {
                 .select_xinput_events(window.xwindow, ffi::XIAllMasterDevices, mask)
                 .queue();
 
+            if window.is_popup && window_attrs.visible {
+                window.grab_popup_pointer();
+            }
+
+            // Select Present events, used to pace `Window::request_frame`
+            if let (Some(xpresent), Some(_)) = (&xconn.xpresent, event_loop.present_opcode) {
+                unsafe {
+                    (xpresent.XPresentSelectInput)(
+                        xconn.display,
+                        window.xwindow,
+                        ffi::PresentCompleteNotifyMask as c_uint,
+                    )
+                };
+            }
+
             {
                 let result = event_loop.ime.borrow_mut().create_context(window.xwindow);
                 if let Err(err) = result {
@@ -440,9 +585,9 @@ impl UnownedWindow {
                     .set_fullscreen_inner(window_attrs.fullscreen.clone())
                     .map(|flusher| flusher.queue());
             }
-            if window_attrs.always_on_top {
+            if window_attrs.window_level != WindowLevel::Normal {
                 window
-                    .set_always_on_top_inner(window_attrs.always_on_top)
+                    .set_window_level_inner(window_attrs.window_level)
                     .queue();
             }
         }
@@ -523,6 +668,38 @@ impl UnownedWindow {
         )
     }
 
+    fn unset_gtk_theme_variant(&self) -> util::Flusher<'_> {
+        let hint_atom = unsafe { self.xconn.get_atom_unchecked(b"_GTK_THEME_VARIANT\0") };
+        unsafe {
+            (self.xconn.xlib.XDeleteProperty)(self.xconn.display, self.xwindow, hint_atom);
+        }
+        util::Flusher::new(&self.xconn)
+    }
+
+    /// Blurs behind `region`, given as `[x, y, width, height]` or empty for the whole window, or
+    /// disables the blur if it's `None`.
+    fn set_blur_inner(&self, region: Option<&[util::Cardinal]>) -> util::Flusher<'_> {
+        let blur_atom = unsafe {
+            self.xconn
+                .get_atom_unchecked(b"_KDE_NET_WM_BLUR_BEHIND_REGION\0")
+        };
+        match region {
+            Some(region) => self.xconn.change_property(
+                self.xwindow,
+                blur_atom,
+                ffi::XA_CARDINAL,
+                util::PropMode::Replace,
+                region,
+            ),
+            None => {
+                unsafe {
+                    (self.xconn.xlib.XDeleteProperty)(self.xconn.display, self.xwindow, blur_atom);
+                }
+                util::Flusher::new(&self.xconn)
+            }
+        }
+    }
+
     fn set_netwm(
         &self,
         operation: util::StateOperation,
@@ -636,7 +813,6 @@ impl UnownedWindow {
                         inner: PlatformMonitorHandle::X(monitor),
                     })) => (None, monitor),
                     Fullscreen::Borderless(None) => (None, self.current_monitor()),
-                    #[cfg(feature = "wayland")]
                     _ => unreachable!(),
                 };
 
@@ -706,6 +882,13 @@ impl UnownedWindow {
         }
     }
 
+    // Called by EventProcessor when the window is unmapped or its visibility changes, returns
+    // whether `WindowEvent::Occluded` should be sent
+    pub(crate) fn set_occluded(&self, occluded: bool) -> bool {
+        let mut shared_state = self.shared_state.lock();
+        replace(&mut shared_state.occluded, occluded) != occluded
+    }
+
     // Called by EventProcessor when a VisibilityNotify event is received
     pub(crate) fn visibility_notify(&self) {
         let mut shared_state = self.shared_state.lock();
@@ -783,6 +966,38 @@ impl UnownedWindow {
         )
     }
 
+    /// Returns the `_NET_WM_STATE` atoms set on the window by the window manager.
+    fn net_wm_state(&self) -> Vec<ffi::Atom> {
+        let state_atom = unsafe { self.xconn.get_atom_unchecked(b"_NET_WM_STATE\0") };
+        self.xconn
+            .get_property(self.xwindow, state_atom, ffi::XA_ATOM)
+            .unwrap_or_default()
+    }
+
+    #[inline]
+    pub fn is_minimized(&self) -> Option<bool> {
+        let hidden_atom = unsafe { self.xconn.get_atom_unchecked(b"_NET_WM_STATE_HIDDEN\0") };
+        if !util::hint_is_supported(hidden_atom) {
+            return None;
+        }
+
+        Some(self.net_wm_state().contains(&hidden_atom))
+    }
+
+    #[inline]
+    pub fn is_maximized(&self) -> bool {
+        let horz_atom = unsafe {
+            self.xconn
+                .get_atom_unchecked(b"_NET_WM_STATE_MAXIMIZED_HORZ\0")
+        };
+        let vert_atom = unsafe {
+            self.xconn
+                .get_atom_unchecked(b"_NET_WM_STATE_MAXIMIZED_VERT\0")
+        };
+        let state = self.net_wm_state();
+        state.contains(&horz_atom) && state.contains(&vert_atom)
+    }
+
     #[inline]
     pub fn set_maximized(&self, maximized: bool) {
         self.set_maximized_inner(maximized)
@@ -834,24 +1049,91 @@ impl UnownedWindow {
         self.invalidate_cached_frame_extents();
     }
 
-    fn set_maximizable_inner(&self, maximizable: bool) -> util::Flusher<'_> {
+    #[inline]
+    pub fn is_decorated(&self) -> bool {
+        self.xconn.get_motif_hints(self.xwindow).decorations()
+    }
+
+    fn set_functions_inner(&self, resizable: bool, buttons: WindowButtons) -> util::Flusher<'_> {
         let mut hints = self.xconn.get_motif_hints(self.xwindow);
 
-        hints.set_maximizable(maximizable);
+        hints.set_maximizable(resizable && buttons.contains(WindowButtons::MAXIMIZE));
+        hints.set_minimizable(buttons.contains(WindowButtons::MINIMIZE));
+        hints.set_closable(buttons.contains(WindowButtons::CLOSE));
 
         self.xconn.set_motif_hints(self.xwindow, &hints)
     }
 
-    fn set_always_on_top_inner(&self, always_on_top: bool) -> util::Flusher<'_> {
+    #[inline]
+    pub fn set_enabled_buttons(&self, buttons: WindowButtons) {
+        let resizable = {
+            let mut shared_state = self.shared_state.lock();
+            shared_state.enabled_buttons = buttons;
+            shared_state.is_resizable
+        };
+        self.set_functions_inner(resizable, buttons)
+            .flush()
+            .expect("Failed to set enabled buttons");
+    }
+
+    #[inline]
+    pub fn enabled_buttons(&self) -> WindowButtons {
+        self.shared_state.lock().enabled_buttons
+    }
+
+    fn set_window_level_inner(&self, level: WindowLevel) -> util::Flusher<'_> {
         let above_atom = unsafe { self.xconn.get_atom_unchecked(b"_NET_WM_STATE_ABOVE\0") };
-        self.set_netwm(always_on_top.into(), (above_atom as c_long, 0, 0, 0))
+        let below_atom = unsafe { self.xconn.get_atom_unchecked(b"_NET_WM_STATE_BELOW\0") };
+
+        // The window can't be both above and below, so the opposite state is removed first.
+        let (opposite_atom, (state_atom, add)) = match level {
+            WindowLevel::AlwaysOnTop => (below_atom, (above_atom, true)),
+            WindowLevel::AlwaysOnBottom => (above_atom, (below_atom, true)),
+            WindowLevel::Normal => (above_atom, (below_atom, false)),
+        };
+        self.set_netwm(
+            util::StateOperation::Remove,
+            (opposite_atom as c_long, 0, 0, 0),
+        )
+        .queue();
+        self.set_netwm(add.into(), (state_atom as c_long, 0, 0, 0))
     }
 
     #[inline]
-    pub fn set_always_on_top(&self, always_on_top: bool) {
-        self.set_always_on_top_inner(always_on_top)
+    pub fn set_window_level(&self, level: WindowLevel) {
+        self.set_window_level_inner(level)
             .flush()
-            .expect("Failed to set always-on-top state");
+            .expect("Failed to set window level");
+    }
+
+    #[inline]
+    pub fn set_blur(&self, blur: bool) {
+        self.set_blur_inner(if blur { Some(&[]) } else { None })
+            .flush()
+            .expect("Failed to set blur");
+    }
+
+    #[inline]
+    pub fn set_blur_region(&self, position: Position, size: Size) {
+        let scale_factor = self.scale_factor();
+        let (x, y): (i32, i32) = position.to_physical::<i32>(scale_factor).into();
+        let (width, height): (u32, u32) = size.to_physical::<u32>(scale_factor).into();
+
+        // The region is clipped to the window, since the property can't hold negative positions.
+        let window_size = self.inner_size();
+        let left = x.max(0) as i64;
+        let top = y.max(0) as i64;
+        let right = (x as i64 + width as i64).min(window_size.width as i64);
+        let bottom = (y as i64 + height as i64).min(window_size.height as i64);
+        let region = [
+            left as util::Cardinal,
+            top as util::Cardinal,
+            (right - left).max(0) as util::Cardinal,
+            (bottom - top).max(0) as util::Cardinal,
+        ];
+        self.set_blur_inner(Some(&region))
+            .flush()
+            .expect("Failed to set blur");
     }
 
     fn set_icon_inner(&self, icon: Icon) -> util::Flusher<'_> {
@@ -888,6 +1170,11 @@ impl UnownedWindow {
         .expect("Failed to set icons");
     }
 
+    #[inline]
+    pub fn is_visible(&self) -> Option<bool> {
+        self.xconn.is_viewable(self.xwindow).ok()
+    }
+
     #[inline]
     pub fn set_visible(&self, visible: bool) {
         let mut shared_state = self.shared_state.lock();
@@ -907,7 +1194,15 @@ impl UnownedWindow {
                 .flush_requests()
                 .expect("Failed to call XMapRaised");
             shared_state.visibility = Visibility::YesWait;
+            if self.is_popup {
+                self.grab_popup_pointer();
+            }
         } else {
+            if self.is_popup {
+                self.xconn
+                    .ungrab_xinput_pointer(util::VIRTUAL_CORE_POINTER)
+                    .queue();
+            }
             unsafe {
                 (self.xconn.xlib.XUnmapWindow)(self.xconn.display, self.xwindow);
             }
@@ -918,6 +1213,42 @@ impl UnownedWindow {
         }
     }
 
+    /// Whether this is a visible modal window owned by `owner`, which swallows its input.
+    pub(crate) fn is_modal_for(&self, owner: ffi::Window) -> bool {
+        self.modal
+            && self.owner == Some(owner)
+            && self.shared_state.lock().visibility != Visibility::No
+    }
+
+    /// Grabs the pointer for a visible popup, so that it's dismissed by clicks outside of it.
+    fn grab_popup_pointer(&self) {
+        let mask = ffi::XI_MotionMask
+            | ffi::XI_ButtonPressMask
+            | ffi::XI_ButtonReleaseMask
+            | ffi::XI_EnterMask
+            | ffi::XI_LeaveMask;
+        if !self
+            .xconn
+            .grab_xinput_pointer(self.xwindow, util::VIRTUAL_CORE_POINTER, mask)
+        {
+            warn!("Failed to grab the pointer for a popup, clicks outside of it won't dismiss it");
+        }
+    }
+
+    /// Hides a popup when its grabbed pointer is pressed outside of it, and returns whether it was
+    /// dismissed.
+    pub(crate) fn dismiss_popup(&self, x: f64, y: f64) -> bool {
+        if !self.is_popup {
+            return false;
+        }
+        let (width, height) = self.inner_size_physical();
+        if x >= 0.0 && y >= 0.0 && x < width as f64 && y < height as f64 {
+            return false;
+        }
+        self.set_visible(false);
+        true
+    }
+
     fn update_cached_frame_extents(&self) {
         let extents = self
             .xconn
@@ -1080,6 +1411,17 @@ impl UnownedWindow {
         self.set_max_inner_size_physical(physical_dimensions);
     }
 
+    #[inline]
+    pub fn set_resize_increments(&self, increments: Option<Size>) {
+        self.shared_state.lock().resize_increments = increments;
+        let physical_increments =
+            increments.map(|increments| increments.to_physical::<u32>(self.scale_factor()).into());
+        self.update_normal_hints(|normal_hints| {
+            normal_hints.set_resize_increments(physical_increments)
+        })
+        .expect("Failed to call `XSetWMNormalHints`");
+    }
+
     pub(crate) fn adjust_for_dpi(
         &self,
         old_scale_factor: f64,
@@ -1109,6 +1451,14 @@ impl UnownedWindow {
         (new_width, new_height)
     }
 
+    #[inline]
+    pub fn is_resizable(&self) -> bool {
+        self.xconn
+            .get_normal_hints(self.xwindow)
+            .map(|normal_hints| normal_hints.resizable())
+            .unwrap_or_else(|_| self.shared_state.lock().is_resizable)
+    }
+
     pub fn set_resizable(&self, resizable: bool) {
         if util::wm_name_is_one_of(&["Xfwm4"]) {
             // Making the window unresizable on Xfwm prevents further changes to `WM_NORMAL_HINTS` from being detected.
@@ -1118,6 +1468,8 @@ impl UnownedWindow {
             return;
         }
 
+        self.shared_state.lock().is_resizable = resizable;
+
         let (min_size, max_size) = if resizable {
             let shared_state_lock = self.shared_state.lock();
             (
@@ -1129,7 +1481,8 @@ impl UnownedWindow {
             (window_size.clone(), window_size)
         };
 
-        self.set_maximizable_inner(resizable).queue();
+        let enabled_buttons = self.shared_state.lock().enabled_buttons;
+        self.set_functions_inner(resizable, enabled_buttons).queue();
 
         let scale_factor = self.scale_factor();
         let min_inner_size = min_size
@@ -1172,16 +1525,72 @@ impl UnownedWindow {
 
     #[inline]
     pub fn set_cursor_icon(&self, cursor: CursorIcon) {
-        let old_cursor = replace(&mut *self.cursor.lock(), cursor);
+        self.set_cursor(Cursor::Icon(cursor));
+    }
+
+    #[inline]
+    pub fn set_custom_cursor(&self, cursor: CustomCursor) {
+        self.set_cursor(Cursor::Custom(cursor));
+    }
+
+    fn set_cursor(&self, cursor: Cursor) {
+        let old_cursor = replace(&mut *self.cursor.lock(), cursor.clone());
         if cursor != old_cursor && *self.cursor_visible.lock() {
-            self.xconn.set_cursor_icon(self.xwindow, Some(cursor));
+            self.update_cursor(Some(&cursor));
+        }
+    }
+
+    /// Shows `cursor` over the window, or hides the cursor if it's `None`.
+    fn update_cursor(&self, cursor: Option<&Cursor>) {
+        match cursor {
+            Some(Cursor::Icon(icon)) => {
+                self.xconn
+                    .set_cursor_icon(self.xwindow, Some(*icon), &self.scaled_cursor_theme())
+            }
+            Some(Cursor::Custom(cursor)) => self.xconn.set_custom_cursor(self.xwindow, cursor),
+            None => self
+                .xconn
+                .set_cursor_icon(self.xwindow, None, &self.scaled_cursor_theme()),
+        }
+    }
+
+    /// Returns the cursor theme of the window, with its size scaled by the scale factor.
+    fn scaled_cursor_theme(&self) -> CursorTheme {
+        let theme = self
+            .cursor_theme
+            .lock()
+            .clone()
+            .unwrap_or_else(|| self.xconn.default_cursor_theme(&self.xsettings.lock()));
+        CursorTheme {
+            size: theme.scaled_size(self.scale_factor()),
+            ..theme
+        }
+    }
+
+    #[inline]
+    pub fn set_cursor_theme(&self, name: &str, size: u32) {
+        *self.cursor_theme.lock() = Some(CursorTheme {
+            name: name.to_owned(),
+            size,
+        });
+        self.reload_cursor();
+    }
+
+    /// Loads the cursor from the cursor theme again, after the theme or the scale factor changed.
+    pub fn reload_cursor(&self) {
+        if !*self.cursor_visible.lock() {
+            return;
+        }
+        let cursor = self.cursor.lock().clone();
+        if let Cursor::Icon(_) = cursor {
+            self.update_cursor(Some(&cursor));
         }
     }
 
     #[inline]
-    pub fn set_cursor_grab(&self, grab: bool) -> Result<(), ExternalError> {
-        let mut grabbed_lock = self.cursor_grabbed.lock();
-        if grab == *grabbed_lock {
+    pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
+        let mut grab_lock = self.cursor_grab.lock();
+        if mode == *grab_lock {
             return Ok(());
         }
         unsafe {
@@ -1189,7 +1598,9 @@ impl UnownedWindow {
             // Therefore, this is common to both codepaths.
             (self.xconn.xlib.XUngrabPointer)(self.xconn.display, ffi::CurrentTime);
         }
-        let result = if grab {
+        // X11 can't lock the pointer, so a locked cursor is confined and kept at the center of
+        // the window by `recenter_locked_cursor`.
+        let result = if mode != CursorGrabMode::None {
             let result = unsafe {
                 (self.xconn.xlib.XGrabPointer)(
                     self.xconn.display,
@@ -1235,11 +1646,35 @@ impl UnownedWindow {
                 .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))
         };
         if result.is_ok() {
-            *grabbed_lock = grab;
+            *grab_lock = mode;
+            drop(grab_lock);
+            if mode == CursorGrabMode::Locked {
+                self.recenter_locked_cursor(None);
+            }
         }
         result
     }
 
+    #[inline]
+    pub fn is_cursor_locked(&self) -> bool {
+        *self.cursor_grab.lock() == CursorGrabMode::Locked
+    }
+
+    /// Moves the locked cursor back to the center of the window, unless `cursor_position` is
+    /// already there.
+    pub fn recenter_locked_cursor(&self, cursor_position: Option<(f64, f64)>) {
+        let size = self.shared_state.lock().size;
+        let (width, height) = size.unwrap_or_else(|| self.inner_size_physical());
+        let center = ((width / 2) as i32, (height / 2) as i32);
+        let at_center = cursor_position
+            .map(|(x, y)| (x as i32, y as i32) == center)
+            .unwrap_or(false);
+        if !at_center {
+            // The warp causes a motion event at the center, which is then ignored.
+            let _ = self.set_cursor_position_physical(center.0, center.1);
+        }
+    }
+
     #[inline]
     pub fn set_cursor_visible(&self, visible: bool) {
         let mut visible_lock = self.cursor_visible.lock();
@@ -1247,13 +1682,13 @@ impl UnownedWindow {
             return;
         }
         let cursor = if visible {
-            Some(*self.cursor.lock())
+            Some(self.cursor.lock().clone())
         } else {
             None
         };
         *visible_lock = visible;
         drop(visible_lock);
-        self.xconn.set_cursor_icon(self.xwindow, cursor);
+        self.update_cursor(cursor.as_ref());
     }
 
     #[inline]
@@ -1276,6 +1711,87 @@ impl UnownedWindow {
         self.set_cursor_position_physical(x, y)
     }
 
+    #[inline]
+    pub fn drag_window(&self) -> Result<(), ExternalError> {
+        self.drag_initiate(util::MoveResizeDirection::Move)
+    }
+
+    #[inline]
+    pub fn drag_resize_window(&self, direction: ResizeDirection) -> Result<(), ExternalError> {
+        self.drag_initiate(direction.into())
+    }
+
+    #[inline]
+    pub fn set_cursor_hittest(&self, hittest: bool) -> Result<(), ExternalError> {
+        let xfixes = match self.xconn.xfixes {
+            Some(ref xfixes) => xfixes,
+            None => return Err(ExternalError::NotSupported(NotSupportedError::new())),
+        };
+        unsafe {
+            // An empty input shape lets the cursor events through, and no shape restores the default.
+            let region = if hittest {
+                0
+            } else {
+                (xfixes.XFixesCreateRegion)(self.xconn.display, ptr::null_mut(), 0)
+            };
+            (xfixes.XFixesSetWindowShapeRegion)(
+                self.xconn.display,
+                self.xwindow,
+                ffi::SHAPE_INPUT,
+                0,
+                0,
+                region,
+            );
+            if region != 0 {
+                (xfixes.XFixesDestroyRegion)(self.xconn.display, region);
+            }
+        }
+        self.xconn
+            .flush_requests()
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))
+    }
+
+    /// Hands the pointer over to the window manager with `_NET_WM_MOVERESIZE`.
+    fn drag_initiate(&self, direction: util::MoveResizeDirection) -> Result<(), ExternalError> {
+        let pointer = self
+            .xconn
+            .query_pointer(self.xwindow, util::VIRTUAL_CORE_POINTER)
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))?;
+        let (root_x, root_y) = (pointer.root_x as c_long, pointer.root_y as c_long);
+        drop(pointer);
+
+        let message = unsafe { self.xconn.get_atom_unchecked(b"_NET_WM_MOVERESIZE\0") };
+
+        // The window manager can't grab the pointer while we hold a grab, and `set_cursor_grab`
+        // only ungrabs when it believes the cursor to be grabbed, so we ungrab unconditionally.
+        let mut grab_lock = self.cursor_grab.lock();
+        unsafe {
+            (self.xconn.xlib.XUngrabPointer)(self.xconn.display, ffi::CurrentTime);
+        }
+        self.xconn
+            .flush_requests()
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))?;
+        *grab_lock = CursorGrabMode::None;
+
+        self.xconn
+            .send_client_msg(
+                self.xwindow,
+                self.root,
+                message,
+                Some(ffi::SubstructureRedirectMask | ffi::SubstructureNotifyMask),
+                [
+                    root_x,
+                    root_y,
+                    direction as c_long,
+                    ffi::Button1 as c_long,
+                    // The source indication of a normal application.
+                    1,
+                ],
+            )
+            .flush()
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))
+    }
+
     pub(crate) fn set_ime_position_physical(&self, x: i32, y: i32) {
         let _ = self
             .ime_sender
@@ -1306,6 +1822,26 @@ impl UnownedWindow {
             .expect("Failed to set urgency hint");
     }
 
+    #[inline]
+    pub fn set_theme(&self, theme: Option<Theme>) {
+        self.shared_state.lock().preferred_theme = theme;
+        match theme {
+            Some(Theme::Dark) => self.set_gtk_theme_variant("dark".to_owned()),
+            Some(Theme::Light) => self.set_gtk_theme_variant("light".to_owned()),
+            None => self.unset_gtk_theme_variant(),
+        }
+        .flush()
+        .expect("Failed to set `_GTK_THEME_VARIANT`");
+    }
+
+    #[inline]
+    pub fn theme(&self) -> Option<Theme> {
+        self.shared_state
+            .lock()
+            .preferred_theme
+            .or_else(|| self.xsettings.lock().desktop_theme)
+    }
+
     #[inline]
     pub fn id(&self) -> WindowId {
         WindowId(self.xwindow)
@@ -1316,6 +1852,11 @@ impl UnownedWindow {
         self.redraw_sender.send(WindowId(self.xwindow)).unwrap();
     }
 
+    #[inline]
+    pub fn request_frame(&self) {
+        self.frame_sender.send(WindowId(self.xwindow)).unwrap();
+    }
+
     #[inline]
     pub fn raw_window_handle(&self) -> XlibHandle {
         XlibHandle {
@@ -3,6 +3,7 @@ use std::{collections::HashMap, error::Error, fmt, os::raw::c_int, ptr};
 use libc;
 use parking_lot::Mutex;
 
+use crate::platform_impl::platform::cursor_theme::CursorTheme;
 use crate::window::CursorIcon;
 
 use super::ffi;
@@ -18,10 +19,14 @@ pub struct XConnection {
     pub xinput2: ffi::XInput2,
     pub xlib_xcb: ffi::Xlib_xcb,
     pub xrender: ffi::Xrender,
+    /// Exposes the Present extension, if available
+    pub xpresent: Option<ffi::Xpresent>,
+    /// Exposes the XFixes extension, if available
+    pub xfixes: Option<ffi::XFixes>,
     pub display: *mut ffi::Display,
     pub x11_fd: c_int,
     pub latest_error: Mutex<Option<XError>>,
-    pub cursor_cache: Mutex<HashMap<Option<CursorIcon>, ffi::Cursor>>,
+    pub cursor_cache: Mutex<HashMap<(Option<CursorIcon>, CursorTheme), ffi::Cursor>>,
 }
 
 unsafe impl Send for XConnection {}
@@ -40,6 +45,8 @@ impl XConnection {
         let xinput2 = ffi::XInput2::open()?;
         let xlib_xcb = ffi::Xlib_xcb::open()?;
         let xrender = ffi::Xrender::open()?;
+        let xpresent = ffi::Xpresent::open().ok();
+        let xfixes = ffi::XFixes::open().ok();
 
         unsafe { (xlib.XInitThreads)() };
         unsafe { (xlib.XSetErrorHandler)(error_handler) };
@@ -64,6 +71,8 @@ impl XConnection {
             xinput2,
             xlib_xcb,
             xrender,
+            xpresent,
+            xfixes,
             display,
             x11_fd: fd,
             latest_error: Mutex::new(None),
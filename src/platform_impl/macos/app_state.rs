@@ -53,7 +53,6 @@ pub trait EventHandler: Debug {
 
 struct EventLoopHandler<T: 'static> {
     callback: Box<dyn FnMut(Event<'_, T>, &RootWindowTarget<T>, &mut ControlFlow)>,
-    will_exit: bool,
     window_target: Rc<RootWindowTarget<T>>,
 }
 
@@ -68,23 +67,23 @@ impl<T> Debug for EventLoopHandler<T> {
 
 impl<T> EventHandler for EventLoopHandler<T> {
     fn handle_nonuser_event(&mut self, event: Event<'_, Never>, control_flow: &mut ControlFlow) {
-        (self.callback)(event.userify(), &self.window_target, control_flow);
-        self.will_exit |= *control_flow == ControlFlow::Exit;
-        if self.will_exit {
-            *control_flow = ControlFlow::Exit;
+        if let ControlFlow::ExitWithCode(code) = *control_flow {
+            let dummy = &mut ControlFlow::ExitWithCode(code);
+            (self.callback)(event.userify(), &self.window_target, dummy);
+        } else {
+            (self.callback)(event.userify(), &self.window_target, control_flow);
         }
     }
 
     fn handle_user_events(&mut self, control_flow: &mut ControlFlow) {
-        let mut will_exit = self.will_exit;
         for event in self.window_target.p.receiver.try_iter() {
-            (self.callback)(Event::UserEvent(event), &self.window_target, control_flow);
-            will_exit |= *control_flow == ControlFlow::Exit;
-            if will_exit {
-                *control_flow = ControlFlow::Exit;
+            if let ControlFlow::ExitWithCode(code) = *control_flow {
+                let dummy = &mut ControlFlow::ExitWithCode(code);
+                (self.callback)(Event::UserEvent(event), &self.window_target, dummy);
+            } else {
+                (self.callback)(Event::UserEvent(event), &self.window_target, control_flow);
             }
         }
-        self.will_exit = will_exit;
     }
 }
 
@@ -127,7 +126,10 @@ impl Handler {
     }
 
     fn should_exit(&self) -> bool {
-        *self.control_flow.lock().unwrap() == ControlFlow::Exit
+        matches!(
+            *self.control_flow.lock().unwrap(),
+            ControlFlow::ExitWithCode(_)
+        )
     }
 
     fn get_control_flow_and_update_prev(&self) -> ControlFlow {
@@ -243,16 +245,20 @@ impl AppState {
                 Box<dyn FnMut(Event<'_, T>, &RootWindowTarget<T>, &mut ControlFlow)>,
                 Box<dyn FnMut(Event<'_, T>, &RootWindowTarget<T>, &mut ControlFlow)>,
             >(Box::new(callback)),
-            will_exit: false,
             window_target,
         }));
     }
 
-    pub fn exit() {
+    pub fn exit() -> i32 {
         HANDLER.set_in_callback(true);
         HANDLER.handle_nonuser_event(EventWrapper::StaticEvent(Event::LoopDestroyed));
         HANDLER.set_in_callback(false);
         HANDLER.callback.lock().unwrap().take();
+        if let ControlFlow::ExitWithCode(code) = *HANDLER.control_flow.lock().unwrap() {
+            code
+        } else {
+            0
+        }
     }
 
     pub fn launched() {
@@ -289,7 +295,7 @@ impl AppState {
                     }
                 }
             }
-            ControlFlow::Exit => StartCause::Poll, //panic!("unexpected `ControlFlow::Exit`"),
+            ControlFlow::ExitWithCode(_) => StartCause::Poll, //panic!("unexpected `ControlFlow::Exit`"),
         };
         HANDLER.set_in_callback(true);
         HANDLER.handle_nonuser_event(EventWrapper::StaticEvent(Event::NewEvents(cause)));
@@ -388,7 +394,7 @@ impl AppState {
         }
         HANDLER.update_start_time();
         match HANDLER.get_old_and_new_control_flow() {
-            (ControlFlow::Exit, _) | (_, ControlFlow::Exit) => (),
+            (ControlFlow::ExitWithCode(_), _) | (_, ControlFlow::ExitWithCode(_)) => (),
             (old, new) if old == new => (),
             (_, ControlFlow::Wait) => HANDLER.waker().stop(),
             (_, ControlFlow::WaitUntil(instant)) => HANDLER.waker().start_at(instant),
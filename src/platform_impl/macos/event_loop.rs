@@ -1,6 +1,12 @@
 use std::{
-    collections::VecDeque, marker::PhantomData, mem, os::raw::c_void, process, ptr, rc::Rc,
+    collections::{HashMap, VecDeque},
+    marker::PhantomData,
+    mem,
+    os::raw::c_void,
+    process, ptr,
+    rc::Rc,
     sync::mpsc,
+    time::Instant,
 };
 
 use cocoa::{
@@ -10,8 +16,13 @@ use cocoa::{
 };
 
 use crate::{
+    clipboard::ClipboardKind,
+    error::{ExternalError, NotSupportedError},
     event::Event,
-    event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootWindowTarget},
+    event_loop::{
+        ControlFlow, DeviceEventFilter, EventLoopClosed, EventLoopWindowTarget as RootWindowTarget,
+        TimerId,
+    },
     monitor::MonitorHandle as RootMonitorHandle,
     platform_impl::platform::{
         app::APP_CLASS,
@@ -46,6 +57,30 @@ impl<T: 'static> EventLoopWindowTarget<T> {
         let monitor = monitor::primary_monitor();
         Some(RootMonitorHandle { inner: monitor })
     }
+
+    pub fn set_timer(&self, _deadline: Instant, _id: TimerId) -> Result<(), NotSupportedError> {
+        Err(NotSupportedError::new())
+    }
+
+    pub fn cancel_timer(&self, _id: TimerId) {}
+
+    pub fn set_device_event_filter(&self, _filter: DeviceEventFilter) {}
+
+    pub fn set_clipboard_contents(
+        &self,
+        _kind: ClipboardKind,
+        _contents: HashMap<String, Vec<u8>>,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn clipboard_contents(
+        &self,
+        _kind: ClipboardKind,
+        _mime_type: &str,
+    ) -> Result<Option<Vec<u8>>, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
 }
 
 pub struct EventLoop<T: 'static> {
@@ -82,6 +117,10 @@ impl<T> EventLoop<T> {
         }
     }
 
+    pub fn try_new() -> Result<Self, super::EventLoopError> {
+        Ok(Self::new())
+    }
+
     pub fn window_target(&self) -> &RootWindowTarget<T> {
         &self.window_target
     }
@@ -90,11 +129,11 @@ impl<T> EventLoop<T> {
     where
         F: 'static + FnMut(Event<'_, T>, &RootWindowTarget<T>, &mut ControlFlow),
     {
-        self.run_return(callback);
-        process::exit(0);
+        let exit_code = self.run_return(callback);
+        process::exit(exit_code);
     }
 
-    pub fn run_return<F>(&mut self, callback: F)
+    pub fn run_return<F>(&mut self, callback: F) -> i32
     where
         F: FnMut(Event<'_, T>, &RootWindowTarget<T>, &mut ControlFlow),
     {
@@ -104,8 +143,9 @@ impl<T> EventLoop<T> {
             assert_ne!(app, nil);
             AppState::set_callback(callback, Rc::clone(&self.window_target));
             let _: () = msg_send![app, run];
-            AppState::exit();
+            let exit_code = AppState::exit();
             pool.drain();
+            exit_code
         }
     }
 
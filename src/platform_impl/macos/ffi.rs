@@ -106,6 +106,8 @@ pub const kCGNumberOfWindowLevelKeys: NSInteger = 20;
 
 #[derive(Debug, Clone, Copy)]
 pub enum NSWindowLevel {
+    // AppKit has no named level below the normal one.
+    BelowNormalWindowLevel = (kCGBaseWindowLevelKey - 1) as _,
     NSNormalWindowLevel = kCGBaseWindowLevelKey as _,
     NSFloatingWindowLevel = kCGFloatingWindowLevelKey as _,
     NSTornOffMenuWindowLevel = kCGTornOffMenuWindowLevelKey as _,
@@ -51,6 +51,8 @@ pub enum OsError {
     CreationError(&'static str),
 }
 
+pub type EventLoopError = std::convert::Infallible;
+
 unsafe impl Send for Window {}
 unsafe impl Sync for Window {}
 
@@ -29,7 +29,8 @@ use crate::{
         OsError,
     },
     window::{
-        CursorIcon, Fullscreen, UserAttentionType, WindowAttributes, WindowId as RootWindowId,
+        CursorGrabMode, CursorIcon, CustomCursor, Fullscreen, ResizeDirection, Theme,
+        UserAttentionType, WindowAttributes, WindowButtons, WindowId as RootWindowId, WindowLevel,
     },
 };
 use cocoa::{
@@ -193,6 +194,14 @@ fn create_window(
             masks &= !NSWindowStyleMask::NSResizableWindowMask;
         }
 
+        if !attrs.enabled_buttons.contains(WindowButtons::CLOSE) {
+            masks &= !NSWindowStyleMask::NSClosableWindowMask;
+        }
+
+        if !attrs.enabled_buttons.contains(WindowButtons::MINIMIZE) {
+            masks &= !NSWindowStyleMask::NSMiniaturizableWindowMask;
+        }
+
         if pl_attrs.fullsize_content_view {
             masks |= NSWindowStyleMask::NSFullSizeContentViewWindowMask;
         }
@@ -227,15 +236,16 @@ fn create_window(
                     let _: () = msg_send![button, setHidden: YES];
                 }
             }
+            if !attrs.enabled_buttons.contains(WindowButtons::MAXIMIZE) {
+                let button = ns_window.standardWindowButton_(NSWindowButton::NSWindowZoomButton);
+                let _: () = msg_send![button, setEnabled: NO];
+            }
             if pl_attrs.movable_by_window_background {
                 ns_window.setMovableByWindowBackground_(YES);
             }
 
-            if attrs.always_on_top {
-                let _: () = msg_send![
-                    *ns_window,
-                    setLevel: ffi::NSWindowLevel::NSFloatingWindowLevel
-                ];
+            if attrs.window_level != WindowLevel::Normal {
+                let _: () = msg_send![*ns_window, setLevel: ns_window_level(attrs.window_level)];
             }
 
             if let Some(increments) = pl_attrs.resize_increments {
@@ -281,6 +291,7 @@ lazy_static! {
 #[derive(Default)]
 pub struct SharedState {
     pub resizable: bool,
+    pub enabled_buttons: WindowButtons,
     pub fullscreen: Option<Fullscreen>,
     // This is true between windowWillEnterFullScreen and windowDidEnterFullScreen
     // or windowWillExitFullScreen and windowDidExitFullScreen.
@@ -310,6 +321,7 @@ impl From<WindowAttributes> for SharedState {
     fn from(attribs: WindowAttributes) -> Self {
         SharedState {
             resizable: attribs.resizable,
+            enabled_buttons: attribs.enabled_buttons,
             // This fullscreen field tracks the current state of the window
             // (as seen by `WindowDelegate`), and since the window hasn't
             // actually been fullscreened yet, we can't set it yet. This is
@@ -467,10 +479,18 @@ impl UnownedWindow {
         }
     }
 
+    #[inline]
+    pub fn is_visible(&self) -> Option<bool> {
+        let is_visible: BOOL = unsafe { msg_send![*self.ns_window, isVisible] };
+        Some(is_visible == YES)
+    }
+
     pub fn request_redraw(&self) {
         AppState::queue_redraw(RootWindowId(self.id()));
     }
 
+    pub fn request_frame(&self) {}
+
     pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
         let frame_rect = unsafe { NSWindow::frame(*self.ns_window) };
         let position = LogicalPosition::new(
@@ -547,6 +567,17 @@ impl UnownedWindow {
         }
     }
 
+    pub fn set_resize_increments(&self, increments: Option<Size>) {
+        let increments = increments
+            .map(|increments| increments.to_logical::<f64>(self.scale_factor()))
+            .filter(|increments| increments.width >= 1.0 && increments.height >= 1.0)
+            .unwrap_or_else(|| LogicalSize::new(1.0, 1.0));
+        unsafe {
+            let size = NSSize::new(increments.width as CGFloat, increments.height as CGFloat);
+            self.ns_window.setResizeIncrements_(size);
+        }
+    }
+
     pub fn set_max_inner_size(&self, dimensions: Option<Size>) {
         unsafe {
             let dimensions = dimensions.unwrap_or(Logical(LogicalSize {
@@ -578,6 +609,54 @@ impl UnownedWindow {
         } // Otherwise, we don't change the mask until we exit fullscreen.
     }
 
+    #[inline]
+    pub fn is_resizable(&self) -> bool {
+        let shared_state_lock = self.shared_state.lock().unwrap();
+        shared_state_lock.resizable
+    }
+
+    #[inline]
+    pub fn set_enabled_buttons(&self, buttons: WindowButtons) {
+        let fullscreen = {
+            trace!("Locked shared state in `set_enabled_buttons`");
+            let mut shared_state_lock = self.shared_state.lock().unwrap();
+            shared_state_lock.enabled_buttons = buttons;
+            trace!("Unlocked shared state in `set_enabled_buttons`");
+            shared_state_lock.fullscreen.is_some()
+        };
+        let mut mask = unsafe { self.ns_window.styleMask() };
+        // Borderless windows have no buttons, and the mask is restored when exiting fullscreen.
+        if !fullscreen && mask.contains(NSWindowStyleMask::NSTitledWindowMask) {
+            mask.set(
+                NSWindowStyleMask::NSClosableWindowMask,
+                buttons.contains(WindowButtons::CLOSE),
+            );
+            mask.set(
+                NSWindowStyleMask::NSMiniaturizableWindowMask,
+                buttons.contains(WindowButtons::MINIMIZE),
+            );
+            self.set_style_mask_async(mask);
+        }
+
+        unsafe {
+            let button = self
+                .ns_window
+                .standardWindowButton_(NSWindowButton::NSWindowZoomButton);
+            let enabled = if buttons.contains(WindowButtons::MAXIMIZE) {
+                YES
+            } else {
+                NO
+            };
+            let _: () = msg_send![button, setEnabled: enabled];
+        }
+    }
+
+    #[inline]
+    pub fn enabled_buttons(&self) -> WindowButtons {
+        let shared_state_lock = self.shared_state.lock().unwrap();
+        shared_state_lock.enabled_buttons
+    }
+
     pub fn set_cursor_icon(&self, cursor: CursorIcon) {
         let cursor = util::Cursor::from(cursor);
         if let Some(cursor_access) = self.cursor_state.upgrade() {
@@ -591,12 +670,39 @@ impl UnownedWindow {
     }
 
     #[inline]
-    pub fn set_cursor_grab(&self, grab: bool) -> Result<(), ExternalError> {
-        // TODO: Do this for real https://stackoverflow.com/a/40922095/5435443
-        CGDisplay::associate_mouse_and_mouse_cursor_position(!grab)
+    pub fn set_custom_cursor(&self, _cursor: CustomCursor) {
+        warn!("`Window::set_cursor` with a custom cursor is ignored on macOS")
+    }
+
+    #[inline]
+    pub fn set_cursor_theme(&self, _name: &str, _size: u32) {
+        warn!("`Window::set_cursor_theme` is ignored on macOS")
+    }
+
+    #[inline]
+    pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
+        let associate_mouse_cursor = match mode {
+            CursorGrabMode::None => true,
+            CursorGrabMode::Locked => false,
+            // TODO: Do this for real https://stackoverflow.com/a/40922095/5435443
+            CursorGrabMode::Confined => {
+                return Err(ExternalError::NotSupported(NotSupportedError::new()))
+            }
+        };
+
+        CGDisplay::associate_mouse_and_mouse_cursor_position(associate_mouse_cursor)
             .map_err(|status| ExternalError::Os(os_error!(OsError::CGError(status))))
     }
 
+    #[inline]
+    pub fn set_cursor_hittest(&self, hittest: bool) -> Result<(), ExternalError> {
+        unsafe {
+            let ignore = if hittest { NO } else { YES };
+            let _: () = msg_send![*self.ns_window, setIgnoresMouseEvents: ignore];
+        }
+        Ok(())
+    }
+
     #[inline]
     pub fn set_cursor_visible(&self, visible: bool) {
         if let Some(cursor_access) = self.cursor_state.upgrade() {
@@ -636,6 +742,21 @@ impl UnownedWindow {
         Ok(())
     }
 
+    #[inline]
+    pub fn drag_window(&self) -> Result<(), ExternalError> {
+        unsafe {
+            let event: id = msg_send![NSApp(), currentEvent];
+            let _: () = msg_send![*self.ns_window, performWindowDragWithEvent: event];
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn drag_resize_window(&self, _direction: ResizeDirection) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     pub(crate) fn is_zoomed(&self) -> bool {
         // because `isZoomed` doesn't work if the window's borderless,
         // we make it resizable temporalily.
@@ -663,11 +784,23 @@ impl UnownedWindow {
             .saved_style
             .take()
             .unwrap_or_else(|| unsafe { self.ns_window.styleMask() });
-        if shared_state.resizable {
+        let mut mask = if shared_state.resizable {
             base_mask | NSWindowStyleMask::NSResizableWindowMask
         } else {
             base_mask & !NSWindowStyleMask::NSResizableWindowMask
+        };
+        if mask.contains(NSWindowStyleMask::NSTitledWindowMask) {
+            let buttons = shared_state.enabled_buttons;
+            mask.set(
+                NSWindowStyleMask::NSClosableWindowMask,
+                buttons.contains(WindowButtons::CLOSE),
+            );
+            mask.set(
+                NSWindowStyleMask::NSMiniaturizableWindowMask,
+                buttons.contains(WindowButtons::MINIMIZE),
+            );
         }
+        mask
     }
 
     /// This is called when the window is exiting fullscreen, whether by the
@@ -708,6 +841,12 @@ impl UnownedWindow {
         }
     }
 
+    #[inline]
+    pub fn is_minimized(&self) -> Option<bool> {
+        let is_minimized: BOOL = unsafe { msg_send![*self.ns_window, isMiniaturized] };
+        Some(is_minimized == YES)
+    }
+
     #[inline]
     pub fn set_maximized(&self, maximized: bool) {
         let is_zoomed = self.is_zoomed();
@@ -724,6 +863,11 @@ impl UnownedWindow {
         }
     }
 
+    #[inline]
+    pub fn is_maximized(&self) -> bool {
+        self.is_zoomed()
+    }
+
     #[inline]
     pub fn fullscreen(&self) -> Option<Fullscreen> {
         let shared_state_lock = self.shared_state.lock().unwrap();
@@ -908,13 +1052,14 @@ impl UnownedWindow {
         if decorations != self.decorations.load(Ordering::Acquire) {
             self.decorations.store(decorations, Ordering::Release);
 
-            let (fullscreen, resizable) = {
+            let (fullscreen, resizable, enabled_buttons) = {
                 trace!("Locked shared state in `set_decorations`");
                 let shared_state_lock = self.shared_state.lock().unwrap();
                 trace!("Unlocked shared state in `set_decorations`");
                 (
                     shared_state_lock.fullscreen.is_some(),
                     shared_state_lock.resizable,
+                    shared_state_lock.enabled_buttons,
                 )
             };
 
@@ -937,6 +1082,12 @@ impl UnownedWindow {
                 if !resizable {
                     new_mask &= !NSWindowStyleMask::NSResizableWindowMask;
                 }
+                if !enabled_buttons.contains(WindowButtons::CLOSE) {
+                    new_mask &= !NSWindowStyleMask::NSClosableWindowMask;
+                }
+                if !enabled_buttons.contains(WindowButtons::MINIMIZE) {
+                    new_mask &= !NSWindowStyleMask::NSMiniaturizableWindowMask;
+                }
                 new_mask
             };
             self.set_style_mask_async(new_mask);
@@ -944,13 +1095,23 @@ impl UnownedWindow {
     }
 
     #[inline]
-    pub fn set_always_on_top(&self, always_on_top: bool) {
-        let level = if always_on_top {
-            ffi::NSWindowLevel::NSFloatingWindowLevel
-        } else {
-            ffi::NSWindowLevel::NSNormalWindowLevel
-        };
-        unsafe { util::set_level_async(*self.ns_window, level) };
+    pub fn is_decorated(&self) -> bool {
+        self.decorations.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub fn set_window_level(&self, level: WindowLevel) {
+        unsafe { util::set_level_async(*self.ns_window, ns_window_level(level)) };
+    }
+
+    #[inline]
+    pub fn set_blur(&self, _blur: bool) {
+        warn!("`Window::set_blur` is ignored on macOS")
+    }
+
+    #[inline]
+    pub fn set_blur_region(&self, _position: Position, _size: Size) {
+        warn!("`Window::set_blur_region` is ignored on macOS")
     }
 
     #[inline]
@@ -992,6 +1153,14 @@ impl UnownedWindow {
         }
     }
 
+    #[inline]
+    pub fn set_theme(&self, _theme: Option<Theme>) {}
+
+    #[inline]
+    pub fn theme(&self) -> Option<Theme> {
+        None
+    }
+
     #[inline]
     // Allow directly accessing the current monitor internally without unwrapping.
     pub(crate) fn current_monitor_inner(&self) -> RootMonitorHandle {
@@ -1205,3 +1374,11 @@ unsafe fn set_max_inner_size<V: NSWindow + Copy>(window: V, mut max_size: Logica
         window.setFrame_display_(current_rect, NO)
     }
 }
+
+fn ns_window_level(level: WindowLevel) -> ffi::NSWindowLevel {
+    match level {
+        WindowLevel::AlwaysOnBottom => ffi::NSWindowLevel::BelowNormalWindowLevel,
+        WindowLevel::Normal => ffi::NSWindowLevel::NSNormalWindowLevel,
+        WindowLevel::AlwaysOnTop => ffi::NSWindowLevel::NSFloatingWindowLevel,
+    }
+}
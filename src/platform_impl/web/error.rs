@@ -1,5 +1,7 @@
 use std::fmt;
 
+pub type EventLoopError = std::convert::Infallible;
+
 #[derive(Debug)]
 pub struct OsError(pub String);
 
@@ -26,6 +26,10 @@ impl<T> EventLoop<T> {
         }
     }
 
+    pub fn try_new() -> Result<Self, super::EventLoopError> {
+        Ok(Self::new())
+    }
+
     pub fn run<F>(self, mut event_handler: F) -> !
     where
         F: 'static + FnMut(Event<'_, T>, &root::EventLoopWindowTarget<T>, &mut root::ControlFlow),
@@ -79,18 +79,21 @@ impl<T: 'static> Runner<T> {
                 start,
                 requested_resume: Some(end),
             },
-            State::Exit => return None,
+            State::Exit(_) => return None,
         })
     }
 
     fn handle_single_event(&mut self, event: Event<'_, T>, control: &mut root::ControlFlow) {
-        let is_closed = *control == root::ControlFlow::Exit;
+        let exit_code = match *control {
+            root::ControlFlow::ExitWithCode(code) => Some(code),
+            _ => None,
+        };
 
         (self.event_handler)(event, control);
 
         // Maintain closed state, even if the callback changes it
-        if is_closed {
-            *control = root::ControlFlow::Exit;
+        if let Some(code) = exit_code {
+            *control = root::ControlFlow::ExitWithCode(code);
         }
     }
 }
@@ -369,7 +372,10 @@ impl<T: 'static> Shared<T> {
     }
 
     fn handle_unload(&self) {
-        self.apply_control_flow(root::ControlFlow::Exit);
+        // Keep the exit code if the event loop was already closed.
+        if !self.is_closed() {
+            self.apply_control_flow(root::ControlFlow::Exit);
+        }
         let mut control = self.current_control_flow();
         // We don't call `handle_loop_destroyed` here because we don't need to
         // perform cleanup when the web browser is going to destroy the page.
@@ -381,7 +387,7 @@ impl<T: 'static> Shared<T> {
     // It should only ever be called from `scale_changed`.
     fn handle_single_event_sync(&self, event: Event<'_, T>, control: &mut root::ControlFlow) {
         if self.is_closed() {
-            *control = root::ControlFlow::Exit;
+            *control = self.current_control_flow();
         }
         match *self.0.runner.borrow_mut() {
             RunnerEnum::Running(ref mut runner) => {
@@ -396,7 +402,7 @@ impl<T: 'static> Shared<T> {
     // It should only ever be called from `run_until_cleared` and `scale_changed`.
     fn handle_event(&self, event: Event<'static, T>, control: &mut root::ControlFlow) {
         if self.is_closed() {
-            *control = root::ControlFlow::Exit;
+            *control = self.current_control_flow();
         }
         match *self.0.runner.borrow_mut() {
             RunnerEnum::Running(ref mut runner) => {
@@ -409,7 +415,7 @@ impl<T: 'static> Shared<T> {
             RunnerEnum::Destroyed => return,
         }
 
-        let is_closed = *control == root::ControlFlow::Exit;
+        let is_closed = matches!(*control, root::ControlFlow::ExitWithCode(_));
 
         // Don't take events out of the queue if the loop is closed or the runner doesn't exist
         // If the runner doesn't exist and this method recurses, it will recurse infinitely
@@ -456,7 +462,7 @@ impl<T: 'static> Shared<T> {
                     ),
                 }
             }
-            root::ControlFlow::Exit => State::Exit,
+            root::ControlFlow::ExitWithCode(code) => State::Exit(code),
         };
 
         match *self.0.runner.borrow_mut() {
@@ -17,13 +17,13 @@ pub enum State {
     Poll {
         request: backend::AnimationFrameRequest,
     },
-    Exit,
+    Exit(i32),
 }
 
 impl State {
     pub fn is_exit(&self) -> bool {
         match self {
-            State::Exit => true,
+            State::Exit(_) => true,
             _ => false,
         }
     }
@@ -34,7 +34,7 @@ impl State {
             State::WaitUntil { end, .. } => ControlFlow::WaitUntil(*end),
             State::Wait { .. } => ControlFlow::Wait,
             State::Poll { .. } => ControlFlow::Poll,
-            State::Exit => ControlFlow::Exit,
+            State::Exit(code) => ControlFlow::ExitWithCode(*code),
         }
     }
 }
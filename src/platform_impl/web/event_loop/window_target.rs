@@ -1,12 +1,15 @@
 use super::{super::monitor, backend, device, proxy::Proxy, runner, window};
+use crate::clipboard::ClipboardKind;
 use crate::dpi::{PhysicalSize, Size};
+use crate::error::{ExternalError, NotSupportedError};
 use crate::event::{DeviceId, ElementState, Event, KeyboardInput, TouchPhase, WindowEvent};
-use crate::event_loop::ControlFlow;
+use crate::event_loop::{ControlFlow, DeviceEventFilter, TimerId};
 use crate::monitor::MonitorHandle as RootMH;
 use crate::window::{Theme, WindowId};
+use instant::Instant;
 use std::cell::RefCell;
 use std::clone::Clone;
-use std::collections::{vec_deque::IntoIter as VecDequeIter, VecDeque};
+use std::collections::{vec_deque::IntoIter as VecDequeIter, HashMap, VecDeque};
 use std::rc::Rc;
 
 pub struct WindowTarget<T: 'static> {
@@ -246,4 +249,28 @@ impl<T> WindowTarget<T> {
             inner: monitor::Handle,
         })
     }
+
+    pub fn set_timer(&self, _deadline: Instant, _id: TimerId) -> Result<(), NotSupportedError> {
+        Err(NotSupportedError::new())
+    }
+
+    pub fn cancel_timer(&self, _id: TimerId) {}
+
+    pub fn set_device_event_filter(&self, _filter: DeviceEventFilter) {}
+
+    pub fn set_clipboard_contents(
+        &self,
+        _kind: ClipboardKind,
+        _contents: HashMap<String, Vec<u8>>,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn clipboard_contents(
+        &self,
+        _kind: ClipboardKind,
+        _mime_type: &str,
+    ) -> Result<Option<Vec<u8>>, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
 }
@@ -38,7 +38,7 @@ mod backend;
 compile_error!("Please select a feature to build for web: `web-sys`, `stdweb`");
 
 pub use self::device::Id as DeviceId;
-pub use self::error::OsError;
+pub use self::error::{EventLoopError, OsError};
 pub use self::event_loop::{
     EventLoop, Proxy as EventLoopProxy, WindowTarget as EventLoopWindowTarget,
 };
@@ -4,7 +4,8 @@ use crate::event;
 use crate::icon::Icon;
 use crate::monitor::MonitorHandle as RootMH;
 use crate::window::{
-    CursorIcon, Fullscreen, UserAttentionType, WindowAttributes, WindowId as RootWI,
+    CursorGrabMode, CursorIcon, CustomCursor, Fullscreen, ResizeDirection, Theme,
+    UserAttentionType, WindowAttributes, WindowButtons, WindowId as RootWI, WindowLevel,
 };
 
 use raw_window_handle::web::WebHandle;
@@ -89,10 +90,17 @@ impl Window {
         // Intentionally a no-op
     }
 
+    #[inline]
+    pub fn is_visible(&self) -> Option<bool> {
+        None
+    }
+
     pub fn request_redraw(&self) {
         (self.register_redraw_request)();
     }
 
+    pub fn request_frame(&self) {}
+
     pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
         Ok(self
             .canvas
@@ -146,11 +154,31 @@ impl Window {
         // Intentionally a no-op: users can't resize canvas elements
     }
 
+    #[inline]
+    pub fn set_resize_increments(&self, _increments: Option<Size>) {
+        // Intentionally a no-op: users can't resize canvas elements
+    }
+
     #[inline]
     pub fn set_resizable(&self, _resizable: bool) {
         // Intentionally a no-op: users can't resize canvas elements
     }
 
+    #[inline]
+    pub fn is_resizable(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    pub fn set_enabled_buttons(&self, _buttons: WindowButtons) {
+        // Intentionally a no-op: canvas elements have no title bar
+    }
+
+    #[inline]
+    pub fn enabled_buttons(&self) -> WindowButtons {
+        WindowButtons::all()
+    }
+
     #[inline]
     pub fn scale_factor(&self) -> f64 {
         super::backend::scale_factor()
@@ -201,13 +229,38 @@ impl Window {
         backend::set_canvas_style_property(self.canvas.borrow().raw(), "cursor", text);
     }
 
+    #[inline]
+    pub fn set_custom_cursor(&self, _cursor: CustomCursor) {
+        // Intentionally a no-op, custom cursors aren't supported yet
+    }
+
+    #[inline]
+    pub fn set_cursor_theme(&self, _name: &str, _size: u32) {
+        // Intentionally a no-op, the browser draws the cursor
+    }
+
     #[inline]
     pub fn set_cursor_position(&self, _position: Position) -> Result<(), ExternalError> {
         Err(ExternalError::NotSupported(NotSupportedError::new()))
     }
 
     #[inline]
-    pub fn set_cursor_grab(&self, _grab: bool) -> Result<(), ExternalError> {
+    pub fn set_cursor_grab(&self, _mode: CursorGrabMode) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_cursor_hittest(&self, _hittest: bool) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn drag_window(&self) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn drag_resize_window(&self, _direction: ResizeDirection) -> Result<(), ExternalError> {
         Err(ExternalError::NotSupported(NotSupportedError::new()))
     }
 
@@ -227,11 +280,21 @@ impl Window {
         // Intentionally a no-op, as canvases cannot be 'minimized'
     }
 
+    #[inline]
+    pub fn is_minimized(&self) -> Option<bool> {
+        None
+    }
+
     #[inline]
     pub fn set_maximized(&self, _maximized: bool) {
         // Intentionally a no-op, as canvases cannot be 'maximized'
     }
 
+    #[inline]
+    pub fn is_maximized(&self) -> bool {
+        false
+    }
+
     #[inline]
     pub fn fullscreen(&self) -> Option<Fullscreen> {
         if self.canvas.borrow().is_fullscreen() {
@@ -256,10 +319,25 @@ impl Window {
     }
 
     #[inline]
-    pub fn set_always_on_top(&self, _always_on_top: bool) {
+    pub fn is_decorated(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    pub fn set_window_level(&self, _level: WindowLevel) {
         // Intentionally a no-op, no window ordering
     }
 
+    #[inline]
+    pub fn set_blur(&self, _blur: bool) {
+        // Intentionally a no-op, the canvas is composited by the browser
+    }
+
+    #[inline]
+    pub fn set_blur_region(&self, _position: Position, _size: Size) {
+        // Intentionally a no-op, the canvas is composited by the browser
+    }
+
     #[inline]
     pub fn set_window_icon(&self, _window_icon: Option<Icon>) {
         // Currently an intentional no-op
@@ -275,6 +353,16 @@ impl Window {
         // Currently an intentional no-op
     }
 
+    #[inline]
+    pub fn set_theme(&self, _theme: Option<Theme>) {
+        // Currently an intentional no-op
+    }
+
+    #[inline]
+    pub fn theme(&self) -> Option<Theme> {
+        None
+    }
+
     #[inline]
     // Allow directly accessing the current monitor internally without unwrapping.
     fn current_monitor_inner(&self) -> RootMH {
@@ -5,7 +5,7 @@ mod runner;
 use parking_lot::Mutex;
 use std::{
     cell::Cell,
-    collections::VecDeque,
+    collections::{HashMap, VecDeque},
     marker::PhantomData,
     mem, panic, ptr,
     rc::Rc,
@@ -32,9 +32,13 @@ use winapi::{
 };
 
 use crate::{
+    clipboard::ClipboardKind,
     dpi::{PhysicalPosition, PhysicalSize},
+    error::{ExternalError, NotSupportedError},
     event::{DeviceEvent, Event, Force, KeyboardInput, Touch, TouchPhase, WindowEvent},
-    event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootELW},
+    event_loop::{
+        ControlFlow, DeviceEventFilter, EventLoopClosed, EventLoopWindowTarget as RootELW, TimerId,
+    },
     monitor::MonitorHandle as RootMonitorHandle,
     platform_impl::platform::{
         dark_mode::try_theme,
@@ -141,6 +145,10 @@ impl<T: 'static> EventLoop<T> {
         Self::new_any_thread()
     }
 
+    pub fn try_new() -> Result<EventLoop<T>, super::EventLoopError> {
+        Ok(Self::new())
+    }
+
     pub fn new_any_thread() -> EventLoop<T> {
         become_dpi_aware();
         Self::new_dpi_unaware_any_thread()
@@ -188,11 +196,11 @@ impl<T: 'static> EventLoop<T> {
     where
         F: 'static + FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
     {
-        self.run_return(event_handler);
-        ::std::process::exit(0);
+        let exit_code = self.run_return(event_handler);
+        ::std::process::exit(exit_code);
     }
 
-    pub fn run_return<F>(&mut self, mut event_handler: F)
+    pub fn run_return<F>(&mut self, mut event_handler: F) -> i32
     where
         F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
     {
@@ -209,13 +217,13 @@ impl<T: 'static> EventLoop<T> {
 
         let runner = &self.window_target.p.runner_shared;
 
-        unsafe {
+        let exit_code = unsafe {
             let mut msg = mem::zeroed();
 
             runner.poll();
             'main: loop {
                 if 0 == winuser::GetMessageW(&mut msg, ptr::null_mut(), 0, 0) {
-                    break 'main;
+                    break 'main 0;
                 }
                 winuser::TranslateMessage(&mut msg);
                 winuser::DispatchMessageW(&mut msg);
@@ -225,16 +233,19 @@ impl<T: 'static> EventLoop<T> {
                     panic::resume_unwind(payload);
                 }
 
-                if runner.control_flow() == ControlFlow::Exit && !runner.handling_events() {
-                    break 'main;
+                if let ControlFlow::ExitWithCode(code) = runner.control_flow() {
+                    if !runner.handling_events() {
+                        break 'main code;
+                    }
                 }
             }
-        }
+        };
 
         unsafe {
             runner.loop_destroyed();
         }
         runner.reset_runner();
+        exit_code
     }
 
     pub fn create_proxy(&self) -> EventLoopProxy<T> {
@@ -263,6 +274,30 @@ impl<T> EventLoopWindowTarget<T> {
         let monitor = monitor::primary_monitor();
         Some(RootMonitorHandle { inner: monitor })
     }
+
+    pub fn set_timer(&self, _deadline: Instant, _id: TimerId) -> Result<(), NotSupportedError> {
+        Err(NotSupportedError::new())
+    }
+
+    pub fn cancel_timer(&self, _id: TimerId) {}
+
+    pub fn set_device_event_filter(&self, _filter: DeviceEventFilter) {}
+
+    pub fn set_clipboard_contents(
+        &self,
+        _kind: ClipboardKind,
+        _contents: HashMap<String, Vec<u8>>,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn clipboard_contents(
+        &self,
+        _kind: ClipboardKind,
+        _mime_type: &str,
+    ) -> Result<Option<Vec<u8>>, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
 }
 
 fn main_thread_id() -> DWORD {
@@ -740,7 +775,7 @@ unsafe fn process_control_flow<T: 'static>(runner: &EventLoopRunner<T>) {
                 Box::into_raw(WaitUntilInstantBox::new(until)) as LPARAM,
             );
         }
-        ControlFlow::Exit => (),
+        ControlFlow::ExitWithCode(_) => (),
     }
 }
 
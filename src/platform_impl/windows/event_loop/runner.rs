@@ -241,10 +241,10 @@ impl<T> EventLoopRunner<T> {
             let mut event_handler = self.event_handler.take()
                 .expect("either event handler is re-entrant (likely), or no event handler is registered (very unlikely)");
 
-            if control_flow != ControlFlow::Exit {
-                event_handler(event, &mut control_flow);
+            if let ControlFlow::ExitWithCode(code) = control_flow {
+                event_handler(event, &mut ControlFlow::ExitWithCode(code));
             } else {
-                event_handler(event, &mut ControlFlow::Exit);
+                event_handler(event, &mut control_flow);
             }
 
             assert!(self.event_handler.replace(Some(event_handler)).is_none());
@@ -372,10 +372,12 @@ impl<T> EventLoopRunner<T> {
         let start_cause = match (init, self.control_flow()) {
             (true, _) => StartCause::Init,
             (false, ControlFlow::Poll) => StartCause::Poll,
-            (false, ControlFlow::Exit) | (false, ControlFlow::Wait) => StartCause::WaitCancelled {
-                requested_resume: None,
-                start: self.last_events_cleared.get(),
-            },
+            (false, ControlFlow::ExitWithCode(_)) | (false, ControlFlow::Wait) => {
+                StartCause::WaitCancelled {
+                    requested_resume: None,
+                    start: self.last_events_cleared.get(),
+                }
+            }
             (false, ControlFlow::WaitUntil(requested_resume)) => {
                 if Instant::now() < requested_resume {
                     StartCause::WaitCancelled {
@@ -14,7 +14,7 @@ use std::{
 use winapi::{
     ctypes::c_int,
     shared::{
-        minwindef::{HINSTANCE, UINT},
+        minwindef::{HINSTANCE, LPARAM, UINT, WPARAM},
         windef::{HWND, POINT, RECT},
     },
     um::{
@@ -45,7 +45,10 @@ use crate::{
         window_state::{CursorFlags, SavedWindow, WindowFlags, WindowState},
         PlatformSpecificWindowBuilderAttributes, WindowId,
     },
-    window::{CursorIcon, Fullscreen, Theme, UserAttentionType, WindowAttributes},
+    window::{
+        CursorGrabMode, CursorIcon, CustomCursor, Fullscreen, ResizeDirection, Theme,
+        UserAttentionType, WindowAttributes, WindowButtons, WindowLevel,
+    },
 };
 
 /// The Win32 implementation of the main `Window` object.
@@ -145,6 +148,11 @@ impl Window {
         });
     }
 
+    #[inline]
+    pub fn is_visible(&self) -> Option<bool> {
+        Some(unsafe { winuser::IsWindowVisible(self.window.0) } != 0)
+    }
+
     #[inline]
     pub fn request_redraw(&self) {
         unsafe {
@@ -157,6 +165,9 @@ impl Window {
         }
     }
 
+    #[inline]
+    pub fn request_frame(&self) {}
+
     #[inline]
     pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
         util::get_window_rect(self.window.0)
@@ -258,6 +269,11 @@ impl Window {
         self.set_inner_size(size.into());
     }
 
+    #[inline]
+    pub fn set_resize_increments(&self, _increments: Option<Size>) {
+        warn!("`Window::set_resize_increments` is ignored on Windows")
+    }
+
     #[inline]
     pub fn set_resizable(&self, resizable: bool) {
         let window = self.window.clone();
@@ -270,6 +286,54 @@ impl Window {
         });
     }
 
+    #[inline]
+    pub fn is_resizable(&self) -> bool {
+        let window_state = self.window_state.lock();
+        window_state.window_flags().contains(WindowFlags::RESIZABLE)
+    }
+
+    #[inline]
+    pub fn set_enabled_buttons(&self, buttons: WindowButtons) {
+        let window = self.window.clone();
+        let window_state = Arc::clone(&self.window_state);
+
+        self.thread_executor.execute_in_thread(move || {
+            WindowState::set_window_flags(window_state.lock(), window.0, |f| {
+                f.set(
+                    WindowFlags::MINIMIZABLE,
+                    buttons.contains(WindowButtons::MINIMIZE),
+                );
+                f.set(
+                    WindowFlags::MAXIMIZABLE,
+                    buttons.contains(WindowButtons::MAXIMIZE),
+                );
+                f.set(
+                    WindowFlags::CLOSABLE,
+                    buttons.contains(WindowButtons::CLOSE),
+                );
+            });
+        });
+    }
+
+    #[inline]
+    pub fn enabled_buttons(&self) -> WindowButtons {
+        let window_flags = self.window_state.lock().window_flags();
+        let mut buttons = WindowButtons::empty();
+        buttons.set(
+            WindowButtons::MINIMIZE,
+            window_flags.contains(WindowFlags::MINIMIZABLE),
+        );
+        buttons.set(
+            WindowButtons::MAXIMIZE,
+            window_flags.contains(WindowFlags::MAXIMIZABLE),
+        );
+        buttons.set(
+            WindowButtons::CLOSE,
+            window_flags.contains(WindowFlags::CLOSABLE),
+        );
+        buttons
+    }
+
     /// Returns the `hwnd` of this window.
     #[inline]
     pub fn hwnd(&self) -> HWND {
@@ -301,7 +365,25 @@ impl Window {
     }
 
     #[inline]
-    pub fn set_cursor_grab(&self, grab: bool) -> Result<(), ExternalError> {
+    pub fn set_custom_cursor(&self, _cursor: CustomCursor) {
+        warn!("`Window::set_cursor` with a custom cursor is ignored on Windows")
+    }
+
+    #[inline]
+    pub fn set_cursor_theme(&self, _name: &str, _size: u32) {
+        warn!("`Window::set_cursor_theme` is ignored on Windows")
+    }
+
+    #[inline]
+    pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
+        let grab = match mode {
+            CursorGrabMode::None => false,
+            CursorGrabMode::Confined => true,
+            CursorGrabMode::Locked => {
+                return Err(ExternalError::NotSupported(NotSupportedError::new()))
+            }
+        };
+
         let window = self.window.clone();
         let window_state = Arc::clone(&self.window_state);
         let (tx, rx) = channel();
@@ -317,6 +399,19 @@ impl Window {
         rx.recv().unwrap()
     }
 
+    #[inline]
+    pub fn set_cursor_hittest(&self, hittest: bool) -> Result<(), ExternalError> {
+        let window = self.window.clone();
+        let window_state = Arc::clone(&self.window_state);
+
+        self.thread_executor.execute_in_thread(move || {
+            WindowState::set_window_flags(window_state.lock(), window.0, |f| {
+                f.set(WindowFlags::IGNORE_CURSOR_EVENT, !hittest)
+            });
+        });
+        Ok(())
+    }
+
     #[inline]
     pub fn set_cursor_visible(&self, visible: bool) {
         let window = self.window.clone();
@@ -356,6 +451,46 @@ impl Window {
         Ok(())
     }
 
+    /// Hands the cursor over to the system, as if it pressed the given part of the frame.
+    fn handle_os_dragging(&self, hit_test: isize) -> Result<(), ExternalError> {
+        unsafe {
+            let mut point = POINT { x: 0, y: 0 };
+            if winuser::GetCursorPos(&mut point) == 0 {
+                return Err(ExternalError::Os(os_error!(io::Error::last_os_error())));
+            }
+            // The screen coordinates, packed like `MAKELPARAM`.
+            let lparam = (((point.y as u16 as u32) << 16) | point.x as u16 as u32) as LPARAM;
+
+            winuser::ReleaseCapture();
+            winuser::PostMessageW(
+                self.window.0,
+                winuser::WM_NCLBUTTONDOWN,
+                hit_test as WPARAM,
+                lparam,
+            );
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub fn drag_window(&self) -> Result<(), ExternalError> {
+        self.handle_os_dragging(winuser::HTCAPTION)
+    }
+
+    #[inline]
+    pub fn drag_resize_window(&self, direction: ResizeDirection) -> Result<(), ExternalError> {
+        self.handle_os_dragging(match direction {
+            ResizeDirection::East => winuser::HTRIGHT,
+            ResizeDirection::North => winuser::HTTOP,
+            ResizeDirection::NorthEast => winuser::HTTOPRIGHT,
+            ResizeDirection::NorthWest => winuser::HTTOPLEFT,
+            ResizeDirection::South => winuser::HTBOTTOM,
+            ResizeDirection::SouthEast => winuser::HTBOTTOMRIGHT,
+            ResizeDirection::SouthWest => winuser::HTBOTTOMLEFT,
+            ResizeDirection::West => winuser::HTLEFT,
+        })
+    }
+
     #[inline]
     pub fn id(&self) -> WindowId {
         WindowId(self.window.0)
@@ -373,6 +508,11 @@ impl Window {
         });
     }
 
+    #[inline]
+    pub fn is_minimized(&self) -> Option<bool> {
+        Some(unsafe { winuser::IsIconic(self.window.0) } != 0)
+    }
+
     #[inline]
     pub fn set_maximized(&self, maximized: bool) {
         let window = self.window.clone();
@@ -385,6 +525,12 @@ impl Window {
         });
     }
 
+    #[inline]
+    pub fn is_maximized(&self) -> bool {
+        let window_state = self.window_state.lock();
+        window_state.window_flags().contains(WindowFlags::MAXIMIZED)
+    }
+
     #[inline]
     pub fn fullscreen(&self) -> Option<Fullscreen> {
         let window_state = self.window_state.lock();
@@ -556,17 +702,42 @@ impl Window {
     }
 
     #[inline]
-    pub fn set_always_on_top(&self, always_on_top: bool) {
+    pub fn is_decorated(&self) -> bool {
+        let window_state = self.window_state.lock();
+        window_state
+            .window_flags()
+            .contains(WindowFlags::DECORATIONS)
+    }
+
+    #[inline]
+    pub fn set_window_level(&self, level: WindowLevel) {
         let window = self.window.clone();
         let window_state = Arc::clone(&self.window_state);
 
         self.thread_executor.execute_in_thread(move || {
             WindowState::set_window_flags(window_state.lock(), window.0, |f| {
-                f.set(WindowFlags::ALWAYS_ON_TOP, always_on_top)
+                f.set(
+                    WindowFlags::ALWAYS_ON_TOP,
+                    level == WindowLevel::AlwaysOnTop,
+                );
+                f.set(
+                    WindowFlags::ALWAYS_ON_BOTTOM,
+                    level == WindowLevel::AlwaysOnBottom,
+                );
             });
         });
     }
 
+    #[inline]
+    pub fn set_blur(&self, _blur: bool) {
+        warn!("`Window::set_blur` is ignored on Windows")
+    }
+
+    #[inline]
+    pub fn set_blur_region(&self, _position: Position, _size: Size) {
+        warn!("`Window::set_blur_region` is ignored on Windows")
+    }
+
     #[inline]
     pub fn current_monitor(&self) -> Option<RootMonitorHandle> {
         Some(RootMonitorHandle {
@@ -651,8 +822,21 @@ impl Window {
     }
 
     #[inline]
-    pub fn theme(&self) -> Theme {
-        self.window_state.lock().current_theme
+    pub fn set_theme(&self, theme: Option<Theme>) {
+        let window = self.window.clone();
+        let window_state = Arc::clone(&self.window_state);
+
+        self.thread_executor.execute_in_thread(move || {
+            let current_theme = try_theme(window.0, theme);
+            let mut window_state = window_state.lock();
+            window_state.preferred_theme = theme;
+            window_state.current_theme = current_theme;
+        });
+    }
+
+    #[inline]
+    pub fn theme(&self) -> Option<Theme> {
+        Some(self.window_state.lock().current_theme)
     }
 }
 
@@ -694,7 +878,14 @@ unsafe fn init<T: 'static>(
 
     let mut window_flags = WindowFlags::empty();
     window_flags.set(WindowFlags::DECORATIONS, attributes.decorations);
-    window_flags.set(WindowFlags::ALWAYS_ON_TOP, attributes.always_on_top);
+    window_flags.set(
+        WindowFlags::ALWAYS_ON_TOP,
+        attributes.window_level == WindowLevel::AlwaysOnTop,
+    );
+    window_flags.set(
+        WindowFlags::ALWAYS_ON_BOTTOM,
+        attributes.window_level == WindowLevel::AlwaysOnBottom,
+    );
     window_flags.set(
         WindowFlags::NO_BACK_BUFFER,
         pl_attribs.no_redirection_bitmap,
@@ -702,6 +893,19 @@ unsafe fn init<T: 'static>(
     window_flags.set(WindowFlags::TRANSPARENT, attributes.transparent);
     // WindowFlags::VISIBLE and MAXIMIZED are set down below after the window has been configured.
     window_flags.set(WindowFlags::RESIZABLE, attributes.resizable);
+    let buttons = attributes.enabled_buttons;
+    window_flags.set(
+        WindowFlags::MINIMIZABLE,
+        buttons.contains(WindowButtons::MINIMIZE),
+    );
+    window_flags.set(
+        WindowFlags::MAXIMIZABLE,
+        buttons.contains(WindowButtons::MAXIMIZE),
+    );
+    window_flags.set(
+        WindowFlags::CLOSABLE,
+        buttons.contains(WindowButtons::CLOSE),
+    );
     window_flags.set(WindowFlags::CHILD, pl_attribs.parent.is_some());
     window_flags.set(WindowFlags::ON_TASKBAR, true);
 
@@ -85,6 +85,14 @@ bitflags! {
 
         const MINIMIZED = 1 << 12;
 
+        const ALWAYS_ON_BOTTOM = 1 << 14;
+
+        const MINIMIZABLE = 1 << 15;
+        const MAXIMIZABLE = 1 << 16;
+        const CLOSABLE    = 1 << 17;
+
+        const IGNORE_CURSOR_EVENT = 1 << 18;
+
         const EXCLUSIVE_FULLSCREEN_OR_MASK = WindowFlags::ALWAYS_ON_TOP.bits;
         const NO_DECORATIONS_AND_MASK = !WindowFlags::RESIZABLE.bits;
         const INVISIBLE_AND_MASK = !WindowFlags::MAXIMIZED.bits;
@@ -192,10 +200,16 @@ impl WindowFlags {
         let (mut style, mut style_ex) = (0, 0);
 
         if self.contains(WindowFlags::RESIZABLE) {
-            style |= WS_SIZEBOX | WS_MAXIMIZEBOX;
+            style |= WS_SIZEBOX;
+            if self.contains(WindowFlags::MAXIMIZABLE) {
+                style |= WS_MAXIMIZEBOX;
+            }
         }
         if self.contains(WindowFlags::DECORATIONS) {
-            style |= WS_CAPTION | WS_MINIMIZEBOX | WS_BORDER;
+            style |= WS_CAPTION | WS_BORDER;
+            if self.contains(WindowFlags::MINIMIZABLE) {
+                style |= WS_MINIMIZEBOX;
+            }
             style_ex = WS_EX_WINDOWEDGE;
         }
         if self.contains(WindowFlags::VISIBLE) {
@@ -213,6 +227,9 @@ impl WindowFlags {
         if self.contains(WindowFlags::TRANSPARENT) && self.contains(WindowFlags::DECORATIONS) {
             style_ex |= WS_EX_LAYERED;
         }
+        if self.contains(WindowFlags::IGNORE_CURSOR_EVENT) {
+            style_ex |= WS_EX_TRANSPARENT | WS_EX_LAYERED;
+        }
         if self.contains(WindowFlags::CHILD) {
             style |= WS_CHILD; // This is incompatible with WS_POPUP if that gets added eventually.
         }
@@ -256,13 +273,16 @@ impl WindowFlags {
                 );
             }
         }
-        if diff.contains(WindowFlags::ALWAYS_ON_TOP) {
+        if diff.intersects(WindowFlags::ALWAYS_ON_TOP | WindowFlags::ALWAYS_ON_BOTTOM) {
             unsafe {
                 winuser::SetWindowPos(
                     window,
-                    match new.contains(WindowFlags::ALWAYS_ON_TOP) {
-                        true => winuser::HWND_TOPMOST,
-                        false => winuser::HWND_NOTOPMOST,
+                    if new.contains(WindowFlags::ALWAYS_ON_TOP) {
+                        winuser::HWND_TOPMOST
+                    } else if new.contains(WindowFlags::ALWAYS_ON_BOTTOM) {
+                        winuser::HWND_BOTTOM
+                    } else {
+                        winuser::HWND_NOTOPMOST
                     },
                     0,
                     0,
@@ -289,6 +309,22 @@ impl WindowFlags {
             }
         }
 
+        // The system menu is reset along with the styles, so a disabled close button is disabled
+        // again after every change.
+        if diff.contains(WindowFlags::CLOSABLE) || !new.contains(WindowFlags::CLOSABLE) {
+            unsafe {
+                winuser::EnableMenuItem(
+                    winuser::GetSystemMenu(window, 0),
+                    winuser::SC_CLOSE as _,
+                    winuser::MF_BYCOMMAND
+                        | match new.contains(WindowFlags::CLOSABLE) {
+                            true => winuser::MF_ENABLED,
+                            false => winuser::MF_DISABLED | winuser::MF_GRAYED,
+                        },
+                );
+            }
+        }
+
         // Minimize operations should execute after maximize for proper window animations
         if diff.contains(WindowFlags::MINIMIZED) {
             unsafe {
@@ -314,6 +350,14 @@ impl WindowFlags {
                     winuser::SetWindowLongW(window, winuser::GWL_EXSTYLE, style_ex as _);
                 }
 
+                // A layered window isn't drawn until its attributes are set, which adding
+                // `WS_EX_LAYERED` to let the cursor through resets.
+                if diff.contains(WindowFlags::IGNORE_CURSOR_EVENT)
+                    && style_ex & winuser::WS_EX_LAYERED != 0
+                {
+                    winuser::SetLayeredWindowAttributes(window, 0, 255, winuser::LWA_ALPHA);
+                }
+
                 let mut flags = winuser::SWP_NOZORDER
                     | winuser::SWP_NOMOVE
                     | winuser::SWP_NOSIZE
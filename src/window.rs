@@ -9,6 +9,7 @@ use crate::{
     platform_impl,
 };
 
+pub use crate::cursor::{BadCursor, Cursor, CustomCursor};
 pub use crate::icon::{BadIcon, Icon};
 
 /// Represents a window.
@@ -121,6 +122,11 @@ pub struct WindowAttributes {
     /// The default is `true`.
     pub resizable: bool,
 
+    /// The buttons of the title bar which are enabled.
+    ///
+    /// The default is `WindowButtons::all()`.
+    pub enabled_buttons: WindowButtons,
+
     /// Whether the window should be set as fullscreen upon creation.
     ///
     /// The default is `None`.
@@ -147,15 +153,20 @@ pub struct WindowAttributes {
     /// The default is `false`.
     pub transparent: bool,
 
+    /// Whether what is behind the window should be blurred by the compositor.
+    ///
+    /// The default is `false`.
+    pub blur: bool,
+
     /// Whether the window should have borders and bars.
     ///
     /// The default is `true`.
     pub decorations: bool,
 
-    /// Whether the window should always be on top of other windows.
+    /// The level of the window, relative to other windows.
     ///
-    /// The default is `false`.
-    pub always_on_top: bool,
+    /// The default is `WindowLevel::Normal`.
+    pub window_level: WindowLevel,
 
     /// The window icon.
     ///
@@ -171,13 +182,15 @@ impl Default for WindowAttributes {
             min_inner_size: None,
             max_inner_size: None,
             resizable: true,
+            enabled_buttons: WindowButtons::all(),
             title: "winit window".to_owned(),
             maximized: false,
             fullscreen: None,
             visible: true,
             transparent: false,
+            blur: false,
             decorations: true,
-            always_on_top: false,
+            window_level: WindowLevel::Normal,
             window_icon: None,
         }
     }
@@ -234,6 +247,17 @@ impl WindowBuilder {
         self
     }
 
+    /// Sets the buttons of the title bar which are enabled.
+    ///
+    /// See [`Window::set_enabled_buttons`] for details.
+    ///
+    /// [`Window::set_enabled_buttons`]: crate::window::Window::set_enabled_buttons
+    #[inline]
+    pub fn with_enabled_buttons(mut self, buttons: WindowButtons) -> Self {
+        self.window.enabled_buttons = buttons;
+        self
+    }
+
     /// Requests a specific title for the window.
     ///
     /// See [`Window::set_title`] for details.
@@ -285,6 +309,17 @@ impl WindowBuilder {
         self
     }
 
+    /// Sets whether what is behind the window should be blurred.
+    ///
+    /// See [`Window::set_blur`] for details.
+    ///
+    /// [`Window::set_blur`]: crate::window::Window::set_blur
+    #[inline]
+    pub fn with_blur(mut self, blur: bool) -> Self {
+        self.window.blur = blur;
+        self
+    }
+
     /// Sets whether the window should have a border, a title bar, etc.
     ///
     /// See [`Window::set_decorations`] for details.
@@ -296,14 +331,14 @@ impl WindowBuilder {
         self
     }
 
-    /// Sets whether or not the window will always be on top of other windows.
+    /// Sets the level of the window, relative to other windows.
     ///
-    /// See [`Window::set_always_on_top`] for details.
+    /// See [`Window::set_window_level`] for details.
     ///
-    /// [`Window::set_always_on_top`]: crate::window::Window::set_always_on_top
+    /// [`Window::set_window_level`]: crate::window::Window::set_window_level
     #[inline]
-    pub fn with_always_on_top(mut self, always_on_top: bool) -> Self {
-        self.window.always_on_top = always_on_top;
+    pub fn with_window_level(mut self, level: WindowLevel) -> Self {
+        self.window.window_level = level;
         self
     }
 
@@ -406,6 +441,27 @@ impl Window {
     pub fn request_redraw(&self) {
         self.window.request_redraw()
     }
+
+    /// Emits a single `WindowEvent::FrameReady` event once the compositor is ready for a new
+    /// frame, which paces rendering to the refresh rate of the monitor showing the window.
+    ///
+    /// Calling this function again before `FrameReady` is emitted has no effect. Unlike
+    /// `request_redraw`, no `RedrawRequested` event is emitted, so the frame should be drawn and
+    /// presented while handling `FrameReady`.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Driven by `wl_surface.frame` callbacks, which are scheduled by the next
+    ///   commit of the surface, so this should be called before presenting the current frame,
+    ///   e.g. before `swap_buffers`. Since the compositor doesn't send them for hidden windows, no
+    ///   `FrameReady` is emitted while the window is hidden.
+    /// - **X11:** Driven by the Present extension when available, and otherwise by a timer based
+    ///   on the refresh rate of the monitor.
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported.
+    #[inline]
+    pub fn request_frame(&self) {
+        self.window.request_frame()
+    }
 }
 
 /// Position and size functions.
@@ -531,6 +587,21 @@ impl Window {
     pub fn set_max_inner_size<S: Into<Size>>(&self, max_size: Option<S>) {
         self.window.set_max_inner_size(max_size.map(|s| s.into()))
     }
+
+    /// Sets the resize increments of the window, which its size snaps to when the user resizes
+    /// it, like the size of a character cell in a terminal emulator. Pass `None` to unset them.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** The protocol has no resize increments, so winit snaps the sizes suggested by
+    ///   the compositor instead. The current size is snapped right away, which is reported with
+    ///   `WindowEvent::Resized`.
+    /// - **Windows / iOS / Android / Web:** Unsupported.
+    #[inline]
+    pub fn set_resize_increments<S: Into<Size>>(&self, increments: Option<S>) {
+        self.window
+            .set_resize_increments(increments.map(|s| s.into()))
+    }
 }
 
 /// Misc. attribute functions.
@@ -557,6 +628,20 @@ impl Window {
         self.window.set_visible(visible)
     }
 
+    /// Gets the window's current visibility state.
+    ///
+    /// `None` means it couldn't be determined, so it is not recommended to use this to drive your
+    /// rendering backend.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Whether the window is mapped, which it isn't while it's minimized either.
+    /// - **iOS / Android / Wayland / Web:** Unsupported.
+    #[inline]
+    pub fn is_visible(&self) -> Option<bool> {
+        self.window.is_visible()
+    }
+
     /// Sets whether the window is resizable or not.
     ///
     /// Note that making the window unresizable doesn't exempt you from handling `Resized`, as that event can still be
@@ -576,6 +661,40 @@ impl Window {
         self.window.set_resizable(resizable)
     }
 
+    /// Gets the window's current resizable state.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Whether the minimum and maximum sizes in `WM_NORMAL_HINTS` differ, which window
+    ///   managers honor.
+    /// - **iOS / Android / Web:** Unsupported.
+    #[inline]
+    pub fn is_resizable(&self) -> bool {
+        self.window.is_resizable()
+    }
+
+    /// Sets the buttons of the title bar which are enabled, keeping the other decorations.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Sets the functions of the Motif hints, which the window manager may ignore.
+    /// - **Wayland:** Only affects the frame drawn by winit.
+    /// - **iOS / Android / Web:** Unsupported.
+    #[inline]
+    pub fn set_enabled_buttons(&self, buttons: WindowButtons) {
+        self.window.set_enabled_buttons(buttons)
+    }
+
+    /// Gets the buttons of the title bar which are enabled.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **iOS / Android / Web:** Unsupported, always returns `WindowButtons::all()`.
+    #[inline]
+    pub fn enabled_buttons(&self) -> WindowButtons {
+        self.window.enabled_buttons()
+    }
+
     /// Sets the window to minimized or back
     ///
     /// ## Platform-specific
@@ -587,6 +706,24 @@ impl Window {
         self.window.set_minimized(minimized);
     }
 
+    /// Gets the window's current minimized state.
+    ///
+    /// `None` will be returned, if the minimized state couldn't be determined.
+    ///
+    /// ## Note
+    ///
+    /// - You shouldn't stop rendering for minimized windows, however you could lower the fps.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Requires the window manager to support `_NET_WM_STATE_HIDDEN`.
+    /// - **Wayland:** always `None`.
+    /// - **iOS / Android / Web:** Unsupported.
+    #[inline]
+    pub fn is_minimized(&self) -> Option<bool> {
+        self.window.is_minimized()
+    }
+
     /// Sets the window to maximized or back.
     ///
     /// ## Platform-specific
@@ -597,6 +734,16 @@ impl Window {
         self.window.set_maximized(maximized)
     }
 
+    /// Gets the window's current maximized state.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **iOS / Android / Web:** Unsupported.
+    #[inline]
+    pub fn is_maximized(&self) -> bool {
+        self.window.is_maximized()
+    }
+
     /// Sets the window to fullscreen or back.
     ///
     /// ## Platform-specific
@@ -646,14 +793,58 @@ impl Window {
         self.window.set_decorations(decorations)
     }
 
-    /// Change whether or not the window will always be on top of other windows.
+    /// Gets the window's current decorations state.
+    ///
+    /// Returns `true` when windows are decorated (server-side or by Wayland client-side
+    /// decorations), `false` otherwise.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **iOS / Android / Web:** Always returns `true`.
+    #[inline]
+    pub fn is_decorated(&self) -> bool {
+        self.window.is_decorated()
+    }
+
+    /// Sets whether what is behind the window should be blurred by the compositor.
+    ///
+    /// This only makes a difference for a transparent window, and the whole window is blurred.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Sets `_KDE_NET_WM_BLUR_BEHIND_REGION`, which is read by KWin.
+    /// - **Wayland:** Requires the `org_kde_kwin_blur_manager` global, which is advertised by
+    ///   KWin. Applied with the next commit of the surface, after `RedrawRequested`.
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported.
+    #[inline]
+    pub fn set_blur(&self, blur: bool) {
+        self.window.set_blur(blur)
+    }
+
+    /// Blurs what is behind a part of the window, instead of the whole window.
+    ///
+    /// The position is relative to the top left of the client area, and the parts of the region
+    /// outside of the client area are ignored. Use [`Window::set_blur`] to blur the whole window
+    /// again, or to disable the blur.
+    ///
+    /// ## Platform-specific
+    ///
+    /// See [`Window::set_blur`].
+    #[inline]
+    pub fn set_blur_region<P: Into<Position>, S: Into<Size>>(&self, position: P, size: S) {
+        self.window.set_blur_region(position.into(), size.into())
+    }
+
+    /// Changes the level of the window, relative to other windows.
+    ///
+    /// See [`WindowLevel`] for details.
     ///
     /// ## Platform-specific
     ///
     /// - **iOS / Android / Web / Wayland:** Unsupported.
     #[inline]
-    pub fn set_always_on_top(&self, always_on_top: bool) {
-        self.window.set_always_on_top(always_on_top)
+    pub fn set_window_level(&self, level: WindowLevel) {
+        self.window.set_window_level(level)
     }
 
     /// Sets the window icon. On Windows and X11, this is typically the small icon in the top-left
@@ -699,6 +890,35 @@ impl Window {
     pub fn request_user_attention(&self, request_type: Option<UserAttentionType>) {
         self.window.request_user_attention(request_type)
     }
+
+    /// Sets the theme of the window, or makes it follow the desktop theme if `None` was provided.
+    ///
+    /// `WindowEvent::ThemeChanged` is only sent when the desktop theme changes while the window
+    /// follows it, not in response to this call.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **macOS / iOS / Android / Web:** Unsupported.
+    /// - **X11:** Sets `_GTK_THEME_VARIANT`, which the window manager may use for the decorations.
+    ///   The desktop theme is the `Net/ThemeName` XSETTINGS setting.
+    /// - **Wayland:** Changes the colors of the client-side decorations, unless they were
+    ///   customized with `WindowExtUnix::set_wayland_theme`. The desktop theme is the
+    ///   `org.freedesktop.appearance` `color-scheme` setting of the XDG desktop portal.
+    #[inline]
+    pub fn set_theme(&self, theme: Option<Theme>) {
+        self.window.set_theme(theme)
+    }
+
+    /// Returns the theme of the window: the theme set with [`Window::set_theme`], or else the
+    /// desktop theme. Returns `None` if the desktop theme is unknown.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **macOS / iOS / Android / Web:** Unsupported, always returns `None`.
+    #[inline]
+    pub fn theme(&self) -> Option<Theme> {
+        self.window.theme()
+    }
 }
 
 /// Cursor functions.
@@ -713,28 +933,73 @@ impl Window {
         self.window.set_cursor_icon(cursor);
     }
 
+    /// Modifies the cursor of the window, which can be a [`CustomCursor`] made by the
+    /// application.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Animated cursors are played by the X server.
+    /// - **Wayland:** The images are scaled by the scale factor of the window, so that the cursor
+    ///   keeps the same size on every output.
+    /// - **Windows / macOS / iOS / Android / Web:** Custom cursors are unsupported, and leave the
+    ///   cursor unchanged.
+    #[inline]
+    pub fn set_cursor<C: Into<Cursor>>(&self, cursor: C) {
+        match cursor.into() {
+            Cursor::Icon(icon) => self.window.set_cursor_icon(icon),
+            Cursor::Custom(cursor) => self.window.set_custom_cursor(cursor),
+        }
+    }
+
+    /// Overrides the cursor theme of the desktop with the theme named `name`, whose cursors are
+    /// `size` pixels tall at a scale factor of 1.
+    ///
+    /// By default, the theme and its size are read from `XCURSOR_THEME` and `XCURSOR_SIZE`, or
+    /// else from the settings of the desktop. Either way, the cursors are loaded again at the new
+    /// size when the scale factor of the window changes.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** The settings of the desktop are `Gtk/CursorThemeName` and
+    ///   `Gtk/CursorThemeSize` of the XSETTINGS manager, then the `Xcursor.theme` and
+    ///   `Xcursor.size` resources.
+    /// - **Wayland:** Only the environment variables are read.
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported.
+    #[inline]
+    pub fn set_cursor_theme(&self, name: &str, size: u32) {
+        self.window.set_cursor_theme(name, size);
+    }
+
     /// Changes the position of the cursor in window coordinates.
     ///
     /// ## Platform-specific
     ///
-    /// - **iOS / Android / Web / Wayland:** Always returns an [`ExternalError::NotSupported`].
+    /// - **Wayland:** Only supported while the cursor is [locked], in which case the position is
+    ///   a hint of where the cursor should be once it's unlocked. The hint is applied on the next
+    ///   commit of the window's surface, e.g. by presenting a frame, or at the latest when the
+    ///   cursor is unlocked. Otherwise, returns an [`ExternalError::NotSupported`].
+    /// - **iOS / Android / Web:** Always returns an [`ExternalError::NotSupported`].
+    ///
+    /// [locked]: CursorGrabMode::Locked
     #[inline]
     pub fn set_cursor_position<P: Into<Position>>(&self, position: P) -> Result<(), ExternalError> {
         self.window.set_cursor_position(position.into())
     }
 
-    /// Grabs the cursor, preventing it from leaving the window.
+    /// Grabs the cursor, either confining it to the window or locking it in place, or releases
+    /// it with [`CursorGrabMode::None`].
     ///
-    /// There's no guarantee that the cursor will be hidden. You should
-    /// hide it by yourself if you want so.
+    /// Returns an [`ExternalError::NotSupported`] if the mode isn't supported by the platform,
+    /// in which case the grab is left as it was.
     ///
     /// ## Platform-specific
     ///
-    /// - **macOS:** This locks the cursor in a fixed location, which looks visually awkward.
+    /// - **macOS:** Only [`CursorGrabMode::Locked`] is supported.
+    /// - **Windows:** Only [`CursorGrabMode::Confined`] is supported.
     /// - **iOS / Android / Web:** Always returns an [`ExternalError::NotSupported`].
     #[inline]
-    pub fn set_cursor_grab(&self, grab: bool) -> Result<(), ExternalError> {
-        self.window.set_cursor_grab(grab)
+    pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
+        self.window.set_cursor_grab(mode)
     }
 
     /// Modifies the cursor's visibility.
@@ -753,6 +1018,52 @@ impl Window {
     pub fn set_cursor_visible(&self, visible: bool) {
         self.window.set_cursor_visible(visible)
     }
+
+    /// Modifies whether the window catches cursor events.
+    ///
+    /// If `false`, the cursor events pass through the window to whatever is beneath it, which
+    /// together with a transparent window that is always on top makes an overlay.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Requires the XFixes extension.
+    /// - **Wayland:** Applied with the next commit of the surface, after `RedrawRequested`.
+    /// - **iOS / Android / Web:** Always returns an [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn set_cursor_hittest(&self, hittest: bool) -> Result<(), ExternalError> {
+        self.window.set_cursor_hittest(hittest)
+    }
+
+    /// Moves the window with the left mouse button until the button is released.
+    ///
+    /// There's no guarantee that this will work unless the left mouse button was pressed
+    /// immediately before this function is called.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Un-grabs the cursor.
+    /// - **Wayland:** Requires the cursor to be inside the window to be dragged.
+    /// - **macOS:** May prevent the button release event to be triggered.
+    /// - **iOS / Android / Web:** Always returns an [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn drag_window(&self) -> Result<(), ExternalError> {
+        self.window.drag_window()
+    }
+
+    /// Resizes the window with the left mouse button until the button is released.
+    ///
+    /// There's no guarantee that this will work unless the left mouse button was pressed
+    /// immediately before this function is called.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Un-grabs the cursor.
+    /// - **Wayland:** Requires the cursor to be inside the window to be dragged.
+    /// - **macOS / iOS / Android / Web:** Always returns an [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn drag_resize_window(&self, direction: ResizeDirection) -> Result<(), ExternalError> {
+        self.window.drag_resize_window(direction)
+    }
 }
 
 /// Monitor info functions.
@@ -877,6 +1188,51 @@ impl Default for CursorIcon {
     }
 }
 
+/// How the cursor is grabbed by a window, with [`Window::set_cursor_grab`].
+///
+/// There's no guarantee that the cursor will be hidden. You should hide it by yourself if you
+/// want so.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CursorGrabMode {
+    /// The cursor isn't grabbed.
+    #[default]
+    None,
+
+    /// The cursor can move, but can't leave the window.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **macOS:** Unsupported.
+    Confined,
+
+    /// The cursor stays where it is, while [`DeviceEvent::MouseMotion`] still reports the motion
+    /// of the mouse. This is what games controlling a camera with the mouse want.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Emulated by confining the cursor, and moving it back to the center of the
+    ///   window whenever it moves. No `CursorMoved` events are sent meanwhile.
+    /// - **Windows:** Unsupported.
+    ///
+    /// [`DeviceEvent::MouseMotion`]: crate::event::DeviceEvent::MouseMotion
+    Locked,
+}
+
+/// Defines the orientation that a window resize will be performed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ResizeDirection {
+    East,
+    North,
+    NorthEast,
+    NorthWest,
+    South,
+    SouthEast,
+    SouthWest,
+    West,
+}
+
 /// Fullscreen modes.
 #[derive(Clone, Debug, PartialEq)]
 pub enum Fullscreen {
@@ -887,11 +1243,53 @@ pub enum Fullscreen {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Theme {
     Light,
     Dark,
 }
 
+/// A window level groups windows with respect to their z-position.
+///
+/// The relative ordering between windows in different window levels is fixed.
+/// The z-order of a window within the same window level may change dynamically on user interaction.
+///
+/// ## Platform-specific
+///
+/// - **Windows:** An `AlwaysOnBottom` window is only moved to the bottom when its level is set,
+///   and rises again when it's activated.
+/// - **iOS / Android / Web / Wayland:** Unsupported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum WindowLevel {
+    /// The window will always be below normal windows.
+    ///
+    /// This is useful for a widget-based app.
+    AlwaysOnBottom,
+
+    /// The default.
+    #[default]
+    Normal,
+
+    /// The window will always be on top of normal windows.
+    AlwaysOnTop,
+}
+
+bitflags! {
+    /// The buttons of the title bar of a window, which can be enabled separately.
+    pub struct WindowButtons: u32 {
+        const CLOSE = 1 << 0;
+        const MINIMIZE = 1 << 1;
+        const MAXIMIZE = 1 << 2;
+    }
+}
+
+impl Default for WindowButtons {
+    fn default() -> Self {
+        WindowButtons::all()
+    }
+}
+
 /// ## Platform-specific
 ///
 /// - **X11:** Sets the WM's `XUrgencyHint`. No distinction between `Critical` and `Informational`.
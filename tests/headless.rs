@@ -0,0 +1,448 @@
+#![cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use winit::{
+    clipboard::ClipboardKind,
+    dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize},
+    error::ExternalError,
+    event::{DeviceEvent, Event, WindowEvent},
+    event_loop::{ControlFlow, DeviceEventFilter, EventLoop, EventLoopClosed, TrySendError},
+    platform::{
+        headless::{EventLoopExtHeadless, EventLoopWindowTargetExtHeadless, WindowExtHeadless},
+        pump_events::{EventLoopExtPumpEvents, PumpStatus},
+        unix::{
+            EventLoopExtBoundedProxy, EventLoopProxyExtUnix, PopupAnchorRect,
+            PopupConstraintAdjustment, PopupGravity, WindowBuilderExtUnix,
+        },
+    },
+    window::{
+        BadCursor, CursorGrabMode, CursorIcon, CustomCursor, ResizeDirection, Theme, WindowBuilder,
+        WindowButtons, WindowLevel,
+    },
+};
+
+#[derive(Debug, PartialEq)]
+enum Dispatched {
+    Window(WindowEvent<'static>),
+    ScaleFactorChanged(f64, PhysicalSize<u32>),
+}
+
+/// Dispatches a single iteration, returning the window events it dispatched.
+fn step(event_loop: &mut EventLoop<()>) -> Vec<Dispatched> {
+    let mut events = Vec::new();
+    let status =
+        event_loop.pump_events(Some(Duration::from_millis(0)), |event, _, control_flow| {
+            *control_flow = ControlFlow::Wait;
+            match event {
+                Event::WindowEvent {
+                    event:
+                        WindowEvent::ScaleFactorChanged {
+                            scale_factor,
+                            new_inner_size,
+                        },
+                    ..
+                } => events.push(Dispatched::ScaleFactorChanged(
+                    scale_factor,
+                    *new_inner_size,
+                )),
+                Event::WindowEvent { event, .. } => {
+                    events.push(Dispatched::Window(event.to_static().unwrap()))
+                }
+                _ => (),
+            }
+        });
+    assert_eq!(status, PumpStatus::Continue);
+    events
+}
+
+#[test]
+fn headless_window_api() {
+    let mut event_loop: EventLoop<()> = EventLoop::new_headless();
+    assert!(event_loop.is_headless());
+
+    let window = WindowBuilder::new()
+        .with_inner_size(LogicalSize::new(400.0, 300.0))
+        .build(&event_loop)
+        .unwrap();
+    assert_eq!(window.inner_size(), PhysicalSize::new(400, 300));
+    assert_eq!(window.scale_factor(), 1.0);
+    step(&mut event_loop);
+
+    window.set_inner_size(LogicalSize::new(500.0, 400.0));
+    window.set_cursor_icon(CursorIcon::Hand);
+    window.set_window_level(WindowLevel::AlwaysOnBottom);
+    assert_eq!(
+        step(&mut event_loop),
+        vec![Dispatched::Window(WindowEvent::Resized(PhysicalSize::new(
+            500, 400
+        )))]
+    );
+    let virtual_state = window.virtual_state().unwrap();
+    assert_eq!(virtual_state.cursor_icon, CursorIcon::Hand);
+    assert_eq!(virtual_state.window_level, WindowLevel::AlwaysOnBottom);
+    assert!(virtual_state.cursor_hittest);
+    window.set_cursor_hittest(false).unwrap();
+    assert!(!window.virtual_state().unwrap().cursor_hittest);
+
+    assert_eq!(virtual_state.cursor_grab, CursorGrabMode::None);
+    window.set_cursor_grab(CursorGrabMode::Locked).unwrap();
+    assert_eq!(
+        window.virtual_state().unwrap().cursor_grab,
+        CursorGrabMode::Locked
+    );
+
+    let hotspot = PhysicalPosition::new(1, 1);
+    assert!(matches!(
+        CustomCursor::from_rgba(vec![0; 4 * 4], 2, 2, PhysicalPosition::new(2, 0)),
+        Err(BadCursor::HotspotOutOfBounds { .. })
+    ));
+    let frame = CustomCursor::from_rgba(vec![255; 4 * 4], 2, 2, hotspot).unwrap();
+    assert!(!frame.is_animated());
+    let cursor = CustomCursor::from_animation(vec![
+        (frame.clone(), Duration::from_millis(50)),
+        (frame, Duration::from_millis(50)),
+    ])
+    .unwrap();
+    assert!(cursor.is_animated());
+    window.set_cursor(cursor.clone());
+    let virtual_state = window.virtual_state().unwrap();
+    assert_eq!(virtual_state.cursor_icon, CursorIcon::Hand);
+    assert_eq!(virtual_state.custom_cursor, Some(cursor));
+    window.set_cursor(CursorIcon::Text);
+    let virtual_state = window.virtual_state().unwrap();
+    assert_eq!(virtual_state.cursor_icon, CursorIcon::Text);
+    assert_eq!(virtual_state.custom_cursor, None);
+
+    assert_eq!(virtual_state.cursor_theme, None);
+    window.set_cursor_theme("Adwaita", 32);
+    let virtual_state = window.virtual_state().unwrap();
+    assert_eq!(virtual_state.cursor_theme, Some(("Adwaita".to_owned(), 32)));
+
+    assert!(!virtual_state.blur);
+    window.set_blur_region(PhysicalPosition::new(0, 0), LogicalSize::new(100.0, 50.0));
+    let virtual_state = window.virtual_state().unwrap();
+    assert!(virtual_state.blur);
+    assert_eq!(
+        virtual_state.blur_region,
+        Some((PhysicalPosition::new(0, 0), PhysicalSize::new(100, 50)))
+    );
+    window.set_blur(false);
+    let virtual_state = window.virtual_state().unwrap();
+    assert!(!virtual_state.blur);
+    assert_eq!(virtual_state.blur_region, None);
+
+    window.set_resize_increments(Some(LogicalSize::new(8.0, 16.0)));
+    assert_eq!(
+        window.virtual_state().unwrap().resize_increments,
+        Some(PhysicalSize::new(8, 16))
+    );
+    window.set_resize_increments(None::<LogicalSize<f64>>);
+    assert_eq!(window.virtual_state().unwrap().resize_increments, None);
+
+    // A virtual desktop has no theme, so only the theme of the window is known.
+    assert_eq!(window.theme(), None);
+    window.set_theme(Some(Theme::Dark));
+    assert_eq!(window.theme(), Some(Theme::Dark));
+
+    assert!(window.is_resizable());
+    assert!(window.is_decorated());
+    assert_eq!(window.is_visible(), Some(true));
+    assert_eq!(window.is_minimized(), Some(false));
+    assert!(!window.is_maximized());
+    assert_eq!(window.enabled_buttons(), WindowButtons::all());
+    window.set_enabled_buttons(WindowButtons::CLOSE | WindowButtons::MINIMIZE);
+    assert_eq!(
+        window.enabled_buttons(),
+        WindowButtons::CLOSE | WindowButtons::MINIMIZE
+    );
+    window.set_resizable(false);
+    window.set_decorations(false);
+    window.set_minimized(true);
+    assert!(!window.is_resizable());
+    assert!(!window.is_decorated());
+    assert_eq!(window.is_minimized(), Some(true));
+    window.set_minimized(false);
+
+    // There's no window manager to move or resize the window interactively.
+    assert!(matches!(
+        window.drag_window(),
+        Err(ExternalError::NotSupported(_))
+    ));
+    assert!(matches!(
+        window.drag_resize_window(ResizeDirection::SouthEast),
+        Err(ExternalError::NotSupported(_))
+    ));
+
+    // Moving to a HiDPI monitor changes the scale factor, and the size follows.
+    event_loop
+        .add_virtual_monitor(
+            PhysicalPosition::new(1920, 0),
+            PhysicalSize::new(3840, 2160),
+            2.0,
+        )
+        .unwrap();
+    window.set_outer_position(PhysicalPosition::new(2000, 100));
+    assert_eq!(
+        step(&mut event_loop),
+        vec![
+            Dispatched::Window(WindowEvent::Moved(PhysicalPosition::new(2000, 100))),
+            Dispatched::ScaleFactorChanged(2.0, PhysicalSize::new(1000, 800)),
+            Dispatched::Window(WindowEvent::Resized(PhysicalSize::new(1000, 800))),
+        ]
+    );
+    assert_eq!(window.scale_factor(), 2.0);
+
+    event_loop
+        .inject_window_event(window.id(), WindowEvent::CloseRequested)
+        .unwrap();
+    assert_eq!(
+        step(&mut event_loop),
+        vec![Dispatched::Window(WindowEvent::CloseRequested)]
+    );
+    assert_eq!(step(&mut event_loop), vec![]);
+}
+
+#[test]
+fn headless_device_event_filter() {
+    let mut event_loop: EventLoop<()> = EventLoop::new_headless();
+    let window = WindowBuilder::new().build(&event_loop).unwrap();
+    let device_id = event_loop.virtual_device_id();
+    let motion = DeviceEvent::MouseMotion { delta: (1.0, 0.0) };
+
+    let device_events = |event_loop: &mut EventLoop<()>| {
+        let mut count = 0;
+        event_loop.pump_events(Some(Duration::from_millis(0)), |event, _, control_flow| {
+            *control_flow = ControlFlow::Wait;
+            if let Event::DeviceEvent { .. } = event {
+                count += 1;
+            }
+        });
+        count
+    };
+
+    // Device events are filtered while no window is focused by default.
+    event_loop
+        .inject_device_event(device_id, motion.clone())
+        .unwrap();
+    assert_eq!(device_events(&mut event_loop), 0);
+
+    event_loop
+        .inject_window_event(window.id(), WindowEvent::Focused(true))
+        .unwrap();
+    event_loop
+        .inject_device_event(device_id, motion.clone())
+        .unwrap();
+    assert_eq!(device_events(&mut event_loop), 1);
+
+    event_loop.set_device_event_filter(DeviceEventFilter::Always);
+    event_loop
+        .inject_device_event(device_id, motion.clone())
+        .unwrap();
+    assert_eq!(device_events(&mut event_loop), 0);
+
+    event_loop
+        .inject_window_event(window.id(), WindowEvent::Focused(false))
+        .unwrap();
+    event_loop.set_device_event_filter(DeviceEventFilter::Never);
+    event_loop.inject_device_event(device_id, motion).unwrap();
+    assert_eq!(device_events(&mut event_loop), 1);
+}
+
+#[test]
+fn headless_popup_placement() {
+    let event_loop: EventLoop<()> = EventLoop::new_headless();
+    let parent = WindowBuilder::new()
+        .with_inner_size(LogicalSize::new(1920.0, 1080.0))
+        .build(&event_loop)
+        .unwrap();
+
+    let popup = |anchor_x: f64, adjustment: PopupConstraintAdjustment| {
+        let anchor_rect = PopupAnchorRect {
+            position: LogicalPosition::new(anchor_x, 50.0).into(),
+            size: LogicalSize::new(20.0, 20.0).into(),
+        };
+        let popup = WindowBuilder::new()
+            .with_inner_size(LogicalSize::new(200.0, 100.0))
+            .with_popup(&parent, anchor_rect, PopupGravity::BottomRight, adjustment)
+            .build(&event_loop)
+            .unwrap();
+        assert!(!popup.is_decorated());
+        (popup.inner_position().unwrap(), popup.inner_size())
+    };
+
+    let size = PhysicalSize::new(200, 100);
+    assert_eq!(
+        popup(100.0, PopupConstraintAdjustment::empty()),
+        (PhysicalPosition::new(120, 70), size)
+    );
+    // Past the right edge of the monitor, the popup is adjusted as allowed.
+    assert_eq!(
+        popup(1880.0, PopupConstraintAdjustment::empty()),
+        (PhysicalPosition::new(1900, 70), size)
+    );
+    assert_eq!(
+        popup(1880.0, PopupConstraintAdjustment::FLIP_X),
+        (PhysicalPosition::new(1680, 70), size)
+    );
+    assert_eq!(
+        popup(1880.0, PopupConstraintAdjustment::SLIDE_X),
+        (PhysicalPosition::new(1720, 70), size)
+    );
+    assert_eq!(
+        popup(1880.0, PopupConstraintAdjustment::RESIZE_X),
+        (PhysicalPosition::new(1900, 70), PhysicalSize::new(20, 100))
+    );
+}
+
+#[test]
+fn headless_frame_ready() {
+    let mut event_loop: EventLoop<()> = EventLoop::new_headless();
+    let window = WindowBuilder::new().build(&event_loop).unwrap();
+    step(&mut event_loop);
+
+    // A single `FrameReady` is emitted, however many times the frame was requested.
+    window.request_frame();
+    window.request_frame();
+    let events = step(&mut event_loop);
+    assert_eq!(events.len(), 1);
+    assert!(matches!(
+        events[0],
+        Dispatched::Window(WindowEvent::FrameReady { .. })
+    ));
+    assert_eq!(step(&mut event_loop), vec![]);
+
+    // Once it was emitted, the frame can be requested again.
+    window.request_frame();
+    assert!(matches!(
+        step(&mut event_loop)[..],
+        [Dispatched::Window(WindowEvent::FrameReady { .. })]
+    ));
+}
+
+#[test]
+fn headless_bounded_proxy() {
+    let mut event_loop: EventLoop<u32> = EventLoop::new_headless();
+    let proxy = event_loop.create_proxy_bounded(2);
+
+    // The capacity is shared with the clones of the proxy.
+    proxy.try_send_event(1).unwrap();
+    proxy.clone().try_send_event(2).unwrap();
+    assert_eq!(proxy.try_send_event(3), Err(TrySendError::Full(3)));
+
+    // Receiving the events frees up the capacity.
+    let mut received = Vec::new();
+    let status =
+        event_loop.pump_events(Some(Duration::from_millis(0)), |event, _, control_flow| {
+            *control_flow = ControlFlow::Wait;
+            if let Event::UserEvent(event) = event {
+                received.push(event);
+            }
+        });
+    assert_eq!(status, PumpStatus::Continue);
+    assert_eq!(received, [1, 2]);
+    proxy.try_send_event(3).unwrap();
+}
+
+#[test]
+fn headless_proxy_after_exit() {
+    let event_loop: EventLoop<u32> = EventLoop::new_headless();
+    let proxy = event_loop.create_proxy();
+    let bounded_proxy = event_loop.create_proxy_bounded(1);
+    bounded_proxy.try_send_event(1).unwrap();
+    drop(event_loop);
+
+    assert_eq!(proxy.send_event(2), Err(EventLoopClosed(2)));
+    assert_eq!(proxy.try_send_event(3), Err(TrySendError::Closed(3)));
+
+    // The events the event loop never received don't take up the capacity anymore, so sending
+    // fails instead of blocking.
+    assert_eq!(bounded_proxy.send_event(4), Err(EventLoopClosed(4)));
+    assert_eq!(
+        bounded_proxy.try_send_event(5),
+        Err(TrySendError::Closed(5))
+    );
+}
+
+#[test]
+fn headless_modal_window() {
+    let mut event_loop: EventLoop<()> = EventLoop::new_headless();
+    let owner = WindowBuilder::new().build(&event_loop).unwrap();
+    let dialog = WindowBuilder::new()
+        .with_owner(&owner)
+        .with_modal(true)
+        .build(&event_loop)
+        .unwrap();
+    step(&mut event_loop);
+
+    // Input to the owner is swallowed while the modal window is visible, other events aren't.
+    event_loop
+        .inject_window_event(owner.id(), WindowEvent::ReceivedCharacter('a'))
+        .unwrap();
+    event_loop
+        .inject_window_event(owner.id(), WindowEvent::Focused(true))
+        .unwrap();
+    assert_eq!(
+        step(&mut event_loop),
+        vec![Dispatched::Window(WindowEvent::Focused(true))]
+    );
+
+    dialog.set_visible(false);
+    event_loop
+        .inject_window_event(owner.id(), WindowEvent::ReceivedCharacter('a'))
+        .unwrap();
+    assert_eq!(
+        step(&mut event_loop),
+        vec![Dispatched::Window(WindowEvent::ReceivedCharacter('a'))]
+    );
+}
+
+#[test]
+fn headless_clipboard() {
+    let event_loop: EventLoop<()> = EventLoop::new_headless();
+    let clipboard = event_loop.clipboard();
+    let text = "text/plain;charset=utf-8";
+
+    let mut contents = HashMap::new();
+    contents.insert(text.to_owned(), b"copied".to_vec());
+    clipboard
+        .set_contents(ClipboardKind::Clipboard, contents)
+        .unwrap();
+    assert_eq!(
+        clipboard
+            .get_contents(ClipboardKind::Clipboard, text)
+            .unwrap(),
+        Some(b"copied".to_vec())
+    );
+    assert_eq!(
+        clipboard
+            .get_contents(ClipboardKind::Clipboard, "image/png")
+            .unwrap(),
+        None
+    );
+
+    // The primary selection is separate from the clipboard.
+    assert_eq!(
+        clipboard
+            .get_contents(ClipboardKind::Primary, text)
+            .unwrap(),
+        None
+    );
+
+    clipboard
+        .set_contents(ClipboardKind::Clipboard, HashMap::new())
+        .unwrap();
+    assert_eq!(
+        clipboard
+            .get_contents(ClipboardKind::Clipboard, text)
+            .unwrap(),
+        None
+    );
+}
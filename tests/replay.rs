@@ -0,0 +1,94 @@
+#![cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+
+use std::time::Duration;
+
+use winit::{
+    event::{Event, StartCause, WindowEvent},
+    event_loop::EventLoop,
+    platform::{
+        replay::{
+            EventLoopExtReplay, EventRecord, EventRecorder, RecordedEvent, RecordedWindowEvent,
+        },
+        run_return::EventLoopExtRunReturn,
+    },
+};
+
+fn record(event: RecordedEvent) -> EventRecord {
+    EventRecord {
+        time: Duration::from_millis(0),
+        event,
+    }
+}
+
+#[test]
+fn replay_dispatches_records() {
+    let records = vec![
+        record(RecordedEvent::NewEvents),
+        record(RecordedEvent::WindowEvent {
+            window_id: 1,
+            event: RecordedWindowEvent::Focused(true),
+        }),
+        record(RecordedEvent::NewEvents),
+        record(RecordedEvent::WindowEvent {
+            window_id: 1,
+            event: RecordedWindowEvent::CloseRequested,
+        }),
+    ];
+    let mut event_loop: EventLoop<()> = EventLoop::new_replay(records.clone());
+
+    let recorder = EventRecorder::new();
+    let mut rerecorded = Vec::new();
+    let mut iterations = 0;
+    let exit_code = event_loop.run_return(|event, _, _| {
+        match event {
+            Event::NewEvents(StartCause::Init) => assert_eq!(iterations, 0),
+            Event::NewEvents(_) => assert!(iterations > 0),
+            Event::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                ..
+            } => assert_eq!(iterations, 1),
+            Event::RedrawEventsCleared => iterations += 1,
+            _ => (),
+        }
+        if let Some(mut record) = recorder.record(&event) {
+            record.time = Duration::from_millis(0);
+            rerecorded.push(record);
+        }
+    });
+
+    assert_eq!(exit_code, 0);
+    assert_eq!(iterations, 2);
+    assert_eq!(rerecorded, records);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn records_round_trip_through_a_trace() {
+    use winit::platform::replay::read_records;
+
+    let recorder = EventRecorder::new();
+    let events: Vec<Event<'_, ()>> = vec![
+        Event::NewEvents(StartCause::Init),
+        Event::UserEvent(()),
+        Event::Resumed,
+        Event::MainEventsCleared,
+    ];
+    let mut trace = Vec::new();
+    for event in events.iter() {
+        recorder.record_to(event, &mut trace).unwrap();
+    }
+    trace.extend_from_slice(b"\n");
+
+    // The events generated by the replay itself aren't recorded.
+    let records = read_records(&trace[..]).unwrap();
+    let events: Vec<_> = records.into_iter().map(|record| record.event).collect();
+    assert_eq!(events, [RecordedEvent::NewEvents, RecordedEvent::Resumed]);
+
+    assert!(read_records(&b"{\"time\":\n"[..]).is_err());
+}
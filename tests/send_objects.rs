@@ -18,6 +18,12 @@ fn window_send() {
     needs_send::<winit::window::Window>();
 }
 
+#[test]
+fn custom_cursor_send() {
+    // ensures that `winit::window::CustomCursor` implements `Send`
+    needs_send::<winit::window::CustomCursor>();
+}
+
 #[test]
 fn ids_send() {
     // ensures that the various `..Id` types implement `Send`
@@ -4,10 +4,10 @@ use serde::{Deserialize, Serialize};
 use winit::{
     dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize},
     event::{
-        ElementState, KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta, TouchPhase,
-        VirtualKeyCode,
+        DeviceEvent, ElementState, Force, KeyboardInput, ModifiersState, MouseButton,
+        MouseScrollDelta, TouchPhase, VirtualKeyCode,
     },
-    window::CursorIcon,
+    window::{CursorIcon, Theme},
 };
 
 #[allow(dead_code)]
@@ -16,6 +16,7 @@ fn needs_serde<S: Serialize + Deserialize<'static>>() {}
 #[test]
 fn window_serde() {
     needs_serde::<CursorIcon>();
+    needs_serde::<Theme>();
 }
 
 #[test]
@@ -27,6 +28,24 @@ fn events_serde() {
     needs_serde::<MouseScrollDelta>();
     needs_serde::<VirtualKeyCode>();
     needs_serde::<ModifiersState>();
+    needs_serde::<DeviceEvent>();
+    needs_serde::<Force>();
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+#[test]
+fn replay_serde() {
+    use winit::platform::replay::{EventRecord, RecordedEvent, RecordedWindowEvent};
+
+    needs_serde::<EventRecord>();
+    needs_serde::<RecordedEvent>();
+    needs_serde::<RecordedWindowEvent>();
 }
 
 #[test]